@@ -22,6 +22,13 @@ pub fn db_path() -> PathBuf {
     dir
 }
 
+/// 获取活动记录归档目录（按年拆分的归档库）
+pub fn archive_dir() -> PathBuf {
+    let mut dir = app_data_dir();
+    dir.push("archive");
+    dir
+}
+
 /// 获取 Python 脚本根目录
 pub fn python_dir() -> PathBuf {
     let mut dir = app_data_dir();
@@ -1,6 +1,7 @@
-use rusqlite::{Connection, Result as SqlResult};
+use rusqlite::{Connection, OptionalExtension, Result as SqlResult};
 use std::sync::Mutex;
 use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc, Duration as ChronoDuration};
+use crate::migrations::{add_column_if_missing, run_migrations, Migration};
 
 pub struct DbState {
     pub db: Mutex<Connection>,
@@ -13,6 +14,7 @@ pub struct ActivityLog {
     pub window_title: String,
     pub start_time: String,
     pub duration_seconds: i64,
+    pub is_idle: bool,
 }
 
 #[derive(serde::Serialize, Clone)]
@@ -20,9 +22,43 @@ pub struct TimelineActivity {
     pub app_name: String,
     pub start_time: String,
     pub duration_seconds: i64,
+    pub is_idle: bool,
 }
 
-pub fn init_db(conn: &Connection) -> SqlResult<()> {
+/// 本地日期当天 00:00 对应的本地时间
+pub fn local_day_start(date: &str) -> Result<DateTime<Local>, String> {
+    let naive = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| format!("解析日期失败: {}", e))?;
+    Local
+        .from_local_datetime(&naive.and_hms_opt(0, 0, 0).ok_or("无效时间")?)
+        .single()
+        .ok_or_else(|| "无法唯一确定本地时间".to_string())
+}
+
+/// 本地日期闭区间 [start_date, end_date] 对应的 UTC 边界（RFC3339，右开）
+pub fn local_range_bounds(start_date: &str, end_date: &str) -> Result<(String, String), String> {
+    let start = local_day_start(start_date)?;
+    let end_day = NaiveDate::parse_from_str(end_date, "%Y-%m-%d").map_err(|e| format!("解析日期失败: {}", e))?;
+    let next_day = (end_day + ChronoDuration::days(1)).format("%Y-%m-%d").to_string();
+    let end = local_day_start(&next_day)?;
+    if end <= start { return Err("结束日期早于开始日期".into()); }
+    let start_utc: DateTime<Utc> = DateTime::<Utc>::from(start);
+    let end_utc: DateTime<Utc> = DateTime::<Utc>::from(end);
+    Ok((start_utc.to_rfc3339(), end_utc.to_rfc3339()))
+}
+
+/// 活动数据库的全部迁移，按版本顺序排列；已发布的迁移不能再修改，只能追加
+pub const MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, description: "activity_log", up: migrate_v1_activity_log },
+    Migration { version: 2, description: "idle / open segments", up: migrate_v2_idle_open },
+    Migration { version: 3, description: "categories and rules", up: migrate_v3_categories },
+    Migration { version: 4, description: "start_time index", up: migrate_v4_start_time_index },
+    Migration { version: 5, description: "hourly summaries and settings", up: migrate_v5_summary_settings },
+    Migration { version: 6, description: "privacy rules", up: migrate_v6_privacy },
+    Migration { version: 7, description: "time goals", up: migrate_v7_goals },
+    Migration { version: 8, description: "planned blocks", up: migrate_v8_planned_blocks },
+];
+
+fn migrate_v1_activity_log(conn: &Connection) -> SqlResult<()> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS activity_log (
             id              INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -36,15 +72,183 @@ pub fn init_db(conn: &Connection) -> SqlResult<()> {
     Ok(())
 }
 
+fn migrate_v2_idle_open(conn: &Connection) -> SqlResult<()> {
+    add_column_if_missing(conn, "activity_log", "is_idle", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "activity_log", "is_open", "INTEGER NOT NULL DEFAULT 0")?;
+    Ok(())
+}
+
+fn migrate_v3_categories(conn: &Connection) -> SqlResult<()> {
+    // 活动分类与规则（规则按 priority 从高到低匹配，首个命中的规则生效）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS activity_categories (
+            id              INTEGER PRIMARY KEY AUTOINCREMENT,
+            name            TEXT NOT NULL UNIQUE,
+            color           TEXT
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS activity_rules (
+            id              INTEGER PRIMARY KEY AUTOINCREMENT,
+            priority        INTEGER NOT NULL DEFAULT 0,
+            match_kind      TEXT NOT NULL DEFAULT 'glob' CHECK(match_kind IN ('glob', 'regex')),
+            app_pattern     TEXT,
+            title_pattern   TEXT,
+            category_id     INTEGER,
+            project         TEXT,
+            enabled         INTEGER NOT NULL DEFAULT 1
+        )",
+        [],
+    )?;
+    add_column_if_missing(conn, "activity_log", "category_id", "INTEGER")?;
+    add_column_if_missing(conn, "activity_log", "project", "TEXT")?;
+    Ok(())
+}
+
+fn migrate_v4_start_time_index(conn: &Connection) -> SqlResult<()> {
+    // 按时间区间查询与导入去重都依赖 start_time
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_activity_log_start_time ON activity_log(start_time)", [])?;
+    Ok(())
+}
+
+fn migrate_v5_summary_settings(conn: &Connection) -> SqlResult<()> {
+    // 保留策略把旧的原始记录合并为每小时、每程序一行
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS activity_hourly_summary (
+            id              INTEGER PRIMARY KEY AUTOINCREMENT,
+            hour_start      TEXT NOT NULL,
+            app_name        TEXT NOT NULL,
+            category_id     INTEGER,
+            project         TEXT,
+            is_idle         INTEGER NOT NULL DEFAULT 0,
+            total_seconds   INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_activity_hourly_summary_hour ON activity_hourly_summary(hour_start)", [])?;
+    // 需要持久化的设置（JSON 值）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS app_settings (
+            key             TEXT PRIMARY KEY,
+            value           TEXT NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn migrate_v6_privacy(conn: &Connection) -> SqlResult<()> {
+    // 窗口标题脱敏：按程序匹配的处理方式（首个命中的规则生效）与全局正则黑名单
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS privacy_rules (
+            id              INTEGER PRIMARY KEY AUTOINCREMENT,
+            priority        INTEGER NOT NULL DEFAULT 0,
+            match_kind      TEXT NOT NULL DEFAULT 'glob' CHECK(match_kind IN ('glob', 'regex')),
+            app_pattern     TEXT NOT NULL,
+            action          TEXT NOT NULL CHECK(action IN ('keep', 'drop', 'domain', 'hash')),
+            enabled         INTEGER NOT NULL DEFAULT 1
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS privacy_denylist (
+            id              INTEGER PRIMARY KEY AUTOINCREMENT,
+            pattern         TEXT NOT NULL,
+            enabled         INTEGER NOT NULL DEFAULT 1
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn migrate_v7_goals(conn: &Connection) -> SqlResult<()> {
+    // 每日/每周时间目标；category_id 与 app_pattern 都为空时统计全部非空闲时间
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS activity_goals (
+            id              INTEGER PRIMARY KEY AUTOINCREMENT,
+            name            TEXT NOT NULL,
+            period          TEXT NOT NULL CHECK(period IN ('day', 'week')),
+            direction       TEXT NOT NULL CHECK(direction IN ('at_least', 'at_most')),
+            target_seconds  INTEGER NOT NULL CHECK(target_seconds > 0),
+            category_id     INTEGER,
+            app_pattern     TEXT,
+            enabled         INTEGER NOT NULL DEFAULT 1
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn migrate_v8_planned_blocks(conn: &Connection) -> SqlResult<()> {
+    // 从 .ics 导入的计划时间块，用于与实际记录对比
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS planned_blocks (
+            id              INTEGER PRIMARY KEY AUTOINCREMENT,
+            uid             TEXT NOT NULL,
+            title           TEXT NOT NULL,
+            start_time      TEXT NOT NULL,
+            end_time        TEXT NOT NULL,
+            category_id     INTEGER,
+            source          TEXT,
+            UNIQUE(uid, start_time)
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_planned_blocks_start_time ON planned_blocks(start_time)", [])?;
+    Ok(())
+}
+
+/// 等待其它连接释放文件锁的最长时间；保留策略的完整 VACUUM 在单独的连接上执行，
+/// 期间追踪线程的写入会等待，而不是以 SQLITE_BUSY 失败
+pub const BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+
+/// 把数据库迁移到最新 schema 并设置忙等待时间；数据库版本比程序新时返回错误
+pub fn init_db(conn: &Connection) -> Result<(), String> {
+    conn.busy_timeout(BUSY_TIMEOUT).map_err(|e| e.to_string())?;
+    run_migrations(conn, "time_tracker.db", MIGRATIONS).map(|_| ())
+}
+
+/// 读取 app_settings 中的设置，不存在时返回 None
+pub fn get_setting<T: serde::de::DeserializeOwned>(conn: &Connection, key: &str) -> Result<Option<T>, String> {
+    let value: Option<String> = conn
+        .query_row("SELECT value FROM app_settings WHERE key = ?1", [key], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match value {
+        Some(v) => serde_json::from_str(&v).map(Some).map_err(|e| format!("设置 {} 格式错误: {}", key, e)),
+        None => Ok(None),
+    }
+}
+
+pub fn set_setting<T: serde::Serialize>(conn: &Connection, key: &str, value: &T) -> Result<(), String> {
+    let v = serde_json::to_string(value).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO app_settings (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        [key, v.as_str()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// 关闭上次非正常退出遗留的进行中分段，返回处理的行数
+///
+/// 遗留行的时长停留在最后一次心跳，直接按该时长关闭；不超过 1 秒的删除。
+pub fn recover_open_segments(conn: &Connection) -> SqlResult<usize> {
+    let removed = conn.execute("DELETE FROM activity_log WHERE is_open = 1 AND duration_seconds <= 1", [])?;
+    let closed = conn.execute("UPDATE activity_log SET is_open = 0 WHERE is_open = 1", [])?;
+    Ok(removed + closed)
+}
+
 pub fn get_latest_activities(state: tauri::State<DbState>) -> Result<Vec<ActivityLog>, String> {
     let conn = state.db.lock().unwrap();
     let mut stmt = conn
-        .prepare("SELECT id, app_name, window_title, start_time, duration_seconds FROM activity_log ORDER BY start_time DESC LIMIT 10")
+        .prepare("SELECT id, app_name, window_title, start_time, duration_seconds, is_idle FROM activity_log ORDER BY start_time DESC LIMIT 10")
         .map_err(|e| e.to_string())?;
 
     let iter = stmt
         .query_map([], |row| {
-            Ok(ActivityLog { id: row.get(0)?, app_name: row.get(1)?, window_title: row.get(2)?, start_time: row.get(3)?, duration_seconds: row.get(4)?, })
+            Ok(ActivityLog { id: row.get(0)?, app_name: row.get(1)?, window_title: row.get(2)?, start_time: row.get(3)?, duration_seconds: row.get(4)?, is_idle: row.get(5)?, })
         })
         .map_err(|e| e.to_string())?;
 
@@ -53,26 +257,18 @@ pub fn get_latest_activities(state: tauri::State<DbState>) -> Result<Vec<Activit
     Ok(out)
 }
 
-pub fn get_activities_for_day(state: tauri::State<DbState>, date: String) -> Result<Vec<TimelineActivity>, String> {
+/// 某日（本地时间）的活动；`include_idle` 为 false 时不返回空闲段
+pub fn get_activities_for_day(state: tauri::State<DbState>, date: String, include_idle: bool) -> Result<Vec<TimelineActivity>, String> {
     let conn = state.db.lock().unwrap();
-    let naive = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| format!("解析日期失败: {}", e))?;
-    let local_start = Local
-        .from_local_datetime(&naive.and_hms_opt(0, 0, 0).ok_or("无效时间")?)
-        .single()
-        .ok_or("无法唯一确定本地时间")?;
-    let local_end = local_start + ChronoDuration::days(1);
-    let start_utc: DateTime<Utc> = DateTime::<Utc>::from(local_start);
-    let end_utc: DateTime<Utc> = DateTime::<Utc>::from(local_end);
-    let start_s = start_utc.to_rfc3339();
-    let end_s = end_utc.to_rfc3339();
+    let (start_s, end_s) = local_range_bounds(&date, &date)?;
 
     let mut stmt = conn
-        .prepare("SELECT app_name, start_time, duration_seconds FROM activity_log WHERE start_time >= ?1 AND start_time < ?2 ORDER BY start_time ASC")
+        .prepare("SELECT app_name, start_time, duration_seconds, is_idle FROM activity_log WHERE start_time >= ?1 AND start_time < ?2 AND (?3 OR is_idle = 0) ORDER BY start_time ASC")
         .map_err(|e| e.to_string())?;
 
     let iter = stmt
-        .query_map(rusqlite::params![start_s, end_s], |row| {
-            Ok(TimelineActivity { app_name: row.get(0)?, start_time: row.get(1)?, duration_seconds: row.get(2)?, })
+        .query_map(rusqlite::params![start_s, end_s, include_idle], |row| {
+            Ok(TimelineActivity { app_name: row.get(0)?, start_time: row.get(1)?, duration_seconds: row.get(2)?, is_idle: row.get(3)?, })
         })
         .map_err(|e| e.to_string())?;
 
@@ -80,3 +276,99 @@ pub fn get_activities_for_day(state: tauri::State<DbState>, date: String) -> Res
     for r in iter { out.push(r.map_err(|e| e.to_string())?); }
     Ok(out)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::schema_version;
+
+    fn columns(conn: &Connection, table: &str) -> Vec<String> {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table)).unwrap();
+        stmt.query_map([], |row| row.get(1)).unwrap().map(|c| c.unwrap()).collect()
+    }
+
+    fn tables(conn: &Connection) -> Vec<String> {
+        let mut stmt = conn
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name")
+            .unwrap();
+        stmt.query_map([], |row| row.get(0)).unwrap().map(|t| t.unwrap()).collect()
+    }
+
+    fn assert_latest_schema(conn: &Connection) {
+        assert_eq!(schema_version(conn).unwrap() as usize, MIGRATIONS.len());
+        assert_eq!(
+            columns(conn, "activity_log"),
+            [
+                "id", "app_name", "window_title", "start_time", "duration_seconds",
+                "is_idle", "is_open", "category_id", "project",
+            ]
+        );
+        assert_eq!(
+            tables(conn),
+            [
+                "activity_categories", "activity_goals", "activity_hourly_summary", "activity_log",
+                "activity_rules", "app_settings", "planned_blocks", "privacy_denylist", "privacy_rules",
+            ]
+        );
+    }
+
+    #[test]
+    fn migrates_from_every_version() {
+        for version in 0..=MIGRATIONS.len() {
+            let conn = Connection::open_in_memory().unwrap();
+            run_migrations(&conn, "fixture", &MIGRATIONS[..version]).unwrap();
+            assert_eq!(schema_version(&conn).unwrap() as usize, version);
+            if version > 0 {
+                conn.execute(
+                    "INSERT INTO activity_log (app_name, window_title, start_time, duration_seconds)
+                     VALUES ('Code', 'main.rs', '2026-01-01T00:00:00+00:00', 5)",
+                    [],
+                )
+                .unwrap();
+            }
+
+            init_db(&conn).unwrap();
+            assert_latest_schema(&conn);
+            // 已有数据原样保留，新列取默认值
+            if version > 0 {
+                let row: (String, i64, bool, bool, Option<i64>) = conn
+                    .query_row(
+                        "SELECT app_name, duration_seconds, is_idle, is_open, category_id FROM activity_log",
+                        [],
+                        |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?)),
+                    )
+                    .unwrap();
+                assert_eq!(row, ("Code".to_string(), 5, false, false, None));
+            }
+        }
+    }
+
+    #[test]
+    fn migrates_unversioned_database_with_partial_columns() {
+        // 引入版本号之前的库：只补过 is_idle 列，user_version 仍为 0
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE activity_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, app_name TEXT NOT NULL, window_title TEXT,
+                start_time TEXT NOT NULL, duration_seconds INTEGER NOT NULL, is_idle INTEGER NOT NULL DEFAULT 0
+             );
+             INSERT INTO activity_log (app_name, start_time, duration_seconds, is_idle) VALUES ('idle', 't', 60, 1);",
+        )
+        .unwrap();
+        init_db(&conn).unwrap();
+        assert_latest_schema(&conn);
+        let is_idle: bool = conn.query_row("SELECT is_idle FROM activity_log", [], |r| r.get(0)).unwrap();
+        assert!(is_idle);
+    }
+
+    #[test]
+    fn refuses_newer_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_db(&conn).unwrap();
+        let newer = MIGRATIONS.len() as u32 + 1;
+        conn.pragma_update(None, "user_version", newer).unwrap();
+        let err = init_db(&conn).unwrap_err();
+        assert!(err.contains(&newer.to_string()), "{}", err);
+        assert_eq!(schema_version(&conn).unwrap(), newer);
+    }
+}
@@ -0,0 +1,68 @@
+use rusqlite::Connection;
+
+/// 一次有序的 schema 变更；`version` 从 1 开始连续递增
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub up: fn(&Connection) -> rusqlite::Result<()>,
+}
+
+/// 当前 schema 版本（PRAGMA user_version，新库为 0）
+pub fn schema_version(conn: &Connection) -> rusqlite::Result<u32> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// 依次执行尚未应用的迁移，每个迁移与版本号的更新在同一个事务内完成
+///
+/// 数据库版本高于程序已知的最新版本时直接报错，避免旧版程序改写新版数据。
+/// 返回迁移后的版本号。
+pub fn run_migrations(conn: &Connection, db_name: &str, migrations: &[Migration]) -> Result<u32, String> {
+    for (i, m) in migrations.iter().enumerate() {
+        if m.version as usize != i + 1 {
+            return Err(format!("{}: 迁移版本号必须从 1 开始连续递增（第 {} 个为 {}）", db_name, i + 1, m.version));
+        }
+    }
+    let latest = migrations.len() as u32;
+    let current = schema_version(conn).map_err(|e| format!("{}: 读取 schema 版本失败: {}", db_name, e))?;
+    if current > latest {
+        return Err(format!(
+            "{} 的 schema 版本为 {}，高于当前程序支持的 {}；请升级程序后再打开",
+            db_name, current, latest
+        ));
+    }
+
+    for m in &migrations[current as usize..] {
+        let tx = conn
+            .unchecked_transaction()
+            .map_err(|e| format!("{}: 开始迁移 {} 失败: {}", db_name, m.version, e))?;
+        (m.up)(&tx).map_err(|e| format!("{}: 迁移 {} ({}) 失败: {}", db_name, m.version, m.description, e))?;
+        tx.pragma_update(None, "user_version", m.version)
+            .map_err(|e| format!("{}: 更新 schema 版本失败: {}", db_name, e))?;
+        tx.commit()
+            .map_err(|e| format!("{}: 提交迁移 {} 失败: {}", db_name, m.version, e))?;
+    }
+    Ok(latest)
+}
+
+/// 表中是否已有某列
+pub fn has_column(conn: &Connection, table: &str, column: &str) -> rusqlite::Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    for name in names {
+        if name?.eq_ignore_ascii_case(column) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// 列不存在时才 ADD COLUMN
+///
+/// 引入版本号之前的旧库靠每次启动重复 ALTER 补列，可能已经处于任意中间状态，
+/// 因此早期迁移需要按实际结构判断，而不是吞掉报错。
+pub fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> rusqlite::Result<()> {
+    if !has_column(conn, table, column)? {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
@@ -0,0 +1,413 @@
+use crate::categories;
+use crate::db::{self, DbState};
+use crate::privacy;
+use chrono::{DateTime, Utc};
+use polars::prelude::{Column, DataFrame, DataType, ParquetCompression, ParquetReader, ParquetWriter, SerReader};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use tauri::State;
+
+/// 导出/导入的一条活动记录；分类按名称导出，以便在另一台机器上对应到本地分类
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityRecord {
+    pub app_name: String,
+    #[serde(default)]
+    pub window_title: String,
+    pub start_time: String,
+    pub duration_seconds: i64,
+    #[serde(default)]
+    pub is_idle: bool,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub project: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            "parquet" => Ok(Self::Parquet),
+            other => Err(format!("不支持的格式: {}（可选 csv / json / parquet）", other)),
+        }
+    }
+
+    /// 按文件扩展名推断格式
+    pub fn from_path(path: &str) -> Result<Self, String> {
+        let ext = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .ok_or_else(|| format!("无法从文件名推断格式: {}", path))?;
+        Self::parse(ext)
+    }
+}
+
+/// 读取本地日期闭区间内已结束的记录（含空闲段），按开始时间排序
+///
+/// 进行中的分段时长只到最近一次心跳，不导出，避免把截断的时长带到别的机器上。
+pub fn load_records(conn: &Connection, start_date: &str, end_date: &str) -> Result<Vec<ActivityRecord>, String> {
+    let (start_s, end_s) = db::local_range_bounds(start_date, end_date)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT a.app_name, COALESCE(a.window_title, ''), a.start_time, a.duration_seconds, a.is_idle, c.name, a.project
+             FROM activity_log a LEFT JOIN activity_categories c ON c.id = a.category_id
+             WHERE a.start_time >= ?1 AND a.start_time < ?2 AND a.is_open = 0
+             ORDER BY a.start_time ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![start_s, end_s], |row| {
+            Ok(ActivityRecord {
+                app_name: row.get(0)?,
+                window_title: row.get(1)?,
+                start_time: row.get(2)?,
+                duration_seconds: row.get(3)?,
+                is_idle: row.get(4)?,
+                category: row.get(5)?,
+                project: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())
+}
+
+// ==================== 编码 / 解码 ====================
+
+pub fn records_to_json(records: &[ActivityRecord]) -> Result<String, String> {
+    serde_json::to_string_pretty(records).map_err(|e| e.to_string())
+}
+
+pub fn records_to_csv(records: &[ActivityRecord]) -> Result<String, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for r in records {
+        writer.serialize(r).map_err(|e| e.to_string())?;
+    }
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+pub fn records_from_json(text: &str) -> Result<Vec<ActivityRecord>, String> {
+    serde_json::from_str(text).map_err(|e| format!("解析 JSON 失败: {}", e))
+}
+
+pub fn records_from_csv(text: &str) -> Result<Vec<ActivityRecord>, String> {
+    let mut reader = csv::Reader::from_reader(text.as_bytes());
+    let mut out = Vec::new();
+    for (i, r) in reader.deserialize().enumerate() {
+        // 行号 +2：表头占一行，且从 1 开始计数
+        out.push(r.map_err(|e| format!("解析 CSV 第 {} 行失败: {}", i + 2, e))?);
+    }
+    Ok(out)
+}
+
+pub fn write_parquet(records: &[ActivityRecord], path: &str) -> Result<(), String> {
+    let mut df = DataFrame::new(vec![
+        Column::new("app_name".into(), records.iter().map(|r| r.app_name.as_str()).collect::<Vec<_>>()),
+        Column::new("window_title".into(), records.iter().map(|r| r.window_title.as_str()).collect::<Vec<_>>()),
+        Column::new("start_time".into(), records.iter().map(|r| r.start_time.as_str()).collect::<Vec<_>>()),
+        Column::new("duration_seconds".into(), records.iter().map(|r| r.duration_seconds).collect::<Vec<_>>()),
+        Column::new("is_idle".into(), records.iter().map(|r| r.is_idle).collect::<Vec<_>>()),
+        Column::new("category".into(), records.iter().map(|r| r.category.as_deref()).collect::<Vec<_>>()),
+        Column::new("project".into(), records.iter().map(|r| r.project.as_deref()).collect::<Vec<_>>()),
+    ])
+    .map_err(|e| e.to_string())?;
+    let file = File::create(path).map_err(|e| format!("无法创建文件 {}: {}", path, e))?;
+    ParquetWriter::new(file)
+        .with_compression(ParquetCompression::Zstd(None))
+        .finish(&mut df)
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn read_parquet(path: &str) -> Result<Vec<ActivityRecord>, String> {
+    let file = File::open(path).map_err(|e| format!("无法打开文件 {}: {}", path, e))?;
+    let df = ParquetReader::new(file).finish().map_err(|e| e.to_string())?;
+
+    let str_col = |name: &str| -> Result<Vec<Option<String>>, String> {
+        match df.column(name) {
+            Ok(c) => {
+                let s = c.as_materialized_series().cast(&DataType::String).map_err(|e| e.to_string())?;
+                let ca = s.str().map_err(|e| e.to_string())?;
+                Ok(ca.into_iter().map(|v| v.map(str::to_string)).collect())
+            }
+            // 可选列缺失时按空值处理
+            Err(_) => Ok(vec![None; df.height()]),
+        }
+    };
+    let app_names = str_col("app_name")?;
+    let titles = str_col("window_title")?;
+    let start_times = str_col("start_time")?;
+    let categories = str_col("category")?;
+    let projects = str_col("project")?;
+    let durations: Vec<Option<i64>> = df
+        .column("duration_seconds")
+        .map_err(|e| e.to_string())?
+        .as_materialized_series()
+        .cast(&DataType::Int64)
+        .map_err(|e| e.to_string())?
+        .i64()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .collect();
+    let idles: Vec<Option<bool>> = match df.column("is_idle") {
+        Ok(c) => c
+            .as_materialized_series()
+            .cast(&DataType::Boolean)
+            .map_err(|e| e.to_string())?
+            .bool()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .collect(),
+        Err(_) => vec![None; df.height()],
+    };
+
+    let mut out = Vec::with_capacity(df.height());
+    for i in 0..df.height() {
+        let (Some(app_name), Some(start_time), Some(duration_seconds)) =
+            (app_names[i].clone(), start_times[i].clone(), durations[i])
+        else {
+            return Err(format!("Parquet 第 {} 行缺少必填字段", i + 1));
+        };
+        out.push(ActivityRecord {
+            app_name,
+            window_title: titles[i].clone().unwrap_or_default(),
+            start_time,
+            duration_seconds,
+            is_idle: idles[i].unwrap_or(false),
+            category: categories[i].clone(),
+            project: projects[i].clone(),
+        });
+    }
+    Ok(out)
+}
+
+// ==================== 导入 ====================
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub inserted: usize,
+    /// 本地已存在、但导入的时长更长而更新时长的记录数
+    pub updated: usize,
+    /// 本地已存在（开始时间、程序名、窗口标题均相同）而跳过的记录数
+    pub skipped: usize,
+}
+
+/// 合并导入记录；按 (start_time, app_name, window_title) 去重，可重复导入同一文件
+///
+/// 已存在的记录取两边较长的时长，旧版本导出的未结束分段可以被之后的完整记录修正。
+///
+/// 标题先按本地隐私规则处理再去重、写入；分类按名称对应本地分类，本地没有同名分类时按本地规则重新分类。
+pub fn merge_records(conn: &mut Connection, records: Vec<ActivityRecord>) -> Result<ImportSummary, String> {
+    let category_ids: HashMap<String, i64> = categories::list_categories(conn)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|c| (c.name.to_lowercase(), c.id))
+        .collect();
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut summary = ImportSummary::default();
+    {
+        let mut exists = tx
+            .prepare("SELECT id, duration_seconds FROM activity_log WHERE start_time = ?1 AND app_name = ?2 AND COALESCE(window_title, '') = ?3 LIMIT 1")
+            .map_err(|e| e.to_string())?;
+        let mut extend = tx
+            .prepare("UPDATE activity_log SET duration_seconds = ?1 WHERE id = ?2")
+            .map_err(|e| e.to_string())?;
+        let mut insert = tx
+            .prepare(
+                "INSERT INTO activity_log (app_name, window_title, start_time, duration_seconds, is_idle, is_open, category_id, project)
+                 VALUES (?1, ?2, ?3, ?4, ?5, 0, ?6, ?7)",
+            )
+            .map_err(|e| e.to_string())?;
+
+        for (i, mut r) in records.into_iter().enumerate() {
+            if r.app_name.is_empty() {
+                return Err(format!("第 {} 条记录缺少 app_name", i + 1));
+            }
+            if r.duration_seconds < 0 {
+                return Err(format!("第 {} 条记录的 duration_seconds 为负数", i + 1));
+            }
+            // 统一成追踪器写入时的 UTC RFC3339 形式，保证去重比较的是同一种写法
+            let start_time = DateTime::parse_from_rfc3339(&r.start_time)
+                .map_err(|e| format!("第 {} 条记录的 start_time 无效 '{}': {}", i + 1, r.start_time, e))?
+                .with_timezone(&Utc)
+                .to_rfc3339();
+            // 导入的文件可能来自没有脱敏规则的机器，写入前同样要经过本地规则
+            r.window_title = privacy::redact_stored(&tx, &r.app_name, &r.window_title)?;
+
+            let found: Option<(i64, i64)> = exists
+                .query_row(params![start_time, r.app_name, r.window_title], |row| Ok((row.get(0)?, row.get(1)?)))
+                .optional()
+                .map_err(|e| e.to_string())?;
+            if let Some((id, duration)) = found {
+                if r.duration_seconds > duration {
+                    extend.execute(params![r.duration_seconds, id]).map_err(|e| e.to_string())?;
+                    summary.updated += 1;
+                } else {
+                    summary.skipped += 1;
+                }
+                continue;
+            }
+
+            let class = if r.is_idle {
+                categories::Classification::default()
+            } else {
+                match r.category.as_ref().and_then(|n| category_ids.get(&n.to_lowercase())) {
+                    Some(id) => categories::Classification { category_id: Some(*id), project: r.project.clone() },
+                    None => {
+                        let mut c = categories::classify(&tx, &r.app_name, &r.window_title).map_err(|e| e.to_string())?;
+                        if r.project.is_some() { c.project = r.project.clone(); }
+                        c
+                    }
+                }
+            };
+            insert
+                .execute(params![r.app_name, r.window_title, start_time, r.duration_seconds, r.is_idle, class.category_id, class.project])
+                .map_err(|e| e.to_string())?;
+            summary.inserted += 1;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(summary)
+}
+
+pub fn read_records(path: &str, format: ExportFormat) -> Result<Vec<ActivityRecord>, String> {
+    match format {
+        ExportFormat::Parquet => read_parquet(path),
+        ExportFormat::Json | ExportFormat::Csv => {
+            let text = std::fs::read_to_string(path).map_err(|e| format!("无法读取文件 {}: {}", path, e))?;
+            if format == ExportFormat::Json { records_from_json(&text) } else { records_from_csv(&text) }
+        }
+    }
+}
+
+// ==================== Tauri 命令 ====================
+
+/// 导出本地日期闭区间内的活动记录
+///
+/// 传入 `file_path` 时写入文件并返回该路径；否则直接返回 JSON / CSV 文本（Parquet 必须指定文件）。
+/// `format` 缺省时按 `file_path` 的扩展名推断，都没有则为 JSON。
+#[tauri::command]
+pub async fn export_activities(
+    app_handle: tauri::AppHandle,
+    start_date: String,
+    end_date: String,
+    format: Option<String>,
+    file_path: Option<String>,
+) -> Result<String, String> {
+    use tauri::Manager;
+    let format = match (&format, &file_path) {
+        (Some(f), _) => ExportFormat::parse(f)?,
+        (None, Some(p)) => ExportFormat::from_path(p)?,
+        (None, None) => ExportFormat::Json,
+    };
+    tokio::task::spawn_blocking(move || {
+        let records = {
+            let state: State<DbState> = app_handle.state();
+            let conn = state.db.lock().unwrap();
+            load_records(&conn, &start_date, &end_date)?
+        };
+        match (format, file_path) {
+            (ExportFormat::Parquet, Some(path)) => {
+                write_parquet(&records, &path)?;
+                Ok(path)
+            }
+            (ExportFormat::Parquet, None) => Err("导出 Parquet 需要指定 file_path".into()),
+            (fmt, path) => {
+                let text = if fmt == ExportFormat::Json { records_to_json(&records)? } else { records_to_csv(&records)? };
+                match path {
+                    Some(path) => {
+                        std::fs::write(&path, text).map_err(|e| format!("写入文件失败 {}: {}", path, e))?;
+                        Ok(path)
+                    }
+                    None => Ok(text),
+                }
+            }
+        }
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// 从另一台机器导出的文件合并活动记录；格式缺省时按扩展名推断
+#[tauri::command]
+pub async fn import_activities(app_handle: tauri::AppHandle, file_path: String, format: Option<String>) -> Result<ImportSummary, String> {
+    use tauri::Manager;
+    let format = match format {
+        Some(f) => ExportFormat::parse(&f)?,
+        None => ExportFormat::from_path(&file_path)?,
+    };
+    tokio::task::spawn_blocking(move || {
+        let records = read_records(&file_path, format)?;
+        let state: State<DbState> = app_handle.state();
+        let mut conn = state.db.lock().unwrap();
+        merge_records(&mut conn, records)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_db(&conn).unwrap();
+        conn
+    }
+
+    fn record(start_time: &str, duration_seconds: i64) -> ActivityRecord {
+        ActivityRecord {
+            app_name: "Code".into(),
+            window_title: "main.rs".into(),
+            start_time: start_time.into(),
+            duration_seconds,
+            is_idle: false,
+            category: None,
+            project: None,
+        }
+    }
+
+    #[test]
+    fn export_skips_open_segments() {
+        let conn = test_db();
+        conn.execute(
+            "INSERT INTO activity_log (app_name, window_title, start_time, duration_seconds, is_open) VALUES
+             ('Code', 'a', '2026-01-01T10:00:00+00:00', 100, 0),
+             ('Code', 'b', '2026-01-01T11:00:00+00:00', 20, 1)",
+            [],
+        )
+        .unwrap();
+        let records = load_records(&conn, "2025-12-31", "2026-01-02").unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].window_title, "a");
+    }
+
+    #[test]
+    fn merge_keeps_the_longer_duration() {
+        let mut conn = test_db();
+        let s = merge_records(&mut conn, vec![record("2026-01-01T10:00:00Z", 20)]).unwrap();
+        assert_eq!((s.inserted, s.updated, s.skipped), (1, 0, 0));
+
+        // 之后导出的完整记录覆盖截断的时长；更短的重复记录不影响
+        let s = merge_records(&mut conn, vec![record("2026-01-01T12:00:00+02:00", 300)]).unwrap();
+        assert_eq!((s.inserted, s.updated, s.skipped), (0, 1, 0));
+        let s = merge_records(&mut conn, vec![record("2026-01-01T10:00:00Z", 40)]).unwrap();
+        assert_eq!((s.inserted, s.updated, s.skipped), (0, 0, 1));
+
+        let duration: i64 = conn.query_row("SELECT duration_seconds FROM activity_log", [], |r| r.get(0)).unwrap();
+        assert_eq!(duration, 300);
+    }
+}
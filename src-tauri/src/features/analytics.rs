@@ -0,0 +1,399 @@
+use crate::db::DbState;
+use crate::reports::{self, local_hour_start, ReportSegment};
+use chrono::{DateTime, Local, Timelike, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tauri::State;
+
+/// 相邻分段之间不超过该间隔视为连续（采样间隔 2 秒，不超过 1 秒的分段会被删除）
+const CONTIGUOUS_GAP_SECS: i64 = 5;
+
+/// 默认深度工作最短时长
+pub const DEFAULT_DEEP_WORK_MIN_MINUTES: u32 = 25;
+
+/// 默认可忽略的中断时长（累计）
+pub const DEFAULT_INTERRUPTION_TOLERANCE_SECS: u32 = 120;
+
+/// 分析参数；缺省字段使用默认值
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FocusOptions {
+    pub deep_work_min_minutes: Option<u32>,
+    /// 深度工作块内累计不超过该时长的非专注活动/空白会被忽略
+    pub interruption_tolerance_secs: Option<u32>,
+    /// 视为专注的分类；缺省时任意已分类的活动都算
+    pub focus_category_ids: Option<Vec<i64>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HourSwitchCount {
+    /// 本地小时起点（UTC RFC3339）
+    pub hour_start: String,
+    /// 本地时间的小时 0-23
+    pub hour: u32,
+    pub switches: u32,
+}
+
+/// 某个程序/分类最长的一段不间断使用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusStreak {
+    pub key: String,
+    pub category_id: Option<i64>,
+    pub start_time: String,
+    pub end_time: String,
+    pub seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeepWorkBlock {
+    pub start_time: String,
+    pub end_time: String,
+    /// 块内专注活动的累计时长（不含被忽略的中断）
+    pub focused_seconds: i64,
+    pub interruptions: u32,
+    /// 块内耗时最多的分类
+    pub main_category_id: Option<i64>,
+    pub main_category: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusAnalytics {
+    pub start_date: String,
+    pub end_date: String,
+    pub active_seconds: i64,
+    /// 已被保留策略压缩成每小时汇总的时长；这部分没有先后顺序，不参与分析
+    pub compacted_seconds: i64,
+    pub context_switches: u32,
+    pub switches_by_hour: Vec<HourSwitchCount>,
+    pub app_streaks: Vec<FocusStreak>,
+    pub category_streaks: Vec<FocusStreak>,
+    pub deep_work: Vec<DeepWorkBlock>,
+    pub deep_work_seconds: i64,
+}
+
+fn gap_secs(from: DateTime<Utc>, to: DateTime<Utc>) -> i64 {
+    to.signed_duration_since(from).num_seconds().max(0)
+}
+
+/// 相邻两个非空闲分段的程序不同即计一次切换；中间有空闲或空白时不算
+pub fn count_switches(segments: &[ReportSegment]) -> BTreeMap<DateTime<Utc>, u32> {
+    let mut by_hour = BTreeMap::new();
+    let mut prev: Option<&ReportSegment> = None;
+    for seg in segments {
+        if seg.is_idle {
+            prev = None;
+            continue;
+        }
+        if let Some(p) = prev {
+            if p.app_name != seg.app_name && gap_secs(p.end, seg.start) <= CONTIGUOUS_GAP_SECS {
+                *by_hour.entry(local_hour_start(seg.start)).or_insert(0) += 1;
+            }
+        }
+        prev = Some(seg);
+    }
+    by_hour
+}
+
+/// (分类, 开始, 结束)
+type StreakSpan = (Option<i64>, DateTime<Utc>, DateTime<Utc>);
+/// 正在累计的一段：(key, 分类, 开始, 结束)
+type OpenRun = (String, Option<i64>, DateTime<Utc>, DateTime<Utc>);
+
+/// 按 key 合并连续分段，返回每个 key 最长的一段
+fn longest_streaks<F>(segments: &[ReportSegment], key_of: F) -> HashMap<String, StreakSpan>
+where
+    F: Fn(&ReportSegment) -> Option<(String, Option<i64>)>,
+{
+    let mut best: HashMap<String, StreakSpan> = HashMap::new();
+    let mut run: Option<OpenRun> = None;
+    let mut close = |run: Option<OpenRun>| {
+        if let Some((key, cat, start, end)) = run {
+            let entry = best.entry(key).or_insert((cat, start, start));
+            if end - start > entry.2 - entry.1 {
+                *entry = (cat, start, end);
+            }
+        }
+    };
+    for seg in segments {
+        let key = if seg.is_idle { None } else { key_of(seg) };
+        match (&mut run, key) {
+            (Some(r), Some((k, _))) if r.0 == k && gap_secs(r.3, seg.start) <= CONTIGUOUS_GAP_SECS => {
+                r.3 = r.3.max(seg.end);
+            }
+            (_, key) => {
+                close(run.take());
+                run = key.map(|(k, cat)| (k, cat, seg.start, seg.end));
+            }
+        }
+    }
+    close(run);
+    best
+}
+
+fn to_streaks(map: HashMap<String, StreakSpan>) -> Vec<FocusStreak> {
+    let mut v: Vec<FocusStreak> = map
+        .into_iter()
+        .map(|(key, (category_id, start, end))| FocusStreak {
+            key,
+            category_id,
+            start_time: start.to_rfc3339(),
+            end_time: end.to_rfc3339(),
+            seconds: gap_secs(start, end),
+        })
+        .collect();
+    v.sort_by(|a, b| b.seconds.cmp(&a.seconds).then_with(|| a.key.cmp(&b.key)));
+    v
+}
+
+struct OpenBlock {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    focused: i64,
+    interruptions: u32,
+    /// 自上一段专注活动结束后累计的中断时长
+    pending: i64,
+    by_category: HashMap<Option<i64>, i64>,
+}
+
+/// (开始, 结束, 专注秒数, 中断次数, 主要分类)
+type DeepWorkSpan = (DateTime<Utc>, DateTime<Utc>, i64, u32, Option<i64>);
+
+/// 深度工作块：连续的专注活动，累计不超过 tolerance 的中断会被忽略
+pub fn deep_work_blocks(
+    segments: &[ReportSegment],
+    focus: &dyn Fn(&ReportSegment) -> bool,
+    min_seconds: i64,
+    tolerance_secs: i64,
+) -> Vec<DeepWorkSpan> {
+    let mut out = Vec::new();
+    let mut block: Option<OpenBlock> = None;
+    let mut finish = |b: Option<OpenBlock>| {
+        if let Some(b) = b {
+            if b.focused >= min_seconds {
+                let main = b.by_category.into_iter().max_by_key(|(_, s)| *s).and_then(|(c, _)| c);
+                out.push((b.start, b.end, b.focused, b.interruptions, main));
+            }
+        }
+    };
+    for seg in segments {
+        let secs = seg.seconds();
+        if focus(seg) {
+            if let Some(b) = block.as_mut() {
+                let gap = gap_secs(b.end, seg.start);
+                let pending = b.pending + if gap > CONTIGUOUS_GAP_SECS { gap } else { 0 };
+                if pending <= tolerance_secs {
+                    if pending > 0 { b.interruptions += 1; }
+                    b.pending = 0;
+                    b.end = seg.end;
+                    b.focused += secs;
+                    *b.by_category.entry(seg.category_id).or_default() += secs;
+                    continue;
+                }
+            }
+            finish(block.take());
+            let mut by_category = HashMap::new();
+            by_category.insert(seg.category_id, secs);
+            block = Some(OpenBlock { start: seg.start, end: seg.end, focused: secs, interruptions: 0, pending: 0, by_category });
+        } else if let Some(b) = block.as_mut() {
+            let gap = gap_secs(b.end, seg.start);
+            b.pending += secs + if gap > CONTIGUOUS_GAP_SECS { gap } else { 0 };
+            if b.pending > tolerance_secs {
+                finish(block.take());
+            }
+        }
+    }
+    finish(block);
+    out
+}
+
+/// 本地日期闭区间的专注与分心分析
+pub fn focus_analytics(conn: &Connection, start_date: &str, end_date: &str, options: &FocusOptions) -> Result<FocusAnalytics, String> {
+    let all = reports::load_segments(conn, start_date, end_date, true)?;
+    let compacted_seconds = all.iter().filter(|s| s.from_summary && !s.is_idle).map(|s| s.seconds()).sum();
+    let segments: Vec<ReportSegment> = all.into_iter().filter(|s| !s.from_summary).collect();
+    let active_seconds = segments.iter().filter(|s| !s.is_idle).map(|s| s.seconds()).sum();
+
+    let names: HashMap<i64, String> = crate::categories::list_categories(conn)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|c| (c.id, c.name))
+        .collect();
+
+    let switches = count_switches(&segments);
+    let context_switches = switches.values().sum();
+    let switches_by_hour = switches
+        .into_iter()
+        .map(|(hour, switches)| HourSwitchCount {
+            hour_start: hour.to_rfc3339(),
+            hour: hour.with_timezone(&Local).hour(),
+            switches,
+        })
+        .collect();
+
+    let app_streaks = to_streaks(longest_streaks(&segments, |s| Some((s.app_name.clone(), s.category_id))));
+    let category_streaks = to_streaks(longest_streaks(&segments, |s| {
+        s.category_id.map(|id| (names.get(&id).cloned().unwrap_or_else(|| format!("#{}", id)), Some(id)))
+    }));
+
+    let focus_set: Option<HashSet<i64>> = options.focus_category_ids.as_ref().map(|v| v.iter().copied().collect());
+    let is_focus = |s: &ReportSegment| {
+        !s.is_idle
+            && match (s.category_id, &focus_set) {
+                (Some(id), Some(set)) => set.contains(&id),
+                (Some(_), None) => true,
+                (None, _) => false,
+            }
+    };
+    let min_seconds = options.deep_work_min_minutes.unwrap_or(DEFAULT_DEEP_WORK_MIN_MINUTES) as i64 * 60;
+    let tolerance = options.interruption_tolerance_secs.unwrap_or(DEFAULT_INTERRUPTION_TOLERANCE_SECS) as i64;
+    let deep_work: Vec<DeepWorkBlock> = deep_work_blocks(&segments, &is_focus, min_seconds, tolerance)
+        .into_iter()
+        .map(|(start, end, focused_seconds, interruptions, main)| DeepWorkBlock {
+            start_time: start.to_rfc3339(),
+            end_time: end.to_rfc3339(),
+            focused_seconds,
+            interruptions,
+            main_category_id: main,
+            main_category: main.and_then(|id| names.get(&id).cloned()),
+        })
+        .collect();
+    let deep_work_seconds = deep_work.iter().map(|b| b.focused_seconds).sum();
+
+    Ok(FocusAnalytics {
+        start_date: start_date.to_string(),
+        end_date: end_date.to_string(),
+        active_seconds,
+        compacted_seconds,
+        context_switches,
+        switches_by_hour,
+        app_streaks,
+        category_streaks,
+        deep_work,
+        deep_work_seconds,
+    })
+}
+
+// ==================== Tauri 命令 ====================
+
+/// 包含 date 的某天（默认）或某周的专注分析
+#[tauri::command]
+pub fn activity_focus_analytics(
+    state: State<DbState>,
+    date: String,
+    period: Option<String>,
+    options: Option<FocusOptions>,
+) -> Result<FocusAnalytics, String> {
+    let period = period.unwrap_or_else(|| "day".into());
+    if period != "day" && period != "week" {
+        return Err(format!("period must be day or week (got {})", period));
+    }
+    let (start, end) = reports::period_bounds(&date, &period)?;
+    let conn = state.db.lock().unwrap();
+    focus_analytics(&conn, &start, &end, &options.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    /// 本地 2026-03-10 09:00，按本地整点统计的结果与时区无关
+    fn base() -> DateTime<Utc> {
+        (crate::db::local_day_start("2026-03-10").unwrap() + Duration::hours(9)).with_timezone(&Utc)
+    }
+
+    fn seg(app: &str, category_id: Option<i64>, start: i64, end: i64) -> ReportSegment {
+        ReportSegment {
+            app_name: app.into(),
+            window_title: String::new(),
+            category_id,
+            project: None,
+            is_idle: false,
+            start: base() + Duration::seconds(start),
+            end: base() + Duration::seconds(end),
+            from_summary: false,
+        }
+    }
+
+    fn idle(start: i64, end: i64) -> ReportSegment {
+        ReportSegment { is_idle: true, ..seg("idle", None, start, end) }
+    }
+
+    #[test]
+    fn switches_need_adjacent_different_apps() {
+        let segments = vec![
+            seg("code", None, 0, 100),
+            seg("chrome", None, 100, 200),
+            seg("chrome", None, 200, 300),
+            // 小于 CONTIGUOUS_GAP_SECS 的空白仍算连续
+            seg("code", None, 303, 400),
+            // 空白太长、或中间隔着空闲都不算切换
+            seg("slack", None, 420, 500),
+            idle(500, 600),
+            seg("code", None, 600, 3605),
+            seg("slack", None, 3605, 3700),
+        ];
+        let by_hour = count_switches(&segments);
+        let expected: BTreeMap<_, _> = [(base(), 2), (base() + Duration::hours(1), 1)].into_iter().collect();
+        assert_eq!(by_hour, expected);
+    }
+
+    #[test]
+    fn streaks_merge_short_gaps_and_break_on_long_ones() {
+        let segments = vec![
+            seg("code", Some(1), 0, 100),
+            seg("code", Some(1), 102, 300),
+            seg("chrome", Some(3), 300, 400),
+            seg("code", Some(1), 400, 1000),
+            idle(1000, 1100),
+            seg("code", Some(1), 2000, 2650),
+            seg("code", Some(1), 2660, 3000),
+        ];
+        let apps = to_streaks(longest_streaks(&segments, |s| Some((s.app_name.clone(), s.category_id))));
+        let summary: Vec<_> = apps.iter().map(|s| (s.key.as_str(), s.seconds)).collect();
+        assert_eq!(summary, vec![("code", 650), ("chrome", 100)]);
+        assert_eq!(apps[0].start_time, (base() + Duration::seconds(2000)).to_rfc3339());
+
+        // 未分类的分段打断分类连续段
+        let segments = vec![
+            seg("code", Some(1), 0, 300),
+            seg("term", Some(1), 300, 500),
+            seg("chrome", None, 500, 510),
+            seg("code", Some(1), 510, 900),
+        ];
+        let cats = longest_streaks(&segments, |s| s.category_id.map(|id| (id.to_string(), Some(id))));
+        assert_eq!(cats["1"], (Some(1), base(), base() + Duration::seconds(500)));
+    }
+
+    #[test]
+    fn deep_work_ignores_short_interruptions() {
+        let segments = vec![
+            // 块 1：一次 60 秒分心和一段 30 秒空白都在容忍范围内
+            seg("code", Some(1), 0, 1000),
+            seg("chrome", Some(3), 1000, 1060),
+            seg("code", Some(1), 1060, 1800),
+            seg("code", Some(1), 1830, 2400),
+            // 300 秒空白超出容忍，块 1 结束；随后的块不足最短时长被丢弃
+            seg("code", Some(1), 2700, 3300),
+            seg("chrome", Some(3), 3300, 3500),
+            // 块 2：2 秒空白视为连续，不算中断
+            seg("code", Some(1), 3500, 4000),
+            seg("term", Some(2), 4002, 5300),
+            // 中断按累计计算：两段 70 秒合计超出容忍
+            seg("chrome", Some(3), 5300, 5370),
+            idle(5370, 5440),
+            seg("code", Some(1), 5440, 7000),
+        ];
+        let focus = |s: &ReportSegment| !s.is_idle && matches!(s.category_id, Some(1) | Some(2));
+        let at = |secs: i64| base() + Duration::seconds(secs);
+        let blocks = deep_work_blocks(&segments, &focus, 1500, 120);
+        assert_eq!(
+            blocks,
+            vec![
+                (at(0), at(2400), 2310, 2, Some(1)),
+                (at(3500), at(5300), 1798, 0, Some(2)),
+                (at(5440), at(7000), 1560, 0, Some(1)),
+            ]
+        );
+    }
+}
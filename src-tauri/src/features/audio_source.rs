@@ -0,0 +1,637 @@
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+use crate::sample_format::{decode_interleaved, Endianness, SampleEncoding, SampleFormat};
+use serde::{Deserialize, Serialize};
+
+/// 采集到的 PCM 格式（样本统一为交错排列的 f32）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioFormat {
+    pub sample_rate: u32,
+    pub channels: usize,
+}
+
+/// 一次读取的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadStatus {
+    /// 已向缓冲追加了新样本
+    Data,
+    /// 暂时没有数据（设备静默、文件按实时节奏等待）
+    Waiting,
+    /// 数据已结束（文件播放完毕且不循环）
+    Ended,
+}
+
+/// 频谱分析的音频来源；read 只应短暂阻塞（几十毫秒内），以便采集线程及时响应停止
+pub trait AudioSource {
+    fn format(&self) -> AudioFormat;
+    /// 用于状态提示的来源描述
+    fn describe(&self) -> String;
+    /// 把交错的 f32 样本追加到 out
+    fn read(&mut self, out: &mut Vec<f32>) -> Result<ReadStatus, String>;
+}
+
+/// 前端选择的音频来源
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AudioSourceSpec {
+    /// 平台默认：Windows 为 WASAPI 环回，Linux 为 PulseAudio/PipeWire 监视源
+    #[default]
+    Default,
+    WasapiLoopback,
+    /// device 为 PulseAudio 源名称，缺省为 @DEFAULT_MONITOR@
+    PulseMonitor {
+        #[serde(default)]
+        device: Option<String>,
+    },
+    /// 解码 WAV/FLAC/MP3 文件，按实时节奏送入分析器
+    File {
+        path: String,
+        #[serde(default)]
+        looping: bool,
+    },
+    /// 随机生成的演示数据
+    Simulation,
+}
+
+impl AudioSourceSpec {
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            AudioSourceSpec::Default => "default",
+            AudioSourceSpec::WasapiLoopback => "wasapi_loopback",
+            AudioSourceSpec::PulseMonitor { .. } => "pulse_monitor",
+            AudioSourceSpec::File { .. } => "file",
+            AudioSourceSpec::Simulation => "simulation",
+        }
+    }
+
+    /// 把 Default 解析为当前平台的具体来源
+    pub fn resolve(&self) -> AudioSourceSpec {
+        match self {
+            AudioSourceSpec::Default => {
+                if cfg!(target_os = "windows") {
+                    AudioSourceSpec::WasapiLoopback
+                } else {
+                    AudioSourceSpec::PulseMonitor { device: None }
+                }
+            }
+            other => other.clone(),
+        }
+    }
+}
+
+/// 打开来源；Simulation 不产生 PCM，由调用方单独处理
+pub fn open_source(spec: &AudioSourceSpec) -> Result<Box<dyn AudioSource>, String> {
+    match spec.resolve() {
+        AudioSourceSpec::WasapiLoopback => open_wasapi_loopback(),
+        AudioSourceSpec::PulseMonitor { device } => open_pulse_monitor(device.as_deref()),
+        AudioSourceSpec::File { path, looping } => Ok(Box::new(FileSource::open(&path, looping)?)),
+        AudioSourceSpec::Simulation => Err("simulation 不是 PCM 来源".into()),
+        AudioSourceSpec::Default => unreachable!("resolve 已处理 Default"),
+    }
+}
+
+// ==================== Windows (WASAPI 环回) ====================
+
+#[cfg(not(target_os = "windows"))]
+fn open_wasapi_loopback() -> Result<Box<dyn AudioSource>, String> {
+    Err("WASAPI loopback is only available on Windows".into())
+}
+
+#[cfg(target_os = "windows")]
+fn open_wasapi_loopback() -> Result<Box<dyn AudioSource>, String> {
+    Ok(Box::new(WasapiLoopbackSource::open()?))
+}
+
+/// WASAPI 环回捕获默认渲染设备；COM 对象不能跨线程，需在采集线程内打开
+#[cfg(target_os = "windows")]
+pub struct WasapiLoopbackSource {
+    audio_client: windows::Win32::Media::Audio::IAudioClient,
+    capture: windows::Win32::Media::Audio::IAudioCaptureClient,
+    format: AudioFormat,
+    sample_format: SampleFormat,
+    description: String,
+}
+
+#[cfg(target_os = "windows")]
+impl WasapiLoopbackSource {
+    pub fn open() -> Result<Self, String> {
+        use windows::Win32::Media::Audio::*;
+        use windows::Win32::System::Com::*;
+        unsafe {
+            // 初始化 COM
+            CoInitializeEx(None, COINIT_MULTITHREADED).ok().map_err(|e| format!("CoInitializeEx failed: {e:?}"))?;
+
+            // 创建设备枚举器
+            let mmdev_enum: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
+                .map_err(|e| format!("CoCreateInstance(IMMDeviceEnumerator) failed: {e:?}"))?;
+
+            // 默认渲染设备（输出）：优先使用 eMultimedia，失败再回退 eConsole
+            let (device, role_used) = match mmdev_enum.GetDefaultAudioEndpoint(eRender, eMultimedia) {
+                Ok(d) => (d, eMultimedia),
+                Err(_) => match mmdev_enum.GetDefaultAudioEndpoint(eRender, eConsole) {
+                    Ok(d) => (d, eConsole),
+                    Err(_) => (
+                        mmdev_enum
+                            .GetDefaultAudioEndpoint(eRender, eCommunications)
+                            .map_err(|e| format!("GetDefaultAudioEndpoint failed: {e:?}"))?,
+                        eCommunications,
+                    ),
+                },
+            };
+
+            // 激活 IAudioClient
+            let audio_client: IAudioClient = device.Activate::<IAudioClient>(CLSCTX_ALL, None)
+                .map_err(|e| format!("Activate(IAudioClient) failed: {e:?}"))?;
+
+            // 获取混音格式
+            let mix_format = audio_client.GetMixFormat().map_err(|e| format!("GetMixFormat failed: {e:?}"))?;
+            let format = &*mix_format;
+            // 处理常见格式：IEEE float (3)、PCM (1)、以及可扩展格式 (0xFFFE) 的 PCM/float 子类型
+            const WAVE_FORMAT_PCM_TAG: u16 = 1;
+            const WAVE_FORMAT_IEEE_FLOAT_TAG: u16 = 3;
+            const WAVE_FORMAT_EXTENSIBLE_TAG: u16 = 0xFFFE;
+            let wtag = format.wFormatTag;
+            // 为 WAVEFORMATEXTENSIBLE 做子类型判断
+            let mut is_float = wtag == WAVE_FORMAT_IEEE_FLOAT_TAG;
+            let mut is_pcm = wtag == WAVE_FORMAT_PCM_TAG || wtag == WAVE_FORMAT_EXTENSIBLE_TAG;
+            let mut valid_bits: u16 = format.wBitsPerSample; // 对 EXTENSIBLE 会被覆盖
+            if wtag == WAVE_FORMAT_EXTENSIBLE_TAG {
+                let wfex: &WAVEFORMATEXTENSIBLE = &*(mix_format as *const _ as *const WAVEFORMATEXTENSIBLE);
+                // KSDATAFORMAT_SUBTYPE_IEEE_FLOAT 与 KSDATAFORMAT_SUBTYPE_PCM 的 GUID 常量
+                use windows::core::GUID;
+                const SUBTYPE_IEEE_FLOAT: GUID = GUID::from_values(
+                    0x00000003, 0x0000, 0x0010, [0x80, 0x00, 0x00, 0xaa, 0x00, 0x38, 0x9b, 0x71],
+                );
+                const SUBTYPE_PCM: GUID = GUID::from_values(
+                    0x00000001, 0x0000, 0x0010, [0x80, 0x00, 0x00, 0xaa, 0x00, 0x38, 0x9b, 0x71],
+                );
+                // 从 packed 字段中安全读取值
+                let sub: GUID = std::ptr::read_unaligned(std::ptr::addr_of!(wfex.SubFormat));
+                if sub == SUBTYPE_IEEE_FLOAT { is_float = true; }
+                if sub == SUBTYPE_PCM { is_pcm = true; }
+                // 对于可扩展格式，优先使用有效位数
+                let vbps: u16 = std::ptr::read_unaligned(std::ptr::addr_of!(wfex.Samples.wValidBitsPerSample));
+                if vbps != 0 { valid_bits = vbps; }
+            }
+            if !is_float && !is_pcm {
+                return Err("Unsupported audio format (neither float nor PCM)".into());
+            }
+            let sample_rate = format.nSamplesPerSec;
+            let channels = format.nChannels as usize;
+            let block_align = format.nBlockAlign as usize; // 每帧（所有声道）字节数
+            let bytes_per_sample = block_align / channels.max(1);
+            let sample_format = SampleFormat {
+                encoding: if is_float {
+                    SampleEncoding::Float
+                } else if bytes_per_sample == 1 {
+                    SampleEncoding::UnsignedInt
+                } else {
+                    SampleEncoding::SignedInt
+                },
+                container_bits: (bytes_per_sample * 8) as u16,
+                valid_bits: if is_float { 0 } else { valid_bits },
+                endianness: Endianness::Little,
+                channels,
+            };
+            // 在开始捕获前拒绝无法解码的格式，而不是在读取时静默丢包
+            sample_format.validate()?;
+
+            // 配置环回捕获
+            let hns_buffer_duration = 10000000; // 1s
+            audio_client.Initialize(
+                AUDCLNT_SHAREMODE_SHARED,
+                AUDCLNT_STREAMFLAGS_LOOPBACK,
+                hns_buffer_duration,
+                0,
+                format,
+                None,
+            ).map_err(|e| format!("IAudioClient::Initialize failed: {e:?}"))?;
+
+            // 创建捕获客户端
+            let capture: IAudioCaptureClient = audio_client.GetService::<IAudioCaptureClient>()
+                .map_err(|e| format!("GetService(IAudioCaptureClient) failed: {e:?}"))?;
+
+            // 开始捕获
+            audio_client.Start().map_err(|e| format!("Start failed: {e:?}"))?;
+
+            let bits_copy = format.wBitsPerSample; // 避免对 packed 字段的直接引用
+            let role_name = if role_used == eMultimedia { "multimedia" } else if role_used == eConsole { "console" } else { "communications" };
+            let description = format!(
+                "WASAPI loopback (role={}): tag={}, bits={}, validBits={}, ch={}, bytesPerSample={}",
+                role_name, wtag, bits_copy, valid_bits, channels, bytes_per_sample
+            );
+            Ok(WasapiLoopbackSource {
+                audio_client,
+                capture,
+                format: AudioFormat { sample_rate, channels },
+                sample_format,
+                description,
+            })
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl AudioSource for WasapiLoopbackSource {
+    fn format(&self) -> AudioFormat {
+        self.format
+    }
+
+    fn describe(&self) -> String {
+        self.description.clone()
+    }
+
+    fn read(&mut self, acc: &mut Vec<f32>) -> Result<ReadStatus, String> {
+        use windows::Win32::Media::Audio::AUDCLNT_BUFFERFLAGS_SILENT;
+        unsafe {
+            let mut packet_len: u32 = self.capture.GetNextPacketSize().unwrap_or(0);
+            if packet_len == 0 {
+                std::thread::sleep(std::time::Duration::from_millis(5));
+                return Ok(ReadStatus::Waiting);
+            }
+            while packet_len > 0 {
+                let mut data_ptr: *mut u8 = std::ptr::null_mut();
+                let mut num_frames: u32 = 0;
+                let mut flags: u32 = 0;
+                let mut device_pos: u64 = 0;
+                let mut qpc_pos: u64 = 0;
+                self.capture
+                    .GetBuffer(&mut data_ptr, &mut num_frames, &mut flags, Some(&mut device_pos), Some(&mut qpc_pos))
+                    .map_err(|e| format!("GetBuffer failed: {e:?}"))?;
+
+                let mut decoded = Ok(0);
+                if !data_ptr.is_null() && num_frames > 0 {
+                    // 处理静音包：若 AUDCLNT_BUFFERFLAGS_SILENT 置位，填充 0
+                    if (flags & AUDCLNT_BUFFERFLAGS_SILENT.0 as u32) != 0 {
+                        acc.extend(std::iter::repeat_n(0f32, num_frames as usize * self.format.channels));
+                    } else {
+                        let len = num_frames as usize * self.sample_format.block_align();
+                        let bytes = std::slice::from_raw_parts(data_ptr as *const u8, len);
+                        decoded = decode_interleaved(bytes, &self.sample_format, acc);
+                    }
+                }
+                // 先释放缓冲再返回错误，避免设备端一直占用
+                self.capture.ReleaseBuffer(num_frames).ok();
+                decoded?;
+                packet_len = self.capture.GetNextPacketSize().unwrap_or(0);
+            }
+        }
+        Ok(ReadStatus::Data)
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for WasapiLoopbackSource {
+    fn drop(&mut self) {
+        unsafe {
+            self.audio_client.Stop().ok();
+            windows::Win32::System::Com::CoUninitialize();
+        }
+    }
+}
+
+// ==================== Linux (PulseAudio / PipeWire 监视源) ====================
+
+#[cfg(not(target_os = "linux"))]
+fn open_pulse_monitor(_device: Option<&str>) -> Result<Box<dyn AudioSource>, String> {
+    Err("PulseAudio monitor capture is only available on Linux".into())
+}
+
+#[cfg(target_os = "linux")]
+fn open_pulse_monitor(device: Option<&str>) -> Result<Box<dyn AudioSource>, String> {
+    Ok(Box::new(PulseMonitorSource::open(device)?))
+}
+
+/// 通过 parec 读取监视源的原始 f32 流；PipeWire 通过 pipewire-pulse 提供同样的接口
+///
+/// parec 的标准输出由单独的线程读取并经通道转交，read 最多等待 READ_TIMEOUT，
+/// 即使 parec 卡住不输出，采集线程也能及时响应停止。
+#[cfg(target_os = "linux")]
+pub struct PulseMonitorSource {
+    child: std::process::Child,
+    chunks: std::sync::mpsc::Receiver<Result<Vec<u8>, String>>,
+    device: String,
+    format: AudioFormat,
+    sample_format: SampleFormat,
+    /// 上次读取剩下的不足一帧的字节
+    pending: Vec<u8>,
+}
+
+#[cfg(target_os = "linux")]
+impl PulseMonitorSource {
+    /// 查询不到监视源格式时使用的默认值
+    const DEFAULT_SAMPLE_RATE: u32 = 48_000;
+    const DEFAULT_CHANNELS: usize = 2;
+    const READ_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(50);
+
+    pub fn open(device: Option<&str>) -> Result<Self, String> {
+        use std::process::{Command, Stdio};
+        let device = device.unwrap_or("@DEFAULT_MONITOR@").to_string();
+        // 按监视源自身的采样率与声道数采集，避免 parec 重采样/混音
+        let (sample_rate, channels) = query_source_spec(&device).unwrap_or_else(|e| {
+            eprintln!("[spectrum] 查询 {} 的格式失败，使用默认值: {}", device, e);
+            (Self::DEFAULT_SAMPLE_RATE, Self::DEFAULT_CHANNELS)
+        });
+        let mut child = Command::new("parec")
+            .arg("--raw")
+            .arg("--format=float32le")
+            .arg(format!("--rate={}", sample_rate))
+            .arg(format!("--channels={}", channels))
+            .arg("--latency-msec=20")
+            .arg("-d")
+            .arg(&device)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => "未找到 parec（需要 pulseaudio-utils 或 pipewire-pulse）".to_string(),
+                _ => format!("启动 parec 失败: {}", e),
+            })?;
+        let stdout = child.stdout.take().ok_or("无法读取 parec 输出")?;
+        let chunks = spawn_pipe_reader(stdout);
+        Ok(PulseMonitorSource {
+            child,
+            chunks,
+            device,
+            format: AudioFormat { sample_rate, channels },
+            // 与 parec 的 --format=float32le 对应
+            sample_format: SampleFormat {
+                encoding: SampleEncoding::Float,
+                container_bits: 32,
+                valid_bits: 0,
+                endianness: Endianness::Little,
+                channels,
+            },
+            pending: Vec::new(),
+        })
+    }
+}
+
+/// 在后台线程中读取管道；管道关闭或接收端被丢弃时线程退出
+#[cfg(target_os = "linux")]
+fn spawn_pipe_reader(mut pipe: impl std::io::Read + Send + 'static) -> std::sync::mpsc::Receiver<Result<Vec<u8>, String>> {
+    // 有界通道：分析跟不上时让读线程阻塞，而不是无限堆积
+    let (tx, rx) = std::sync::mpsc::sync_channel(64);
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 8192];
+        loop {
+            let msg = match pipe.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => Ok(buf[..n].to_vec()),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => Err(format!("读取 parec 输出失败: {}", e)),
+            };
+            let failed = msg.is_err();
+            if tx.send(msg).is_err() || failed {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// 查询 PulseAudio 源的采样率与声道数（@DEFAULT_MONITOR@ 解析为默认输出设备的监视源）
+#[cfg(target_os = "linux")]
+fn query_source_spec(device: &str) -> Result<(u32, usize), String> {
+    let name = if device == "@DEFAULT_MONITOR@" {
+        let info = pactl(&["info"])?;
+        let sink = info
+            .lines()
+            .find_map(|l| l.strip_prefix("Default Sink:"))
+            .map(str::trim)
+            .ok_or("pactl info 中没有 Default Sink")?;
+        format!("{}.monitor", sink)
+    } else {
+        device.to_string()
+    };
+    // 每行为：序号 名称 驱动 采样格式（如 "s16le 2ch 44100Hz"） 状态，以制表符分隔
+    let sources = pactl(&["list", "short", "sources"])?;
+    let spec = sources
+        .lines()
+        .map(|l| l.split('\t').collect::<Vec<_>>())
+        .find(|cols| cols.get(1) == Some(&name.as_str()))
+        .and_then(|cols| cols.get(3).copied())
+        .ok_or_else(|| format!("未找到音频源 {}", name))?;
+    parse_sample_spec(spec).ok_or_else(|| format!("无法解析采样格式: {}", spec))
+}
+
+#[cfg(target_os = "linux")]
+fn pactl(args: &[&str]) -> Result<String, String> {
+    let output = std::process::Command::new("pactl")
+        .args(args)
+        // 输出里的字段名不随系统语言变化
+        .env("LC_ALL", "C")
+        .output()
+        .map_err(|e| format!("运行 pactl 失败: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("pactl {} 失败（{}）", args.join(" "), output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// 解析 "s16le 2ch 44100Hz" 形式的采样格式，返回 (采样率, 声道数)
+#[cfg(target_os = "linux")]
+fn parse_sample_spec(spec: &str) -> Option<(u32, usize)> {
+    let mut rate = None;
+    let mut channels = None;
+    for token in spec.split_whitespace() {
+        if let Some(v) = token.strip_suffix("Hz") {
+            rate = v.parse().ok().filter(|r| *r > 0);
+        } else if let Some(v) = token.strip_suffix("ch") {
+            channels = v.parse().ok().filter(|c| *c > 0);
+        }
+    }
+    Some((rate?, channels?))
+}
+
+#[cfg(target_os = "linux")]
+impl AudioSource for PulseMonitorSource {
+    fn format(&self) -> AudioFormat {
+        self.format
+    }
+
+    fn describe(&self) -> String {
+        format!("PulseAudio monitor ({}): {} Hz, ch={}", self.device, self.format.sample_rate, self.format.channels)
+    }
+
+    fn read(&mut self, out: &mut Vec<f32>) -> Result<ReadStatus, String> {
+        use std::sync::mpsc::RecvTimeoutError;
+        let chunk = match self.chunks.recv_timeout(Self::READ_TIMEOUT) {
+            Ok(chunk) => chunk?,
+            Err(RecvTimeoutError::Timeout) => return Ok(ReadStatus::Waiting),
+            Err(RecvTimeoutError::Disconnected) => {
+                let status = self.child.wait().map_err(|e| e.to_string())?;
+                return Err(format!("parec 已退出（{}），请检查设备 {}", status, self.device));
+            }
+        };
+        self.pending.extend_from_slice(&chunk);
+        let block = self.sample_format.block_align();
+        let whole = self.pending.len() / block * block;
+        decode_interleaved(&self.pending[..whole], &self.sample_format, out)?;
+        self.pending.drain(..whole);
+        Ok(ReadStatus::Data)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for PulseMonitorSource {
+    fn drop(&mut self) {
+        // 结束 parec 后管道关闭，读线程随之退出
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// ==================== 文件 ====================
+
+/// 解码 WAV/FLAC/MP3 文件，并按实时节奏输出，使频谱与播放速度一致
+pub struct FileSource {
+    path: String,
+    reader: Box<dyn symphonia::core::formats::FormatReader>,
+    decoder: Box<dyn symphonia::core::codecs::Decoder>,
+    track_id: u32,
+    format: AudioFormat,
+    looping: bool,
+    started: std::time::Instant,
+    /// 已输出的帧数，用于与墙钟时间对齐
+    frames_out: u64,
+    /// 打开时为确定声道数预先解码的样本
+    pending: Vec<f32>,
+}
+
+impl FileSource {
+    pub fn open(path: &str, looping: bool) -> Result<Self, String> {
+        use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+        use symphonia::core::formats::FormatOptions;
+        use symphonia::core::io::MediaSourceStream;
+        use symphonia::core::meta::MetadataOptions;
+        use symphonia::core::probe::Hint;
+
+        let file = std::fs::File::open(path).map_err(|e| format!("无法打开音频文件 {}: {}", path, e))?;
+        let mss = MediaSourceStream::new(Box::new(file), Default::default());
+        let mut hint = Hint::new();
+        if let Some(ext) = std::path::Path::new(path).extension().and_then(|e| e.to_str()) {
+            hint.with_extension(ext);
+        }
+        let probed = symphonia::default::get_probe()
+            .format(&hint, mss, &FormatOptions::default(), &MetadataOptions::default())
+            .map_err(|e| format!("不支持的音频文件 {}: {}", path, e))?;
+        let reader = probed.format;
+        let track = reader
+            .tracks()
+            .iter()
+            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or_else(|| format!("{} 中没有音频轨道", path))?;
+        let track_id = track.id;
+        let sample_rate = track.codec_params.sample_rate.ok_or_else(|| format!("{} 缺少采样率", path))?;
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())
+            .map_err(|e| format!("无法解码 {}: {}", path, e))?;
+
+        let mut source = FileSource {
+            path: path.to_string(),
+            reader,
+            decoder,
+            track_id,
+            format: AudioFormat { sample_rate, channels: 0 },
+            looping,
+            started: std::time::Instant::now(),
+            frames_out: 0,
+            pending: Vec::new(),
+        };
+        // 部分格式（如 MP3）的声道数要解码第一个包才知道
+        let mut first = Vec::new();
+        while source.format.channels == 0 {
+            match source.decode_next(&mut first)? {
+                Some(channels) => source.format.channels = channels,
+                None => return Err(format!("{} 没有可解码的音频数据", path)),
+            }
+        }
+        source.pending = first;
+        source.started = std::time::Instant::now();
+        Ok(source)
+    }
+
+    /// 解码下一个包并追加到 out，返回声道数；文件结束返回 None
+    fn decode_next(&mut self, out: &mut Vec<f32>) -> Result<Option<usize>, String> {
+        use symphonia::core::audio::SampleBuffer;
+        use symphonia::core::errors::Error;
+        loop {
+            let packet = match self.reader.next_packet() {
+                Ok(p) => p,
+                Err(Error::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(e) => return Err(format!("读取 {} 失败: {}", self.path, e)),
+            };
+            if packet.track_id() != self.track_id { continue; }
+            match self.decoder.decode(&packet) {
+                Ok(decoded) => {
+                    let spec = *decoded.spec();
+                    let mut buf = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+                    buf.copy_interleaved_ref(decoded);
+                    out.extend_from_slice(buf.samples());
+                    return Ok(Some(spec.channels.count()));
+                }
+                // 损坏的包跳过即可
+                Err(Error::DecodeError(_)) => continue,
+                Err(e) => return Err(format!("解码 {} 失败: {}", self.path, e)),
+            }
+        }
+    }
+
+    fn rewind(&mut self) -> Result<(), String> {
+        use symphonia::core::formats::{SeekMode, SeekTo};
+        self.reader
+            .seek(SeekMode::Accurate, SeekTo::TimeStamp { ts: 0, track_id: self.track_id })
+            .map_err(|e| format!("无法回到 {} 开头: {}", self.path, e))?;
+        self.decoder.reset();
+        Ok(())
+    }
+}
+
+impl AudioSource for FileSource {
+    fn format(&self) -> AudioFormat {
+        self.format
+    }
+
+    fn describe(&self) -> String {
+        format!("file {}: {} Hz, ch={}{}", self.path, self.format.sample_rate, self.format.channels, if self.looping { ", loop" } else { "" })
+    }
+
+    fn read(&mut self, out: &mut Vec<f32>) -> Result<ReadStatus, String> {
+        // 超前于墙钟时就等待，避免一次性把整首歌送进分析器
+        let ahead = self.frames_out as f64 / self.format.sample_rate as f64 - self.started.elapsed().as_secs_f64();
+        if ahead > 0.0 {
+            std::thread::sleep(std::time::Duration::from_secs_f64(ahead.min(0.01)));
+            return Ok(ReadStatus::Waiting);
+        }
+        let before = out.len();
+        if !self.pending.is_empty() {
+            out.append(&mut self.pending);
+        } else if self.decode_next(out)?.is_none() {
+            if !self.looping { return Ok(ReadStatus::Ended); }
+            self.rewind()?;
+            if self.decode_next(out)?.is_none() { return Ok(ReadStatus::Ended); }
+        }
+        self.frames_out += ((out.len() - before) / self.format.channels.max(1)) as u64;
+        Ok(ReadStatus::Data)
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pactl_sample_spec() {
+        assert_eq!(parse_sample_spec("s16le 2ch 44100Hz"), Some((44_100, 2)));
+        assert_eq!(parse_sample_spec("float32le 6ch 48000Hz"), Some((48_000, 6)));
+        assert_eq!(parse_sample_spec("s16le 0ch 44100Hz"), None);
+        assert_eq!(parse_sample_spec("s16le 2ch"), None);
+    }
+
+    #[test]
+    fn pipe_reader_forwards_chunks_and_closes() {
+        let rx = spawn_pipe_reader(std::io::Cursor::new(vec![1u8, 2, 3]));
+        assert_eq!(rx.recv().unwrap(), Ok(vec![1, 2, 3]));
+        assert!(rx.recv().is_err());
+    }
+}
@@ -0,0 +1,837 @@
+use crate::db::{self, DbState};
+use crate::reports::{self, ReportSegment};
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::State;
+
+const PRODID: &str = "-//Workstation//Activity Tracker//CN";
+
+/// 同一程序的相邻分段间隔不超过该值时合并为一个会话
+pub const DEFAULT_MERGE_GAP_SECS: u32 = 120;
+
+/// 默认只导出不短于 5 分钟的会话
+pub const DEFAULT_MIN_SESSION_MINUTES: u32 = 5;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IcsExportOptions {
+    pub min_session_minutes: Option<u32>,
+    pub merge_gap_secs: Option<u32>,
+    /// app（默认）：按程序合并；category：按分类合并，未分类的按程序
+    pub group_by: Option<String>,
+}
+
+/// 合并后的活动会话
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivitySession {
+    pub summary: String,
+    pub category: Option<String>,
+    pub project: Option<String>,
+    pub start_time: String,
+    pub end_time: String,
+    pub active_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedBlock {
+    pub id: i64,
+    pub uid: String,
+    pub title: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub category_id: Option<i64>,
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IcsImportSummary {
+    pub inserted: usize,
+    pub updated: usize,
+    /// 缺少时间、格式错误等无法导入的事件数
+    pub skipped: usize,
+    /// 已导入的重复事件（RRULE/RDATE）数，这些事件只导入了第一次发生
+    pub recurring: usize,
+    /// 已导入、但时间带 TZID 而按本地时间解释的事件数；TZID 与本机时区不同时时间会有偏差
+    pub assumed_local: usize,
+    /// 上述事件中出现过的 TZID，供用户核对
+    pub time_zones: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IcsExportSummary {
+    pub path: String,
+    pub events: usize,
+}
+
+// ==================== 会话合并 ====================
+
+/// 把分段合并成会话：同一分组、间隔不超过 merge_gap 的相邻分段视为同一会话
+pub fn merge_sessions(segments: &[ReportSegment], names: &HashMap<i64, String>, options: &IcsExportOptions) -> Vec<ActivitySession> {
+    let merge_gap = options.merge_gap_secs.unwrap_or(DEFAULT_MERGE_GAP_SECS) as i64;
+    let min_secs = options.min_session_minutes.unwrap_or(DEFAULT_MIN_SESSION_MINUTES) as i64 * 60;
+    let by_category = options.group_by.as_deref() == Some("category");
+
+    struct Open {
+        key: String,
+        category: Option<String>,
+        project: Option<String>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        active: i64,
+    }
+    let mut out = Vec::new();
+    let mut close = |s: Open| {
+        if s.active >= min_secs {
+            out.push(ActivitySession {
+                summary: s.key,
+                category: s.category,
+                project: s.project,
+                start_time: s.start.to_rfc3339(),
+                end_time: s.end.to_rfc3339(),
+                active_seconds: s.active,
+            });
+        }
+    };
+    let mut open: Option<Open> = None;
+    for seg in segments.iter().filter(|s| !s.is_idle && !s.from_summary) {
+        let category = seg.category_id.and_then(|id| names.get(&id).cloned());
+        let key = match (&category, by_category) {
+            (Some(c), true) => c.clone(),
+            _ => seg.app_name.clone(),
+        };
+        if let Some(o) = open.as_mut() {
+            if o.key == key && seg.start.signed_duration_since(o.end).num_seconds() <= merge_gap {
+                o.end = o.end.max(seg.end);
+                o.active += seg.seconds();
+                if o.project.is_none() { o.project = seg.project.clone(); }
+                continue;
+            }
+        }
+        if let Some(o) = open.take() { close(o); }
+        open = Some(Open { key, category, project: seg.project.clone(), start: seg.start, end: seg.end, active: seg.seconds() });
+    }
+    if let Some(o) = open { close(o); }
+    out
+}
+
+// ==================== ICS 写出 ====================
+
+fn ics_time(t: DateTime<Utc>) -> String {
+    t.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// TEXT 值转义（RFC 5545 3.3.11）
+fn escape_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// 按 75 字节折行（不拆开 UTF-8 字符），行尾为 CRLF
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for ch in line.chars() {
+        let len = ch.len_utf8();
+        if width + len > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(ch);
+        width += len;
+    }
+    out.push_str("\r\n");
+}
+
+fn session_uid(s: &ActivitySession) -> String {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in s.summary.bytes().chain(s.start_time.bytes()) {
+        h ^= b as u64;
+        h = h.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{:016x}@workstation.activity", h)
+}
+
+pub fn sessions_to_ics(sessions: &[ActivitySession], now: DateTime<Utc>) -> Result<String, String> {
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, &format!("PRODID:{}", PRODID));
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    for s in sessions {
+        let start = parse_rfc3339(&s.start_time)?;
+        let end = parse_rfc3339(&s.end_time)?;
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}", session_uid(s)));
+        push_line(&mut out, &format!("DTSTAMP:{}", ics_time(now)));
+        push_line(&mut out, &format!("DTSTART:{}", ics_time(start)));
+        push_line(&mut out, &format!("DTEND:{}", ics_time(end)));
+        push_line(&mut out, &format!("SUMMARY:{}", escape_text(&s.summary)));
+        let mut desc = format!("活跃 {} 分钟", s.active_seconds / 60);
+        if let Some(p) = &s.project { desc.push_str(&format!("\n项目: {}", p)); }
+        push_line(&mut out, &format!("DESCRIPTION:{}", escape_text(&desc)));
+        if let Some(c) = &s.category {
+            push_line(&mut out, &format!("CATEGORIES:{}", escape_text(c)));
+        }
+        push_line(&mut out, "TRANSP:TRANSPARENT");
+        push_line(&mut out, "END:VEVENT");
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    Ok(out)
+}
+
+fn parse_rfc3339(s: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|d| d.with_timezone(&Utc))
+        .map_err(|e| format!("解析时间失败 '{}': {}", s, e))
+}
+
+// ==================== ICS 解析 ====================
+
+/// 解析出的 VEVENT（只保留需要的属性）
+#[derive(Debug, Clone, Default)]
+pub struct IcsEvent {
+    pub uid: Option<String>,
+    pub summary: Option<String>,
+    pub categories: Vec<String>,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub duration: Option<ChronoDuration>,
+    pub all_day: bool,
+    pub recurring: bool,
+    /// 按本地时间解释的 TZID（离线时无法换算任意时区；UTC 类的 TZID 按 UTC 处理，不记在这里）
+    pub tzid: Option<String>,
+    /// 事件内第一处解析错误；有错误的事件不会导入
+    pub error: Option<String>,
+}
+
+fn unescape_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// 按未转义的逗号拆分多值 TEXT
+fn split_text_list(s: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut cur = String::new();
+    let mut escaped = false;
+    for c in s.chars() {
+        if escaped {
+            cur.push('\\');
+            cur.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == ',' {
+            parts.push(unescape_text(&cur));
+            cur.clear();
+        } else {
+            cur.push(c);
+        }
+    }
+    parts.push(unescape_text(&cur));
+    parts.into_iter().map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect()
+}
+
+/// 按第一个不在双引号内的冒号拆开内容行（参数值可以是带冒号的引号串，如 Outlook 的 TZID）
+fn split_content_line(line: &str) -> Option<(&str, &str)> {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ':' if !quoted => return Some((&line[..i], &line[i + 1..])),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 拆分属性参数 `;NAME=value;NAME="quoted"`，返回 (大写参数名, 去掉引号的值)
+fn split_params(params: &str) -> Vec<(String, String)> {
+    let mut parts = Vec::new();
+    let mut cur = String::new();
+    let mut quoted = false;
+    for c in params.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => parts.push(std::mem::take(&mut cur)),
+            c => cur.push(c),
+        }
+    }
+    parts.push(cur);
+    parts
+        .into_iter()
+        .filter_map(|p| p.split_once('=').map(|(n, v)| (n.trim().to_ascii_uppercase(), v.to_string())))
+        .collect()
+}
+
+fn param<'a>(params: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    params.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+}
+
+/// 按 UTC 处理的 TZID
+fn is_utc_tzid(tzid: &str) -> bool {
+    matches!(tzid.trim().to_ascii_uppercase().as_str(), "UTC" | "GMT" | "Z" | "ETC/UTC" | "ETC/GMT" | "ETC/UCT" | "UCT")
+}
+
+/// 解析 DATE / DATE-TIME 值；不带 Z 的时间（浮动时间或带 TZID）按本地时间处理，UTC 类的 TZID 除外
+fn parse_ics_time(params: &[(String, String)], value: &str) -> Result<(DateTime<Utc>, bool), String> {
+    let value = value.trim();
+    let value_kind = param(params, "VALUE").map(|v| v.to_ascii_uppercase());
+    if value_kind.as_deref() == Some("DATE") || value.len() == 8 {
+        let d = NaiveDate::parse_from_str(value, "%Y%m%d").map_err(|e| format!("无效日期 '{}': {}", value, e))?;
+        let start = db::local_day_start(&d.format("%Y-%m-%d").to_string())?;
+        return Ok((start.with_timezone(&Utc), true));
+    }
+    let utc = param(params, "TZID").is_some_and(is_utc_tzid);
+    if let Some(v) = value.strip_suffix('Z').or(utc.then_some(value)) {
+        let t = NaiveDateTime::parse_from_str(v, "%Y%m%dT%H%M%S").map_err(|e| format!("无效时间 '{}': {}", value, e))?;
+        return Ok((Utc.from_utc_datetime(&t), false));
+    }
+    let t = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map_err(|e| format!("无效时间 '{}': {}", value, e))?;
+    let local = Local
+        .from_local_datetime(&t)
+        .earliest()
+        .ok_or_else(|| format!("无法确定本地时间 '{}'", value))?;
+    Ok((local.with_timezone(&Utc), false))
+}
+
+/// 时间值带有非 UTC 的 TZID 时返回该 TZID（只对 DATE-TIME 有意义）
+fn local_tzid(params: &[(String, String)], value: &str) -> Option<String> {
+    let tzid = param(params, "TZID")?;
+    (!is_utc_tzid(tzid) && !value.trim().ends_with('Z') && value.trim().len() > 8).then(|| tzid.to_string())
+}
+
+/// 解析 DURATION（RFC 5545 3.3.6），如 PT1H30M、P1D、P2W
+fn parse_ics_duration(value: &str) -> Result<ChronoDuration, String> {
+    let err = || format!("无效时长 '{}'", value);
+    let v = value.trim();
+    let (negative, v) = match v.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, v.strip_prefix('+').unwrap_or(v)),
+    };
+    let v = v.strip_prefix('P').ok_or_else(err)?;
+    let mut total = ChronoDuration::zero();
+    let mut num = String::new();
+    let mut in_time = false;
+    for c in v.chars() {
+        match c {
+            'T' => in_time = true,
+            '0'..='9' => num.push(c),
+            unit => {
+                let n: i64 = num.parse().map_err(|_| err())?;
+                num.clear();
+                total += match (unit, in_time) {
+                    ('W', false) => ChronoDuration::weeks(n),
+                    ('D', false) => ChronoDuration::days(n),
+                    ('H', true) => ChronoDuration::hours(n),
+                    ('M', true) => ChronoDuration::minutes(n),
+                    ('S', true) => ChronoDuration::seconds(n),
+                    _ => return Err(err()),
+                };
+            }
+        }
+    }
+    if !num.is_empty() { return Err(err()); }
+    Ok(if negative { -total } else { total })
+}
+
+/// 解析 .ics 文本中的全部 VEVENT
+///
+/// 单个事件内的格式错误只记在该事件的 `error` 上，不影响同一文件中的其他事件。
+pub fn parse_ics(text: &str) -> Result<Vec<IcsEvent>, String> {
+    // 展开折行：以空格或制表符开头的行接到上一行
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        if let Some(cont) = raw.strip_prefix(' ').or_else(|| raw.strip_prefix('\t')) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(cont);
+                continue;
+            }
+        }
+        if !raw.is_empty() { lines.push(raw.to_string()); }
+    }
+    if !lines.iter().any(|l| l.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        return Err("不是有效的 iCalendar 文件（缺少 BEGIN:VCALENDAR）".into());
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<IcsEvent> = None;
+    // 嵌套组件（如 VALARM）中的属性不属于事件本身
+    let mut nested = 0usize;
+    for (i, line) in lines.iter().enumerate() {
+        let Some((name_params, value)) = split_content_line(line) else {
+            if let Some(ev) = current.as_mut() {
+                ev.error.get_or_insert_with(|| format!("第 {} 行格式错误: {}", i + 1, line));
+            }
+            continue;
+        };
+        let (name, params) = match name_params.split_once(';') {
+            Some((n, p)) => (n, split_params(p)),
+            None => (name_params, Vec::new()),
+        };
+        let name = name.to_ascii_uppercase();
+        let mut result: Result<(), String> = Ok(());
+        match (name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => current = Some(IcsEvent::default()),
+            ("BEGIN", Some(_)) => nested += 1,
+            ("END", Some(_)) if nested > 0 => nested -= 1,
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => events.extend(current.take()),
+            (_, Some(_)) if nested > 0 => {}
+            ("UID", Some(ev)) => ev.uid = Some(value.trim().to_string()),
+            ("SUMMARY", Some(ev)) => ev.summary = Some(unescape_text(value)),
+            ("CATEGORIES", Some(ev)) => ev.categories.extend(split_text_list(value)),
+            ("DTSTART", Some(ev)) => {
+                result = parse_ics_time(&params, value).map(|(t, all_day)| {
+                    ev.start = Some(t);
+                    ev.all_day = all_day;
+                });
+                ev.tzid = local_tzid(&params, value).or(ev.tzid.take());
+            }
+            ("DTEND", Some(ev)) => {
+                result = parse_ics_time(&params, value).map(|(t, _)| ev.end = Some(t));
+                ev.tzid = ev.tzid.take().or_else(|| local_tzid(&params, value));
+            }
+            ("DURATION", Some(ev)) => result = parse_ics_duration(value).map(|d| ev.duration = Some(d)),
+            ("RRULE", Some(ev)) | ("RDATE", Some(ev)) => ev.recurring = true,
+            _ => {}
+        }
+        if let (Err(e), Some(ev)) = (result, current.as_mut()) {
+            ev.error.get_or_insert_with(|| format!("第 {} 行: {}", i + 1, e));
+        }
+    }
+    Ok(events)
+}
+
+/// 事件的结束时间：DTEND > DURATION > 全天事件为一天 > 与开始相同
+fn event_end(ev: &IcsEvent, start: DateTime<Utc>) -> DateTime<Utc> {
+    match (ev.end, ev.duration) {
+        (Some(end), _) => end,
+        (None, Some(d)) => start + d,
+        (None, None) if ev.all_day => start + ChronoDuration::days(1),
+        (None, None) => start,
+    }
+}
+
+/// 导入计划时间块；(UID, 开始时间) 相同的事件会被更新而不是重复插入
+///
+/// 重复事件（RRULE/RDATE）只导入第一次发生，计入 recurring；
+/// 带 TZID 的时间按本地时间导入，计入 assumed_local 并列出 TZID。
+pub fn import_planned(conn: &mut Connection, events: Vec<IcsEvent>, source: &str) -> Result<IcsImportSummary, String> {
+    let category_ids: HashMap<String, i64> = crate::categories::list_categories(conn)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|c| (c.name.to_lowercase(), c.id))
+        .collect();
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut summary = IcsImportSummary::default();
+    {
+        let mut find = tx
+            .prepare("SELECT id FROM planned_blocks WHERE uid = ?1 AND start_time = ?2")
+            .map_err(|e| e.to_string())?;
+        let mut update = tx
+            .prepare("UPDATE planned_blocks SET title = ?1, end_time = ?2, category_id = ?3, source = ?4 WHERE id = ?5")
+            .map_err(|e| e.to_string())?;
+        let mut insert = tx
+            .prepare(
+                "INSERT INTO planned_blocks (uid, title, start_time, end_time, category_id, source) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )
+            .map_err(|e| e.to_string())?;
+        for ev in events {
+            if let Some(e) = &ev.error {
+                eprintln!("[calendar] 跳过格式错误的事件 {}: {}", ev.uid.as_deref().unwrap_or("(无 UID)"), e);
+                summary.skipped += 1;
+                continue;
+            }
+            let Some(start) = ev.start else {
+                summary.skipped += 1;
+                continue;
+            };
+            let end = event_end(&ev, start);
+            if end <= start {
+                summary.skipped += 1;
+                continue;
+            }
+            let title = ev.summary.clone().unwrap_or_default();
+            // 没有 UID 的事件用标题 + 开始时间去重
+            let uid = ev.uid.clone().unwrap_or_else(|| format!("{}@{}", title, ics_time(start)));
+            let category_id = ev.categories.iter().find_map(|c| category_ids.get(&c.to_lowercase()).copied());
+            let start_s = start.to_rfc3339();
+            let end_s = end.to_rfc3339();
+            let existing: Option<i64> = find
+                .query_row(params![uid, start_s], |row| row.get(0))
+                .optional()
+                .map_err(|e| e.to_string())?;
+            match existing {
+                Some(id) => {
+                    update.execute(params![title, end_s, category_id, source, id]).map_err(|e| e.to_string())?;
+                    summary.updated += 1;
+                }
+                None => {
+                    insert.execute(params![uid, title, start_s, end_s, category_id, source]).map_err(|e| e.to_string())?;
+                    summary.inserted += 1;
+                }
+            }
+            if ev.recurring { summary.recurring += 1; }
+            if let Some(tz) = ev.tzid {
+                summary.assumed_local += 1;
+                if !summary.time_zones.contains(&tz) { summary.time_zones.push(tz); }
+            }
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(summary)
+}
+
+// ==================== 计划 vs 实际 ====================
+
+/// 与本地日期闭区间有交集的计划块
+pub fn list_planned(conn: &Connection, start_date: &str, end_date: &str) -> Result<Vec<PlannedBlock>, String> {
+    let (start_s, end_s) = db::local_range_bounds(start_date, end_date)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, uid, title, start_time, end_time, category_id, source FROM planned_blocks
+             WHERE start_time < ?2 AND end_time > ?1 ORDER BY start_time",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![start_s, end_s], |row| {
+            Ok(PlannedBlock {
+                id: row.get(0)?,
+                uid: row.get(1)?,
+                title: row.get(2)?,
+                start_time: row.get(3)?,
+                end_time: row.get(4)?,
+                category_id: row.get(5)?,
+                source: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanComparison {
+    pub block: PlannedBlock,
+    pub planned_seconds: i64,
+    /// 计划时间内记录到的非空闲时长
+    pub tracked_seconds: i64,
+    /// 其中分类与计划块一致的时长（计划块无分类时为 None）
+    pub matching_seconds: Option<i64>,
+    pub top_apps: Vec<reports::AppTotal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanVsActual {
+    pub date: String,
+    pub blocks: Vec<PlanComparison>,
+    pub planned_seconds: i64,
+    pub tracked_seconds: i64,
+    /// 不在任何计划块内的记录时长
+    pub unplanned_seconds: i64,
+}
+
+fn overlap(a0: DateTime<Utc>, a1: DateTime<Utc>, b0: DateTime<Utc>, b1: DateTime<Utc>) -> i64 {
+    (a1.min(b1) - a0.max(b0)).num_seconds().max(0)
+}
+
+pub fn plan_vs_actual(conn: &Connection, date: &str) -> Result<PlanVsActual, String> {
+    let (day_start, day_end) = db::local_range_bounds(date, date)?;
+    let (day_start, day_end) = (parse_rfc3339(&day_start)?, parse_rfc3339(&day_end)?);
+    let segments = reports::load_segments(conn, date, date, false)?;
+    let blocks = list_planned(conn, date, date)?;
+
+    let mut windows = Vec::with_capacity(blocks.len());
+    let mut comparisons = Vec::with_capacity(blocks.len());
+    for block in blocks {
+        let b0 = parse_rfc3339(&block.start_time)?.max(day_start);
+        let b1 = parse_rfc3339(&block.end_time)?.min(day_end);
+        let mut tracked = 0;
+        let mut matching = 0;
+        let mut apps: HashMap<String, i64> = HashMap::new();
+        for seg in &segments {
+            let secs = overlap(seg.start, seg.end, b0, b1);
+            if secs == 0 { continue; }
+            tracked += secs;
+            if block.category_id.is_some() && seg.category_id == block.category_id { matching += secs; }
+            *apps.entry(seg.app_name.clone()).or_default() += secs;
+        }
+        let mut top_apps: Vec<reports::AppTotal> = apps
+            .into_iter()
+            .map(|(app_name, total_seconds)| reports::AppTotal { app_name, total_seconds })
+            .collect();
+        top_apps.sort_by(|a, b| b.total_seconds.cmp(&a.total_seconds).then_with(|| a.app_name.cmp(&b.app_name)));
+        top_apps.truncate(5);
+        windows.push((b0, b1));
+        comparisons.push(PlanComparison {
+            planned_seconds: (b1 - b0).num_seconds().max(0),
+            tracked_seconds: tracked,
+            matching_seconds: block.category_id.map(|_| matching),
+            top_apps,
+            block,
+        });
+    }
+
+    // 计划块可能互相重叠：先合并成不相交的区间再算计划外时长
+    windows.sort();
+    let mut merged: Vec<(DateTime<Utc>, DateTime<Utc>)> = Vec::new();
+    for (s, e) in windows {
+        match merged.last_mut() {
+            Some(last) if s <= last.1 => last.1 = last.1.max(e),
+            _ => merged.push((s, e)),
+        }
+    }
+    let tracked_seconds: i64 = segments.iter().map(|s| s.seconds()).sum();
+    let in_plan: i64 = segments
+        .iter()
+        .map(|seg| merged.iter().map(|(s, e)| overlap(seg.start, seg.end, *s, *e)).sum::<i64>())
+        .sum();
+
+    Ok(PlanVsActual {
+        date: date.to_string(),
+        planned_seconds: merged.iter().map(|(s, e)| (*e - *s).num_seconds()).sum(),
+        tracked_seconds,
+        unplanned_seconds: tracked_seconds - in_plan,
+        blocks: comparisons,
+    })
+}
+
+// ==================== Tauri 命令 ====================
+
+/// 把本地日期闭区间内的活动会话导出为 .ics 文件
+#[tauri::command]
+pub fn calendar_export_ics(
+    state: State<DbState>,
+    start_date: String,
+    end_date: String,
+    file_path: String,
+    options: Option<IcsExportOptions>,
+) -> Result<IcsExportSummary, String> {
+    let options = options.unwrap_or_default();
+    let sessions = {
+        let conn = state.db.lock().unwrap();
+        let segments = reports::load_segments(&conn, &start_date, &end_date, false)?;
+        let names: HashMap<i64, String> = crate::categories::list_categories(&conn)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|c| (c.id, c.name))
+            .collect();
+        merge_sessions(&segments, &names, &options)
+    };
+    let text = sessions_to_ics(&sessions, Utc::now())?;
+    std::fs::write(&file_path, text).map_err(|e| format!("写入文件失败 {}: {}", file_path, e))?;
+    Ok(IcsExportSummary { path: file_path, events: sessions.len() })
+}
+
+/// 从本地 .ics 文件导入计划时间块
+#[tauri::command]
+pub fn calendar_import_ics(state: State<DbState>, file_path: String) -> Result<IcsImportSummary, String> {
+    let text = std::fs::read_to_string(&file_path).map_err(|e| format!("无法读取文件 {}: {}", file_path, e))?;
+    let events = parse_ics(&text)?;
+    let source = Path::new(&file_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| file_path.clone());
+    let mut conn = state.db.lock().unwrap();
+    import_planned(&mut conn, events, &source)
+}
+
+#[tauri::command]
+pub fn calendar_list_planned(state: State<DbState>, start_date: String, end_date: Option<String>) -> Result<Vec<PlannedBlock>, String> {
+    let conn = state.db.lock().unwrap();
+    let end_date = end_date.unwrap_or_else(|| start_date.clone());
+    list_planned(&conn, &start_date, &end_date)
+}
+
+#[tauri::command]
+pub fn calendar_delete_planned(state: State<DbState>, id: i64) -> Result<(), String> {
+    let conn = state.db.lock().unwrap();
+    conn.execute("DELETE FROM planned_blocks WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn calendar_plan_vs_actual(state: State<DbState>, date: String) -> Result<PlanVsActual, String> {
+    let conn = state.db.lock().unwrap();
+    plan_vs_actual(&conn, &date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_db(&conn).unwrap();
+        conn.execute("INSERT INTO activity_categories (id, name) VALUES (1, '开发')", []).unwrap();
+        conn
+    }
+
+    fn calendar(events: &str) -> String {
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n", events)
+    }
+
+    /// 本地日期 date 的 hour:minute
+    fn local(date: &str, hour: i64, minute: i64) -> DateTime<Utc> {
+        (db::local_day_start(date).unwrap() + ChronoDuration::hours(hour) + ChronoDuration::minutes(minute)).with_timezone(&Utc)
+    }
+
+    #[test]
+    fn unfolds_lines_and_unescapes_text() {
+        let text = calendar(
+            "BEGIN:VEVENT\r\nUID:a@x\r\nDTSTART:20260101T090000Z\r\nSUMMARY:周会\\, 计划\\; \r\n 复盘\\n第二行\\\\\r\n\tend\r\nCATEGORIES:开发\\,测试,会议\r\nEND:VEVENT\r\n",
+        );
+        let events = parse_ics(&text).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].summary.as_deref(), Some("周会, 计划; 复盘\n第二行\\end"));
+        assert_eq!(events[0].categories, vec!["开发,测试".to_string(), "会议".to_string()]);
+
+        let raw = "a\\b;c,d\ne";
+        assert_eq!(escape_text(raw), "a\\\\b\\;c\\,d\\ne");
+        assert_eq!(unescape_text(&escape_text(raw)), raw);
+
+        // 折行按字节计且不拆开多字节字符
+        let mut out = String::new();
+        push_line(&mut out, &format!("SUMMARY:{}", "汉".repeat(40)));
+        assert!(out.split("\r\n").all(|l| l.len() <= 75));
+        let events = parse_ics(&calendar(&format!("BEGIN:VEVENT\r\n{}END:VEVENT\r\n", out))).unwrap();
+        assert_eq!(events[0].summary, Some("汉".repeat(40)));
+    }
+
+    #[test]
+    fn parses_dates_times_and_durations() {
+        let text = calendar(concat!(
+            "BEGIN:VEVENT\r\nUID:all-day\r\nDTSTART;VALUE=DATE:20260105\r\nEND:VEVENT\r\n",
+            "BEGIN:VEVENT\r\nUID:utc\r\nDTSTART:20260105T083000Z\r\nDURATION:PT1H30M\r\nEND:VEVENT\r\n",
+            "BEGIN:VEVENT\r\nUID:floating\r\nDTSTART:20260105T090000\r\nDTEND:20260105T100000\r\nEND:VEVENT\r\n",
+            "BEGIN:VEVENT\r\nUID:tz-utc\r\nDTSTART;TZID=Etc/UTC:20260105T120000\r\nDURATION:P1DT2H\r\nEND:VEVENT\r\n",
+            "BEGIN:VEVENT\r\nUID:outlook\r\nDTSTART;TZID=\"(UTC+01:00) Amsterdam, Berlin\":20260105T140000\r\n",
+            "DTEND;TZID=\"(UTC+01:00) Amsterdam, Berlin\":20260105T150000\r\nEND:VEVENT\r\n",
+        ));
+        let events = parse_ics(&text).unwrap();
+        let by_uid: HashMap<_, _> = events.iter().map(|e| (e.uid.clone().unwrap(), e)).collect();
+
+        let all_day = by_uid["all-day"];
+        assert!(all_day.all_day);
+        assert_eq!(all_day.start, Some(local("2026-01-05", 0, 0)));
+        assert_eq!(event_end(all_day, all_day.start.unwrap()), local("2026-01-06", 0, 0));
+
+        let utc = by_uid["utc"];
+        let start = Utc.with_ymd_and_hms(2026, 1, 5, 8, 30, 0).unwrap();
+        assert_eq!(utc.start, Some(start));
+        assert_eq!(event_end(utc, start), start + ChronoDuration::minutes(90));
+
+        let floating = by_uid["floating"];
+        assert_eq!((floating.start, floating.end), (Some(local("2026-01-05", 9, 0)), Some(local("2026-01-05", 10, 0))));
+        assert_eq!(floating.tzid, None);
+
+        let tz_utc = by_uid["tz-utc"];
+        assert_eq!(tz_utc.start, Some(Utc.with_ymd_and_hms(2026, 1, 5, 12, 0, 0).unwrap()));
+        assert_eq!(tz_utc.duration, Some(ChronoDuration::hours(26)));
+        assert_eq!(tz_utc.tzid, None);
+
+        // 引号内的冒号不是名称与值的分隔符；无法换算的 TZID 按本地时间处理并做标记
+        let outlook = by_uid["outlook"];
+        assert_eq!(outlook.error, None);
+        assert_eq!((outlook.start, outlook.end), (Some(local("2026-01-05", 14, 0)), Some(local("2026-01-05", 15, 0))));
+        assert_eq!(outlook.tzid.as_deref(), Some("(UTC+01:00) Amsterdam, Berlin"));
+
+        assert_eq!(parse_ics_duration("-P2W").unwrap(), -ChronoDuration::weeks(2));
+        assert!(parse_ics_duration("PT1H30").is_err());
+        assert!(parse_ics_duration("P1H").is_err());
+    }
+
+    #[test]
+    fn alarm_properties_do_not_leak_into_event() {
+        let text = calendar(concat!(
+            "BEGIN:VEVENT\r\nUID:a@x\r\nSUMMARY:专注\r\nDTSTART:20260105T090000Z\r\nDTEND:20260105T100000Z\r\n",
+            "BEGIN:VALARM\r\nACTION:DISPLAY\r\nSUMMARY:提醒\r\nTRIGGER:-PT15M\r\nDURATION:PT5M\r\nREPEAT:2\r\nEND:VALARM\r\n",
+            "CATEGORIES:开发\r\nEND:VEVENT\r\n",
+        ));
+        let events = parse_ics(&text).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].summary.as_deref(), Some("专注"));
+        assert_eq!(events[0].duration, None);
+        assert_eq!(events[0].categories, vec!["开发".to_string()]);
+    }
+
+    #[test]
+    fn malformed_and_recurring_events_are_counted_once() {
+        let text = calendar(concat!(
+            "BEGIN:VEVENT\r\nUID:bad-time\r\nDTSTART:tomorrow\r\nEND:VEVENT\r\n",
+            "BEGIN:VEVENT\r\nUID:bad-line\r\nDTSTART:20260105T090000Z\r\nthis line has no colon\r\nEND:VEVENT\r\n",
+            "BEGIN:VEVENT\r\nUID:no-start\r\nSUMMARY:x\r\nEND:VEVENT\r\n",
+            "BEGIN:VEVENT\r\nUID:weekly\r\nDTSTART:20260105T090000Z\r\nDTEND:20260105T100000Z\r\nRRULE:FREQ=WEEKLY\r\nEND:VEVENT\r\n",
+            "BEGIN:VEVENT\r\nUID:tz\r\nDTSTART;TZID=America/New_York:20260105T090000\r\nDURATION:PT1H\r\nEND:VEVENT\r\n",
+        ));
+        let events = parse_ics(&text).unwrap();
+        assert_eq!(events.len(), 5);
+        assert!(events[0].error.is_some() && events[1].error.is_some());
+        assert!(parse_ics("not a calendar").is_err());
+
+        let mut conn = test_db();
+        let summary = import_planned(&mut conn, events, "test.ics").unwrap();
+        assert_eq!((summary.inserted, summary.updated, summary.skipped), (2, 0, 3));
+        assert_eq!(summary.recurring, 1);
+        assert_eq!(summary.assumed_local, 1);
+        assert_eq!(summary.time_zones, vec!["America/New_York".to_string()]);
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM planned_blocks", [], |r| r.get(0)).unwrap();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn export_then_import_round_trips() {
+        let session = ActivitySession {
+            summary: format!("code; 修复, 解析\\ {}", "长标题".repeat(20)),
+            category: Some("开发".into()),
+            project: Some("crate".into()),
+            start_time: local("2026-01-05", 9, 0).to_rfc3339(),
+            end_time: local("2026-01-05", 10, 30).to_rfc3339(),
+            active_seconds: 5000,
+        };
+        let text = sessions_to_ics(std::slice::from_ref(&session), Utc::now()).unwrap();
+        assert!(text.split("\r\n").all(|l| l.len() <= 75));
+
+        let events = parse_ics(&text).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].summary.as_deref(), Some(session.summary.as_str()));
+        assert_eq!(events[0].start, Some(local("2026-01-05", 9, 0)));
+        assert_eq!(events[0].end, Some(local("2026-01-05", 10, 30)));
+
+        let mut conn = test_db();
+        let first = import_planned(&mut conn, events.clone(), "a.ics").unwrap();
+        assert_eq!((first.inserted, first.updated, first.skipped), (1, 0, 0));
+        let again = import_planned(&mut conn, events, "a.ics").unwrap();
+        assert_eq!((again.inserted, again.updated), (0, 1));
+
+        let blocks = list_planned(&conn, "2026-01-05", "2026-01-05").unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].title, session.summary);
+        assert_eq!(blocks[0].category_id, Some(1));
+        assert_eq!(blocks[0].uid, session_uid(&session));
+    }
+}
@@ -0,0 +1,711 @@
+use crate::db::{self, DbState};
+use once_cell::sync::Lazy;
+use regex::{Regex, RegexBuilder};
+use rusqlite::{params, Connection, Result as SqlResult};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+/// 活动分类
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityCategory {
+    pub id: i64,
+    pub name: String,
+    pub color: Option<String>,
+}
+
+/// 分类规则：app_pattern / title_pattern 中非空的部分都匹配时命中
+///
+/// title_pattern 匹配的是按隐私规则处理后写入数据库的标题（实时分类与重新应用规则一致）；
+/// 被清空或哈希的标题不会命中标题规则，这类程序请用 app_pattern 分类。
+/// 已压缩的每小时汇总没有标题，重新应用规则时只有不带 title_pattern 的规则参与。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityRule {
+    pub id: i64,
+    pub priority: i64,
+    /// "glob" 或 "regex"
+    pub match_kind: String,
+    pub app_pattern: Option<String>,
+    pub title_pattern: Option<String>,
+    pub category_id: Option<i64>,
+    pub project: Option<String>,
+    pub enabled: bool,
+}
+
+/// 创建/更新规则时前端传入的字段
+///
+/// 可清空的字段为两层 Option：字段缺省表示不修改，显式传 null 表示清空。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityRuleInput {
+    pub priority: Option<i64>,
+    pub match_kind: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    pub app_pattern: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub title_pattern: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub category_id: Option<Option<i64>>,
+    #[serde(default, deserialize_with = "present")]
+    pub project: Option<Option<String>>,
+    pub enabled: Option<bool>,
+}
+
+/// 字段出现（包括 null）时为 Some，配合 #[serde(default)] 区分“缺省”与“置空”
+pub(crate) fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// 单个分组的耗时合计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryTotal {
+    pub category_id: Option<i64>,
+    pub name: Option<String>,
+    pub total_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTotal {
+    pub project: Option<String>,
+    pub total_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryTotals {
+    pub categories: Vec<CategoryTotal>,
+    pub projects: Vec<ProjectTotal>,
+}
+
+/// 规则命中结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Classification {
+    pub category_id: Option<i64>,
+    pub project: Option<String>,
+}
+
+struct CompiledRule {
+    app: Option<Regex>,
+    title: Option<Regex>,
+    category_id: Option<i64>,
+    project: Option<String>,
+}
+
+/// 已编译、按优先级排好序的规则集
+pub struct RuleSet {
+    rules: Vec<CompiledRule>,
+}
+
+impl RuleSet {
+    pub fn load(conn: &Connection) -> SqlResult<Self> {
+        let rules = list_rules(conn)?
+            .into_iter()
+            .filter(|r| r.enabled)
+            .filter_map(|r| match compile_rule(&r) {
+                Ok(c) => Some(c),
+                Err(e) => {
+                    eprintln!("[categories] 跳过无效规则 (id={}): {}", r.id, e);
+                    None
+                }
+            })
+            .collect();
+        Ok(Self { rules })
+    }
+
+    pub fn classify(&self, app_name: &str, window_title: &str) -> Classification {
+        self.classify_with(app_name, Some(window_title))
+    }
+
+    /// 只按 app_pattern 分类，带 title_pattern 的规则不参与（用于没有窗口标题的每小时汇总）
+    pub fn classify_app(&self, app_name: &str) -> Classification {
+        self.classify_with(app_name, None)
+    }
+
+    fn classify_with(&self, app_name: &str, window_title: Option<&str>) -> Classification {
+        for rule in &self.rules {
+            let app_ok = rule.app.as_ref().is_none_or(|re| re.is_match(app_name));
+            let title_ok = match (&rule.title, window_title) {
+                (None, _) => true,
+                (Some(re), Some(title)) => re.is_match(title),
+                (Some(_), None) => false,
+            };
+            if app_ok && title_ok {
+                return Classification { category_id: rule.category_id, project: rule.project.clone() };
+            }
+        }
+        Classification::default()
+    }
+}
+
+// 追踪线程使用的规则缓存；规则增删改后失效，下次分类时重新加载
+static RULE_CACHE: Lazy<Mutex<Option<Arc<RuleSet>>>> = Lazy::new(|| Mutex::new(None));
+
+fn invalidate_rule_cache() {
+    if let Ok(mut cache) = RULE_CACHE.lock() {
+        *cache = None;
+    }
+}
+
+fn cached_rule_set(conn: &Connection) -> SqlResult<Arc<RuleSet>> {
+    let mut cache = RULE_CACHE.lock().unwrap();
+    if let Some(set) = cache.as_ref() {
+        return Ok(set.clone());
+    }
+    let set = Arc::new(RuleSet::load(conn)?);
+    *cache = Some(set.clone());
+    Ok(set)
+}
+
+/// 按当前规则为一条活动分类（供追踪器写入时使用）
+pub fn classify(conn: &Connection, app_name: &str, window_title: &str) -> SqlResult<Classification> {
+    Ok(cached_rule_set(conn)?.classify(app_name, window_title))
+}
+
+/// 把 glob（* 与 ?）转换成等价的锚定正则
+fn glob_to_regex(glob: &str) -> String {
+    let mut out = String::with_capacity(glob.len() + 8);
+    out.push('^');
+    for ch in glob.chars() {
+        match ch {
+            '*' => out.push_str(".*"),
+            '?' => out.push('.'),
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    out.push('$');
+    out
+}
+
+/// 按 glob / regex 编译大小写不敏感的匹配模式
+pub fn compile_pattern(kind: &str, pattern: &str) -> Result<Regex, String> {
+    let source = match kind {
+        "glob" => glob_to_regex(pattern),
+        "regex" => pattern.to_string(),
+        other => return Err(format!("未知的匹配方式: {}", other)),
+    };
+    RegexBuilder::new(&source)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| format!("无效的匹配模式 '{}': {}", pattern, e))
+}
+
+fn compile_rule(rule: &ActivityRule) -> Result<CompiledRule, String> {
+    let non_empty = |p: &Option<String>| p.as_deref().filter(|s| !s.is_empty()).map(str::to_string);
+    let app = non_empty(&rule.app_pattern);
+    let title = non_empty(&rule.title_pattern);
+    if app.is_none() && title.is_none() {
+        return Err("规则至少需要 app_pattern 或 title_pattern 之一".into());
+    }
+    Ok(CompiledRule {
+        app: app.map(|p| compile_pattern(&rule.match_kind, &p)).transpose()?,
+        title: title.map(|p| compile_pattern(&rule.match_kind, &p)).transpose()?,
+        category_id: rule.category_id,
+        project: rule.project.clone().filter(|p| !p.is_empty()),
+    })
+}
+
+// ==================== 数据库操作 ====================
+
+pub fn list_categories(conn: &Connection) -> SqlResult<Vec<ActivityCategory>> {
+    let mut stmt = conn.prepare("SELECT id, name, color FROM activity_categories ORDER BY name")?;
+    let rows = stmt
+        .query_map([], |row| Ok(ActivityCategory { id: row.get(0)?, name: row.get(1)?, color: row.get(2)? }))?
+        .collect::<SqlResult<Vec<_>>>()?;
+    Ok(rows)
+}
+
+pub fn list_rules(conn: &Connection) -> SqlResult<Vec<ActivityRule>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, priority, match_kind, app_pattern, title_pattern, category_id, project, enabled
+         FROM activity_rules ORDER BY priority DESC, id ASC",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(ActivityRule {
+                id: row.get(0)?,
+                priority: row.get(1)?,
+                match_kind: row.get(2)?,
+                app_pattern: row.get(3)?,
+                title_pattern: row.get(4)?,
+                category_id: row.get(5)?,
+                project: row.get(6)?,
+                enabled: row.get(7)?,
+            })
+        })?
+        .collect::<SqlResult<Vec<_>>>()?;
+    Ok(rows)
+}
+
+fn get_rule(conn: &Connection, id: i64) -> SqlResult<Option<ActivityRule>> {
+    Ok(list_rules(conn)?.into_iter().find(|r| r.id == id))
+}
+
+/// 每小时汇总的键：(小时, 程序, 分类, 项目)
+type SummaryKey = (String, String, Option<i64>, Option<String>);
+
+/// 用当前规则重新分类已有记录；`range` 为本地日期闭区间，None 表示全部。返回更新的行数
+///
+/// 已压缩的每小时汇总没有窗口标题，只按 app_pattern 规则重新分类；分类后键相同的汇总行合并为一行。
+pub fn reapply_rules(conn: &mut Connection, range: Option<(String, String)>) -> Result<usize, String> {
+    let set = RuleSet::load(conn).map_err(|e| e.to_string())?;
+    let bounds = range.map(|(start, end)| db::local_range_bounds(&start, &end)).transpose()?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut updated = 0usize;
+    {
+        let select = |sql: &str, time_column: &str| -> Result<Vec<(i64, String, String, i64)>, String> {
+            let sql = match bounds {
+                Some(_) => format!("{} AND {} >= ?1 AND {} < ?2", sql, time_column, time_column),
+                None => sql.to_string(),
+            };
+            let mut stmt = tx.prepare(&sql).map_err(|e| e.to_string())?;
+            let map_row = |row: &rusqlite::Row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?));
+            let rows = match &bounds {
+                Some((s, e)) => stmt.query_map(params![s, e], map_row),
+                None => stmt.query_map([], map_row),
+            }
+            .map_err(|e| e.to_string())?
+            .collect::<SqlResult<Vec<_>>>()
+            .map_err(|e| e.to_string())?;
+            Ok(rows)
+        };
+
+        let rows = select(
+            "SELECT id, app_name, COALESCE(window_title, ''), 0 FROM activity_log WHERE is_idle = 0",
+            "start_time",
+        )?;
+        let mut update = tx
+            .prepare("UPDATE activity_log SET category_id = ?1, project = ?2 WHERE id = ?3")
+            .map_err(|e| e.to_string())?;
+        for (id, app_name, title, _) in rows {
+            let c = set.classify(&app_name, &title);
+            updated += update.execute(params![c.category_id, c.project, id]).map_err(|e| e.to_string())?;
+        }
+
+        let rows = select(
+            "SELECT id, hour_start, app_name, total_seconds FROM activity_hourly_summary WHERE is_idle = 0",
+            "hour_start",
+        )?;
+        // 键 → (保留的行 id, 合计秒数)
+        let mut merged: HashMap<SummaryKey, (i64, i64)> = HashMap::new();
+        let mut duplicates = Vec::new();
+        for (id, hour_start, app_name, seconds) in rows {
+            let c = set.classify_app(&app_name);
+            match merged.entry((hour_start, app_name, c.category_id, c.project)) {
+                std::collections::hash_map::Entry::Occupied(mut e) => {
+                    e.get_mut().1 += seconds;
+                    duplicates.push(id);
+                }
+                std::collections::hash_map::Entry::Vacant(e) => {
+                    e.insert((id, seconds));
+                }
+            }
+        }
+        let mut update = tx
+            .prepare("UPDATE activity_hourly_summary SET category_id = ?1, project = ?2, total_seconds = ?3 WHERE id = ?4")
+            .map_err(|e| e.to_string())?;
+        for ((_, _, category_id, project), (id, seconds)) in &merged {
+            updated += update.execute(params![category_id, project, seconds, id]).map_err(|e| e.to_string())?;
+        }
+        for id in duplicates {
+            updated += tx
+                .execute("DELETE FROM activity_hourly_summary WHERE id = ?1", [id])
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(updated)
+}
+
+/// 本地日期闭区间内按分类、按项目汇总的耗时（不含空闲段，包含已压缩的每小时汇总与已归档的年份）
+pub fn category_totals(conn: &Connection, start_date: &str, end_date: &str) -> Result<CategoryTotals, String> {
+    let (start_s, end_s) = db::local_range_bounds(start_date, end_date)?;
+    let mut by_category: HashMap<Option<i64>, i64> = HashMap::new();
+    let mut by_project: HashMap<Option<String>, i64> = HashMap::new();
+    let mut collect = |schema: &str| -> Result<(), String> {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT category_id, project, SUM(seconds)
+                 FROM (SELECT category_id, project, duration_seconds AS seconds FROM {0}.activity_log
+                       WHERE is_idle = 0 AND start_time >= ?1 AND start_time < ?2
+                       UNION ALL
+                       SELECT category_id, project, total_seconds FROM {0}.activity_hourly_summary
+                       WHERE is_idle = 0 AND hour_start >= ?1 AND hour_start < ?2)
+                 GROUP BY category_id, project",
+                schema
+            ))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![start_s, end_s], |row| {
+                Ok((row.get::<_, Option<i64>>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, i64>(2)?))
+            })
+            .map_err(|e| e.to_string())?;
+        for r in rows {
+            let (category_id, project, seconds) = r.map_err(|e| e.to_string())?;
+            *by_category.entry(category_id).or_default() += seconds;
+            *by_project.entry(project).or_default() += seconds;
+        }
+        Ok(())
+    };
+    collect("main")?;
+    crate::retention::for_each_archive(conn, start_date, end_date, &mut collect)?;
+
+    let names: HashMap<i64, String> = list_categories(conn)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|c| (c.id, c.name))
+        .collect();
+    let mut categories: Vec<CategoryTotal> = by_category
+        .into_iter()
+        .map(|(category_id, total_seconds)| CategoryTotal {
+            category_id,
+            name: category_id.and_then(|id| names.get(&id).cloned()),
+            total_seconds,
+        })
+        .collect();
+    categories.sort_by(|a, b| b.total_seconds.cmp(&a.total_seconds).then_with(|| a.category_id.cmp(&b.category_id)));
+    let mut projects: Vec<ProjectTotal> = by_project
+        .into_iter()
+        .map(|(project, total_seconds)| ProjectTotal { project, total_seconds })
+        .collect();
+    projects.sort_by(|a, b| b.total_seconds.cmp(&a.total_seconds).then_with(|| a.project.cmp(&b.project)));
+
+    Ok(CategoryTotals { categories, projects })
+}
+
+// ==================== Tauri 命令 ====================
+
+#[tauri::command]
+pub fn activity_list_categories(state: State<DbState>) -> Result<Vec<ActivityCategory>, String> {
+    let conn = state.db.lock().unwrap();
+    list_categories(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn activity_create_category(state: State<DbState>, name: String, color: Option<String>) -> Result<ActivityCategory, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() { return Err("分类名称不能为空".into()); }
+    let conn = state.db.lock().unwrap();
+    conn.execute("INSERT INTO activity_categories (name, color) VALUES (?1, ?2)", params![name, color])
+        .map_err(|e| e.to_string())?;
+    Ok(ActivityCategory { id: conn.last_insert_rowid(), name, color })
+}
+
+#[tauri::command]
+pub fn activity_update_category(state: State<DbState>, id: i64, name: Option<String>, color: Option<String>) -> Result<(), String> {
+    let conn = state.db.lock().unwrap();
+    if let Some(n) = name.map(|n| n.trim().to_string()) {
+        if n.is_empty() { return Err("分类名称不能为空".into()); }
+        conn.execute("UPDATE activity_categories SET name = ?1 WHERE id = ?2", params![n, id])
+            .map_err(|e| e.to_string())?;
+    }
+    if let Some(c) = color {
+        conn.execute("UPDATE activity_categories SET color = ?1 WHERE id = ?2", params![c, id])
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// 删除分类；引用该分类的规则与记录改为未分类
+#[tauri::command]
+pub fn activity_delete_category(state: State<DbState>, id: i64) -> Result<(), String> {
+    let mut conn = state.db.lock().unwrap();
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute("UPDATE activity_rules SET category_id = NULL WHERE category_id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    tx.execute("UPDATE activity_log SET category_id = NULL WHERE category_id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM activity_categories WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    invalidate_rule_cache();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn activity_list_rules(state: State<DbState>) -> Result<Vec<ActivityRule>, String> {
+    let conn = state.db.lock().unwrap();
+    list_rules(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn activity_create_rule(state: State<DbState>, rule: ActivityRuleInput) -> Result<ActivityRule, String> {
+    let mut candidate = ActivityRule {
+        id: 0,
+        priority: rule.priority.unwrap_or(0),
+        match_kind: rule.match_kind.unwrap_or_else(|| "glob".into()),
+        app_pattern: rule.app_pattern.flatten(),
+        title_pattern: rule.title_pattern.flatten(),
+        category_id: rule.category_id.flatten(),
+        project: rule.project.flatten(),
+        enabled: rule.enabled.unwrap_or(true),
+    };
+    compile_rule(&candidate)?;
+
+    let conn = state.db.lock().unwrap();
+    conn.execute(
+        "INSERT INTO activity_rules (priority, match_kind, app_pattern, title_pattern, category_id, project, enabled)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            candidate.priority,
+            candidate.match_kind,
+            candidate.app_pattern,
+            candidate.title_pattern,
+            candidate.category_id,
+            candidate.project,
+            candidate.enabled,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    candidate.id = conn.last_insert_rowid();
+    invalidate_rule_cache();
+    Ok(candidate)
+}
+
+/// 更新规则；未提供的字段保持不变，显式为 null 的字段清空
+#[tauri::command]
+pub fn activity_update_rule(state: State<DbState>, id: i64, rule: ActivityRuleInput) -> Result<ActivityRule, String> {
+    let conn = state.db.lock().unwrap();
+    let mut current = get_rule(&conn, id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("规则不存在: {}", id))?;
+    if let Some(v) = rule.priority { current.priority = v; }
+    if let Some(v) = rule.match_kind { current.match_kind = v; }
+    if let Some(v) = rule.app_pattern { current.app_pattern = v; }
+    if let Some(v) = rule.title_pattern { current.title_pattern = v; }
+    if let Some(v) = rule.category_id { current.category_id = v; }
+    if let Some(v) = rule.project { current.project = v; }
+    if let Some(v) = rule.enabled { current.enabled = v; }
+    compile_rule(&current)?;
+
+    conn.execute(
+        "UPDATE activity_rules SET priority = ?1, match_kind = ?2, app_pattern = ?3, title_pattern = ?4,
+             category_id = ?5, project = ?6, enabled = ?7 WHERE id = ?8",
+        params![
+            current.priority,
+            current.match_kind,
+            current.app_pattern,
+            current.title_pattern,
+            current.category_id,
+            current.project,
+            current.enabled,
+            id,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    invalidate_rule_cache();
+    Ok(current)
+}
+
+#[tauri::command]
+pub fn activity_delete_rule(state: State<DbState>, id: i64) -> Result<(), String> {
+    let conn = state.db.lock().unwrap();
+    conn.execute("DELETE FROM activity_rules WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    invalidate_rule_cache();
+    Ok(())
+}
+
+/// 对历史记录重新应用规则；不传日期则处理全部记录
+#[tauri::command]
+pub async fn activity_reapply_rules(
+    app_handle: tauri::AppHandle,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<usize, String> {
+    use tauri::Manager;
+    let range = match (start_date, end_date) {
+        (Some(s), Some(e)) => Some((s, e)),
+        (Some(s), None) => Some((s.clone(), s)),
+        (None, Some(_)) => return Err("缺少 start_date".into()),
+        (None, None) => None,
+    };
+    tokio::task::spawn_blocking(move || {
+        let state: State<DbState> = app_handle.state();
+        let mut conn = state.db.lock().unwrap();
+        reapply_rules(&mut conn, range)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+#[tauri::command]
+pub fn activity_category_totals(state: State<DbState>, start_date: String, end_date: String) -> Result<CategoryTotals, String> {
+    let conn = state.db.lock().unwrap();
+    category_totals(&conn, &start_date, &end_date)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_db(&conn).unwrap();
+        for (id, name) in [(1, "开发"), (2, "沟通"), (3, "娱乐")] {
+            conn.execute("INSERT INTO activity_categories (id, name) VALUES (?1, ?2)", params![id, name]).unwrap();
+        }
+        conn
+    }
+
+    fn add_rule(conn: &Connection, priority: i64, kind: &str, app: Option<&str>, title: Option<&str>, category_id: i64, enabled: bool) {
+        conn.execute(
+            "INSERT INTO activity_rules (priority, match_kind, app_pattern, title_pattern, category_id, project, enabled)
+             VALUES (?1, ?2, ?3, ?4, ?5, NULL, ?6)",
+            params![priority, kind, app, title, category_id, enabled],
+        )
+        .unwrap();
+    }
+
+    /// 本地日期 date 的 hour 点（UTC RFC3339）
+    fn local_time(date: &str, hour: i64) -> String {
+        (db::local_day_start(date).unwrap() + chrono::Duration::hours(hour)).with_timezone(&chrono::Utc).to_rfc3339()
+    }
+
+    fn add_activity(conn: &Connection, app: &str, title: &str, start: &str, seconds: i64, category_id: Option<i64>) {
+        conn.execute(
+            "INSERT INTO activity_log (app_name, window_title, start_time, duration_seconds, category_id) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![app, title, start, seconds, category_id],
+        )
+        .unwrap();
+    }
+
+    fn category_of(conn: &Connection, title: &str) -> Option<i64> {
+        conn.query_row("SELECT category_id FROM activity_log WHERE window_title = ?1", [title], |r| r.get(0)).unwrap()
+    }
+
+    #[test]
+    fn glob_and_regex_match_app_and_title() {
+        let conn = test_db();
+        add_rule(&conn, 0, "glob", Some("code*"), None, 1, true);
+        add_rule(&conn, 0, "regex", None, Some(r"^\[\d+\] .* - Slack$"), 2, true);
+        add_rule(&conn, 0, "glob", Some("*.exe"), Some("*youtube*"), 3, true);
+        let set = RuleSet::load(&conn).unwrap();
+        // glob 锚定整串且不区分大小写
+        assert_eq!(set.classify("Code.exe", "main.rs").category_id, Some(1));
+        assert_eq!(set.classify("vscode", "main.rs").category_id, None);
+        assert_eq!(set.classify("slack", "[3] general - Slack").category_id, Some(2));
+        assert_eq!(set.classify("slack", "general - Slack").category_id, None);
+        // app 与 title 都给出时两者都要匹配；glob 中的 . 是字面量
+        assert_eq!(set.classify("chrome.exe", "Music - YouTube").category_id, Some(3));
+        assert_eq!(set.classify("chrome.exe", "Docs").category_id, None);
+        assert_eq!(set.classify("chromeXexe", "YouTube").category_id, None);
+        assert!(compile_pattern("regex", "(").is_err());
+        assert!(compile_pattern("fuzzy", "x").is_err());
+    }
+
+    #[test]
+    fn higher_priority_wins_and_disabled_rules_are_skipped() {
+        let conn = test_db();
+        add_rule(&conn, 0, "glob", Some("firefox"), None, 3, true);
+        add_rule(&conn, 10, "glob", Some("firefox"), Some("*github*"), 1, true);
+        add_rule(&conn, 20, "glob", Some("firefox"), Some("*github*"), 2, false);
+        // 同优先级按 id 先后
+        add_rule(&conn, 5, "glob", Some("term*"), None, 1, true);
+        add_rule(&conn, 5, "glob", Some("terminal"), None, 2, true);
+        let set = RuleSet::load(&conn).unwrap();
+        assert_eq!(set.classify("firefox", "user/repo - GitHub").category_id, Some(1));
+        assert_eq!(set.classify("firefox", "News").category_id, Some(3));
+        assert_eq!(set.classify("terminal", "").category_id, Some(1));
+    }
+
+    #[test]
+    fn reapply_rules_within_date_range() {
+        let mut conn = test_db();
+        add_activity(&conn, "code", "before", &local_time("2026-05-03", 23), 600, None);
+        add_activity(&conn, "code", "first", &local_time("2026-05-04", 0), 600, None);
+        add_activity(&conn, "code", "last", &local_time("2026-05-05", 23), 600, Some(3));
+        add_activity(&conn, "code", "after", &local_time("2026-05-06", 0), 600, None);
+        add_rule(&conn, 0, "glob", Some("code"), None, 1, true);
+
+        assert_eq!(reapply_rules(&mut conn, Some(("2026-05-04".into(), "2026-05-05".into()))).unwrap(), 2);
+        assert_eq!(category_of(&conn, "before"), None);
+        assert_eq!(category_of(&conn, "first"), Some(1));
+        assert_eq!(category_of(&conn, "last"), Some(1));
+        assert_eq!(category_of(&conn, "after"), None);
+
+        // 不再命中任何规则的记录改为未分类
+        conn.execute("UPDATE activity_rules SET enabled = 0", []).unwrap();
+        assert_eq!(reapply_rules(&mut conn, None).unwrap(), 4);
+        assert_eq!(category_of(&conn, "first"), None);
+    }
+
+    #[test]
+    fn reapply_rules_reclassifies_summaries_by_app() {
+        let mut conn = test_db();
+        let hour = local_time("2026-05-04", 9);
+        // 压缩前按标题分到了不同分类的同一程序
+        for (category_id, seconds) in [(Some(1), 1200), (Some(2), 300), (None, 60)] {
+            conn.execute(
+                "INSERT INTO activity_hourly_summary (hour_start, app_name, category_id, project, is_idle, total_seconds)
+                 VALUES (?1, 'firefox', ?2, NULL, 0, ?3)",
+                params![hour, category_id, seconds],
+            )
+            .unwrap();
+        }
+        conn.execute(
+            "INSERT INTO activity_hourly_summary (hour_start, app_name, category_id, project, is_idle, total_seconds)
+             VALUES (?1, 'code', NULL, NULL, 0, 500)",
+            [local_time("2026-05-06", 9)],
+        )
+        .unwrap();
+        add_rule(&conn, 10, "glob", Some("firefox"), Some("*github*"), 1, true);
+        add_rule(&conn, 0, "glob", Some("fire*"), None, 3, true);
+        add_rule(&conn, 0, "glob", Some("code"), None, 1, true);
+
+        // 标题规则不适用于汇总；三行合并为一行，区间外的汇总不变
+        assert_eq!(reapply_rules(&mut conn, Some(("2026-05-04".into(), "2026-05-04".into()))).unwrap(), 3);
+        let rows: Vec<(String, Option<i64>, i64)> = conn
+            .prepare("SELECT app_name, category_id, total_seconds FROM activity_hourly_summary ORDER BY app_name")
+            .unwrap()
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(rows, [("code".to_string(), None, 500), ("firefox".to_string(), Some(3), 1560)]);
+    }
+
+    #[test]
+    fn category_totals_include_summaries() {
+        let conn = test_db();
+        add_activity(&conn, "code", "a", &local_time("2026-05-04", 9), 1200, Some(1));
+        add_activity(&conn, "slack", "b", &local_time("2026-05-04", 10), 300, Some(2));
+        add_activity(&conn, "code", "out of range", &local_time("2026-05-05", 9), 999, Some(1));
+        conn.execute(
+            "INSERT INTO activity_log (app_name, window_title, start_time, duration_seconds, is_idle) VALUES ('idle', 'idle', ?1, 5000, 1)",
+            [local_time("2026-05-04", 11)],
+        )
+        .unwrap();
+        for (hour, app, category_id, project, seconds) in
+            [(8, "code", Some(1), Some("crate"), 1800), (8, "game", None, None, 600), (12, "slack", Some(2), Some("crate"), 100)]
+        {
+            conn.execute(
+                "INSERT INTO activity_hourly_summary (hour_start, app_name, category_id, project, is_idle, total_seconds)
+                 VALUES (?1, ?2, ?3, ?4, 0, ?5)",
+                params![local_time("2026-05-04", hour), app, category_id, project, seconds],
+            )
+            .unwrap();
+        }
+
+        let totals = category_totals(&conn, "2026-05-04", "2026-05-04").unwrap();
+        let categories: Vec<(Option<i64>, Option<&str>, i64)> =
+            totals.categories.iter().map(|c| (c.category_id, c.name.as_deref(), c.total_seconds)).collect();
+        assert_eq!(categories, [(Some(1), Some("开发"), 3000), (None, None, 600), (Some(2), Some("沟通"), 400)]);
+        let projects: Vec<(Option<&str>, i64)> = totals.projects.iter().map(|p| (p.project.as_deref(), p.total_seconds)).collect();
+        assert_eq!(projects, [(None, 2100), (Some("crate"), 1900)]);
+    }
+
+    #[test]
+    fn rule_input_distinguishes_missing_and_null() {
+        let input: ActivityRuleInput =
+            serde_json::from_str(r#"{"priority": 3, "project": null, "category_id": 7}"#).unwrap();
+        assert_eq!(input.priority, Some(3));
+        assert_eq!(input.app_pattern, None);
+        assert_eq!(input.title_pattern, None);
+        assert_eq!(input.project, Some(None));
+        assert_eq!(input.category_id, Some(Some(7)));
+    }
+}
@@ -0,0 +1,744 @@
+use crate::categories::{compile_pattern, present};
+use crate::db::DbState;
+use crate::reports;
+use crate::tracker::CurrentActivity;
+use chrono::{DateTime, Duration as ChronoDuration, Local, Utc};
+use rusqlite::{params, Connection, Result as SqlResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{Emitter, State};
+
+/// 目标进度的重新计算间隔（追踪线程每 2 秒调用一次，这里再节流）
+const GOAL_CHECK_INTERVAL_SECS: i64 = 30;
+
+/// 达到该比例即视为“接近”
+const APPROACH_RATIO: f64 = 0.8;
+
+/// 超出目标这么久才算“超过”，避免与“达到”在同一次检查里同时触发
+const EXCEED_GRACE_SECS: i64 = 60;
+
+/// 两次 tick 的间隔超过该值（休眠/挂起）时不计入番茄钟的专注/分心时长
+const MAX_TICK_GAP_SECS: i64 = 10;
+
+/// 时间目标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityGoal {
+    pub id: i64,
+    pub name: String,
+    /// day / week（周从周一开始）
+    pub period: String,
+    /// at_least: 至少；at_most: 至多
+    pub direction: String,
+    pub target_seconds: i64,
+    pub category_id: Option<i64>,
+    /// 程序名 glob（大小写不敏感）
+    pub app_pattern: Option<String>,
+    pub enabled: bool,
+}
+
+/// 新建/更新目标的参数；更新时缺省字段保持不变
+///
+/// 可清空的字段为两层 Option：字段缺省表示不修改，显式传 null 表示清空。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActivityGoalInput {
+    pub name: Option<String>,
+    pub period: Option<String>,
+    pub direction: Option<String>,
+    pub target_seconds: Option<i64>,
+    #[serde(default, deserialize_with = "present")]
+    pub category_id: Option<Option<i64>>,
+    #[serde(default, deserialize_with = "present")]
+    pub app_pattern: Option<Option<String>>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GoalLevel {
+    Below,
+    Approaching,
+    Reached,
+    Exceeded,
+}
+
+impl GoalLevel {
+    pub fn of(progress: i64, target: i64) -> Self {
+        if progress > target + EXCEED_GRACE_SECS {
+            Self::Exceeded
+        } else if progress >= target {
+            Self::Reached
+        } else if progress as f64 >= target as f64 * APPROACH_RATIO {
+            Self::Approaching
+        } else {
+            Self::Below
+        }
+    }
+}
+
+/// 目标当前进度（goals:status 事件与 goals_progress 命令共用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoalProgress {
+    pub goal_id: i64,
+    pub name: String,
+    pub period: String,
+    pub direction: String,
+    pub level: GoalLevel,
+    pub progress_seconds: i64,
+    pub target_seconds: i64,
+}
+
+fn validate_goal(goal: &ActivityGoal) -> Result<(), String> {
+    if goal.name.trim().is_empty() { return Err("目标名称不能为空".into()); }
+    if goal.period != "day" && goal.period != "week" { return Err(format!("period must be day or week (got {})", goal.period)); }
+    if goal.direction != "at_least" && goal.direction != "at_most" {
+        return Err(format!("direction must be at_least or at_most (got {})", goal.direction));
+    }
+    if goal.target_seconds <= 0 { return Err("target_seconds 必须大于 0".into()); }
+    if let Some(p) = goal.app_pattern.as_deref().filter(|p| !p.is_empty()) {
+        compile_pattern("glob", p)?;
+    }
+    Ok(())
+}
+
+pub fn list_goals(conn: &Connection) -> SqlResult<Vec<ActivityGoal>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, period, direction, target_seconds, category_id, app_pattern, enabled FROM activity_goals ORDER BY id",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(ActivityGoal {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                period: row.get(2)?,
+                direction: row.get(3)?,
+                target_seconds: row.get(4)?,
+                category_id: row.get(5)?,
+                app_pattern: row.get(6)?,
+                enabled: row.get(7)?,
+            })
+        })?
+        .collect::<SqlResult<Vec<_>>>()?;
+    Ok(rows)
+}
+
+/// 当前周期（包含 now 的本地日/周）的标识，用于判断周期是否已切换
+fn period_key(goal: &ActivityGoal, now: DateTime<Utc>) -> Result<(String, String), String> {
+    let today = now.with_timezone(&Local).format("%Y-%m-%d").to_string();
+    reports::period_bounds(&today, &goal.period)
+}
+
+/// 计算已启用目标在当前周期内的进度（包含已压缩的每小时汇总）
+pub fn evaluate_goals(conn: &Connection, now: DateTime<Utc>) -> Result<Vec<GoalProgress>, String> {
+    let goals: Vec<ActivityGoal> = list_goals(conn).map_err(|e| e.to_string())?.into_iter().filter(|g| g.enabled).collect();
+    // 同一周期的分段只读取一次
+    let mut segments: HashMap<(String, String), Vec<reports::ReportSegment>> = HashMap::new();
+    let mut out = Vec::with_capacity(goals.len());
+    for goal in goals {
+        let bounds = period_key(&goal, now)?;
+        if !segments.contains_key(&bounds) {
+            segments.insert(bounds.clone(), reports::load_segments(conn, &bounds.0, &bounds.1, false)?);
+        }
+        let app_re = match goal.app_pattern.as_deref().filter(|p| !p.is_empty()) {
+            Some(p) => Some(compile_pattern("glob", p)?),
+            None => None,
+        };
+        let progress_seconds = segments[&bounds]
+            .iter()
+            .filter(|s| goal.category_id.is_none_or(|c| s.category_id == Some(c)))
+            .filter(|s| app_re.as_ref().is_none_or(|re| re.is_match(&s.app_name)))
+            .map(|s| s.seconds())
+            .sum();
+        out.push(GoalProgress {
+            goal_id: goal.id,
+            level: GoalLevel::of(progress_seconds, goal.target_seconds),
+            name: goal.name,
+            period: goal.period,
+            direction: goal.direction,
+            progress_seconds,
+            target_seconds: goal.target_seconds,
+        });
+    }
+    Ok(out)
+}
+
+/// 目标状态跟踪：只在进入更高的级别时产生事件，新周期开始时重置
+#[derive(Default)]
+pub struct GoalTracker {
+    last_check: Option<DateTime<Utc>>,
+    /// goal_id -> (周期起始日, 已通知的级别)
+    levels: HashMap<i64, (String, GoalLevel)>,
+}
+
+impl GoalTracker {
+    /// 节流地检查目标，返回需要通知的进度变化
+    ///
+    /// 程序启动后第一次见到某个目标时只记录当前级别，不补发通知。
+    pub fn check(&mut self, conn: &Connection, now: DateTime<Utc>) -> Result<Vec<GoalProgress>, String> {
+        if let Some(last) = self.last_check {
+            if now.signed_duration_since(last).num_seconds() < GOAL_CHECK_INTERVAL_SECS {
+                return Ok(Vec::new());
+            }
+        }
+        self.last_check = Some(now);
+        let today = now.with_timezone(&Local).format("%Y-%m-%d").to_string();
+
+        let mut events = Vec::new();
+        let mut seen = HashMap::new();
+        for p in evaluate_goals(conn, now)? {
+            let (period_start, _) = reports::period_bounds(&today, &p.period)?;
+            let baseline = match self.levels.get(&p.goal_id) {
+                Some((start, level)) if *start == period_start => Some(*level),
+                // 新周期：从 Below 重新开始
+                Some(_) => Some(GoalLevel::Below),
+                // 首次见到：不提醒
+                None => None,
+            };
+            if baseline.is_some_and(|b| p.level > b) {
+                events.push(p.clone());
+            }
+            let level = match self.levels.get(&p.goal_id) {
+                Some((start, level)) if *start == period_start => p.level.max(*level),
+                _ => p.level,
+            };
+            seen.insert(p.goal_id, (period_start, level));
+        }
+        // 已删除或停用的目标不再跟踪
+        self.levels = seen;
+        Ok(events)
+    }
+
+    /// 目标增删改后立即重新检查
+    pub fn invalidate(&mut self) {
+        self.last_check = None;
+    }
+}
+
+// ==================== 番茄钟 ====================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PomodoroPhase {
+    Stopped,
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PomodoroConfig {
+    pub work_minutes: u32,
+    pub short_break_minutes: u32,
+    pub long_break_minutes: u32,
+    /// 每完成几个工作段后进入长休息
+    pub long_break_every: u32,
+    /// 工作段中视为专注的分类；为空时任何非空闲活动都算专注
+    #[serde(default)]
+    pub focus_category_ids: Vec<i64>,
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        Self { work_minutes: 25, short_break_minutes: 5, long_break_minutes: 15, long_break_every: 4, focus_category_ids: Vec::new() }
+    }
+}
+
+/// pomodoro:tick 事件与 pomodoro_status 命令的内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PomodoroStatus {
+    pub phase: PomodoroPhase,
+    pub phase_end: Option<String>,
+    pub remaining_seconds: i64,
+    pub completed_work: u32,
+    /// 当前工作段内前台为专注活动的时长
+    pub on_task_seconds: i64,
+    pub off_task_seconds: i64,
+    pub off_task: bool,
+}
+
+/// 番茄钟事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PomodoroEvent {
+    /// 某阶段结束（包括被跳过/停止）
+    PhaseFinished { phase: PomodoroPhase, on_task_seconds: i64, off_task_seconds: i64, completed_work: u32 },
+    PhaseStarted { phase: PomodoroPhase, phase_end: String },
+    /// 工作段中前台切换到了非专注活动
+    Distraction { app_name: String, window_title: String },
+    BackOnTask,
+}
+
+/// 番茄钟状态机，由追踪线程每次采样驱动
+pub struct Pomodoro {
+    config: PomodoroConfig,
+    phase: PomodoroPhase,
+    phase_end: DateTime<Utc>,
+    completed_work: u32,
+    on_task: i64,
+    off_task: i64,
+    is_off_task: bool,
+    last_tick: DateTime<Utc>,
+}
+
+impl Default for Pomodoro {
+    fn default() -> Self {
+        let now = Utc::now();
+        Self {
+            config: PomodoroConfig::default(),
+            phase: PomodoroPhase::Stopped,
+            phase_end: now,
+            completed_work: 0,
+            on_task: 0,
+            off_task: 0,
+            is_off_task: false,
+            last_tick: now,
+        }
+    }
+}
+
+impl Pomodoro {
+    pub fn status(&self, now: DateTime<Utc>) -> PomodoroStatus {
+        let running = self.phase != PomodoroPhase::Stopped;
+        PomodoroStatus {
+            phase: self.phase,
+            phase_end: running.then(|| self.phase_end.to_rfc3339()),
+            remaining_seconds: if running { self.phase_end.signed_duration_since(now).num_seconds().max(0) } else { 0 },
+            completed_work: self.completed_work,
+            on_task_seconds: self.on_task,
+            off_task_seconds: self.off_task,
+            off_task: self.is_off_task,
+        }
+    }
+
+    fn phase_minutes(&self, phase: PomodoroPhase) -> u32 {
+        match phase {
+            PomodoroPhase::Work => self.config.work_minutes,
+            PomodoroPhase::ShortBreak => self.config.short_break_minutes,
+            PomodoroPhase::LongBreak => self.config.long_break_minutes,
+            PomodoroPhase::Stopped => 0,
+        }
+    }
+
+    fn enter(&mut self, phase: PomodoroPhase, now: DateTime<Utc>, events: &mut Vec<PomodoroEvent>) {
+        self.phase = phase;
+        self.phase_end = now + ChronoDuration::minutes(self.phase_minutes(phase) as i64);
+        self.on_task = 0;
+        self.off_task = 0;
+        self.is_off_task = false;
+        self.last_tick = now;
+        if phase != PomodoroPhase::Stopped {
+            events.push(PomodoroEvent::PhaseStarted { phase, phase_end: self.phase_end.to_rfc3339() });
+        }
+    }
+
+    fn finish_phase(&mut self, events: &mut Vec<PomodoroEvent>) {
+        if self.phase == PomodoroPhase::Stopped { return; }
+        events.push(PomodoroEvent::PhaseFinished {
+            phase: self.phase,
+            on_task_seconds: self.on_task,
+            off_task_seconds: self.off_task,
+            completed_work: self.completed_work,
+        });
+    }
+
+    pub fn start(&mut self, config: PomodoroConfig, now: DateTime<Utc>) -> Result<Vec<PomodoroEvent>, String> {
+        if config.work_minutes == 0 || config.short_break_minutes == 0 || config.long_break_minutes == 0 || config.long_break_every == 0 {
+            return Err("番茄钟各阶段时长与长休息间隔都必须大于 0".into());
+        }
+        let mut events = Vec::new();
+        self.finish_phase(&mut events);
+        self.config = config;
+        self.completed_work = 0;
+        self.enter(PomodoroPhase::Work, now, &mut events);
+        Ok(events)
+    }
+
+    pub fn stop(&mut self, now: DateTime<Utc>) -> Vec<PomodoroEvent> {
+        let mut events = Vec::new();
+        self.finish_phase(&mut events);
+        self.enter(PomodoroPhase::Stopped, now, &mut events);
+        events
+    }
+
+    /// 提前结束当前阶段，进入下一阶段；跳过的工作段不计入已完成的番茄数
+    pub fn skip(&mut self, now: DateTime<Utc>) -> Vec<PomodoroEvent> {
+        let mut events = Vec::new();
+        if self.phase != PomodoroPhase::Stopped {
+            self.advance(now, false, &mut events);
+        }
+        events
+    }
+
+    /// `completed` 为 false 表示阶段被跳过：工作段不计数，之后只进入短休息
+    fn advance(&mut self, now: DateTime<Utc>, completed: bool, events: &mut Vec<PomodoroEvent>) {
+        let finished_work = completed && self.phase == PomodoroPhase::Work;
+        if finished_work { self.completed_work += 1; }
+        self.finish_phase(events);
+        let next = match self.phase {
+            PomodoroPhase::Work if finished_work && self.completed_work.is_multiple_of(self.config.long_break_every) => PomodoroPhase::LongBreak,
+            PomodoroPhase::Work => PomodoroPhase::ShortBreak,
+            _ => PomodoroPhase::Work,
+        };
+        self.enter(next, now, events);
+    }
+
+    /// 当前前台活动是否算专注（None 表示空闲/暂停/无法获取）
+    pub fn is_on_task(&self, activity: Option<&CurrentActivity>, category_id: Option<i64>) -> bool {
+        activity.is_some()
+            && (self.config.focus_category_ids.is_empty()
+                || category_id.is_some_and(|c| self.config.focus_category_ids.contains(&c)))
+    }
+
+    /// 追踪线程每次采样调用；`activity` 为 None 表示空闲或未在记录
+    pub fn tick(&mut self, now: DateTime<Utc>, activity: Option<&CurrentActivity>, category_id: Option<i64>) -> Vec<PomodoroEvent> {
+        let mut events = Vec::new();
+        if self.phase == PomodoroPhase::Stopped { return events; }
+
+        let elapsed = now.signed_duration_since(self.last_tick).num_seconds();
+        self.last_tick = now;
+        if self.phase == PomodoroPhase::Work {
+            let on_task = self.is_on_task(activity, category_id);
+            if (0..=MAX_TICK_GAP_SECS).contains(&elapsed) {
+                if on_task { self.on_task += elapsed; } else { self.off_task += elapsed; }
+            }
+            if !on_task && !self.is_off_task {
+                events.push(PomodoroEvent::Distraction {
+                    app_name: activity.map(|a| a.app_name.clone()).unwrap_or_default(),
+                    window_title: activity.map(|a| a.window_title.clone()).unwrap_or_default(),
+                });
+            } else if on_task && self.is_off_task {
+                events.push(PomodoroEvent::BackOnTask);
+            }
+            self.is_off_task = !on_task;
+        }
+        if now >= self.phase_end {
+            self.advance(now, true, &mut events);
+        }
+        events
+    }
+}
+
+/// 目标与番茄钟的运行时状态（由 main 统一 manage）
+#[derive(Default)]
+pub struct GoalsRuntime {
+    pub goals: Mutex<GoalTracker>,
+    pub pomodoro: Mutex<Pomodoro>,
+}
+
+pub fn emit_pomodoro_events(app: &tauri::AppHandle, events: Vec<PomodoroEvent>) {
+    for e in events {
+        let _ = app.emit("pomodoro:event", e);
+    }
+}
+
+/// 追踪线程每次采样后调用：驱动番茄钟并节流地检查目标
+///
+/// `activity` 为追踪器正在计时的活动，标题已按隐私规则处理（与写入数据库的一致），
+/// 空闲或暂停时为 None。
+pub fn on_tracker_tick(app: &tauri::AppHandle, conn: &Connection, activity: Option<&CurrentActivity>, now: DateTime<Utc>) {
+    use tauri::Manager;
+    let runtime: State<GoalsRuntime> = app.state();
+
+    let events = {
+        let mut pomodoro = runtime.pomodoro.lock().unwrap();
+        if pomodoro.phase == PomodoroPhase::Stopped {
+            Vec::new()
+        } else {
+            let category_id = match activity {
+                Some(a) => crate::categories::classify(conn, &a.app_name, &a.window_title).ok().and_then(|c| c.category_id),
+                None => None,
+            };
+            let events = pomodoro.tick(now, activity, category_id);
+            let _ = app.emit("pomodoro:tick", pomodoro.status(now));
+            events
+        }
+    };
+    emit_pomodoro_events(app, events);
+
+    let goal_events = runtime.goals.lock().unwrap().check(conn, now);
+    match goal_events {
+        Ok(events) => {
+            for e in events {
+                let _ = app.emit("goals:status", e);
+            }
+        }
+        Err(e) => eprintln!("[goals] 检查目标失败: {}", e),
+    }
+}
+
+// ==================== Tauri 命令 ====================
+
+#[tauri::command]
+pub fn goals_list(state: State<DbState>) -> Result<Vec<ActivityGoal>, String> {
+    let conn = state.db.lock().unwrap();
+    list_goals(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn goals_create(state: State<DbState>, runtime: State<GoalsRuntime>, goal: ActivityGoalInput) -> Result<ActivityGoal, String> {
+    let mut candidate = ActivityGoal {
+        id: 0,
+        name: goal.name.unwrap_or_default().trim().to_string(),
+        period: goal.period.unwrap_or_else(|| "day".into()),
+        direction: goal.direction.unwrap_or_else(|| "at_least".into()),
+        target_seconds: goal.target_seconds.ok_or("缺少 target_seconds")?,
+        category_id: goal.category_id.flatten(),
+        app_pattern: goal.app_pattern.flatten().filter(|p| !p.is_empty()),
+        enabled: goal.enabled.unwrap_or(true),
+    };
+    validate_goal(&candidate)?;
+
+    let conn = state.db.lock().unwrap();
+    conn.execute(
+        "INSERT INTO activity_goals (name, period, direction, target_seconds, category_id, app_pattern, enabled)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            candidate.name,
+            candidate.period,
+            candidate.direction,
+            candidate.target_seconds,
+            candidate.category_id,
+            candidate.app_pattern,
+            candidate.enabled,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    candidate.id = conn.last_insert_rowid();
+    runtime.goals.lock().unwrap().invalidate();
+    Ok(candidate)
+}
+
+/// 更新目标；未提供的字段保持不变
+#[tauri::command]
+pub fn goals_update(state: State<DbState>, runtime: State<GoalsRuntime>, id: i64, goal: ActivityGoalInput) -> Result<ActivityGoal, String> {
+    let conn = state.db.lock().unwrap();
+    let mut current = list_goals(&conn)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|g| g.id == id)
+        .ok_or_else(|| format!("目标不存在: {}", id))?;
+    if let Some(v) = goal.name { current.name = v.trim().to_string(); }
+    if let Some(v) = goal.period { current.period = v; }
+    if let Some(v) = goal.direction { current.direction = v; }
+    if let Some(v) = goal.target_seconds { current.target_seconds = v; }
+    if let Some(v) = goal.category_id { current.category_id = v; }
+    if let Some(v) = goal.app_pattern { current.app_pattern = v.filter(|p| !p.is_empty()); }
+    if let Some(v) = goal.enabled { current.enabled = v; }
+    validate_goal(&current)?;
+
+    conn.execute(
+        "UPDATE activity_goals SET name = ?1, period = ?2, direction = ?3, target_seconds = ?4,
+             category_id = ?5, app_pattern = ?6, enabled = ?7 WHERE id = ?8",
+        params![
+            current.name,
+            current.period,
+            current.direction,
+            current.target_seconds,
+            current.category_id,
+            current.app_pattern,
+            current.enabled,
+            id,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    runtime.goals.lock().unwrap().invalidate();
+    Ok(current)
+}
+
+#[tauri::command]
+pub fn goals_delete(state: State<DbState>, runtime: State<GoalsRuntime>, id: i64) -> Result<(), String> {
+    let conn = state.db.lock().unwrap();
+    conn.execute("DELETE FROM activity_goals WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    runtime.goals.lock().unwrap().invalidate();
+    Ok(())
+}
+
+/// 全部已启用目标在当前周期的进度
+#[tauri::command]
+pub fn goals_progress(state: State<DbState>) -> Result<Vec<GoalProgress>, String> {
+    let conn = state.db.lock().unwrap();
+    evaluate_goals(&conn, Utc::now())
+}
+
+#[tauri::command]
+pub fn pomodoro_start(app: tauri::AppHandle, runtime: State<GoalsRuntime>, config: Option<PomodoroConfig>) -> Result<PomodoroStatus, String> {
+    let now = Utc::now();
+    let mut pomodoro = runtime.pomodoro.lock().unwrap();
+    let events = pomodoro.start(config.unwrap_or_default(), now)?;
+    let status = pomodoro.status(now);
+    drop(pomodoro);
+    emit_pomodoro_events(&app, events);
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn pomodoro_stop(app: tauri::AppHandle, runtime: State<GoalsRuntime>) -> Result<PomodoroStatus, String> {
+    let now = Utc::now();
+    let mut pomodoro = runtime.pomodoro.lock().unwrap();
+    let events = pomodoro.stop(now);
+    let status = pomodoro.status(now);
+    drop(pomodoro);
+    emit_pomodoro_events(&app, events);
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn pomodoro_skip(app: tauri::AppHandle, runtime: State<GoalsRuntime>) -> Result<PomodoroStatus, String> {
+    let now = Utc::now();
+    let mut pomodoro = runtime.pomodoro.lock().unwrap();
+    let events = pomodoro.skip(now);
+    let status = pomodoro.status(now);
+    drop(pomodoro);
+    emit_pomodoro_events(&app, events);
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn pomodoro_status(runtime: State<GoalsRuntime>) -> Result<PomodoroStatus, String> {
+    Ok(runtime.pomodoro.lock().unwrap().status(Utc::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn config() -> PomodoroConfig {
+        PomodoroConfig { work_minutes: 1, short_break_minutes: 1, long_break_minutes: 2, long_break_every: 2, focus_category_ids: vec![] }
+    }
+
+    fn test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        conn.execute("INSERT INTO activity_categories (id, name) VALUES (1, '开发')", []).unwrap();
+        conn
+    }
+
+    /// 本地日期 2026-03-10 的 hour 点之后 seconds 秒
+    fn local_at(hour: i64, seconds: i64) -> DateTime<Utc> {
+        (crate::db::local_day_start("2026-03-10").unwrap() + ChronoDuration::hours(hour) + ChronoDuration::seconds(seconds))
+            .with_timezone(&Utc)
+    }
+
+    fn add_activity(conn: &Connection, app: &str, start: DateTime<Utc>, seconds: i64) {
+        conn.execute(
+            "INSERT INTO activity_log (app_name, window_title, start_time, duration_seconds) VALUES (?1, '', ?2, ?3)",
+            params![app, start.to_rfc3339(), seconds],
+        )
+        .unwrap();
+    }
+
+    fn activity(app: &str) -> CurrentActivity {
+        CurrentActivity { app_name: app.into(), window_title: String::new() }
+    }
+
+    #[test]
+    fn goal_input_distinguishes_missing_and_null() {
+        let input: ActivityGoalInput = serde_json::from_str(r#"{"category_id": null, "target_seconds": 60}"#).unwrap();
+        assert_eq!(input.category_id, Some(None));
+        assert_eq!(input.app_pattern, None);
+        assert_eq!(input.target_seconds, Some(60));
+    }
+
+    #[test]
+    fn goal_levels_follow_progress() {
+        assert_eq!(GoalLevel::of(79, 100), GoalLevel::Below);
+        assert_eq!(GoalLevel::of(80, 100), GoalLevel::Approaching);
+        assert_eq!(GoalLevel::of(100, 100), GoalLevel::Reached);
+        assert_eq!(GoalLevel::of(100 + EXCEED_GRACE_SECS, 100), GoalLevel::Reached);
+        assert_eq!(GoalLevel::of(101 + EXCEED_GRACE_SECS, 100), GoalLevel::Exceeded);
+    }
+
+    #[test]
+    fn tracker_reports_rising_levels_with_throttle() {
+        let conn = test_db();
+        conn.execute(
+            "INSERT INTO activity_goals (name, period, direction, target_seconds, app_pattern) VALUES ('写代码', 'day', 'at_least', 3600, 'code*')",
+            [],
+        )
+        .unwrap();
+        add_activity(&conn, "code", local_at(9, 0), 1000);
+        // 其他程序不计入
+        add_activity(&conn, "slack", local_at(8, 0), 3000);
+
+        let levels = |events: Vec<GoalProgress>| events.into_iter().map(|p| p.level).collect::<Vec<_>>();
+        let mut tracker = GoalTracker::default();
+        let now = local_at(12, 0);
+        // 首次检查只记录当前级别
+        assert!(tracker.check(&conn, now).unwrap().is_empty());
+
+        add_activity(&conn, "code", local_at(10, 0), 2000);
+        assert!(tracker.check(&conn, now + ChronoDuration::seconds(GOAL_CHECK_INTERVAL_SECS - 1)).unwrap().is_empty());
+        let events = tracker.check(&conn, now + ChronoDuration::seconds(GOAL_CHECK_INTERVAL_SECS)).unwrap();
+        assert_eq!(events[0].progress_seconds, 3000);
+        assert_eq!(levels(events), vec![GoalLevel::Approaching]);
+
+        add_activity(&conn, "code", local_at(11, 0), 600);
+        let now = now + ChronoDuration::seconds(GOAL_CHECK_INTERVAL_SECS);
+        assert_eq!(levels(tracker.check(&conn, now + ChronoDuration::seconds(GOAL_CHECK_INTERVAL_SECS)).unwrap()), vec![GoalLevel::Reached]);
+
+        // 级别不变时不重复通知；invalidate 后立即重新检查
+        let now = now + ChronoDuration::seconds(GOAL_CHECK_INTERVAL_SECS);
+        tracker.invalidate();
+        assert!(tracker.check(&conn, now + ChronoDuration::seconds(1)).unwrap().is_empty());
+
+        add_activity(&conn, "code", local_at(11, 30), 100);
+        assert!(tracker.check(&conn, now + ChronoDuration::seconds(2)).unwrap().is_empty());
+        tracker.invalidate();
+        assert_eq!(levels(tracker.check(&conn, now + ChronoDuration::seconds(3)).unwrap()), vec![GoalLevel::Exceeded]);
+    }
+
+    #[test]
+    fn pomodoro_work_phase_follows_foreground_app() {
+        let conn = test_db();
+        conn.execute("INSERT INTO activity_rules (match_kind, app_pattern, category_id) VALUES ('glob', 'code*', 1)", []).unwrap();
+        let rules = crate::categories::RuleSet::load(&conn).unwrap();
+        let t0 = Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap();
+        let mut p = Pomodoro::default();
+        p.start(PomodoroConfig { focus_category_ids: vec![1], ..config() }, t0).unwrap();
+
+        let mut tick = |secs: i64, app: Option<&str>| {
+            let a = app.map(activity);
+            let category_id = a.as_ref().and_then(|a| rules.classify(&a.app_name, &a.window_title).category_id);
+            p.tick(t0 + ChronoDuration::seconds(secs), a.as_ref(), category_id)
+        };
+        assert!(tick(2, Some("code")).is_empty());
+        assert!(matches!(&tick(4, Some("chrome"))[..], [PomodoroEvent::Distraction { app_name, .. }] if app_name == "chrome"));
+        assert!(tick(6, Some("chrome")).is_empty());
+        assert!(matches!(&tick(8, Some("code"))[..], [PomodoroEvent::BackOnTask]));
+        // 空闲也算分心
+        assert!(matches!(&tick(10, None)[..], [PomodoroEvent::Distraction { app_name, .. }] if app_name.is_empty()));
+        // 超过 MAX_TICK_GAP_SECS 的间隔不计时
+        assert!(matches!(&tick(40, Some("code"))[..], [PomodoroEvent::BackOnTask]));
+        assert!(tick(50, Some("code")).is_empty());
+        let events = tick(60, Some("code"));
+        assert!(matches!(
+            &events[..],
+            [
+                PomodoroEvent::PhaseFinished { phase: PomodoroPhase::Work, on_task_seconds: 24, off_task_seconds: 6, completed_work: 1 },
+                PomodoroEvent::PhaseStarted { phase: PomodoroPhase::ShortBreak, .. },
+            ]
+        ), "{:?}", events);
+    }
+
+    #[test]
+    fn skipped_work_is_not_counted() {
+        let t0 = Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap();
+        let mut p = Pomodoro::default();
+        p.start(config(), t0).unwrap();
+
+        p.skip(t0 + ChronoDuration::seconds(10));
+        let status = p.status(t0);
+        assert_eq!(status.phase, PomodoroPhase::ShortBreak);
+        assert_eq!(status.completed_work, 0);
+
+        // 休息跳过后的工作段正常完成：第 1 个番茄，进入短休息
+        p.skip(t0 + ChronoDuration::seconds(20));
+        assert_eq!(p.status(t0).phase, PomodoroPhase::Work);
+        let activity = CurrentActivity { app_name: "code".into(), window_title: String::new() };
+        p.tick(t0 + ChronoDuration::seconds(80), Some(&activity), None);
+        let status = p.status(t0);
+        assert_eq!((status.phase, status.completed_work), (PomodoroPhase::ShortBreak, 1));
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// 空闲时长来源：返回距离最后一次键盘/鼠标输入已经过去多久
+pub trait IdleTimeSource {
+    fn idle_duration(&mut self) -> Result<Duration, String>;
+}
+
+/// 按当前平台创建默认的空闲时长来源
+pub fn default_source() -> Box<dyn IdleTimeSource + Send> {
+    #[cfg(target_os = "windows")]
+    {
+        Box::new(Win32IdleSource)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        match X11IdleSource::connect() {
+            Ok(source) => Box::new(source),
+            Err(e) => Box::new(UnsupportedIdleSource { reason: e }),
+        }
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        Box::new(UnsupportedIdleSource {
+            reason: "当前平台不支持空闲检测".into(),
+        })
+    }
+}
+
+// ==================== Windows ====================
+
+/// Win32 实现：GetLastInputInfo 与 GetTickCount 之差
+#[cfg(target_os = "windows")]
+pub struct Win32IdleSource;
+
+#[cfg(target_os = "windows")]
+impl IdleTimeSource for Win32IdleSource {
+    fn idle_duration(&mut self) -> Result<Duration, String> {
+        use windows::Win32::System::SystemInformation::GetTickCount;
+        use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+        unsafe {
+            let mut info = LASTINPUTINFO { cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32, dwTime: 0 };
+            if !GetLastInputInfo(&mut info).as_bool() { return Err("GetLastInputInfo 失败".into()); }
+            // 两者都是 32 位毫秒计数，约 49.7 天回绕一次，用 wrapping_sub 处理
+            let idle_ms = GetTickCount().wrapping_sub(info.dwTime);
+            Ok(Duration::from_millis(idle_ms as u64))
+        }
+    }
+}
+
+// ==================== Linux (X11) ====================
+
+/// Linux 实现：MIT-SCREEN-SAVER 扩展的 ms_since_user_input
+#[cfg(target_os = "linux")]
+pub struct X11IdleSource {
+    conn: x11rb::rust_connection::RustConnection,
+    root: u32,
+}
+
+#[cfg(target_os = "linux")]
+impl X11IdleSource {
+    pub fn connect() -> Result<Self, String> {
+        use x11rb::connection::Connection as _;
+
+        let (conn, screen_num) = x11rb::connect(None).map_err(|e| format!("无法连接 X11: {e}"))?;
+        let root = conn.setup().roots[screen_num].root;
+        Ok(Self { conn, root })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl IdleTimeSource for X11IdleSource {
+    fn idle_duration(&mut self) -> Result<Duration, String> {
+        use x11rb::protocol::screensaver::ConnectionExt as _;
+
+        let info = self
+            .conn
+            .screensaver_query_info(self.root)
+            .map_err(|e| format!("screensaver_query_info 失败: {e}"))?
+            .reply()
+            .map_err(|e| format!("screensaver_query_info 失败: {e}"))?;
+        Ok(Duration::from_millis(info.ms_since_user_input as u64))
+    }
+}
+
+// ==================== 其他 ====================
+
+/// 不可用的来源：始终返回初始化时的错误原因（追踪循环会视为“未空闲”）
+pub struct UnsupportedIdleSource {
+    pub reason: String,
+}
+
+impl IdleTimeSource for UnsupportedIdleSource {
+    fn idle_duration(&mut self) -> Result<Duration, String> {
+        Err(self.reason.clone())
+    }
+}
+
+/// 脚本化来源：按顺序回放预设的空闲时长，耗尽后视为一直有输入
+#[derive(Default)]
+pub struct ScriptedIdleSource {
+    script: VecDeque<Duration>,
+}
+
+impl ScriptedIdleSource {
+    pub fn new<I>(script: I) -> Self
+    where
+        I: IntoIterator<Item = Duration>,
+    {
+        Self { script: script.into_iter().collect() }
+    }
+}
+
+impl IdleTimeSource for ScriptedIdleSource {
+    fn idle_duration(&mut self) -> Result<Duration, String> {
+        Ok(self.script.pop_front().unwrap_or_default())
+    }
+}
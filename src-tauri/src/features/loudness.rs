@@ -0,0 +1,320 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// 电平下限（dBFS / LUFS），静音时代替 -inf
+pub const LEVEL_FLOOR_DB: f32 = -120.0;
+
+/// 积分响度的绝对门限
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+/// 积分响度的相对门限（相对于绝对门限以上块的平均响度）
+const RELATIVE_GATE_LU: f64 = -10.0;
+/// 子块长度 100 ms：瞬时 = 4 个子块（400 ms），短期 = 30 个子块（3 s）
+const SUBBLOCKS_MOMENTARY: usize = 4;
+const SUBBLOCKS_SHORT_TERM: usize = 30;
+
+fn to_db(v: f64) -> f32 {
+    if v > 0.0 { ((20.0 * v.log10()) as f32).max(LEVEL_FLOOR_DB) } else { LEVEL_FLOOR_DB }
+}
+
+/// 均方值（已按声道加权求和）换算为 LUFS
+fn energy_to_lufs(energy: f64) -> f64 {
+    if energy > 0.0 { -0.691 + 10.0 * energy.log10() } else { f64::NEG_INFINITY }
+}
+
+/// 二阶 IIR（Direct Form I）
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 3],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 3]) -> Self {
+        Biquad { b, a, x: [0.0; 2], y: [0.0; 2] }
+    }
+
+    fn process(&mut self, x0: f64) -> f64 {
+        let y0 = self.b[0] * x0 + self.b[1] * self.x[0] + self.b[2] * self.x[1] - self.a[1] * self.y[0] - self.a[2] * self.y[1];
+        self.x = [x0, self.x[0]];
+        self.y = [y0, self.y[0]];
+        y0
+    }
+}
+
+/// ITU-R BS.1770 的 K 计权：高架预滤波 + RLB 高通，系数按采样率计算
+fn k_weighting(sample_rate: u32) -> [Biquad; 2] {
+    let fs = sample_rate as f64;
+
+    let f0 = 1681.974450955533;
+    let g = 3.999843853973347;
+    let q = 0.7071752369554196;
+    let k = (std::f64::consts::PI * f0 / fs).tan();
+    let vh = 10f64.powf(g / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad::new(
+        [(vh + vb * k / q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / q + k * k) / a0],
+        [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    );
+
+    let f0 = 38.13547087602444;
+    let q = 0.5003270373238773;
+    let k = (std::f64::consts::PI * f0 / fs).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let highpass = Biquad::new(
+        [1.0, -2.0, 1.0],
+        [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    );
+    [shelf, highpass]
+}
+
+/// BS.1770 声道权重；5.1（L R C LFE Ls Rs）时 LFE 不计、环绕声道 +1.5 dB
+fn channel_weights(channels: usize) -> Vec<f64> {
+    if channels == 6 {
+        vec![1.0, 1.0, 1.0, 0.0, 1.41, 1.41]
+    } else {
+        vec![1.0; channels]
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ChannelLevel {
+    /// 自上次报告以来的 RMS（dBFS，满幅正弦为 -3 dB）
+    pub rms_db: f32,
+    /// 自上次报告以来的采样峰值（dBFS）
+    pub peak_db: f32,
+}
+
+/// "spectrum:levels" 事件的内容
+#[derive(Debug, Clone, Serialize)]
+pub struct LevelReport {
+    pub channels: Vec<ChannelLevel>,
+    /// EBU R128 瞬时（400 ms）/ 短期（3 s）/ 积分响度，数据不足时为 None
+    pub momentary_lufs: Option<f32>,
+    pub short_term_lufs: Option<f32>,
+    pub integrated_lufs: Option<f32>,
+    /// 积分响度累计的时长（秒）
+    pub integrated_seconds: f32,
+}
+
+/// RMS/峰值电平与 EBU R128 响度
+pub struct LoudnessMeter {
+    channels: usize,
+    weights: Vec<f64>,
+    filters: Vec<[Biquad; 2]>,
+    /// 每个 100 ms 子块的样本数（每声道）
+    subblock_len: usize,
+    /// 当前子块已累计的帧数与各声道 K 计权平方和
+    subblock_frames: usize,
+    subblock_sums: Vec<f64>,
+    /// 最近 30 个子块的加权均方值
+    subblocks: VecDeque<f64>,
+    /// 400 ms 门限块（每 100 ms 一个，75% 重叠）的加权均方值
+    gating_blocks: Vec<f64>,
+    /// 自上次报告以来的平方和、峰值与帧数
+    report_sq: Vec<f64>,
+    report_peak: Vec<f64>,
+    report_frames: usize,
+    sample_rate: u32,
+}
+
+impl LoudnessMeter {
+    pub fn new(sample_rate: u32, channels: usize) -> Self {
+        let channels = channels.max(1);
+        LoudnessMeter {
+            channels,
+            weights: channel_weights(channels),
+            filters: vec![k_weighting(sample_rate); channels],
+            subblock_len: (sample_rate as usize / 10).max(1),
+            subblock_frames: 0,
+            subblock_sums: vec![0.0; channels],
+            subblocks: VecDeque::with_capacity(SUBBLOCKS_SHORT_TERM),
+            gating_blocks: Vec::new(),
+            report_sq: vec![0.0; channels],
+            report_peak: vec![0.0; channels],
+            report_frames: 0,
+            sample_rate,
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// 清空积分响度（以及瞬时/短期窗口）
+    pub fn reset_integrated(&mut self) {
+        self.gating_blocks.clear();
+        self.subblocks.clear();
+        self.subblock_frames = 0;
+        self.subblock_sums.iter_mut().for_each(|s| *s = 0.0);
+        for f in self.filters.iter_mut() {
+            for b in f.iter_mut() {
+                b.x = [0.0; 2];
+                b.y = [0.0; 2];
+            }
+        }
+    }
+
+    /// 输入交错样本
+    pub fn process(&mut self, interleaved: &[f32]) {
+        let ch = self.channels;
+        for frame in interleaved.chunks_exact(ch) {
+            for (c, &s) in frame.iter().enumerate() {
+                let x = s as f64;
+                self.report_sq[c] += x * x;
+                self.report_peak[c] = self.report_peak[c].max(x.abs());
+                let [shelf, hp] = &mut self.filters[c];
+                let y = hp.process(shelf.process(x));
+                self.subblock_sums[c] += y * y;
+            }
+            self.report_frames += 1;
+            self.subblock_frames += 1;
+            if self.subblock_frames == self.subblock_len {
+                self.finish_subblock();
+            }
+        }
+    }
+
+    fn finish_subblock(&mut self) {
+        let n = self.subblock_frames as f64;
+        let energy: f64 = self.subblock_sums.iter().zip(&self.weights).map(|(s, w)| w * s / n).sum();
+        self.subblock_sums.iter_mut().for_each(|s| *s = 0.0);
+        self.subblock_frames = 0;
+        if self.subblocks.len() == SUBBLOCKS_SHORT_TERM {
+            self.subblocks.pop_front();
+        }
+        self.subblocks.push_back(energy);
+        if let Some(block) = self.window_energy(SUBBLOCKS_MOMENTARY) {
+            self.gating_blocks.push(block);
+        }
+    }
+
+    /// 最近 n 个子块的平均能量；子块不足 n 个时为 None
+    fn window_energy(&self, n: usize) -> Option<f64> {
+        if self.subblocks.len() < n { return None; }
+        Some(self.subblocks.iter().rev().take(n).sum::<f64>() / n as f64)
+    }
+
+    /// 两级门限的积分响度（BS.1770-4）
+    pub fn integrated_lufs(&self) -> Option<f64> {
+        let above_abs: Vec<f64> = self
+            .gating_blocks
+            .iter()
+            .copied()
+            .filter(|&e| energy_to_lufs(e) > ABSOLUTE_GATE_LUFS)
+            .collect();
+        if above_abs.is_empty() { return None; }
+        let relative_gate = energy_to_lufs(above_abs.iter().sum::<f64>() / above_abs.len() as f64) + RELATIVE_GATE_LU;
+        let gated: Vec<f64> = above_abs.into_iter().filter(|&e| energy_to_lufs(e) > relative_gate).collect();
+        if gated.is_empty() { return None; }
+        Some(energy_to_lufs(gated.iter().sum::<f64>() / gated.len() as f64))
+    }
+
+    /// 生成报告，并重新开始累计 RMS/峰值
+    pub fn report(&mut self) -> LevelReport {
+        let frames = self.report_frames.max(1) as f64;
+        let channels = self
+            .report_sq
+            .iter()
+            .zip(&self.report_peak)
+            .map(|(sq, peak)| ChannelLevel { rms_db: to_db((sq / frames).sqrt()), peak_db: to_db(*peak) })
+            .collect();
+        self.report_sq.iter_mut().for_each(|s| *s = 0.0);
+        self.report_peak.iter_mut().for_each(|p| *p = 0.0);
+        self.report_frames = 0;
+        let lufs = |e: Option<f64>| e.map(|e| (energy_to_lufs(e) as f32).max(LEVEL_FLOOR_DB));
+        LevelReport {
+            channels,
+            momentary_lufs: lufs(self.window_energy(SUBBLOCKS_MOMENTARY)),
+            short_term_lufs: lufs(self.window_energy(SUBBLOCKS_SHORT_TERM)),
+            integrated_lufs: self.integrated_lufs().map(|l| l as f32),
+            integrated_seconds: self.gating_blocks.len() as f32 / 10.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48_000;
+
+    /// seconds 秒的 997 Hz 正弦（单声道），amplitude_db 为相对满幅的电平
+    fn sine(amplitude_db: f64, seconds: f64) -> Vec<f32> {
+        let amplitude = 10f64.powf(amplitude_db / 20.0);
+        let frames = (seconds * RATE as f64) as usize;
+        (0..frames)
+            .map(|i| (amplitude * (2.0 * std::f64::consts::PI * 997.0 * i as f64 / RATE as f64).sin()) as f32)
+            .collect()
+    }
+
+    fn assert_close(actual: Option<f32>, expected: f32, tol: f32) {
+        let actual = actual.expect("没有读数");
+        assert!((actual - expected).abs() <= tol, "{} vs {}", actual, expected);
+    }
+
+    #[test]
+    fn full_scale_sine_reads_minus_three_lufs() {
+        let mut meter = LoudnessMeter::new(RATE, 1);
+        meter.process(&sine(0.0, 5.0));
+        let report = meter.report();
+        assert_close(report.momentary_lufs, -3.01, 0.05);
+        assert_close(report.short_term_lufs, -3.01, 0.05);
+        assert_close(report.integrated_lufs, -3.01, 0.05);
+        assert_close(Some(report.channels[0].rms_db), -3.01, 0.01);
+        assert_close(Some(report.channels[0].peak_db), 0.0, 0.01);
+        assert!((report.integrated_seconds - 4.7).abs() < 0.05, "{}", report.integrated_seconds);
+
+        // 两个声道各有一份相同信号时能量相加：+3 dB
+        let mut meter = LoudnessMeter::new(RATE, 2);
+        let stereo: Vec<f32> = sine(0.0, 5.0).into_iter().flat_map(|s| [s, s]).collect();
+        meter.process(&stereo);
+        assert_close(meter.report().integrated_lufs, 0.0, 0.05);
+    }
+
+    #[test]
+    fn absolute_gate_ignores_silence_and_very_quiet_audio() {
+        let mut meter = LoudnessMeter::new(RATE, 1);
+        meter.process(&vec![0.0; RATE as usize * 2]);
+        let report = meter.report();
+        assert_eq!(report.integrated_lufs, None);
+        assert_eq!(report.momentary_lufs, Some(LEVEL_FLOOR_DB));
+        assert_eq!(report.channels[0].peak_db, LEVEL_FLOOR_DB);
+
+        // -80 dBFS 约 -83 LUFS，低于 -70 的绝对门限
+        meter.process(&sine(-80.0, 2.0));
+        assert_eq!(meter.integrated_lufs(), None);
+
+        // 静音段不会把积分响度拉低（不加门限时会低约 3 dB）
+        let mut meter = LoudnessMeter::new(RATE, 1);
+        meter.process(&sine(0.0, 5.0));
+        meter.process(&vec![0.0; RATE as usize * 5]);
+        assert_close(meter.report().integrated_lufs, -3.01, 0.2);
+    }
+
+    #[test]
+    fn relative_gate_drops_blocks_ten_lu_below_average() {
+        // -30 dBFS 段高于绝对门限，但比平均响度低 10 LU 以上，被相对门限排除
+        let mut meter = LoudnessMeter::new(RATE, 1);
+        meter.process(&sine(0.0, 5.0));
+        meter.process(&sine(-30.0, 5.0));
+        assert_close(meter.report().integrated_lufs, -3.01, 0.2);
+
+        // -6 dBFS 段在相对门限之内，两段按能量平均
+        let mut meter = LoudnessMeter::new(RATE, 1);
+        meter.process(&sine(0.0, 5.0));
+        meter.process(&sine(-6.0, 5.0));
+        let expected = -3.01 + 10.0 * ((1.0 + 10f32.powf(-0.6)) / 2.0).log10();
+        assert_close(meter.report().integrated_lufs, expected, 0.1);
+
+        // 重置后重新累计
+        meter.reset_integrated();
+        assert_eq!(meter.report().integrated_lufs, None);
+    }
+}
@@ -0,0 +1,240 @@
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+const NOTE_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+/// 低于该 RMS（约 -60 dBFS）的块视为静音，不做估计
+const SILENCE_RMS: f32 = 0.001;
+
+/// 调音器参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TunerConfig {
+    pub enabled: bool,
+    /// 标准音 A4 的频率
+    pub a4_hz: f32,
+    /// 搜索范围；下限还受 FFT 大小限制（sample_rate / (fft_size / 2)）
+    pub min_freq: f32,
+    pub max_freq: f32,
+    /// YIN 的绝对阈值，越小越严格
+    pub threshold: f32,
+}
+
+impl Default for TunerConfig {
+    fn default() -> Self {
+        TunerConfig { enabled: false, a4_hz: 440.0, min_freq: 50.0, max_freq: 2000.0, threshold: 0.15 }
+    }
+}
+
+impl TunerConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(400.0..=480.0).contains(&self.a4_hz) {
+            return Err("a4_hz must be within 400..480".into());
+        }
+        if !(self.min_freq >= 20.0 && self.min_freq < self.max_freq && self.max_freq <= 8000.0) {
+            return Err("tuner range must satisfy 20 <= min_freq < max_freq <= 8000".into());
+        }
+        if !(self.threshold > 0.0 && self.threshold < 1.0) {
+            return Err("threshold must be within (0, 1)".into());
+        }
+        Ok(())
+    }
+}
+
+/// "spectrum:pitch" 事件的内容
+#[derive(Debug, Clone, Serialize)]
+pub struct PitchEstimate {
+    /// 基频（Hz）
+    pub frequency: f32,
+    /// 最近的十二平均律音名（升号记法）与八度，A4 = 440 Hz 时 C4 为中央 C
+    pub note: &'static str,
+    pub octave: i32,
+    /// 相对最近音的偏差（音分，-50..50）
+    pub cents: f32,
+    /// 1 - CMNDF 最小值，越接近 1 越可信
+    pub confidence: f32,
+    pub midi: i32,
+}
+
+/// 频率换算为最近的音名、八度与音分偏差
+pub fn note_for_frequency(frequency: f32, a4_hz: f32) -> (&'static str, i32, f32, i32) {
+    let midi = 69.0 + 12.0 * (frequency / a4_hz).log2();
+    let nearest = midi.round() as i32;
+    let cents = (midi - nearest as f32) * 100.0;
+    (NOTE_NAMES[nearest.rem_euclid(12) as usize], nearest.div_euclid(12) - 1, cents, nearest)
+}
+
+/// YIN 基频估计（de Cheveigné & Kawahara, 2002），差分函数的互相关项用 FFT 计算
+pub struct PitchDetector {
+    size: usize,
+    fft: Arc<dyn Fft<f32>>,
+    ifft: Arc<dyn Fft<f32>>,
+}
+
+impl PitchDetector {
+    /// size 为输入块长度（通常等于 fft_size），可检测的最长周期为 size / 2
+    pub fn new(size: usize) -> Self {
+        let mut planner = FftPlanner::<f32>::new();
+        PitchDetector { size, fft: planner.plan_fft_forward(size * 2), ifft: planner.plan_fft_inverse(size * 2) }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// 差分函数 d(τ) = Σ_{j<W} (x_j - x_{j+τ})²，τ ∈ [0, W)，W = N/2
+    fn difference(&self, x: &[f32]) -> Vec<f32> {
+        let n = self.size;
+        let w = n / 2;
+        let m = n * 2;
+        let mut a: Vec<Complex<f32>> = (0..m).map(|j| Complex::new(if j < w { x[j] } else { 0.0 }, 0.0)).collect();
+        let mut b: Vec<Complex<f32>> = (0..m).map(|j| Complex::new(if j < n { x[j] } else { 0.0 }, 0.0)).collect();
+        self.fft.process(&mut a);
+        self.fft.process(&mut b);
+        let mut c: Vec<Complex<f32>> = a.iter().zip(&b).map(|(a, b)| a.conj() * b).collect();
+        self.ifft.process(&mut c);
+
+        // 能量项用前缀平方和：Σ_{j<W} x_j² 与 Σ_{j<W} x_{j+τ}²
+        let mut prefix = vec![0f64; n + 1];
+        for (i, v) in x.iter().enumerate() {
+            prefix[i + 1] = prefix[i] + (*v as f64) * (*v as f64);
+        }
+        let e0 = prefix[w];
+        (0..w)
+            .map(|tau| {
+                let cross = c[tau].re as f64 / m as f64;
+                let et = prefix[tau + w] - prefix[tau];
+                (e0 + et - 2.0 * cross).max(0.0) as f32
+            })
+            .collect()
+    }
+
+    /// 估计单声道块的基频；静音、无明显周期或超出范围时返回 None
+    pub fn detect(&self, mono: &[f32], sample_rate: u32, config: &TunerConfig) -> Option<PitchEstimate> {
+        if mono.len() != self.size { return None; }
+        let rms = (mono.iter().map(|v| v * v).sum::<f32>() / mono.len() as f32).sqrt();
+        if rms < SILENCE_RMS { return None; }
+
+        let d = self.difference(mono);
+        let w = d.len();
+        let sr = sample_rate as f32;
+        let tau_min = ((sr / config.max_freq).floor() as usize).max(2);
+        let tau_max = ((sr / config.min_freq).ceil() as usize).min(w - 1);
+        if tau_min >= tau_max { return None; }
+
+        // 累积均值归一化差分（CMNDF）
+        let mut cmnd = vec![1f32; w];
+        let mut running = 0f32;
+        for tau in 1..w {
+            running += d[tau];
+            cmnd[tau] = if running > 0.0 { d[tau] * tau as f32 / running } else { 1.0 };
+        }
+
+        // 第一个低于阈值的谷；没有时取范围内的全局最小值
+        let mut tau = (tau_min..tau_max).find(|&t| cmnd[t] < config.threshold);
+        if let Some(t) = tau.as_mut() {
+            while *t + 1 < tau_max && cmnd[*t + 1] < cmnd[*t] {
+                *t += 1;
+            }
+        }
+        let tau = tau.unwrap_or_else(|| {
+            (tau_min..tau_max).min_by(|&a, &b| cmnd[a].total_cmp(&cmnd[b])).unwrap_or(tau_min)
+        });
+        let confidence = (1.0 - cmnd[tau]).clamp(0.0, 1.0);
+        if cmnd[tau] >= 0.5 { return None; }
+
+        // 抛物线插值得到亚采样精度的周期
+        let refined = if tau > 0 && tau + 1 < w {
+            let (s0, s1, s2) = (cmnd[tau - 1], cmnd[tau], cmnd[tau + 1]);
+            let denom = s0 - 2.0 * s1 + s2;
+            if denom.abs() > f32::EPSILON { tau as f32 + 0.5 * (s0 - s2) / denom } else { tau as f32 }
+        } else {
+            tau as f32
+        };
+        let frequency = sr / refined;
+        if frequency < config.min_freq || frequency > config.max_freq { return None; }
+        let (note, octave, cents, midi) = note_for_frequency(frequency, config.a4_hz);
+        Some(PitchEstimate { frequency, note, octave, cents, confidence, midi })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    /// 由 (倍频, 幅值) 组成的谐波音
+    fn tone(sample_rate: u32, freq: f32, partials: &[(f32, f32)], len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| {
+                let t = i as f32 / sample_rate as f32;
+                partials.iter().map(|(k, a)| a * (2.0 * PI * k * freq * t).sin()).sum()
+            })
+            .collect()
+    }
+
+    fn detect(samples: &[f32], sample_rate: u32) -> Option<PitchEstimate> {
+        PitchDetector::new(samples.len()).detect(samples, sample_rate, &TunerConfig::default())
+    }
+
+    #[test]
+    fn a4_is_440_hz() {
+        for sample_rate in [44_100, 48_000] {
+            let p = detect(&tone(sample_rate, 440.0, &[(1.0, 0.5)], 2048), sample_rate).unwrap();
+            assert!((p.frequency - 440.0).abs() < 0.5, "{:?}", p);
+            assert_eq!((p.note, p.octave, p.midi), ("A", 4, 69));
+            assert!(p.cents.abs() < 2.0 && p.confidence > 0.9, "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn reports_the_fundamental_not_an_octave() {
+        // 二次谐波远强于基频：周期仍应取基频的 τ 而不是一半（高八度）
+        let p = detect(&tone(48_000, 110.0, &[(1.0, 0.15), (2.0, 0.6), (3.0, 0.2)], 2048), 48_000).unwrap();
+        assert!((p.frequency - 110.0).abs() < 0.5, "{:?}", p);
+        assert_eq!((p.note, p.octave), ("A", 2));
+        // 纯音在 2τ 处也有同样深的谷：应取第一个谷，不能报低八度
+        let p = detect(&tone(48_000, 220.0, &[(1.0, 0.5)], 2048), 48_000).unwrap();
+        assert!((p.frequency - 220.0).abs() < 0.5, "{:?}", p);
+        assert_eq!((p.note, p.octave), ("A", 3));
+    }
+
+    #[test]
+    fn silence_has_no_pitch() {
+        assert!(detect(&[0.0; 2048], 48_000).is_none());
+        // 低于 -60 dBFS 的底噪同样视为静音
+        assert!(detect(&tone(48_000, 440.0, &[(1.0, 0.0005)], 2048), 48_000).is_none());
+        // 块长度与检测器不符时不估计
+        assert!(PitchDetector::new(2048).detect(&[0.5; 1024], 48_000, &TunerConfig::default()).is_none());
+    }
+
+    #[test]
+    fn out_of_range_tones_are_ignored() {
+        let config = TunerConfig { min_freq: 100.0, max_freq: 1000.0, ..Default::default() };
+        let d = PitchDetector::new(2048);
+        assert!(d.detect(&tone(48_000, 60.0, &[(1.0, 0.5)], 2048), 48_000, &config).is_none());
+        assert!(d.detect(&tone(48_000, 440.0, &[(1.0, 0.5)], 2048), 48_000, &config).is_some());
+    }
+
+    #[test]
+    fn note_names_and_cents() {
+        let (note, octave, cents, midi) = note_for_frequency(440.0 * 2f32.powf(0.3 / 12.0), 440.0);
+        assert_eq!((note, octave, midi), ("A", 4, 69));
+        assert!((cents - 30.0).abs() < 0.01);
+        assert_eq!(note_for_frequency(261.63, 440.0).0, "C");
+        assert_eq!(note_for_frequency(261.63, 440.0).1, 4);
+        assert_eq!(note_for_frequency(27.5, 440.0), ("A", 0, 0.0, 21));
+        // 以 A4 = 432 Hz 定音时 432 Hz 即为 A4
+        assert_eq!(note_for_frequency(432.0, 432.0).3, 69);
+    }
+
+    #[test]
+    fn validate_rejects_bad_config() {
+        assert!(TunerConfig::default().validate().is_ok());
+        assert!(TunerConfig { a4_hz: 500.0, ..Default::default() }.validate().is_err());
+        assert!(TunerConfig { min_freq: 2000.0, max_freq: 1000.0, ..Default::default() }.validate().is_err());
+        assert!(TunerConfig { threshold: 1.0, ..Default::default() }.validate().is_err());
+    }
+}
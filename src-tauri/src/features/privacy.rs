@@ -0,0 +1,506 @@
+use crate::categories::compile_pattern;
+use crate::db::{self, DbState};
+use once_cell::sync::Lazy;
+use regex::{Regex, RegexBuilder};
+use rusqlite::{params, Connection, Result as SqlResult};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+/// app_settings 中保存哈希盐的键（每台机器随机生成一次）
+const HASH_SALT_KEY: &str = "privacy_hash_salt";
+
+/// 哈希后的标题前缀；处理已入库的标题时据此识别已哈希的值，保证重复处理结果不变
+pub const HASHED_TITLE_PREFIX: &str = "#h:";
+
+/// 按程序匹配的标题处理规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivacyRule {
+    pub id: i64,
+    pub priority: i64,
+    pub match_kind: String,
+    pub app_pattern: String,
+    /// keep: 原样保留；drop: 丢弃标题；domain: 只保留其中的域名；hash: 保存标题的哈希
+    pub action: String,
+    pub enabled: bool,
+}
+
+/// 新建/更新规则的参数；更新时缺省字段保持不变
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrivacyRuleInput {
+    pub priority: Option<i64>,
+    pub match_kind: Option<String>,
+    pub app_pattern: Option<String>,
+    pub action: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// 全局黑名单：标题命中任一正则即整条丢弃
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DenylistEntry {
+    pub id: i64,
+    pub pattern: String,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedactAction {
+    Keep,
+    Drop,
+    Domain,
+    Hash,
+}
+
+impl RedactAction {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "keep" => Ok(Self::Keep),
+            "drop" => Ok(Self::Drop),
+            "domain" => Ok(Self::Domain),
+            "hash" => Ok(Self::Hash),
+            other => Err(format!("未知的脱敏方式: {}（可选 keep / drop / domain / hash）", other)),
+        }
+    }
+}
+
+/// 带协议的地址：`scheme://[userinfo@]host`，userinfo 部分不算域名
+static URL_HOST_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b[a-z][a-z0-9+.-]*://(?:[^\s/?#@]*@)?((?:[a-z0-9](?:[a-z0-9-]{0,61}[a-z0-9])?\.)+[a-z]{2,63})\b")
+        .unwrap()
+});
+
+/// 不带协议的主机名，是否采用还要看前后字符和末段
+static BARE_HOST_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:[a-z0-9](?:[a-z0-9-]{0,61}[a-z0-9])?\.)+[a-z]{2,63}\b").unwrap()
+});
+
+/// 常见的文件扩展名：不带协议时 "report.docx"、"main.rs" 这类按文件名处理，不当作域名
+const FILE_EXTENSIONS: &[&str] = &[
+    "bak", "bat", "c", "cfg", "conf", "cpp", "cs", "css", "csv", "doc", "docx", "exe", "gif", "go",
+    "h", "hpp", "htm", "html", "ini", "ipynb", "java", "jpeg", "jpg", "js", "json", "jsx", "key",
+    "kt", "log", "lua", "md", "mp3", "mp4", "numbers", "odp", "ods", "odt", "pages", "pdf", "php",
+    "png", "ppt", "pptx", "ps1", "psd", "py", "rb", "rs", "rtf", "sh", "sql", "svg", "swift", "tex",
+    "toml", "ts", "tsx", "txt", "vue", "wav", "xls", "xlsx", "xml", "yaml", "yml", "zip",
+];
+
+/// 标题中的域名：优先取 `scheme://` 后的主机名，其次取不紧挨 `@`、末段不是文件扩展名的裸主机名；
+/// 邮箱地址的两半和文件名都不算，没有则为 None（domain 规则随即丢弃整条标题）
+pub fn extract_domain(title: &str) -> Option<String> {
+    if let Some(host) = URL_HOST_RE.captures(title).and_then(|c| c.get(1)) {
+        return Some(host.as_str().to_lowercase());
+    }
+    BARE_HOST_RE
+        .find_iter(title)
+        .filter(|m| !title[..m.start()].ends_with('@') && !title[m.end()..].starts_with('@'))
+        .map(|m| m.as_str().to_lowercase())
+        .find(|host| host.rsplit('.').next().is_some_and(|tld| !FILE_EXTENSIONS.contains(&tld)))
+}
+
+/// 加盐的 FNV-1a 64 位哈希：相同标题得到相同结果，便于按标题统计且库里不出现原文
+///
+/// 这不是密码学哈希，盐也和结果保存在同一个库里：拿到数据库的人可以穷举
+/// 取值范围小的标题（例如固定格式加数字）来还原，敏感标题应使用 drop 或黑名单。
+fn hash_title(salt: &str, title: &str) -> String {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in salt.bytes().chain([0u8]).chain(title.bytes()) {
+        h ^= b as u64;
+        h = h.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{}{:016x}", HASHED_TITLE_PREFIX, h)
+}
+
+/// 是否为 hash_title 的输出格式（前缀 + 16 位小写十六进制）
+fn is_hashed(title: &str) -> bool {
+    title
+        .strip_prefix(HASHED_TITLE_PREFIX)
+        .is_some_and(|h| h.len() == 16 && h.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')))
+}
+
+/// 已编译的脱敏策略
+pub struct RedactionPolicy {
+    rules: Vec<(Regex, RedactAction)>,
+    denylist: Vec<Regex>,
+    salt: String,
+}
+
+impl RedactionPolicy {
+    pub fn load(conn: &Connection) -> Result<Self, String> {
+        let rules = list_rules(conn)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|r| r.enabled)
+            .filter_map(|r| match compile_rule(&r) {
+                Ok(c) => Some(c),
+                Err(e) => {
+                    eprintln!("[privacy] 跳过无效规则 (id={}): {}", r.id, e);
+                    None
+                }
+            })
+            .collect();
+        let denylist = list_denylist(conn)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|d| d.enabled)
+            .filter_map(|d| match compile_denylist(&d.pattern) {
+                Ok(re) => Some(re),
+                Err(e) => {
+                    eprintln!("[privacy] 跳过无效黑名单 (id={}): {}", d.id, e);
+                    None
+                }
+            })
+            .collect();
+        Ok(Self { rules, denylist, salt: hash_salt(conn)? })
+    }
+
+    /// 返回应写入数据库的标题；用于刚采集到的原始标题，哈希规则总是重新计算
+    pub fn redact(&self, app_name: &str, window_title: &str) -> String {
+        self.apply(app_name, window_title, false)
+    }
+
+    /// 处理已入库或从导出文件导入的标题：已是哈希格式的值保持不变（丢弃规则仍然生效），
+    /// 同一条记录重复处理、重复导入时结果一致
+    pub fn redact_stored(&self, app_name: &str, window_title: &str) -> String {
+        self.apply(app_name, window_title, true)
+    }
+
+    fn apply(&self, app_name: &str, window_title: &str, stored: bool) -> String {
+        if window_title.is_empty() || self.denylist.iter().any(|re| re.is_match(window_title)) {
+            return String::new();
+        }
+        let action = self
+            .rules
+            .iter()
+            .find(|(re, _)| re.is_match(app_name))
+            .map(|(_, a)| *a)
+            .unwrap_or(RedactAction::Keep);
+        match action {
+            RedactAction::Keep => window_title.to_string(),
+            RedactAction::Drop => String::new(),
+            _ if stored && is_hashed(window_title) => window_title.to_string(),
+            RedactAction::Domain => extract_domain(window_title).unwrap_or_default(),
+            RedactAction::Hash => hash_title(&self.salt, window_title),
+        }
+    }
+}
+
+fn hash_salt(conn: &Connection) -> Result<String, String> {
+    if let Some(salt) = db::get_setting::<String>(conn, HASH_SALT_KEY)? {
+        return Ok(salt);
+    }
+    let salt = format!("{:016x}{:016x}", fastrand::u64(..), fastrand::u64(..));
+    db::set_setting(conn, HASH_SALT_KEY, &salt)?;
+    Ok(salt)
+}
+
+fn compile_rule(rule: &PrivacyRule) -> Result<(Regex, RedactAction), String> {
+    if rule.app_pattern.trim().is_empty() {
+        return Err("app_pattern 不能为空".into());
+    }
+    Ok((compile_pattern(&rule.match_kind, &rule.app_pattern)?, RedactAction::parse(&rule.action)?))
+}
+
+fn compile_denylist(pattern: &str) -> Result<Regex, String> {
+    if pattern.is_empty() {
+        return Err("黑名单模式不能为空".into());
+    }
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| format!("无效的正则 '{}': {}", pattern, e))
+}
+
+// 追踪线程使用的策略缓存；规则或黑名单变化后失效
+static POLICY_CACHE: Lazy<Mutex<Option<Arc<RedactionPolicy>>>> = Lazy::new(|| Mutex::new(None));
+
+fn invalidate_policy_cache() {
+    if let Ok(mut cache) = POLICY_CACHE.lock() {
+        *cache = None;
+    }
+}
+
+fn cached_policy(conn: &Connection) -> Result<Arc<RedactionPolicy>, String> {
+    let mut cache = POLICY_CACHE.lock().unwrap();
+    if let Some(policy) = cache.as_ref() {
+        return Ok(policy.clone());
+    }
+    let policy = Arc::new(RedactionPolicy::load(conn)?);
+    *cache = Some(policy.clone());
+    Ok(policy)
+}
+
+/// 按当前策略脱敏窗口标题（供追踪器写入前使用）
+pub fn redact(conn: &Connection, app_name: &str, window_title: &str) -> Result<String, String> {
+    Ok(cached_policy(conn)?.redact(app_name, window_title))
+}
+
+/// 按当前策略处理已入库或导入的标题，见 RedactionPolicy::redact_stored
+pub fn redact_stored(conn: &Connection, app_name: &str, window_title: &str) -> Result<String, String> {
+    Ok(cached_policy(conn)?.redact_stored(app_name, window_title))
+}
+
+// ==================== 数据库操作 ====================
+
+pub fn list_rules(conn: &Connection) -> SqlResult<Vec<PrivacyRule>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, priority, match_kind, app_pattern, action, enabled
+         FROM privacy_rules ORDER BY priority DESC, id ASC",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(PrivacyRule {
+                id: row.get(0)?,
+                priority: row.get(1)?,
+                match_kind: row.get(2)?,
+                app_pattern: row.get(3)?,
+                action: row.get(4)?,
+                enabled: row.get(5)?,
+            })
+        })?
+        .collect::<SqlResult<Vec<_>>>()?;
+    Ok(rows)
+}
+
+pub fn list_denylist(conn: &Connection) -> SqlResult<Vec<DenylistEntry>> {
+    let mut stmt = conn.prepare("SELECT id, pattern, enabled FROM privacy_denylist ORDER BY id")?;
+    let rows = stmt
+        .query_map([], |row| Ok(DenylistEntry { id: row.get(0)?, pattern: row.get(1)?, enabled: row.get(2)? }))?
+        .collect::<SqlResult<Vec<_>>>()?;
+    Ok(rows)
+}
+
+/// 用当前策略重新脱敏已有记录；`range` 为本地日期闭区间，None 表示全部。返回改动的行数
+///
+/// 脱敏不可逆：已丢弃或哈希的标题不会因为规则放宽而恢复。
+pub fn reredact_history(conn: &mut Connection, range: Option<(String, String)>) -> Result<usize, String> {
+    let policy = RedactionPolicy::load(conn)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut updated = 0usize;
+    {
+        let (sql, bounds) = match range {
+            Some((start, end)) => (
+                "SELECT id, app_name, window_title FROM activity_log WHERE window_title <> '' AND start_time >= ?1 AND start_time < ?2",
+                Some(db::local_range_bounds(&start, &end)?),
+            ),
+            None => ("SELECT id, app_name, window_title FROM activity_log WHERE window_title <> ''", None),
+        };
+        let mut select = tx.prepare(sql).map_err(|e| e.to_string())?;
+        let map_row = |row: &rusqlite::Row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?));
+        let rows = match &bounds {
+            Some((s, e)) => select.query_map(params![s, e], map_row),
+            None => select.query_map([], map_row),
+        }
+        .map_err(|e| e.to_string())?
+        .collect::<SqlResult<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+
+        let mut update = tx
+            .prepare("UPDATE activity_log SET window_title = ?1 WHERE id = ?2")
+            .map_err(|e| e.to_string())?;
+        for (id, app_name, title) in rows {
+            let redacted = policy.redact_stored(&app_name, &title);
+            if redacted != title {
+                updated += update.execute(params![redacted, id]).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(updated)
+}
+
+// ==================== Tauri 命令 ====================
+
+#[tauri::command]
+pub fn privacy_list_rules(state: State<DbState>) -> Result<Vec<PrivacyRule>, String> {
+    let conn = state.db.lock().unwrap();
+    list_rules(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn privacy_create_rule(state: State<DbState>, rule: PrivacyRuleInput) -> Result<PrivacyRule, String> {
+    let mut candidate = PrivacyRule {
+        id: 0,
+        priority: rule.priority.unwrap_or(0),
+        match_kind: rule.match_kind.unwrap_or_else(|| "glob".into()),
+        app_pattern: rule.app_pattern.unwrap_or_default(),
+        action: rule.action.ok_or("缺少 action")?,
+        enabled: rule.enabled.unwrap_or(true),
+    };
+    compile_rule(&candidate)?;
+
+    let conn = state.db.lock().unwrap();
+    conn.execute(
+        "INSERT INTO privacy_rules (priority, match_kind, app_pattern, action, enabled) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![candidate.priority, candidate.match_kind, candidate.app_pattern, candidate.action, candidate.enabled],
+    )
+    .map_err(|e| e.to_string())?;
+    candidate.id = conn.last_insert_rowid();
+    invalidate_policy_cache();
+    Ok(candidate)
+}
+
+/// 更新规则；未提供的字段保持不变
+#[tauri::command]
+pub fn privacy_update_rule(state: State<DbState>, id: i64, rule: PrivacyRuleInput) -> Result<PrivacyRule, String> {
+    let conn = state.db.lock().unwrap();
+    let mut current = list_rules(&conn)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|r| r.id == id)
+        .ok_or_else(|| format!("规则不存在: {}", id))?;
+    if let Some(v) = rule.priority { current.priority = v; }
+    if let Some(v) = rule.match_kind { current.match_kind = v; }
+    if let Some(v) = rule.app_pattern { current.app_pattern = v; }
+    if let Some(v) = rule.action { current.action = v; }
+    if let Some(v) = rule.enabled { current.enabled = v; }
+    compile_rule(&current)?;
+
+    conn.execute(
+        "UPDATE privacy_rules SET priority = ?1, match_kind = ?2, app_pattern = ?3, action = ?4, enabled = ?5 WHERE id = ?6",
+        params![current.priority, current.match_kind, current.app_pattern, current.action, current.enabled, id],
+    )
+    .map_err(|e| e.to_string())?;
+    invalidate_policy_cache();
+    Ok(current)
+}
+
+#[tauri::command]
+pub fn privacy_delete_rule(state: State<DbState>, id: i64) -> Result<(), String> {
+    let conn = state.db.lock().unwrap();
+    conn.execute("DELETE FROM privacy_rules WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    invalidate_policy_cache();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn privacy_list_denylist(state: State<DbState>) -> Result<Vec<DenylistEntry>, String> {
+    let conn = state.db.lock().unwrap();
+    list_denylist(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn privacy_add_denylist(state: State<DbState>, pattern: String) -> Result<DenylistEntry, String> {
+    compile_denylist(&pattern)?;
+    let conn = state.db.lock().unwrap();
+    conn.execute("INSERT INTO privacy_denylist (pattern) VALUES (?1)", [&pattern])
+        .map_err(|e| e.to_string())?;
+    invalidate_policy_cache();
+    Ok(DenylistEntry { id: conn.last_insert_rowid(), pattern, enabled: true })
+}
+
+#[tauri::command]
+pub fn privacy_set_denylist_enabled(state: State<DbState>, id: i64, enabled: bool) -> Result<(), String> {
+    let conn = state.db.lock().unwrap();
+    conn.execute("UPDATE privacy_denylist SET enabled = ?1 WHERE id = ?2", params![enabled, id])
+        .map_err(|e| e.to_string())?;
+    invalidate_policy_cache();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn privacy_delete_denylist(state: State<DbState>, id: i64) -> Result<(), String> {
+    let conn = state.db.lock().unwrap();
+    conn.execute("DELETE FROM privacy_denylist WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    invalidate_policy_cache();
+    Ok(())
+}
+
+/// 对历史记录重新应用脱敏规则；不传日期则处理全部记录
+#[tauri::command]
+pub async fn privacy_reredact_history(
+    app_handle: tauri::AppHandle,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<usize, String> {
+    use tauri::Manager;
+    let range = match (start_date, end_date) {
+        (Some(s), Some(e)) => Some((s, e)),
+        (Some(s), None) => Some((s.clone(), s)),
+        (None, Some(_)) => return Err("缺少 start_date".into()),
+        (None, None) => None,
+    };
+    tokio::task::spawn_blocking(move || {
+        let state: State<DbState> = app_handle.state();
+        let mut conn = state.db.lock().unwrap();
+        reredact_history(&mut conn, range)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy_with(action: &str) -> RedactionPolicy {
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_db(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO privacy_rules (app_pattern, action) VALUES ('browser', ?1)",
+            [action],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO privacy_denylist (pattern) VALUES ('secret')", []).unwrap();
+        RedactionPolicy::load(&conn).unwrap()
+    }
+
+    #[test]
+    fn hash_rule_always_hashes_raw_titles() {
+        let policy = policy_with("hash");
+        let hashed = policy.redact("browser", "Inbox");
+        assert!(is_hashed(&hashed), "{}", hashed);
+        assert_eq!(policy.redact("browser", "Inbox"), hashed);
+
+        // 原始标题恰好带有哈希前缀也要重新哈希
+        let fake = format!("{}0123456789abcdef", HASHED_TITLE_PREFIX);
+        let rehashed = policy.redact("browser", &fake);
+        assert_ne!(rehashed, fake);
+        assert!(is_hashed(&rehashed));
+    }
+
+    #[test]
+    fn stored_titles_are_stable() {
+        let policy = policy_with("hash");
+        let hashed = policy.redact("browser", "Inbox");
+        assert_eq!(policy.redact_stored("browser", &hashed), hashed);
+        assert_eq!(policy.redact_stored("browser", "Inbox"), hashed);
+        assert_eq!(policy.redact_stored("browser", "my secret page"), "");
+
+        let policy = policy_with("drop");
+        assert_eq!(policy.redact_stored("browser", &hashed), "");
+    }
+
+    #[test]
+    fn domain_and_denylist() {
+        let policy = policy_with("domain");
+        assert_eq!(policy.redact("browser", "Docs - https://Example.com/path"), "example.com");
+        assert_eq!(policy.redact("browser", "no domain here"), "");
+        assert_eq!(policy.redact("editor", "notes.txt"), "notes.txt");
+        assert_eq!(policy.redact("editor", "Secret plan"), "");
+    }
+
+    #[test]
+    fn domain_ignores_email_addresses() {
+        assert_eq!(extract_domain("Inbox - alice.smith@gmail.com - Gmail"), None);
+        assert_eq!(extract_domain("alice.smith@gmail.com on mail.google.com"), Some("mail.google.com".into()));
+        // userinfo 不是主机名
+        assert_eq!(extract_domain("https://alice.smith@example.com/x"), Some("example.com".into()));
+
+        let policy = policy_with("domain");
+        assert_eq!(policy.redact("browser", "Inbox - alice.smith@gmail.com - Gmail"), "");
+    }
+
+    #[test]
+    fn domain_ignores_filenames() {
+        assert_eq!(extract_domain("report.docx - Word"), None);
+        assert_eq!(extract_domain("main.rs - crate - Visual Studio Code"), None);
+        // 带协议时按地址处理
+        assert_eq!(extract_domain("file at http://docs.rs/regex"), Some("docs.rs".into()));
+        assert_eq!(extract_domain("report.docx - GitHub.com"), Some("github.com".into()));
+
+        let policy = policy_with("domain");
+        assert_eq!(policy.redact("browser", "report.docx - Word"), "");
+        assert_eq!(policy.redact_stored("browser", "example.com"), "example.com");
+    }
+}
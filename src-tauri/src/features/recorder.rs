@@ -0,0 +1,455 @@
+use crate::audio_source::AudioFormat;
+use crate::spectrum_dsp::MagnitudeScale;
+use crate::spectrum_view::{SpectrumFrame, SpectrumMode};
+use polars::prelude::{Column, DataFrame, ParquetCompression, ParquetWriter};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+/// 写入线程的队列长度；队列满时丢弃新消息而不是阻塞采集线程
+const QUEUE_CAPACITY: usize = 512;
+
+/// 频谱帧文件格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameFileFormat {
+    /// 紧凑二进制，边录边写（格式见 BinaryFrameWriter）
+    #[default]
+    Binary,
+    /// 宽表 Parquet（timestamp_ms, seq, channel, mode, scale, v0..vN），停止时一次写出，可直接在 Datascope 打开
+    Parquet,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingOptions {
+    /// 音频输出路径（32 位浮点 WAV）
+    pub wav_path: String,
+    /// 频谱帧输出路径；为空时只录音频
+    #[serde(default)]
+    pub frames_path: Option<String>,
+    #[serde(default)]
+    pub frames_format: FrameFileFormat,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingSummary {
+    pub wav_path: String,
+    pub frames_path: Option<String>,
+    /// 写入的音频帧数（每声道样本数）
+    pub audio_frames: u64,
+    pub spectrum_frames: u64,
+    /// 因写入线程跟不上而丢弃的消息数；非 0 时 WAV 中存在缺口
+    pub dropped: u64,
+    pub duration_secs: f64,
+}
+
+enum RecorderMessage {
+    Audio(Vec<f32>),
+    Frame { timestamp_ms: u64, frame: SpectrumFrame },
+}
+
+/// 正在进行的录制。采集线程只做非阻塞的 try_send，编码与磁盘 IO 都在写入线程完成
+pub struct Recorder {
+    tx: SyncSender<RecorderMessage>,
+    worker: JoinHandle<Result<(u64, u64), String>>,
+    dropped: Arc<AtomicU64>,
+    started: Instant,
+    format: AudioFormat,
+    wav_path: String,
+    frames_path: Option<String>,
+}
+
+impl Recorder {
+    /// 创建输出文件并启动写入线程；文件无法创建时直接返回错误
+    pub fn start(options: RecordingOptions, format: AudioFormat) -> Result<Self, String> {
+        let wav = WavWriter::create(&options.wav_path, format)?;
+        let frames: Option<Box<dyn FrameSink>> = match &options.frames_path {
+            None => None,
+            Some(path) => Some(match options.frames_format {
+                FrameFileFormat::Binary => Box::new(BinaryFrameWriter::create(path)?),
+                FrameFileFormat::Parquet => Box::new(ParquetFrameWriter::create(path)?),
+            }),
+        };
+        let (tx, rx) = mpsc::sync_channel(QUEUE_CAPACITY);
+        let worker = std::thread::spawn(move || write_loop(rx, wav, frames));
+        Ok(Recorder {
+            tx,
+            worker,
+            dropped: Arc::new(AtomicU64::new(0)),
+            started: Instant::now(),
+            format,
+            wav_path: options.wav_path,
+            frames_path: options.frames_path,
+        })
+    }
+
+    pub fn format(&self) -> AudioFormat {
+        self.format
+    }
+
+    fn send(&self, message: RecorderMessage) {
+        if let Err(TrySendError::Full(_)) = self.tx.try_send(message) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 交错样本，须与 start 时的格式一致
+    pub fn push_audio(&self, interleaved: &[f32]) {
+        if interleaved.is_empty() { return; }
+        self.send(RecorderMessage::Audio(interleaved.to_vec()));
+    }
+
+    pub fn push_frame(&self, frame: &SpectrumFrame) {
+        let timestamp_ms = self.started.elapsed().as_millis() as u64;
+        self.send(RecorderMessage::Frame { timestamp_ms, frame: frame.clone() });
+    }
+
+    /// 关闭队列，等待写入线程写完并补全文件头
+    pub fn finish(self) -> Result<RecordingSummary, String> {
+        let duration_secs = self.started.elapsed().as_secs_f64();
+        drop(self.tx);
+        let (audio_frames, spectrum_frames) = self
+            .worker
+            .join()
+            .map_err(|_| "recording writer thread panicked".to_string())??;
+        Ok(RecordingSummary {
+            wav_path: self.wav_path,
+            frames_path: self.frames_path,
+            audio_frames,
+            spectrum_frames,
+            dropped: self.dropped.load(Ordering::Relaxed),
+            duration_secs,
+        })
+    }
+}
+
+fn write_loop(rx: Receiver<RecorderMessage>, mut wav: WavWriter, mut frames: Option<Box<dyn FrameSink>>) -> Result<(u64, u64), String> {
+    let mut spectrum_frames = 0u64;
+    // 出错后继续消费队列直到录制结束，避免发送端积压，最后统一返回第一个错误
+    let mut error: Option<String> = None;
+    for message in rx {
+        if error.is_some() { continue; }
+        let result = match message {
+            RecorderMessage::Audio(samples) => wav.write(&samples),
+            RecorderMessage::Frame { timestamp_ms, frame } => match frames.as_mut() {
+                Some(sink) => {
+                    spectrum_frames += 1;
+                    sink.write(timestamp_ms, &frame)
+                }
+                None => Ok(()),
+            },
+        };
+        if let Err(e) = result { error = Some(e); }
+    }
+    let audio_frames = wav.frames();
+    let wav_result = wav.finalize();
+    let frames_result = frames.map(|sink| sink.finalize()).unwrap_or(Ok(()));
+    if let Some(e) = error { return Err(e); }
+    wav_result?;
+    frames_result?;
+    Ok((audio_frames, spectrum_frames))
+}
+
+/// WAVE_FORMAT_IEEE_FLOAT 的 32 位浮点 WAV；数据长度在 finalize 时回填
+struct WavWriter {
+    out: BufWriter<File>,
+    channels: u16,
+    data_bytes: u64,
+}
+
+impl WavWriter {
+    fn create(path: &str, format: AudioFormat) -> Result<Self, String> {
+        let channels = u16::try_from(format.channels).map_err(|_| format!("too many channels: {}", format.channels))?;
+        let file = File::create(path).map_err(|e| format!("无法创建 WAV 文件 {}: {}", path, e))?;
+        let mut w = WavWriter { out: BufWriter::new(file), channels, data_bytes: 0 };
+        w.write_header(format.sample_rate).map_err(|e| e.to_string())?;
+        Ok(w)
+    }
+
+    fn write_header(&mut self, sample_rate: u32) -> std::io::Result<()> {
+        let block_align = self.channels * 4;
+        let o = &mut self.out;
+        o.write_all(b"RIFF")?;
+        o.write_all(&0u32.to_le_bytes())?; // RIFF 长度，finalize 时回填
+        o.write_all(b"WAVEfmt ")?;
+        o.write_all(&16u32.to_le_bytes())?;
+        o.write_all(&3u16.to_le_bytes())?; // WAVE_FORMAT_IEEE_FLOAT
+        o.write_all(&self.channels.to_le_bytes())?;
+        o.write_all(&sample_rate.to_le_bytes())?;
+        o.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        o.write_all(&block_align.to_le_bytes())?;
+        o.write_all(&32u16.to_le_bytes())?;
+        o.write_all(b"data")?;
+        o.write_all(&0u32.to_le_bytes())?; // data 长度，finalize 时回填
+        Ok(())
+    }
+
+    fn write(&mut self, samples: &[f32]) -> Result<(), String> {
+        for s in samples {
+            self.out.write_all(&s.to_le_bytes()).map_err(|e| e.to_string())?;
+        }
+        self.data_bytes += samples.len() as u64 * 4;
+        Ok(())
+    }
+
+    fn frames(&self) -> u64 {
+        self.data_bytes / (self.channels.max(1) as u64 * 4)
+    }
+
+    fn finalize(mut self) -> Result<(), String> {
+        // RIFF 长度字段为 32 位，超过 4 GiB 的录音按上限截断长度字段
+        let data_len = self.data_bytes.min(u32::MAX as u64 - 36) as u32;
+        let patch = |out: &mut BufWriter<File>| -> std::io::Result<()> {
+            out.flush()?;
+            let file = out.get_mut();
+            file.seek(SeekFrom::Start(4))?;
+            file.write_all(&(data_len + 36).to_le_bytes())?;
+            file.seek(SeekFrom::Start(40))?;
+            file.write_all(&data_len.to_le_bytes())?;
+            file.sync_all()
+        };
+        patch(&mut self.out).map_err(|e| format!("failed to finalize WAV file: {}", e))
+    }
+}
+
+trait FrameSink: Send {
+    fn write(&mut self, timestamp_ms: u64, frame: &SpectrumFrame) -> Result<(), String>;
+    fn finalize(self: Box<Self>) -> Result<(), String>;
+}
+
+fn mode_name(mode: SpectrumMode) -> &'static str {
+    match mode {
+        SpectrumMode::Columns => "columns",
+        SpectrumMode::Spectrogram => "spectrogram",
+        SpectrumMode::PeakHold => "peak_hold",
+        SpectrumMode::OctaveBands => "octave_bands",
+    }
+}
+
+fn scale_name(scale: MagnitudeScale) -> &'static str {
+    match scale {
+        MagnitudeScale::Linear => "linear",
+        MagnitudeScale::Db => "db",
+    }
+}
+
+/// 二进制帧文件：
+/// 文件头 `SPFR` + u8 版本(1)；之后每帧一条记录（小端）：
+/// u64 timestamp_ms, u64 seq, u8 mode, u8 scale(0 linear / 1 db), u8 声道数, u16 每声道值个数, 随后 f32 值按声道依次排列。
+/// mode: 0 columns / 1 spectrogram / 2 peak_hold / 3 octave_bands
+struct BinaryFrameWriter {
+    out: BufWriter<File>,
+}
+
+impl BinaryFrameWriter {
+    fn create(path: &str) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("无法创建频谱帧文件 {}: {}", path, e))?;
+        let mut out = BufWriter::new(file);
+        out.write_all(b"SPFR\x01").map_err(|e| e.to_string())?;
+        Ok(BinaryFrameWriter { out })
+    }
+}
+
+impl FrameSink for BinaryFrameWriter {
+    fn write(&mut self, timestamp_ms: u64, frame: &SpectrumFrame) -> Result<(), String> {
+        let values = frame.channels.first().map(|c| c.len()).unwrap_or(0);
+        let mode: u8 = match frame.mode {
+            SpectrumMode::Columns => 0,
+            SpectrumMode::Spectrogram => 1,
+            SpectrumMode::PeakHold => 2,
+            SpectrumMode::OctaveBands => 3,
+        };
+        let scale: u8 = match frame.scale {
+            MagnitudeScale::Linear => 0,
+            MagnitudeScale::Db => 1,
+        };
+        let o = &mut self.out;
+        let mut record = || -> std::io::Result<()> {
+            o.write_all(&timestamp_ms.to_le_bytes())?;
+            o.write_all(&frame.seq.to_le_bytes())?;
+            o.write_all(&[mode, scale, frame.channels.len() as u8])?;
+            o.write_all(&(values as u16).to_le_bytes())?;
+            for channel in &frame.channels {
+                for v in channel.iter().take(values) {
+                    o.write_all(&v.to_le_bytes())?;
+                }
+            }
+            Ok(())
+        };
+        record().map_err(|e| e.to_string())
+    }
+
+    fn finalize(mut self: Box<Self>) -> Result<(), String> {
+        self.out.flush().map_err(|e| e.to_string())
+    }
+}
+
+/// 按列缓存所有帧，停止时写出一个 Parquet 文件；每个声道一行，值列个数取录制期间的最大值，不足的为 null
+struct ParquetFrameWriter {
+    file: File,
+    timestamp_ms: Vec<u64>,
+    seq: Vec<u64>,
+    channel: Vec<u32>,
+    mode: Vec<&'static str>,
+    scale: Vec<&'static str>,
+    values: Vec<Vec<f32>>,
+}
+
+impl ParquetFrameWriter {
+    fn create(path: &str) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("无法创建 Parquet 文件 {}: {}", path, e))?;
+        Ok(ParquetFrameWriter {
+            file,
+            timestamp_ms: Vec::new(),
+            seq: Vec::new(),
+            channel: Vec::new(),
+            mode: Vec::new(),
+            scale: Vec::new(),
+            values: Vec::new(),
+        })
+    }
+}
+
+impl FrameSink for ParquetFrameWriter {
+    fn write(&mut self, timestamp_ms: u64, frame: &SpectrumFrame) -> Result<(), String> {
+        for (i, channel) in frame.channels.iter().enumerate() {
+            self.timestamp_ms.push(timestamp_ms);
+            self.seq.push(frame.seq);
+            self.channel.push(i as u32);
+            self.mode.push(mode_name(frame.mode));
+            self.scale.push(scale_name(frame.scale));
+            self.values.push(channel.clone());
+        }
+        Ok(())
+    }
+
+    fn finalize(self: Box<Self>) -> Result<(), String> {
+        let this = *self;
+        let width = this.values.iter().map(|v| v.len()).max().unwrap_or(0);
+        let mut columns = vec![
+            Column::new("timestamp_ms".into(), this.timestamp_ms),
+            Column::new("seq".into(), this.seq),
+            Column::new("channel".into(), this.channel),
+            Column::new("mode".into(), this.mode),
+            Column::new("scale".into(), this.scale),
+        ];
+        for i in 0..width {
+            let col: Vec<Option<f32>> = this.values.iter().map(|v| v.get(i).copied()).collect();
+            columns.push(Column::new(format!("v{}", i).into(), col));
+        }
+        let mut df = DataFrame::new(columns).map_err(|e| e.to_string())?;
+        ParquetWriter::new(this.file)
+            .with_compression(ParquetCompression::Zstd(None))
+            .finish(&mut df)
+            .map_err(|e| format!("failed to write Parquet frames: {}", e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u16_at(bytes: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes(bytes[at..at + 2].try_into().unwrap())
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    fn u64_at(bytes: &[u8], at: usize) -> u64 {
+        u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+    }
+
+    fn frame(mode: SpectrumMode, scale: MagnitudeScale, seq: u64, channels: Vec<Vec<f32>>) -> SpectrumFrame {
+        SpectrumFrame { mode, scale, channels, peaks: None, band_centres: None, seq }
+    }
+
+    #[test]
+    fn recording_writes_float_wav_and_binary_frames() {
+        let dir = std::env::temp_dir().join(format!("recorder_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let wav_path = dir.join("audio.wav").to_string_lossy().to_string();
+        let frames_path = dir.join("frames.spfr").to_string_lossy().to_string();
+        let options = RecordingOptions {
+            wav_path: wav_path.clone(),
+            frames_path: Some(frames_path.clone()),
+            frames_format: FrameFileFormat::Binary,
+        };
+        let recorder = Recorder::start(options, AudioFormat { sample_rate: 48_000, channels: 2 }).unwrap();
+
+        // 3 段各 100 帧的立体声
+        let samples: Vec<f32> = (0..600).map(|i| i as f32 / 600.0 - 0.5).collect();
+        for chunk in samples.chunks(200) {
+            recorder.push_audio(chunk);
+        }
+        let frames = [
+            frame(SpectrumMode::Columns, MagnitudeScale::Db, 7, vec![vec![-1.0, -2.0, -3.0, -4.0], vec![-5.0, -6.0, -7.0, -8.0]]),
+            frame(SpectrumMode::OctaveBands, MagnitudeScale::Linear, 8, vec![vec![0.5, 0.25, 0.125]]),
+        ];
+        for f in &frames {
+            recorder.push_frame(f);
+        }
+        let summary = recorder.finish().unwrap();
+        assert_eq!((summary.audio_frames, summary.spectrum_frames, summary.dropped), (300, 2, 0));
+
+        // WAV：长度字段已回填，fmt 为 IEEE float
+        let wav = std::fs::read(&wav_path).unwrap();
+        let data_len = 600 * 4;
+        assert_eq!(wav.len(), 44 + data_len);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(u32_at(&wav, 4) as usize, 36 + data_len);
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(u32_at(&wav, 16), 16);
+        assert_eq!(u16_at(&wav, 20), 3);
+        assert_eq!(u16_at(&wav, 22), 2);
+        assert_eq!(u32_at(&wav, 24), 48_000);
+        assert_eq!(u32_at(&wav, 28), 48_000 * 8);
+        assert_eq!((u16_at(&wav, 32), u16_at(&wav, 34)), (8, 32));
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(u32_at(&wav, 40) as usize, data_len);
+        let decoded: Vec<f32> = wav[44..].chunks_exact(4).map(|b| f32::from_le_bytes(b.try_into().unwrap())).collect();
+        assert_eq!(decoded, samples);
+
+        // SPFR：文件头 + 每帧一条记录
+        let spfr = std::fs::read(&frames_path).unwrap();
+        assert_eq!(&spfr[0..5], b"SPFR\x01");
+        let mut at = 5;
+        let mut last_ts = 0;
+        for (f, (mode, scale)) in frames.iter().zip([(0u8, 1u8), (3, 0)]) {
+            let ts = u64_at(&spfr, at);
+            assert!(ts >= last_ts);
+            last_ts = ts;
+            assert_eq!(u64_at(&spfr, at + 8), f.seq);
+            assert_eq!(&spfr[at + 16..at + 19], &[mode, scale, f.channels.len() as u8]);
+            let values = u16_at(&spfr, at + 19) as usize;
+            assert_eq!(values, f.channels[0].len());
+            at += 21;
+            for channel in &f.channels {
+                let read: Vec<f32> =
+                    spfr[at..at + values * 4].chunks_exact(4).map(|b| f32::from_le_bytes(b.try_into().unwrap())).collect();
+                assert_eq!(&read, channel);
+                at += values * 4;
+            }
+        }
+        assert_eq!(at, spfr.len());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn unwritable_path_fails_at_start() {
+        let options = RecordingOptions {
+            wav_path: std::env::temp_dir().join("no_such_dir_for_recorder").join("a.wav").to_string_lossy().to_string(),
+            frames_path: None,
+            frames_format: FrameFileFormat::Binary,
+        };
+        assert!(Recorder::start(options, AudioFormat { sample_rate: 48_000, channels: 1 }).is_err());
+    }
+}
@@ -0,0 +1,563 @@
+use crate::categories::CategoryTotal;
+use crate::db::{self, DbState};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, TimeZone, Timelike, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tauri::State;
+
+/// 从 activity_log 中取出的一段活动，已裁剪到查询区间内
+#[derive(Debug, Clone)]
+pub struct ReportSegment {
+    pub app_name: String,
+    pub window_title: String,
+    pub category_id: Option<i64>,
+    pub project: Option<String>,
+    pub is_idle: bool,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// 来自每小时汇总（已压缩），只有总量，没有真实的起止顺序
+    pub from_summary: bool,
+}
+
+impl ReportSegment {
+    pub fn seconds(&self) -> i64 {
+        self.end.signed_duration_since(self.start).num_seconds()
+    }
+}
+
+/// 按本地整点切开后的片段
+#[derive(Debug, Clone)]
+pub struct HourPiece<Tz: TimeZone = Local> {
+    pub local_start: DateTime<Tz>,
+    pub seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppTotal {
+    pub app_name: String,
+    pub total_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HourTotal {
+    /// 本地时间的小时 0-23（跨多天时为各天同一小时之和）
+    pub hour: u32,
+    pub total_seconds: i64,
+    pub apps: Vec<AppTotal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodTotal {
+    /// 日: 2024-05-01；周: 2024-W18（ISO 周）；月: 2024-05
+    pub period: String,
+    /// 该周期在查询区间内的第一天
+    pub start_date: String,
+    pub total_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeReport {
+    pub start_date: String,
+    pub end_date: String,
+    pub granularity: String,
+    pub total_seconds: i64,
+    pub by_app: Vec<AppTotal>,
+    pub by_category: Vec<CategoryTotal>,
+    pub by_hour: Vec<HourTotal>,
+    pub by_period: Vec<PeriodTotal>,
+}
+
+/// 取出与本地日期闭区间 [start_date, end_date] 有交集的全部分段，并裁剪到区间内
+///
+/// 开始于区间之前、但延续到区间内的分段（例如跨午夜）也会被包含；
+/// 已被保留策略压缩的时间段从 activity_hourly_summary 读取，已归档的年份从对应的归档库读取。
+pub fn load_segments(conn: &Connection, start_date: &str, end_date: &str, include_idle: bool) -> Result<Vec<ReportSegment>, String> {
+    let (start_s, end_s) = db::local_range_bounds(start_date, end_date)?;
+    let range = (parse_utc(&start_s)?, parse_utc(&end_s)?);
+    let mut out = Vec::new();
+    load_segments_from(conn, "main", range, include_idle, &mut out)?;
+    crate::retention::for_each_archive(conn, start_date, end_date, |schema| {
+        load_segments_from(conn, schema, range, include_idle, &mut out)
+    })?;
+    out.sort_by_key(|s| s.start);
+    Ok(out)
+}
+
+/// 从 schema（main 或附加的归档库）中读取与 [range.0, range.1) 相交的分段，追加到 out
+fn load_segments_from(
+    conn: &Connection,
+    schema: &str,
+    (range_start, range_end): (DateTime<Utc>, DateTime<Utc>),
+    include_idle: bool,
+    out: &mut Vec<ReportSegment>,
+) -> Result<(), String> {
+    let (start_s, end_s) = (range_start.to_rfc3339(), range_end.to_rfc3339());
+
+    // 向前回看最长分段的时长，才能找到从区间之前开始的分段
+    let max_duration: i64 = conn
+        .query_row(&format!("SELECT COALESCE(MAX(duration_seconds), 0) FROM {}.activity_log", schema), [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    let lookback = (range_start - ChronoDuration::seconds(max_duration)).to_rfc3339();
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT app_name, COALESCE(window_title, ''), category_id, project, is_idle, start_time, duration_seconds
+             FROM {}.activity_log
+             WHERE start_time >= ?1 AND start_time < ?2 AND (?3 OR is_idle = 0)
+             ORDER BY start_time ASC",
+            schema
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![lookback, end_s, include_idle], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<i64>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, bool>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, i64>(6)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    for r in rows {
+        let (app_name, window_title, category_id, project, is_idle, start_time, duration) = r.map_err(|e| e.to_string())?;
+        let start = parse_utc(&start_time)?;
+        let end = start + ChronoDuration::seconds(duration);
+        let start = start.max(range_start);
+        let end = end.min(range_end);
+        if end <= start { continue; }
+        out.push(ReportSegment { app_name, window_title, category_id, project, is_idle, start, end, from_summary: false });
+    }
+
+    // 已压缩的每小时汇总：视为从该小时起点开始的一段（不含窗口标题）
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT app_name, category_id, project, is_idle, hour_start, total_seconds
+             FROM {}.activity_hourly_summary
+             WHERE hour_start >= ?1 AND hour_start < ?2 AND (?3 OR is_idle = 0)",
+            schema
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![start_s, end_s, include_idle], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<i64>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, bool>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, i64>(5)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    for r in rows {
+        let (app_name, category_id, project, is_idle, hour_start, total) = r.map_err(|e| e.to_string())?;
+        let start = parse_utc(&hour_start)?;
+        let end = (start + ChronoDuration::seconds(total)).min(range_end);
+        if end <= start { continue; }
+        out.push(ReportSegment { app_name, window_title: String::new(), category_id, project, is_idle, start, end, from_summary: true });
+    }
+    Ok(())
+}
+
+fn parse_utc(s: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|d| d.with_timezone(&Utc))
+        .map_err(|e| format!("解析时间失败 '{}': {}", s, e))
+}
+
+/// t 所在本地小时的起点
+pub fn local_hour_start(t: DateTime<Utc>) -> DateTime<Utc> {
+    hour_start_in(t, &Local)
+}
+
+/// t 在时区 tz 中所在小时的起点
+///
+/// 直接减去当地的分、秒，而不是重新构造当地时间：夏令时结束时重复的那个小时
+/// 按当地时间构造有歧义，会得不到起点。
+pub fn hour_start_in<Tz: TimeZone>(t: DateTime<Utc>, tz: &Tz) -> DateTime<Utc> {
+    let local = t.with_timezone(tz);
+    t - ChronoDuration::seconds(local.minute() as i64 * 60 + local.second() as i64)
+        - ChronoDuration::nanoseconds(local.nanosecond() as i64)
+}
+
+/// 把 [start, end) 按本地整点切开（自然处理跨小时、跨午夜与夏令时）
+pub fn split_by_local_hour(start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<HourPiece> {
+    split_by_hour_in(start, end, &Local)
+}
+
+/// 同 split_by_local_hour，按指定时区的整点切开
+pub fn split_by_hour_in<Tz: TimeZone>(start: DateTime<Utc>, end: DateTime<Utc>, tz: &Tz) -> Vec<HourPiece<Tz>> {
+    let mut pieces = Vec::new();
+    let mut cursor = start;
+    while cursor < end {
+        let next = (hour_start_in(cursor, tz) + ChronoDuration::hours(1)).min(end);
+        // 防御：保证前进
+        let next = if next <= cursor { end } else { next };
+        pieces.push(HourPiece { local_start: cursor.with_timezone(tz), seconds: next.signed_duration_since(cursor).num_seconds() });
+        cursor = next;
+    }
+    pieces
+}
+
+/// 日期所在周期（周一开始的周 / 自然月）的第一天
+fn period_start(date: NaiveDate, granularity: &str) -> NaiveDate {
+    match granularity {
+        "week" => date - ChronoDuration::days(date.weekday().num_days_from_monday() as i64),
+        "month" => date.with_day(1).unwrap_or(date),
+        _ => date,
+    }
+}
+
+fn period_key(date: NaiveDate, granularity: &str) -> String {
+    match granularity {
+        "week" => {
+            let w = date.iso_week();
+            format!("{}-W{:02}", w.year(), w.week())
+        }
+        "month" => format!("{}-{:02}", date.year(), date.month()),
+        _ => date.format("%Y-%m-%d").to_string(),
+    }
+}
+
+fn sorted_app_totals(map: HashMap<String, i64>) -> Vec<AppTotal> {
+    let mut v: Vec<AppTotal> = map
+        .into_iter()
+        .map(|(app_name, total_seconds)| AppTotal { app_name, total_seconds })
+        .collect();
+    v.sort_by(|a, b| b.total_seconds.cmp(&a.total_seconds).then_with(|| a.app_name.cmp(&b.app_name)));
+    v
+}
+
+/// 汇总报表：按程序、分类、小时与日/周/月统计
+pub fn build_report(conn: &Connection, start_date: &str, end_date: &str, granularity: &str) -> Result<TimeReport, String> {
+    if !matches!(granularity, "day" | "week" | "month") {
+        return Err(format!("granularity must be day, week or month (got {})", granularity));
+    }
+    let segments = load_segments(conn, start_date, end_date, false)?;
+    let range_first = NaiveDate::parse_from_str(start_date, "%Y-%m-%d").map_err(|e| format!("解析日期失败: {}", e))?;
+
+    let mut total_seconds = 0i64;
+    let mut by_app: HashMap<String, i64> = HashMap::new();
+    let mut by_category: HashMap<Option<i64>, i64> = HashMap::new();
+    let mut by_hour: Vec<HashMap<String, i64>> = vec![HashMap::new(); 24];
+    let mut by_period: BTreeMap<String, (NaiveDate, i64)> = BTreeMap::new();
+
+    for seg in &segments {
+        let secs = seg.seconds();
+        total_seconds += secs;
+        *by_app.entry(seg.app_name.clone()).or_default() += secs;
+        *by_category.entry(seg.category_id).or_default() += secs;
+        for piece in split_by_local_hour(seg.start, seg.end) {
+            *by_hour[piece.local_start.hour() as usize].entry(seg.app_name.clone()).or_default() += piece.seconds;
+            let date = piece.local_start.date_naive();
+            let first = period_start(date, granularity).max(range_first);
+            by_period.entry(period_key(date, granularity)).or_insert((first, 0)).1 += piece.seconds;
+        }
+    }
+
+    let names: HashMap<i64, String> = crate::categories::list_categories(conn)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|c| (c.id, c.name))
+        .collect();
+    let mut by_category: Vec<CategoryTotal> = by_category
+        .into_iter()
+        .map(|(category_id, total_seconds)| CategoryTotal {
+            category_id,
+            name: category_id.and_then(|id| names.get(&id).cloned()),
+            total_seconds,
+        })
+        .collect();
+    by_category.sort_by_key(|c| std::cmp::Reverse(c.total_seconds));
+
+    let by_hour = by_hour
+        .into_iter()
+        .enumerate()
+        .map(|(hour, apps)| HourTotal {
+            hour: hour as u32,
+            total_seconds: apps.values().sum(),
+            apps: sorted_app_totals(apps),
+        })
+        .collect();
+
+    let by_period = by_period
+        .into_iter()
+        .map(|(period, (first, total_seconds))| PeriodTotal {
+            period,
+            start_date: first.format("%Y-%m-%d").to_string(),
+            total_seconds,
+        })
+        .collect();
+
+    Ok(TimeReport {
+        start_date: start_date.to_string(),
+        end_date: end_date.to_string(),
+        granularity: granularity.to_string(),
+        total_seconds,
+        by_app: sorted_app_totals(by_app),
+        by_category,
+        by_hour,
+        by_period,
+    })
+}
+
+/// 某日期所在周（周一开始）/月的本地日期闭区间
+pub fn period_bounds(date: &str, period: &str) -> Result<(String, String), String> {
+    let d = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| format!("解析日期失败: {}", e))?;
+    let (first, last) = match period {
+        "day" => (d, d),
+        "week" => {
+            let first = d - ChronoDuration::days(d.weekday().num_days_from_monday() as i64);
+            (first, first + ChronoDuration::days(6))
+        }
+        "month" => {
+            let first = d.with_day(1).ok_or("无效日期")?;
+            let next = if d.month() == 12 {
+                NaiveDate::from_ymd_opt(d.year() + 1, 1, 1)
+            } else {
+                NaiveDate::from_ymd_opt(d.year(), d.month() + 1, 1)
+            }
+            .ok_or("无效日期")?;
+            (first, next - ChronoDuration::days(1))
+        }
+        other => return Err(format!("period must be day, week or month (got {})", other)),
+    };
+    Ok((first.format("%Y-%m-%d").to_string(), last.format("%Y-%m-%d").to_string()))
+}
+
+// ==================== Tauri 命令 ====================
+
+/// 任意本地日期区间的汇总报表；granularity 为 day / week / month（默认 day）
+#[tauri::command]
+pub fn activity_report(
+    state: State<DbState>,
+    start_date: String,
+    end_date: String,
+    granularity: Option<String>,
+) -> Result<TimeReport, String> {
+    let conn = state.db.lock().unwrap();
+    build_report(&conn, &start_date, &end_date, granularity.as_deref().unwrap_or("day"))
+}
+
+/// 包含 date 的整周/整月报表（周按天汇总，月按周汇总）
+#[tauri::command]
+pub fn activity_period_report(state: State<DbState>, date: String, period: String) -> Result<TimeReport, String> {
+    let (start, end) = period_bounds(&date, &period)?;
+    let granularity = if period == "month" { "week" } else { "day" };
+    let conn = state.db.lock().unwrap();
+    build_report(&conn, &start, &end, granularity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, LocalResult, NaiveDateTime};
+
+    /// 测试用时区：与中欧时间相同，2026-03-29 01:00 UTC 起为 UTC+2，2026-10-25 01:00 UTC 起回到 UTC+1
+    #[derive(Debug, Clone, Copy)]
+    struct Cet;
+
+    impl Cet {
+        fn offset_at(utc: &NaiveDateTime) -> FixedOffset {
+            let switch = |m, d| NaiveDate::from_ymd_opt(2026, m, d).unwrap().and_hms_opt(1, 0, 0).unwrap();
+            let hours = if *utc >= switch(3, 29) && *utc < switch(10, 25) { 2 } else { 1 };
+            FixedOffset::east_opt(hours * 3600).unwrap()
+        }
+    }
+
+    impl TimeZone for Cet {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            Cet
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            // 夏令时偏移在前，对应较早的时刻
+            let valid: Vec<FixedOffset> = [2, 1]
+                .into_iter()
+                .map(|h| FixedOffset::east_opt(h * 3600).unwrap())
+                .filter(|o| Cet::offset_at(&(*local - ChronoDuration::seconds(o.local_minus_utc() as i64))) == *o)
+                .collect();
+            match valid[..] {
+                [] => LocalResult::None,
+                [o] => LocalResult::Single(o),
+                [a, b, ..] => LocalResult::Ambiguous(a, b),
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            Cet::offset_at(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            Cet::offset_at(utc)
+        }
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        parse_utc(s).unwrap()
+    }
+
+    /// (当地日期, 当地小时, 秒数)
+    fn pieces<Tz: TimeZone>(start: &str, end: &str, tz: &Tz) -> Vec<(String, u32, i64)> {
+        split_by_hour_in(utc(start), utc(end), tz)
+            .into_iter()
+            .map(|p| (p.local_start.date_naive().to_string(), p.local_start.hour(), p.seconds))
+            .collect()
+    }
+
+    fn day(d: &str) -> String {
+        d.to_string()
+    }
+
+    #[test]
+    fn splits_across_midnight() {
+        let beijing = FixedOffset::east_opt(8 * 3600).unwrap();
+        // 当地 2026-05-04 23:40 → 05-05 00:30
+        assert_eq!(
+            pieces("2026-05-04T15:40:00Z", "2026-05-04T16:30:00Z", &beijing),
+            [(day("2026-05-04"), 23, 1200), (day("2026-05-05"), 0, 1800)]
+        );
+    }
+
+    #[test]
+    fn splits_across_several_hours() {
+        let beijing = FixedOffset::east_opt(8 * 3600).unwrap();
+        // 当地 09:15 → 12:05
+        assert_eq!(
+            pieces("2026-05-04T01:15:00Z", "2026-05-04T04:05:00Z", &beijing),
+            [(day("2026-05-04"), 9, 2700), (day("2026-05-04"), 10, 3600), (day("2026-05-04"), 11, 3600), (day("2026-05-04"), 12, 300)]
+        );
+        // 半小时时区：整点按当地时间计算
+        let india = FixedOffset::east_opt(5 * 3600 + 1800).unwrap();
+        assert_eq!(
+            pieces("2026-05-04T03:00:00Z", "2026-05-04T04:00:00Z", &india),
+            [(day("2026-05-04"), 8, 1800), (day("2026-05-04"), 9, 1800)]
+        );
+        assert!(split_by_hour_in(utc("2026-05-04T03:00:00Z"), utc("2026-05-04T03:00:00Z"), &india).is_empty());
+    }
+
+    #[test]
+    fn splits_across_dst_transitions() {
+        // 春季：当地 01:30 → 03:30，02 点不存在，实际只有 1 小时
+        assert_eq!(
+            pieces("2026-03-29T00:30:00Z", "2026-03-29T01:30:00Z", &Cet),
+            [(day("2026-03-29"), 1, 1800), (day("2026-03-29"), 3, 1800)]
+        );
+        // 秋季：当地 02 点出现两次（先 UTC+2 后 UTC+1）
+        assert_eq!(
+            pieces("2026-10-25T00:10:00Z", "2026-10-25T02:10:00Z", &Cet),
+            [(day("2026-10-25"), 2, 3000), (day("2026-10-25"), 2, 3600), (day("2026-10-25"), 3, 600)]
+        );
+        // 从重复的那个小时中间开始，起点仍是该小时的整点
+        assert_eq!(hour_start_in(utc("2026-10-25T01:10:00Z"), &Cet), utc("2026-10-25T01:00:00Z"));
+        assert_eq!(
+            pieces("2026-10-25T01:10:00Z", "2026-10-25T02:30:00Z", &Cet),
+            [(day("2026-10-25"), 2, 3000), (day("2026-10-25"), 3, 1800)]
+        );
+    }
+
+    // ---------- 数据库报表：按本机时区构造时间，与运行环境的时区无关 ----------
+
+    fn test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_db(&conn).unwrap();
+        conn.execute("INSERT INTO activity_categories (id, name) VALUES (1, '开发')", []).unwrap();
+        conn
+    }
+
+    /// 本地日期 date 的 hour:minute（UTC RFC3339）
+    fn local_time(date: &str, hour: i64, minute: i64) -> String {
+        (db::local_day_start(date).unwrap() + ChronoDuration::minutes(hour * 60 + minute)).with_timezone(&Utc).to_rfc3339()
+    }
+
+    fn add_raw(conn: &Connection, app: &str, start: &str, seconds: i64, category_id: Option<i64>) {
+        conn.execute(
+            "INSERT INTO activity_log (app_name, window_title, start_time, duration_seconds, category_id) VALUES (?1, 't', ?2, ?3, ?4)",
+            params![app, start, seconds, category_id],
+        )
+        .unwrap();
+    }
+
+    fn add_summary(conn: &Connection, app: &str, hour_start: &str, seconds: i64, category_id: Option<i64>) {
+        conn.execute(
+            "INSERT INTO activity_hourly_summary (hour_start, app_name, category_id, project, is_idle, total_seconds) VALUES (?1, ?2, ?3, NULL, 0, ?4)",
+            params![hour_start, app, category_id, seconds],
+        )
+        .unwrap();
+    }
+
+    fn periods(report: &TimeReport) -> Vec<(&str, &str, i64)> {
+        report.by_period.iter().map(|p| (p.period.as_str(), p.start_date.as_str(), p.total_seconds)).collect()
+    }
+
+    fn apps(report: &TimeReport) -> Vec<(&str, i64)> {
+        report.by_app.iter().map(|a| (a.app_name.as_str(), a.total_seconds)).collect()
+    }
+
+    #[test]
+    fn report_mixes_summaries_and_raw_rows() {
+        let conn = test_db();
+        // 压缩后的 5 月 4 日与原始记录的 5 月 5 日，外加一段跨午夜的原始记录
+        add_summary(&conn, "code", &local_time("2026-05-04", 9, 0), 3000, Some(1));
+        add_summary(&conn, "slack", &local_time("2026-05-04", 9, 0), 600, None);
+        add_raw(&conn, "code", &local_time("2026-05-04", 23, 30), 3600, Some(1));
+        add_raw(&conn, "slack", &local_time("2026-05-05", 9, 50), 1200, None);
+        conn.execute(
+            "INSERT INTO activity_log (app_name, start_time, duration_seconds, is_idle) VALUES ('idle', ?1, 7200, 1)",
+            [local_time("2026-05-05", 12, 0)],
+        )
+        .unwrap();
+
+        let report = build_report(&conn, "2026-05-04", "2026-05-05", "day").unwrap();
+        assert_eq!(report.total_seconds, 3000 + 600 + 3600 + 1200);
+        assert_eq!(apps(&report), [("code", 6600), ("slack", 1800)]);
+        assert_eq!(periods(&report), [("2026-05-04", "2026-05-04", 5400), ("2026-05-05", "2026-05-05", 3000)]);
+        let hours: Vec<(u32, i64)> = report.by_hour.iter().filter(|h| h.total_seconds > 0).map(|h| (h.hour, h.total_seconds)).collect();
+        assert_eq!(hours, [(0, 1800), (9, 3600 + 600), (10, 600), (23, 1800)]);
+        let categories: Vec<(Option<i64>, i64)> = report.by_category.iter().map(|c| (c.category_id, c.total_seconds)).collect();
+        assert_eq!(categories, [(Some(1), 6600), (None, 1800)]);
+
+        // 从区间之前开始的跨午夜记录只计入区间内的部分
+        let report = build_report(&conn, "2026-05-05", "2026-05-05", "day").unwrap();
+        assert_eq!(apps(&report), [("code", 1800), ("slack", 1200)]);
+    }
+
+    #[test]
+    fn week_and_month_rollups() {
+        let conn = test_db();
+        // 2026-04-30 周四与 05-03 周日属于 ISO 第 18 周，05-04 周一属于第 19 周
+        add_raw(&conn, "code", &local_time("2026-04-30", 10, 0), 100, None);
+        add_raw(&conn, "code", &local_time("2026-05-03", 10, 0), 200, None);
+        add_summary(&conn, "code", &local_time("2026-05-04", 10, 0), 400, None);
+        add_raw(&conn, "code", &local_time("2026-05-31", 23, 0), 7200, None);
+
+        let weeks = build_report(&conn, "2026-04-29", "2026-06-01", "week").unwrap();
+        assert_eq!(
+            periods(&weeks),
+            [("2026-W18", "2026-04-29", 300), ("2026-W19", "2026-05-04", 400), ("2026-W22", "2026-05-25", 3600), ("2026-W23", "2026-06-01", 3600)]
+        );
+        let months = build_report(&conn, "2026-04-01", "2026-06-30", "month").unwrap();
+        assert_eq!(periods(&months), [("2026-04", "2026-04-01", 100), ("2026-05", "2026-05-01", 3600 + 600), ("2026-06", "2026-06-01", 3600)]);
+        assert!(build_report(&conn, "2026-04-01", "2026-04-30", "year").is_err());
+    }
+
+    #[test]
+    fn period_bounds_cover_whole_weeks_and_months() {
+        assert_eq!(period_bounds("2026-05-06", "day").unwrap(), (day("2026-05-06"), day("2026-05-06")));
+        assert_eq!(period_bounds("2026-05-06", "week").unwrap(), (day("2026-05-04"), day("2026-05-10")));
+        assert_eq!(period_bounds("2026-02-14", "month").unwrap(), (day("2026-02-01"), day("2026-02-28")));
+        assert_eq!(period_bounds("2026-12-31", "month").unwrap(), (day("2026-12-01"), day("2026-12-31")));
+        assert!(period_bounds("2026-05-06", "quarter").is_err());
+    }
+}
@@ -0,0 +1,514 @@
+use crate::db::{self, DbState};
+use crate::reports::{local_hour_start, split_by_local_hour};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{Manager, State};
+
+/// app_settings 中保存保留策略的键
+pub const RETENTION_SETTING_KEY: &str = "retention_policy";
+
+/// app_settings 中记录已归档年份（年份 → 归档库路径）的键，报表据此读取归档库
+pub const ARCHIVES_SETTING_KEY: &str = "activity_archives";
+
+/// 读取归档库时使用的 schema 名
+const ARCHIVE_SCHEMA: &str = "archive";
+
+/// 后台执行保留策略的间隔
+const RETENTION_INTERVAL: Duration = Duration::from_secs(24 * 3600);
+
+/// 活动记录保留策略；默认既不合并也不归档，需要用户主动开启
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// 原始记录保留天数，更早的记录合并为每小时、每程序的汇总；0 表示不合并
+    pub raw_retention_days: u32,
+    /// 保留今年及之前 N 个完整年份，更早的年份移入按年的归档库；0 表示不归档
+    pub archive_after_years: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedYear {
+    pub year: i32,
+    pub path: String,
+    pub raw_rows: usize,
+    pub summary_rows: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionReport {
+    /// 被合并（并删除）的原始记录数
+    pub compacted_rows: usize,
+    /// 新增或累加的汇总行数
+    pub summary_rows: usize,
+    pub archived: Vec<ArchivedYear>,
+    pub vacuumed: bool,
+    pub size_before: u64,
+    pub size_after: u64,
+    /// 还需要一次完整 VACUUM（由 run_once 在释放 DbState 锁后执行）
+    #[serde(skip)]
+    pub needs_full_vacuum: bool,
+}
+
+pub fn load_policy(conn: &Connection) -> Result<RetentionPolicy, String> {
+    Ok(db::get_setting(conn, RETENTION_SETTING_KEY)?.unwrap_or_default())
+}
+
+fn db_size(conn: &Connection) -> Result<u64, String> {
+    let pages: u64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0)).map_err(|e| e.to_string())?;
+    let page_size: u64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0)).map_err(|e| e.to_string())?;
+    Ok(pages * page_size)
+}
+
+type SummaryKey = (String, String, Option<i64>, Option<String>, bool);
+
+/// 把开始于 cutoff 之前的已关闭记录合并进 activity_hourly_summary，返回 (合并的记录数, 汇总行数)
+pub fn compact_before(conn: &mut Connection, cutoff: DateTime<Utc>) -> Result<(usize, usize), String> {
+    let cutoff_s = cutoff.to_rfc3339();
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let mut totals: HashMap<SummaryKey, i64> = HashMap::new();
+    {
+        let mut stmt = tx
+            .prepare(
+                "SELECT app_name, category_id, project, is_idle, start_time, duration_seconds
+                 FROM activity_log WHERE is_open = 0 AND start_time < ?1",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([&cutoff_s], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<i64>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, bool>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, i64>(5)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        for r in rows {
+            let (app_name, category_id, project, is_idle, start_time, duration) = r.map_err(|e| e.to_string())?;
+            let start = DateTime::parse_from_rfc3339(&start_time)
+                .map_err(|e| format!("解析时间失败 '{}': {}", start_time, e))?
+                .with_timezone(&Utc);
+            for piece in split_by_local_hour(start, start + ChronoDuration::seconds(duration)) {
+                let hour = local_hour_start(piece.local_start.with_timezone(&Utc)).to_rfc3339();
+                *totals
+                    .entry((hour, app_name.clone(), category_id, project.clone(), is_idle))
+                    .or_default() += piece.seconds;
+            }
+        }
+    }
+
+    {
+        let mut update = tx
+            .prepare(
+                "UPDATE activity_hourly_summary SET total_seconds = total_seconds + ?1
+                 WHERE hour_start = ?2 AND app_name = ?3 AND category_id IS ?4 AND project IS ?5 AND is_idle = ?6",
+            )
+            .map_err(|e| e.to_string())?;
+        let mut insert = tx
+            .prepare(
+                "INSERT INTO activity_hourly_summary (total_seconds, hour_start, app_name, category_id, project, is_idle)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )
+            .map_err(|e| e.to_string())?;
+        for ((hour, app_name, category_id, project, is_idle), secs) in &totals {
+            let p = params![secs, hour, app_name, category_id, project, is_idle];
+            if update.execute(p).map_err(|e| e.to_string())? == 0 {
+                insert.execute(p).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
+    let compacted = tx
+        .execute("DELETE FROM activity_log WHERE is_open = 0 AND start_time < ?1", [&cutoff_s])
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok((compacted, totals.len()))
+}
+
+pub fn archive_path(dir: &Path, year: i32) -> PathBuf {
+    dir.join(format!("activity_archive_{}.db", year))
+}
+
+/// 把某个本地年份的原始记录与汇总移到单独的归档库（已存在时追加）
+pub fn archive_year(conn: &Connection, year: i32, dir: &Path) -> Result<Option<ArchivedYear>, String> {
+    let (start_s, end_s) = db::local_range_bounds(&format!("{}-01-01", year), &format!("{}-12-31", year))?;
+    let pending: i64 = conn
+        .query_row(
+            "SELECT (SELECT COUNT(*) FROM activity_log WHERE is_open = 0 AND start_time >= ?1 AND start_time < ?2)
+                  + (SELECT COUNT(*) FROM activity_hourly_summary WHERE hour_start >= ?1 AND hour_start < ?2)",
+            [&start_s, &end_s],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if pending == 0 {
+        return Ok(None);
+    }
+
+    std::fs::create_dir_all(dir).map_err(|e| format!("无法创建归档目录 {:?}: {}", dir, e))?;
+    let path = archive_path(dir, year);
+    // 归档库与主库使用同一套 schema
+    {
+        let archive = Connection::open(&path).map_err(|e| format!("无法打开归档库 {:?}: {}", path, e))?;
+        db::init_db(&archive)?;
+    }
+
+    let path_s = path.to_string_lossy().to_string();
+    conn.execute("ATTACH DATABASE ?1 AS archive", [&path_s]).map_err(|e| e.to_string())?;
+    let moved = (|| -> Result<(usize, usize), String> {
+        let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+        tx.execute("INSERT OR REPLACE INTO archive.activity_categories SELECT * FROM main.activity_categories", [])
+            .map_err(|e| e.to_string())?;
+        let raw = tx
+            .execute(
+                "INSERT INTO archive.activity_log (app_name, window_title, start_time, duration_seconds, is_idle, is_open, category_id, project)
+                 SELECT app_name, window_title, start_time, duration_seconds, is_idle, 0, category_id, project
+                 FROM main.activity_log WHERE is_open = 0 AND start_time >= ?1 AND start_time < ?2",
+                [&start_s, &end_s],
+            )
+            .map_err(|e| e.to_string())?;
+        let summary = tx
+            .execute(
+                "INSERT INTO archive.activity_hourly_summary (hour_start, app_name, category_id, project, is_idle, total_seconds)
+                 SELECT hour_start, app_name, category_id, project, is_idle, total_seconds
+                 FROM main.activity_hourly_summary WHERE hour_start >= ?1 AND hour_start < ?2",
+                [&start_s, &end_s],
+            )
+            .map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM main.activity_log WHERE is_open = 0 AND start_time >= ?1 AND start_time < ?2", [&start_s, &end_s])
+            .map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM main.activity_hourly_summary WHERE hour_start >= ?1 AND hour_start < ?2", [&start_s, &end_s])
+            .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok((raw, summary))
+    })();
+    // 无论成功与否都要分离归档库
+    let detached = conn.execute("DETACH DATABASE archive", []).map_err(|e| e.to_string());
+    let (raw_rows, summary_rows) = moved?;
+    detached?;
+
+    let mut archives = archived_years(conn)?;
+    archives.insert(year, path_s.clone());
+    db::set_setting(conn, ARCHIVES_SETTING_KEY, &archives)?;
+    Ok(Some(ArchivedYear { year, path: path_s, raw_rows, summary_rows }))
+}
+
+/// 已归档的年份及对应的归档库路径
+pub fn archived_years(conn: &Connection) -> Result<BTreeMap<i32, String>, String> {
+    Ok(db::get_setting(conn, ARCHIVES_SETTING_KEY)?.unwrap_or_default())
+}
+
+/// 对与本地日期闭区间 [start_date, end_date] 相交的每个归档库，附加后以其 schema 名调用 f，结束后分离
+///
+/// 报表在主库之外再用它读取已归档的年份；归档库文件缺失时返回错误，而不是把该年份当作没有记录。
+pub fn for_each_archive<F>(conn: &Connection, start_date: &str, end_date: &str, mut f: F) -> Result<(), String>
+where
+    F: FnMut(&str) -> Result<(), String>,
+{
+    let year_of = |date: &str| {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map(|d| d.year())
+            .map_err(|e| format!("解析日期失败: {}", e))
+    };
+    let (first, last) = (year_of(start_date)?, year_of(end_date)?);
+    for (year, path) in archived_years(conn)?.range(first..=last) {
+        if !Path::new(path).exists() {
+            return Err(format!("{} 年的归档库不存在: {}", year, path));
+        }
+        conn.execute("ATTACH DATABASE ?1 AS archive", [path]).map_err(|e| e.to_string())?;
+        let result = f(ARCHIVE_SCHEMA);
+        let detached = conn.execute("DETACH DATABASE archive", []).map_err(|e| e.to_string());
+        result?;
+        detached?;
+    }
+    Ok(())
+}
+
+/// 回收删除后留下的空闲页；返回 false 表示库还不是增量回收模式，需要一次 full_vacuum
+pub fn reclaim_space(conn: &Connection) -> Result<bool, String> {
+    let mode: i64 = conn.query_row("PRAGMA auto_vacuum", [], |row| row.get(0)).map_err(|e| e.to_string())?;
+    if mode != 2 {
+        return Ok(false);
+    }
+    conn.execute_batch("PRAGMA incremental_vacuum;").map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 把库切换为增量回收模式并执行一次完整 VACUUM
+///
+/// 完整 VACUUM 会重写整个文件，耗时与库大小成正比；这里用单独的连接执行，
+/// 调用方不能持有 DbState 的锁。期间主连接的写入会等待文件锁，最长为 db::BUSY_TIMEOUT，
+/// 超过后追踪线程的这次写入失败并记录日志。
+pub fn full_vacuum(db_path: &Path) -> Result<(), String> {
+    let conn = Connection::open(db_path).map_err(|e| format!("无法打开数据库 {:?}: {}", db_path, e))?;
+    conn.busy_timeout(Duration::from_secs(30)).map_err(|e| e.to_string())?;
+    conn.execute_batch("PRAGMA auto_vacuum = INCREMENTAL; VACUUM;").map_err(|e| e.to_string())
+}
+
+/// 按策略执行一次压缩、归档与空间回收
+pub fn apply_policy(conn: &mut Connection, policy: &RetentionPolicy, now: DateTime<Utc>, archive_dir: &Path) -> Result<RetentionReport, String> {
+    let mut report = RetentionReport { size_before: db_size(conn)?, ..Default::default() };
+    let today = now.with_timezone(&Local).date_naive();
+
+    if policy.raw_retention_days > 0 {
+        let cutoff_day = (today - ChronoDuration::days(policy.raw_retention_days as i64)).format("%Y-%m-%d").to_string();
+        let cutoff = db::local_day_start(&cutoff_day)?.with_timezone(&Utc);
+        let (compacted, summaries) = compact_before(conn, cutoff)?;
+        report.compacted_rows = compacted;
+        report.summary_rows = summaries;
+    }
+
+    if policy.archive_after_years > 0 {
+        let last_year = today.year() - policy.archive_after_years as i32 - 1;
+        let earliest: Option<String> = conn
+            .query_row(
+                "SELECT MIN(t) FROM (SELECT MIN(start_time) AS t FROM activity_log UNION ALL SELECT MIN(hour_start) FROM activity_hourly_summary)",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if let Some(earliest) = earliest {
+            let first_year = DateTime::parse_from_rfc3339(&earliest)
+                .map_err(|e| format!("解析时间失败 '{}': {}", earliest, e))?
+                .with_timezone(&Local)
+                .year();
+            for year in first_year..=last_year {
+                if let Some(archived) = archive_year(conn, year, archive_dir)? {
+                    report.archived.push(archived);
+                }
+            }
+        }
+    }
+
+    if report.compacted_rows > 0 || !report.archived.is_empty() {
+        report.vacuumed = reclaim_space(conn)?;
+        report.needs_full_vacuum = !report.vacuumed;
+    }
+    report.size_after = db_size(conn)?;
+    Ok(report)
+}
+
+/// 按当前策略执行一次：压缩与归档持有 DbState 锁，完整 VACUUM 在锁外执行
+fn run_once(app_handle: &tauri::AppHandle) -> Result<RetentionReport, String> {
+    let state: State<DbState> = app_handle.state();
+    let mut report = {
+        let mut conn = state.db.lock().unwrap();
+        let policy = load_policy(&conn)?;
+        apply_policy(&mut conn, &policy, Utc::now(), &crate::app_paths::archive_dir())?
+    };
+    if report.needs_full_vacuum {
+        full_vacuum(&crate::app_paths::db_path())?;
+        report.needs_full_vacuum = false;
+        report.vacuumed = true;
+        report.size_after = db_size(&state.db.lock().unwrap())?;
+    }
+    Ok(report)
+}
+
+/// 后台线程：启动后稍等片刻执行一次，之后每天执行一次
+pub fn run_retention_loop(app_handle: tauri::AppHandle) {
+    std::thread::sleep(Duration::from_secs(60));
+    loop {
+        match run_once(&app_handle) {
+            Ok(r) if r.compacted_rows > 0 || !r.archived.is_empty() => println!(
+                "[retention] 合并 {} 条记录，归档 {} 个年份，数据库 {} -> {} 字节",
+                r.compacted_rows,
+                r.archived.len(),
+                r.size_before,
+                r.size_after
+            ),
+            Ok(_) => {}
+            Err(e) => eprintln!("[retention] 执行保留策略失败: {}", e),
+        }
+        std::thread::sleep(RETENTION_INTERVAL);
+    }
+}
+
+// ==================== Tauri 命令 ====================
+
+#[tauri::command]
+pub fn activity_get_retention_policy(state: State<DbState>) -> Result<RetentionPolicy, String> {
+    let conn = state.db.lock().unwrap();
+    load_policy(&conn)
+}
+
+#[tauri::command]
+pub fn activity_set_retention_policy(state: State<DbState>, policy: RetentionPolicy) -> Result<(), String> {
+    let conn = state.db.lock().unwrap();
+    db::set_setting(&conn, RETENTION_SETTING_KEY, &policy)
+}
+
+/// 立即按当前策略执行一次
+#[tauri::command]
+pub async fn activity_run_retention(app_handle: tauri::AppHandle) -> Result<RetentionReport, String> {
+    tokio::task::spawn_blocking(move || run_once(&app_handle))
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_db(&conn).unwrap();
+        conn.execute("INSERT INTO activity_categories (id, name, color) VALUES (1, '开发', '#0a0'), (2, '沟通', NULL)", [])
+            .unwrap();
+        conn
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("retention_test_{}_{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// 本地日期 date 的 hour:minute
+    fn local_time(date: &str, hour: i64, minute: i64) -> DateTime<Utc> {
+        (db::local_day_start(date).unwrap() + ChronoDuration::minutes(hour * 60 + minute)).with_timezone(&Utc)
+    }
+
+    fn add_raw(conn: &Connection, app: &str, start: DateTime<Utc>, seconds: i64, category_id: Option<i64>, is_idle: bool, is_open: bool) {
+        conn.execute(
+            "INSERT INTO activity_log (app_name, window_title, start_time, duration_seconds, is_idle, is_open, category_id)
+             VALUES (?1, 'title', ?2, ?3, ?4, ?5, ?6)",
+            params![app, start.to_rfc3339(), seconds, is_idle, is_open, category_id],
+        )
+        .unwrap();
+    }
+
+    fn count(conn: &Connection, sql: &str) -> i64 {
+        conn.query_row(sql, [], |r| r.get(0)).unwrap()
+    }
+
+    /// (本地小时起点, 程序, 分类, 是否空闲, 秒数)
+    type SummaryRow = (DateTime<Utc>, String, Option<i64>, bool, i64);
+
+    /// 按小时与程序排序的全部汇总行
+    fn summaries(conn: &Connection) -> Vec<SummaryRow> {
+        let mut rows: Vec<SummaryRow> = conn
+            .prepare("SELECT hour_start, app_name, category_id, is_idle, total_seconds FROM activity_hourly_summary")
+            .unwrap()
+            .query_map([], |r| Ok((r.get::<_, String>(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?)))
+            .unwrap()
+            .map(|r| {
+                let (hour, app, category_id, is_idle, seconds) = r.unwrap();
+                (DateTime::parse_from_rfc3339(&hour).unwrap().with_timezone(&Utc), app, category_id, is_idle, seconds)
+            })
+            .collect();
+        rows.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)).then_with(|| a.3.cmp(&b.3)));
+        rows
+    }
+
+    #[test]
+    fn policy_is_off_by_default() {
+        let mut conn = test_db();
+        let policy = load_policy(&conn).unwrap();
+        assert_eq!((policy.raw_retention_days, policy.archive_after_years), (0, 0));
+
+        add_raw(&conn, "code", local_time("2019-03-01", 9, 0), 600, None, false, false);
+        let dir = temp_dir("default");
+        let report = apply_policy(&mut conn, &policy, Utc::now(), &dir).unwrap();
+        assert_eq!(report.compacted_rows, 0);
+        assert!(report.archived.is_empty() && !report.vacuumed && !report.needs_full_vacuum);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM activity_log"), 1);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn compact_before_keeps_hour_buckets_and_totals() {
+        let mut conn = test_db();
+        let day = "2026-05-04";
+        add_raw(&conn, "code", local_time(day, 9, 40), 1800, Some(1), false, false); // 09:40 → 10:10
+        add_raw(&conn, "code", local_time(day, 9, 10), 600, Some(1), false, false);
+        add_raw(&conn, "slack", local_time(day, 9, 30), 120, Some(2), false, false);
+        add_raw(&conn, "idle", local_time(day, 11, 0), 900, None, true, false);
+        // 仍在进行的分段与 cutoff 之后的记录保持原样
+        add_raw(&conn, "code", local_time(day, 11, 30), 60, Some(1), false, true);
+        add_raw(&conn, "code", local_time("2026-05-05", 9, 0), 300, Some(1), false, false);
+        let cutoff = local_time("2026-05-05", 0, 0);
+        let before: i64 = conn
+            .query_row("SELECT SUM(duration_seconds) FROM activity_log WHERE start_time < ?1", [cutoff.to_rfc3339()], |r| r.get(0))
+            .unwrap();
+
+        let (compacted, summary_rows) = compact_before(&mut conn, cutoff).unwrap();
+        assert_eq!((compacted, summary_rows), (4, 4));
+        assert_eq!(
+            summaries(&conn),
+            [
+                (local_time(day, 9, 0), "code".to_string(), Some(1), false, 1200 + 600),
+                (local_time(day, 9, 0), "slack".to_string(), Some(2), false, 120),
+                (local_time(day, 10, 0), "code".to_string(), Some(1), false, 600),
+                (local_time(day, 11, 0), "idle".to_string(), None, true, 900),
+            ]
+        );
+        let after: i64 = count(&conn, "SELECT SUM(total_seconds) FROM activity_hourly_summary")
+            + count(&conn, "SELECT COALESCE(SUM(duration_seconds), 0) FROM activity_log WHERE is_open = 1");
+        assert_eq!(after, before);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM activity_log"), 2);
+
+        // 再次压缩同一小时的新记录时累加到已有汇总行
+        add_raw(&conn, "code", local_time(day, 9, 0), 60, Some(1), false, false);
+        assert_eq!(compact_before(&mut conn, cutoff).unwrap(), (1, 1));
+        assert_eq!(summaries(&conn)[0].4, 1860);
+        assert_eq!(summaries(&conn).len(), 4);
+    }
+
+    #[test]
+    fn archive_year_moves_rows_and_reports_still_see_them() {
+        let conn = test_db();
+        add_raw(&conn, "code", local_time("2023-06-01", 9, 0), 600, Some(1), false, false);
+        add_raw(&conn, "slack", local_time("2023-12-31", 23, 30), 3600, Some(2), false, false); // 跨入 2024 年
+        add_raw(&conn, "code", local_time("2024-01-02", 9, 0), 300, Some(1), false, false);
+        conn.execute(
+            "INSERT INTO activity_hourly_summary (hour_start, app_name, category_id, project, is_idle, total_seconds)
+             VALUES (?1, 'code', 1, 'crate', 0, 1200)",
+            [local_time("2023-02-01", 10, 0).to_rfc3339()],
+        )
+        .unwrap();
+        let report_before = crate::reports::build_report(&conn, "2023-01-01", "2024-12-31", "month").unwrap();
+        let totals_before = crate::categories::category_totals(&conn, "2023-01-01", "2023-12-31").unwrap();
+
+        let dir = temp_dir("archive");
+        let archived = archive_year(&conn, 2023, &dir).unwrap().unwrap();
+        assert_eq!((archived.year, archived.raw_rows, archived.summary_rows), (2023, 2, 1));
+        assert!(archive_year(&conn, 2023, &dir).unwrap().is_none());
+
+        // 主库只剩 2024 年的记录，归档库有 2023 年的原始记录、汇总与分类
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM activity_log"), 1);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM activity_hourly_summary"), 0);
+        let archive = Connection::open(&archived.path).unwrap();
+        assert_eq!(count(&archive, "SELECT COUNT(*) FROM activity_log"), 2);
+        assert_eq!(count(&archive, "SELECT SUM(total_seconds) FROM activity_hourly_summary WHERE project = 'crate'"), 1200);
+        let categories: Vec<(i64, String, Option<String>)> = archive
+            .prepare("SELECT id, name, color FROM activity_categories ORDER BY id")
+            .unwrap()
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(categories, [(1, "开发".to_string(), Some("#0a0".to_string())), (2, "沟通".to_string(), None)]);
+        assert_eq!(archived_years(&conn).unwrap().get(&2023), Some(&archived.path));
+
+        // 报表透明地读取归档库
+        let report_after = crate::reports::build_report(&conn, "2023-01-01", "2024-12-31", "month").unwrap();
+        assert_eq!(report_after.total_seconds, report_before.total_seconds);
+        let periods = |r: &crate::reports::TimeReport| r.by_period.iter().map(|p| (p.period.clone(), p.total_seconds)).collect::<Vec<_>>();
+        assert_eq!(periods(&report_after), periods(&report_before));
+        let totals_after = crate::categories::category_totals(&conn, "2023-01-01", "2023-12-31").unwrap();
+        let key = |t: &crate::categories::CategoryTotals| t.categories.iter().map(|c| (c.category_id, c.total_seconds)).collect::<Vec<_>>();
+        assert_eq!(key(&totals_after), key(&totals_before));
+
+        // 归档库丢失时报错，而不是返回 0
+        std::fs::remove_dir_all(&dir).unwrap();
+        let err = crate::reports::build_report(&conn, "2023-01-01", "2023-12-31", "day").unwrap_err();
+        assert!(err.contains("2023"), "{}", err);
+        assert!(crate::reports::build_report(&conn, "2024-01-01", "2024-12-31", "day").is_ok());
+    }
+}
@@ -0,0 +1,251 @@
+/// 样本编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleEncoding {
+    /// 无符号整数（WAV 的 8 位 PCM），以 2^(bits-1) 为零点
+    UnsignedInt,
+    SignedInt,
+    /// IEEE 浮点，容器须为 32 或 64 位
+    Float,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+/// 交错 PCM 缓冲的格式描述
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleFormat {
+    pub encoding: SampleEncoding,
+    /// 每个样本占用的位数：8/16/24/32（浮点为 32/64）
+    pub container_bits: u16,
+    /// 有效位数，按 WAVEFORMATEXTENSIBLE 约定靠高位对齐；0 表示与容器相同
+    pub valid_bits: u16,
+    pub endianness: Endianness,
+    pub channels: usize,
+}
+
+impl SampleFormat {
+    pub fn bytes_per_sample(&self) -> usize {
+        self.container_bits as usize / 8
+    }
+
+    /// 每帧（所有声道）字节数
+    pub fn block_align(&self) -> usize {
+        self.bytes_per_sample() * self.channels
+    }
+
+    fn effective_valid_bits(&self) -> u16 {
+        if self.valid_bits == 0 { self.container_bits } else { self.valid_bits }
+    }
+
+    /// 检查格式是否受支持；不支持的格式返回说明原因的错误，而不是静默跳过
+    pub fn validate(&self) -> Result<(), String> {
+        if self.channels == 0 {
+            return Err("channels must be at least 1".into());
+        }
+        let bits = self.container_bits;
+        match self.encoding {
+            SampleEncoding::Float => {
+                if bits != 32 && bits != 64 {
+                    return Err(format!("unsupported float sample width: {} bits (expected 32 or 64)", bits));
+                }
+                if self.valid_bits != 0 && self.valid_bits != bits {
+                    return Err(format!("float samples cannot have {} valid bits in a {}-bit container", self.valid_bits, bits));
+                }
+            }
+            SampleEncoding::SignedInt | SampleEncoding::UnsignedInt => {
+                if !matches!(bits, 8 | 16 | 24 | 32) {
+                    return Err(format!("unsupported integer sample width: {} bits (expected 8, 16, 24 or 32)", bits));
+                }
+                let valid = self.effective_valid_bits();
+                if valid < 2 || valid > bits {
+                    return Err(format!("invalid valid_bits {} for a {}-bit container", self.valid_bits, bits));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 读取一个容器宽度的原始整数（未做符号扩展）
+fn read_raw(bytes: &[u8], endianness: Endianness) -> u64 {
+    match endianness {
+        Endianness::Little => bytes.iter().rev().fold(0u64, |acc, &b| (acc << 8) | b as u64),
+        Endianness::Big => bytes.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64),
+    }
+}
+
+/// 把交错的字节缓冲解码为 f32 样本追加到 out，整数样本归一化到 [-1, 1)；返回解码的帧数
+pub fn decode_interleaved(bytes: &[u8], format: &SampleFormat, out: &mut Vec<f32>) -> Result<usize, String> {
+    format.validate()?;
+    let width = format.bytes_per_sample();
+    let block = format.block_align();
+    if !bytes.len().is_multiple_of(block) {
+        return Err(format!("buffer length {} is not a multiple of the frame size {}", bytes.len(), block));
+    }
+    out.reserve(bytes.len() / width);
+    let samples = bytes.chunks_exact(width);
+    match format.encoding {
+        SampleEncoding::Float if width == 4 => {
+            out.extend(samples.map(|b| f32::from_bits(read_raw(b, format.endianness) as u32)));
+        }
+        SampleEncoding::Float => {
+            out.extend(samples.map(|b| f64::from_bits(read_raw(b, format.endianness)) as f32));
+        }
+        SampleEncoding::SignedInt => {
+            let container = format.container_bits as u32;
+            let valid = format.effective_valid_bits() as u32;
+            let scale = (1u64 << (valid - 1)) as f64;
+            out.extend(samples.map(|b| {
+                // 先按容器宽度做符号扩展，再右移去掉低位的填充位
+                let raw = read_raw(b, format.endianness) << (64 - container);
+                let v = (raw as i64) >> (64 - valid);
+                (v as f64 / scale) as f32
+            }));
+        }
+        SampleEncoding::UnsignedInt => {
+            let container = format.container_bits as u32;
+            let valid = format.effective_valid_bits() as u32;
+            let zero = 1i64 << (valid - 1);
+            out.extend(samples.map(|b| {
+                let v = (read_raw(b, format.endianness) >> (container - valid)) as i64;
+                ((v - zero) as f64 / zero as f64) as f32
+            }));
+        }
+    }
+    Ok(bytes.len() / block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENDIANS: [Endianness; 2] = [Endianness::Little, Endianness::Big];
+
+    fn format(encoding: SampleEncoding, container_bits: u16, valid_bits: u16, endianness: Endianness) -> SampleFormat {
+        SampleFormat { encoding, container_bits, valid_bits, endianness, channels: 1 }
+    }
+
+    fn decode(bytes: &[u8], format: &SampleFormat) -> Vec<f32> {
+        let mut out = Vec::new();
+        decode_interleaved(bytes, format, &mut out).unwrap();
+        out
+    }
+
+    /// 按格式把有效位上的整数值编码为一个样本；padding 填入低位的填充位（应被忽略）
+    fn encode_int(value: i64, format: &SampleFormat, padding: u64) -> Vec<u8> {
+        let container = format.container_bits as u32;
+        let valid = format.effective_valid_bits() as u32;
+        let stored = match format.encoding {
+            SampleEncoding::SignedInt => value,
+            _ => value + (1i64 << (valid - 1)),
+        };
+        let pad_mask = (1u64 << (container - valid)) - 1;
+        let raw = ((stored as u64) << (container - valid)) | (padding & pad_mask);
+        let le: Vec<u8> = (0..container / 8).map(|i| (raw >> (8 * i)) as u8).collect();
+        match format.endianness {
+            Endianness::Little => le,
+            Endianness::Big => le.into_iter().rev().collect(),
+        }
+    }
+
+    #[test]
+    fn integer_extremes_for_every_layout() {
+        // (容器位数, 有效位数)，有效位数 0 表示与容器相同
+        let layouts = [(8, 0), (16, 0), (16, 12), (24, 0), (24, 20), (32, 0), (32, 24), (32, 20)];
+        for encoding in [SampleEncoding::SignedInt, SampleEncoding::UnsignedInt] {
+            for (container, valid) in layouts {
+                for endianness in ENDIANS {
+                    let fmt = format(encoding, container, valid, endianness);
+                    let bits = fmt.effective_valid_bits() as u32;
+                    let full = (1i64 << (bits - 1)) as f64;
+                    let cases = [(-(1i64 << (bits - 1)), -1.0), (0, 0.0), ((1i64 << (bits - 1)) - 1, ((full - 1.0) / full) as f32), (1, (1.0 / full) as f32)];
+                    for (value, expected) in cases {
+                        for padding in [0, u64::MAX] {
+                            let bytes = encode_int(value, &fmt, padding);
+                            assert_eq!(decode(&bytes, &fmt), vec![expected], "{:?} value={} padding={:x}", fmt, value, padding);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn known_byte_patterns() {
+        use Endianness::*;
+        use SampleEncoding::*;
+        // 8 位 WAV 为无符号，128 为零点
+        assert_eq!(decode(&[0, 128, 255], &format(UnsignedInt, 8, 0, Little)), vec![-1.0, 0.0, 127.0 / 128.0]);
+        assert_eq!(decode(&[0x00, 0x80, 0xff, 0x7f], &format(SignedInt, 16, 0, Little)), vec![-1.0, 32767.0 / 32768.0]);
+        assert_eq!(decode(&[0x80, 0x00, 0x7f, 0xff], &format(SignedInt, 16, 0, Big)), vec![-1.0, 32767.0 / 32768.0]);
+        // 24 位：-2^23 → -1.0
+        assert_eq!(decode(&[0x00, 0x00, 0x80], &format(SignedInt, 24, 0, Little)), vec![-1.0]);
+        assert_eq!(decode(&[0x80, 0x00, 0x00], &format(SignedInt, 24, 0, Big)), vec![-1.0]);
+        assert_eq!(decode(&[0xff, 0xff, 0xff], &format(SignedInt, 24, 0, Little)), vec![-1.0 / 8_388_608.0]);
+        // 32 位容器中的 24 位有效数据靠高位对齐，低字节为填充
+        let in32 = format(SignedInt, 32, 24, Little);
+        assert_eq!(decode(&[0xaa, 0x00, 0x00, 0x80], &in32), vec![-1.0]);
+        assert_eq!(decode(&[0x55, 0xff, 0xff, 0x7f], &in32), vec![8_388_607.0 / 8_388_608.0]);
+        assert_eq!(decode(&[0x80, 0x00, 0x00, 0xaa], &format(SignedInt, 32, 24, Big)), vec![-1.0]);
+        assert_eq!(decode(&[0x00, 0x00, 0x00, 0x80], &format(SignedInt, 32, 0, Little)), vec![-1.0]);
+    }
+
+    #[test]
+    fn floats_in_both_widths_and_byte_orders() {
+        let values = [-1.0f32, 0.0, 0.5, 1.0, -0.25];
+        for endianness in ENDIANS {
+            let f32_bytes: Vec<u8> = values
+                .iter()
+                .flat_map(|v| if endianness == Endianness::Little { v.to_le_bytes() } else { v.to_be_bytes() })
+                .collect();
+            assert_eq!(decode(&f32_bytes, &format(SampleEncoding::Float, 32, 0, endianness)), values);
+            let f64_bytes: Vec<u8> = values
+                .iter()
+                .flat_map(|&v| if endianness == Endianness::Little { (v as f64).to_le_bytes() } else { (v as f64).to_be_bytes() })
+                .collect();
+            assert_eq!(decode(&f64_bytes, &format(SampleEncoding::Float, 64, 64, endianness)), values);
+        }
+    }
+
+    #[test]
+    fn interleaved_frames() {
+        let stereo = SampleFormat { channels: 2, ..format(SampleEncoding::SignedInt, 16, 0, Endianness::Little) };
+        let mut out = Vec::new();
+        assert_eq!(decode_interleaved(&[0x00, 0x80, 0x00, 0x40, 0, 0, 0, 0], &stereo, &mut out), Ok(2));
+        assert_eq!(out, vec![-1.0, 0.5, 0.0, 0.0]);
+        // 不足一帧的缓冲被拒绝，out 不变
+        assert!(decode_interleaved(&[0; 6], &stereo, &mut out).unwrap_err().contains("frame size 4"));
+        assert_eq!(out.len(), 4);
+    }
+
+    #[test]
+    fn validate_rejects_unsupported_formats() {
+        use Endianness::Little;
+        use SampleEncoding::*;
+        let cases = [
+            (SampleFormat { channels: 0, ..format(SignedInt, 16, 0, Little) }, "channels"),
+            (format(Float, 16, 0, Little), "float sample width"),
+            (format(Float, 24, 0, Little), "float sample width"),
+            (format(Float, 32, 24, Little), "valid bits"),
+            (format(SignedInt, 12, 0, Little), "integer sample width"),
+            (format(UnsignedInt, 40, 0, Little), "integer sample width"),
+            (format(SignedInt, 16, 20, Little), "invalid valid_bits"),
+            (format(SignedInt, 32, 1, Little), "invalid valid_bits"),
+            (format(UnsignedInt, 8, 9, Little), "invalid valid_bits"),
+        ];
+        for (fmt, message) in cases {
+            let err = fmt.validate().unwrap_err();
+            assert!(err.contains(message), "{:?}: {}", fmt, err);
+            // 解码同样报错，不会静默输出
+            let mut out = Vec::new();
+            assert!(decode_interleaved(&[0; 64], &fmt, &mut out).is_err());
+            assert!(out.is_empty());
+        }
+        for fmt in [format(Float, 32, 32, Little), format(SignedInt, 32, 24, Little), format(UnsignedInt, 8, 0, Little)] {
+            assert_eq!(fmt.validate(), Ok(()));
+        }
+    }
+}
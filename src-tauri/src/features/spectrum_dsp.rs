@@ -0,0 +1,460 @@
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+/// 窗函数（周期形式，分母为 N）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowFunction {
+    #[default]
+    Hann,
+    Hamming,
+    /// 4 项 Blackman-Harris，旁瓣约 -92 dB
+    BlackmanHarris,
+    /// 平顶窗：幅值最准，频率分辨率最差
+    FlatTop,
+}
+
+impl WindowFunction {
+    /// 余弦和窗的系数 a0, a1, a2...：w(n) = Σ (-1)^k a_k cos(2πkn/N)
+    fn cosine_terms(self) -> &'static [f32] {
+        match self {
+            WindowFunction::Hann => &[0.5, 0.5],
+            WindowFunction::Hamming => &[0.54, 0.46],
+            WindowFunction::BlackmanHarris => &[0.35875, 0.48829, 0.14128, 0.01168],
+            WindowFunction::FlatTop => &[0.215_578_95, 0.416_631_58, 0.277_263_16, 0.083_578_95, 0.006_947_368],
+        }
+    }
+
+    pub fn coefficients(self, n: usize) -> Vec<f32> {
+        let terms = self.cosine_terms();
+        (0..n)
+            .map(|i| {
+                let x = 2.0 * PI * i as f32 / n as f32;
+                terms
+                    .iter()
+                    .enumerate()
+                    .map(|(k, a)| if k % 2 == 0 { a * (k as f32 * x).cos() } else { -a * (k as f32 * x).cos() })
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+/// 输出幅值的刻度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MagnitudeScale {
+    /// 未归一化的 FFT 幅值（前端自行换算 dB）
+    #[default]
+    Linear,
+    /// dBFS：满幅正弦为 0 dB，低于 db_floor 的截断为 db_floor
+    Db,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessorConfig {
+    /// 2 的幂，512..=8192
+    pub fft_size: usize,
+    /// 对数频率列数，32..=256
+    pub columns: usize,
+    pub window: WindowFunction,
+    pub scale: MagnitudeScale,
+    pub db_floor: f32,
+    pub a_weighting: bool,
+    /// 低于该频率的 bin 不参与分列
+    pub min_freq: f32,
+}
+
+impl Default for ProcessorConfig {
+    fn default() -> Self {
+        ProcessorConfig {
+            fft_size: 2048,
+            columns: 96,
+            window: WindowFunction::Hann,
+            scale: MagnitudeScale::Linear,
+            db_floor: -90.0,
+            a_weighting: false,
+            min_freq: 20.0,
+        }
+    }
+}
+
+impl ProcessorConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.fft_size.is_power_of_two() || !(512..=8192).contains(&self.fft_size) {
+            return Err("fft_size must be power-of-two within 512..8192".into());
+        }
+        if !(32..=256).contains(&self.columns) {
+            return Err("columns must be within 32..256".into());
+        }
+        if !self.db_floor.is_finite() || self.db_floor >= 0.0 {
+            return Err("db_floor must be a negative number".into());
+        }
+        Ok(())
+    }
+}
+
+/// A 计权增益（dB），1 kHz 处为 0（IEC 61672-1）
+pub fn a_weighting_db(freq: f32) -> f32 {
+    if freq <= 0.0 { return f32::NEG_INFINITY; }
+    let f2 = (freq as f64).powi(2);
+    let ra = 12194.0f64.powi(2) * f2 * f2
+        / ((f2 + 20.6f64.powi(2))
+            * ((f2 + 107.7f64.powi(2)) * (f2 + 737.9f64.powi(2))).sqrt()
+            * (f2 + 12194.0f64.powi(2)));
+    (20.0 * ra.log10() + 2.0) as f32
+}
+
+/// 交错多声道样本混为单声道（各声道取平均）
+pub fn downmix(interleaved: &[f32], channels: usize) -> Vec<f32> {
+    let channels = channels.max(1);
+    interleaved
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+/// 与平台无关的频谱处理：PCM 帧 → 窗函数 + FFT（75% 重叠）→ 对数频率列
+pub struct SpectrumProcessor {
+    config: ProcessorConfig,
+    sample_rate: u32,
+    channels: usize,
+    fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    /// 窗函数之和，用于把幅值归一化到满幅正弦 = 1
+    window_sum: f32,
+    /// 窗函数平方和，用于频带能量归一化
+    window_sq_sum: f32,
+    /// 每个 bin 的 A 计权线性增益
+    a_gain: Vec<f32>,
+    /// 采集到的交错样本
+    acc: Vec<f32>,
+}
+
+impl SpectrumProcessor {
+    pub fn new(config: ProcessorConfig, sample_rate: u32, channels: usize) -> Result<Self, String> {
+        config.validate()?;
+        if sample_rate == 0 { return Err("sample_rate must be positive".into()); }
+        let mut p = SpectrumProcessor {
+            fft: FftPlanner::<f32>::new().plan_fft_forward(config.fft_size),
+            window: Vec::new(),
+            window_sum: 0.0,
+            window_sq_sum: 0.0,
+            a_gain: Vec::new(),
+            acc: Vec::with_capacity(config.fft_size * channels.max(1)),
+            config,
+            sample_rate,
+            channels: channels.max(1),
+        };
+        p.rebuild_tables();
+        Ok(p)
+    }
+
+    pub fn config(&self) -> &ProcessorConfig {
+        &self.config
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    fn rebuild_tables(&mut self) {
+        let n = self.config.fft_size;
+        self.window = self.config.window.coefficients(n);
+        self.window_sum = self.window.iter().sum::<f32>().max(f32::EPSILON);
+        self.window_sq_sum = self.window.iter().map(|w| w * w).sum::<f32>().max(f32::EPSILON);
+        self.a_gain = (0..n / 2)
+            .map(|k| 10f32.powf(a_weighting_db(self.bin_freq(k)) / 20.0))
+            .collect();
+    }
+
+    /// 更新配置；FFT 大小变化时丢弃已积累的样本，避免跨尺寸拼接
+    pub fn set_config(&mut self, config: ProcessorConfig) -> Result<(), String> {
+        config.validate()?;
+        if config == self.config { return Ok(()); }
+        let size_changed = config.fft_size != self.config.fft_size;
+        let tables_changed = size_changed || config.window != self.config.window;
+        self.config = config;
+        if size_changed {
+            self.fft = FftPlanner::<f32>::new().plan_fft_forward(self.config.fft_size);
+            self.acc.clear();
+        }
+        if tables_changed { self.rebuild_tables(); }
+        Ok(())
+    }
+
+    /// bin k 的中心频率（Hz）
+    pub fn bin_freq(&self, k: usize) -> f32 {
+        k as f32 * self.sample_rate as f32 / self.config.fft_size as f32
+    }
+
+    /// 追加交错样本；只保留最新的 N + hop 帧，更早的样本不会再被用到
+    pub fn push(&mut self, interleaved: &[f32]) {
+        self.acc.extend_from_slice(interleaved);
+        let n = self.config.fft_size;
+        self.retain_latest(n + Self::hop(n));
+    }
+
+    /// 75% 重叠对应的步长
+    fn hop(n: usize) -> usize {
+        (n / 4).max(1)
+    }
+
+    /// 丢弃最新 frames 帧之前的样本
+    fn retain_latest(&mut self, frames: usize) {
+        let keep = frames * self.channels;
+        if self.acc.len() > keep {
+            self.acc.drain(..self.acc.len() - keep);
+        }
+    }
+
+    /// 已积累的帧数（每声道）
+    pub fn buffered_frames(&self) -> usize {
+        self.acc.len() / self.channels
+    }
+
+    /// 积累满 fft_size 帧时取最新的 N 帧，返回单声道样本；之后只保留其中最新的 N - hop 帧（hop = N/4），
+    /// 再来 hop 帧才产生下一块，形成 75% 重叠，积压的旧样本一并丢弃
+    pub fn next_block(&mut self) -> Option<Vec<f32>> {
+        self.next_blocks(false).map(|mut blocks| blocks.remove(0))
+    }
+
+    /// 同 next_block；per_channel 时分别返回左右声道（单声道来源复制一份）
+    pub fn next_blocks(&mut self, per_channel: bool) -> Option<Vec<Vec<f32>>> {
+        let n = self.config.fft_size;
+        let ch = self.channels;
+        if self.acc.len() < n * ch { return None; }
+        // 取“最新”的 N 帧，避免积压时延迟越来越大
+        let start = self.acc.len() - n * ch;
+        let latest = &self.acc[start..];
+        let blocks = if per_channel {
+            let channel = |c: usize| latest.iter().skip(c.min(ch - 1)).step_by(ch).copied().collect::<Vec<f32>>();
+            vec![channel(0), channel(1)]
+        } else {
+            vec![downmix(latest, ch)]
+        };
+        self.retain_latest(n - Self::hop(n));
+        Some(blocks)
+    }
+
+    /// 加窗并做 FFT，返回前半谱（N/2 个 bin）未归一化的线性幅值，按配置做 A 计权
+    pub fn spectrum(&self, mono: &[f32]) -> Vec<f32> {
+        let n = self.config.fft_size;
+        let mut buf: Vec<Complex<f32>> = mono
+            .iter()
+            .zip(&self.window)
+            .map(|(x, w)| Complex { re: x * w, im: 0.0 })
+            .collect();
+        buf.resize(n, Complex { re: 0.0, im: 0.0 });
+        self.fft.process(&mut buf);
+        buf[..n / 2]
+            .iter()
+            .zip(&self.a_gain)
+            .map(|(c, a)| if self.config.a_weighting { c.norm() * a } else { c.norm() })
+            .collect()
+    }
+
+    /// 把 spectrum 的线性幅值换算到输出刻度
+    pub fn scale(&self, m: f32) -> f32 {
+        match self.config.scale {
+            MagnitudeScale::Linear => m,
+            MagnitudeScale::Db => {
+                let amp = m * 2.0 / self.window_sum;
+                (20.0 * amp.max(1e-12).log10()).max(self.config.db_floor)
+            }
+        }
+    }
+
+    /// 一组 bin 的能量（幅值平方和）折算为与单个正弦峰值同一尺度的线性幅值
+    pub fn band_amplitude(&self, power: f32) -> f32 {
+        (power / (self.config.fft_size as f32 * self.window_sq_sum)).sqrt() * self.window_sum
+    }
+
+    /// spectrum 之后换算到输出刻度
+    pub fn magnitudes(&self, mono: &[f32]) -> Vec<f32> {
+        self.spectrum(mono).into_iter().map(|m| self.scale(m)).collect()
+    }
+
+    /// 低于 min_freq 的 bin 被裁掉后，按对数频率把幅值重采样到固定列（区间内取最大）
+    pub fn columns(&self, mags: &[f32]) -> Vec<f32> {
+        let n = self.config.fft_size;
+        let cols = self.config.columns;
+        let half = mags.len().min(n / 2);
+        let floor = match self.config.scale {
+            MagnitudeScale::Linear => 0.0,
+            MagnitudeScale::Db => self.config.db_floor,
+        };
+        // bin 频率: k * sample_rate / fft_size => k >= ceil(min_freq * fft_size / sample_rate)
+        let min_bin = ((self.config.min_freq * n as f32 / self.sample_rate as f32).ceil() as usize).max(1);
+        let max_bin = (half - 1).max(min_bin + 1);
+        let ln_min = (min_bin as f32).ln();
+        let span = ((max_bin as f32).ln() - ln_min).max(1e-6);
+        (0..cols)
+            .map(|i| {
+                let p0 = i as f32 / cols as f32;
+                let p1 = (i + 1) as f32 / cols as f32;
+                let start = ((ln_min + p0 * span).exp() as usize).clamp(min_bin, max_bin);
+                let end = ((ln_min + p1 * span).exp() as usize).clamp(start + 1, max_bin + 1).min(half);
+                mags.get(start..end).unwrap_or(&[]).iter().fold(floor, |mx, &m| mx.max(m))
+            })
+            .collect()
+    }
+
+    /// 取出下一块并计算列；样本不足时返回 None
+    pub fn next_frame(&mut self) -> Option<Vec<f32>> {
+        let mono = self.next_block()?;
+        let mags = self.magnitudes(&mono);
+        Some(self.columns(&mags))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_WINDOWS: [WindowFunction; 4] =
+        [WindowFunction::Hann, WindowFunction::Hamming, WindowFunction::BlackmanHarris, WindowFunction::FlatTop];
+
+    /// n 帧交错正弦，各声道相同
+    fn sine(freq: f32, amplitude: f32, sample_rate: u32, frames: usize, channels: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                let v = amplitude * (2.0 * PI * freq * i as f32 / sample_rate as f32).sin();
+                std::iter::repeat_n(v, channels)
+            })
+            .collect()
+    }
+
+    fn argmax(values: &[f32]) -> (usize, f32) {
+        values.iter().copied().enumerate().fold((0, f32::MIN), |best, (i, v)| if v > best.1 { (i, v) } else { best })
+    }
+
+    fn db_processor(window: WindowFunction, sample_rate: u32, channels: usize) -> SpectrumProcessor {
+        let config = ProcessorConfig { scale: MagnitudeScale::Db, window, ..Default::default() };
+        SpectrumProcessor::new(config, sample_rate, channels).unwrap()
+    }
+
+    #[test]
+    fn sine_peaks_at_its_bin_and_column() {
+        // 48 kHz / 2048 点，bin 43 ≈ 1007.8 Hz
+        let mut p = db_processor(WindowFunction::Hann, 48_000, 2);
+        p.push(&sine(p.bin_freq(43), 1.0, 48_000, 2048, 2));
+        let block = p.next_block().unwrap();
+        let mags = p.magnitudes(&block);
+        assert_eq!(argmax(&mags).0, 43);
+        // min_bin = 1、max_bin = 1023、96 列：bin 43 落在第 floor(ln 43 / ln 1023 × 96) = 52 列（覆盖 bin 42..45）
+        let cols = p.columns(&mags);
+        assert_eq!(cols.len(), 96);
+        let (col, peak) = argmax(&cols);
+        assert_eq!(col, 52);
+        assert!(peak.abs() < 0.05, "{}", peak);
+    }
+
+    #[test]
+    fn db_scale_corrects_for_window_gain() {
+        for window in ALL_WINDOWS {
+            for (amplitude, expected) in [(1.0, 0.0), (0.5, -6.02), (0.1, -20.0)] {
+                let mut p = db_processor(window, 48_000, 1);
+                p.push(&sine(p.bin_freq(43), amplitude, 48_000, 2048, 1));
+                let block = p.next_block().unwrap();
+                let (_, peak) = argmax(&p.magnitudes(&block));
+                assert!((peak - expected).abs() < 0.05, "{:?} {} → {}", window, amplitude, peak);
+            }
+        }
+        // 平顶窗在两个 bin 正中间也保持幅值
+        let mut p = db_processor(WindowFunction::FlatTop, 48_000, 1);
+        p.push(&sine(p.bin_freq(43) + p.bin_freq(1) / 2.0, 1.0, 48_000, 2048, 1));
+        let block = p.next_block().unwrap();
+        let (_, peak) = argmax(&p.magnitudes(&block));
+        assert!(peak.abs() < 0.1, "{}", peak);
+    }
+
+    #[test]
+    fn db_scale_clamps_to_floor() {
+        let config = ProcessorConfig { scale: MagnitudeScale::Db, db_floor: -60.0, ..Default::default() };
+        let mut p = SpectrumProcessor::new(config, 48_000, 1).unwrap();
+        p.push(&vec![0.0; 2048]);
+        let cols = p.next_frame().unwrap();
+        assert!(cols.iter().all(|&v| v == -60.0));
+    }
+
+    #[test]
+    fn a_weighting_is_zero_at_1khz() {
+        assert!(a_weighting_db(1000.0).abs() < 0.01, "{}", a_weighting_db(1000.0));
+        assert!((a_weighting_db(100.0) + 19.1).abs() < 0.1);
+        assert!((a_weighting_db(10_000.0) + 2.5).abs() < 0.1);
+        assert_eq!(a_weighting_db(0.0), f32::NEG_INFINITY);
+
+        // 40960 Hz / 4096 点：bin 宽 10 Hz，1 kHz 与 100 Hz 都正好落在 bin 上
+        let config = ProcessorConfig { fft_size: 4096, scale: MagnitudeScale::Db, a_weighting: true, ..Default::default() };
+        for (freq, expected) in [(1000.0, 0.0), (100.0, a_weighting_db(100.0))] {
+            let mut p = SpectrumProcessor::new(config.clone(), 40_960, 1).unwrap();
+            p.push(&sine(freq, 1.0, 40_960, 4096, 1));
+            let block = p.next_block().unwrap();
+            let (bin, peak) = argmax(&p.magnitudes(&block));
+            assert_eq!(p.bin_freq(bin), freq);
+            assert!((peak - expected).abs() < 0.05, "{} Hz → {}", freq, peak);
+        }
+    }
+
+    #[test]
+    fn blocks_overlap_by_three_quarters() {
+        let mut p = db_processor(WindowFunction::Hann, 48_000, 2);
+        p.push(&vec![0.0; 2047 * 2]);
+        assert!(p.next_block().is_none());
+        p.push(&[0.0, 0.0]);
+        assert!(p.next_block().is_some());
+        assert_eq!(p.buffered_frames(), 2048 - 512);
+        // 再来 hop 帧才有下一块
+        p.push(&vec![0.0; 511 * 2]);
+        assert!(p.next_block().is_none());
+        p.push(&[0.0, 0.0]);
+        assert!(p.next_block().is_some());
+    }
+
+    #[test]
+    fn backlog_is_dropped() {
+        let mut p = db_processor(WindowFunction::Hann, 48_000, 1);
+        // 积压远超一块：缓冲只保留 N + hop 帧，取出的是最新的 N 帧，之后不再重复产出
+        let mut samples = vec![0.0; 10 * 2048];
+        samples.extend(sine(p.bin_freq(43), 1.0, 48_000, 2048, 1));
+        p.push(&samples);
+        assert_eq!(p.buffered_frames(), 2048 + 512);
+        let block = p.next_block().unwrap();
+        assert_eq!(argmax(&p.magnitudes(&block)).0, 43);
+        assert!(p.next_block().is_none());
+    }
+
+    #[test]
+    fn per_channel_blocks_and_downmix() {
+        let mut p = db_processor(WindowFunction::Hann, 48_000, 2);
+        let frames: Vec<f32> = (0..2048).flat_map(|_| [1.0, -1.0]).collect();
+        p.push(&frames);
+        let blocks = p.next_blocks(true).unwrap();
+        assert!(blocks[0].iter().all(|&v| v == 1.0) && blocks[1].iter().all(|&v| v == -1.0));
+        assert_eq!(downmix(&[1.0, 0.0, 0.5, 0.5], 2), vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn rejects_invalid_config() {
+        let bad = [
+            ProcessorConfig { fft_size: 1000, ..Default::default() },
+            ProcessorConfig { fft_size: 16384, ..Default::default() },
+            ProcessorConfig { columns: 16, ..Default::default() },
+            ProcessorConfig { db_floor: 0.0, ..Default::default() },
+            ProcessorConfig { db_floor: f32::NAN, ..Default::default() },
+        ];
+        for config in bad {
+            assert!(SpectrumProcessor::new(config, 48_000, 1).is_err());
+        }
+        assert!(SpectrumProcessor::new(ProcessorConfig::default(), 0, 1).is_err());
+    }
+}
@@ -0,0 +1,384 @@
+use crate::spectrum_dsp::{MagnitudeScale, SpectrumProcessor};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// 频谱输出模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpectrumMode {
+    /// 对数频率列（原有的柱状显示）
+    #[default]
+    Columns,
+    /// 滚动的时频图：每帧一行，后端保留最近 history_len 行
+    Spectrogram,
+    /// 列 + 缓慢下落的峰值
+    PeakHold,
+    /// ISO 266 标准 1/3 倍频程频带
+    OctaveBands,
+}
+
+/// 显示相关参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewConfig {
+    pub mode: SpectrumMode,
+    /// 分别输出左右声道，否则输出混合后的单声道
+    pub per_channel: bool,
+    /// 上升/下降的时间常数（毫秒），0 表示不平滑
+    pub attack_ms: f32,
+    pub release_ms: f32,
+    /// 峰值保持时间与之后的下落速度
+    pub peak_hold_ms: f32,
+    pub peak_decay_db_per_sec: f32,
+    /// 时频图保留的行数
+    pub history_len: usize,
+}
+
+impl Default for ViewConfig {
+    fn default() -> Self {
+        ViewConfig {
+            mode: SpectrumMode::Columns,
+            per_channel: false,
+            attack_ms: 0.0,
+            release_ms: 0.0,
+            peak_hold_ms: 1000.0,
+            peak_decay_db_per_sec: 20.0,
+            history_len: 128,
+        }
+    }
+}
+
+impl ViewConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, v) in [
+            ("attack_ms", self.attack_ms),
+            ("release_ms", self.release_ms),
+            ("peak_hold_ms", self.peak_hold_ms),
+            ("peak_decay_db_per_sec", self.peak_decay_db_per_sec),
+        ] {
+            if !v.is_finite() || v < 0.0 {
+                return Err(format!("{} must be a non-negative number", name));
+            }
+        }
+        if !(1..=1024).contains(&self.history_len) {
+            return Err("history_len must be within 1..1024".into());
+        }
+        Ok(())
+    }
+}
+
+/// ISO 266 的 1/3 倍频程标称中心频率（25 Hz .. 20 kHz）
+pub const THIRD_OCTAVE_CENTRES: [f32; 30] = [
+    25.0, 31.5, 40.0, 50.0, 63.0, 80.0, 100.0, 125.0, 160.0, 200.0, 250.0, 315.0, 400.0, 500.0, 630.0, 800.0,
+    1000.0, 1250.0, 1600.0, 2000.0, 2500.0, 3150.0, 4000.0, 5000.0, 6300.0, 8000.0, 10000.0, 12500.0, 16000.0,
+    20000.0,
+];
+
+/// 频带边界用以 1 kHz 为基准的精确中心频率 1000·2^(n/3) 计算，标称值只用于显示
+fn band_edges(index: usize) -> (f32, f32) {
+    let n = index as f32 - 16.0; // 1000 Hz 在表中的下标为 16
+    let centre = 1000.0 * 2f32.powf(n / 3.0);
+    (centre * 2f32.powf(-1.0 / 6.0), centre * 2f32.powf(1.0 / 6.0))
+}
+
+/// 采样率下可完整表示的频带（上边界不超过奈奎斯特频率）
+pub fn third_octave_bands(sample_rate: u32) -> Vec<(f32, f32, f32)> {
+    let nyquist = sample_rate as f32 / 2.0;
+    THIRD_OCTAVE_CENTRES
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| {
+            let (lo, hi) = band_edges(i);
+            (c, lo, hi)
+        })
+        .filter(|(_, _, hi)| *hi <= nyquist)
+        .collect()
+}
+
+/// 把线性幅值谱（SpectrumProcessor::spectrum 的输出）按频带合并能量，再换算到输出刻度
+pub fn octave_band_levels(processor: &SpectrumProcessor, spectrum: &[f32], bands: &[(f32, f32, f32)]) -> Vec<f32> {
+    bands
+        .iter()
+        .map(|&(_, lo, hi)| {
+            let power: f32 = spectrum
+                .iter()
+                .enumerate()
+                .filter(|(k, _)| {
+                    let f = processor.bin_freq(*k);
+                    f >= lo && f < hi
+                })
+                .map(|(_, m)| m * m)
+                .sum();
+            processor.scale(processor.band_amplitude(power))
+        })
+        .collect()
+}
+
+/// 一阶 attack/release 平滑
+#[derive(Debug, Clone, Default)]
+pub struct Smoother {
+    state: Vec<f32>,
+}
+
+impl Smoother {
+    pub fn apply(&mut self, values: &mut [f32], dt_secs: f32, attack_ms: f32, release_ms: f32) {
+        if self.state.len() != values.len() {
+            self.state = values.to_vec();
+            return;
+        }
+        let coef = |ms: f32| if ms <= 0.0 { 0.0 } else { (-dt_secs * 1000.0 / ms).exp() };
+        let (a, r) = (coef(attack_ms), coef(release_ms));
+        for (v, s) in values.iter_mut().zip(self.state.iter_mut()) {
+            let c = if *v > *s { a } else { r };
+            *s = *v + (*s - *v) * c;
+            *v = *s;
+        }
+    }
+}
+
+/// 峰值保持：超过保持时间后按 dB/s 下落
+#[derive(Debug, Clone, Default)]
+pub struct PeakHold {
+    peaks: Vec<f32>,
+    /// 每个峰值剩余的保持时间（秒）
+    hold_left: Vec<f32>,
+}
+
+impl PeakHold {
+    pub fn update(&mut self, values: &[f32], dt_secs: f32, hold_ms: f32, decay_db_per_sec: f32, scale: MagnitudeScale) -> &[f32] {
+        if self.peaks.len() != values.len() {
+            self.peaks = values.to_vec();
+            self.hold_left = vec![hold_ms / 1000.0; values.len()];
+            return &self.peaks;
+        }
+        let decay_db = decay_db_per_sec * dt_secs;
+        for ((p, h), &v) in self.peaks.iter_mut().zip(self.hold_left.iter_mut()).zip(values) {
+            if v >= *p {
+                *p = v;
+                *h = hold_ms / 1000.0;
+                continue;
+            }
+            if *h > 0.0 {
+                *h -= dt_secs;
+                continue;
+            }
+            let decayed = match scale {
+                MagnitudeScale::Db => *p - decay_db,
+                MagnitudeScale::Linear => *p * 10f32.powf(-decay_db / 20.0),
+            };
+            *p = decayed.max(v);
+        }
+        &self.peaks
+    }
+}
+
+/// 一帧输出（"spectrum:frame" 事件的内容）
+#[derive(Debug, Clone, Serialize)]
+pub struct SpectrumFrame {
+    pub mode: SpectrumMode,
+    pub scale: MagnitudeScale,
+    /// per_channel 时为 [左, 右]，否则只有混合后的一组
+    pub channels: Vec<Vec<f32>>,
+    /// peak_hold 模式下与 channels 对应的峰值
+    pub peaks: Option<Vec<Vec<f32>>>,
+    /// octave_bands 模式下每个值的标称中心频率
+    pub band_centres: Option<Vec<f32>>,
+    /// 单调递增的帧序号，时频图据此判断是否丢帧
+    pub seq: u64,
+}
+
+/// 有状态的视图：把 SpectrumProcessor 输出的块变换成所选模式的帧
+#[derive(Debug, Default)]
+pub struct SpectrumView {
+    smoothers: Vec<Smoother>,
+    peaks: Vec<PeakHold>,
+    history: VecDeque<Vec<f32>>,
+    seq: u64,
+    /// 上一帧的 (模式, 刻度, 是否分声道)；变化时状态不再可比
+    last_key: Option<(SpectrumMode, MagnitudeScale, bool)>,
+}
+
+impl SpectrumView {
+    /// 清空平滑、峰值与历史
+    pub fn reset(&mut self) {
+        self.smoothers.clear();
+        self.peaks.clear();
+        self.history.clear();
+    }
+
+    /// blocks 为 SpectrumProcessor::next_blocks 的输出（每声道一块单声道样本）
+    pub fn process(&mut self, processor: &SpectrumProcessor, blocks: &[Vec<f32>], config: &ViewConfig, dt_secs: f32) -> SpectrumFrame {
+        let scale = processor.config().scale;
+        let key = (config.mode, scale, config.per_channel);
+        if self.last_key != Some(key) {
+            self.reset();
+            self.last_key = Some(key);
+        }
+        let bands = (config.mode == SpectrumMode::OctaveBands).then(|| third_octave_bands(processor.sample_rate()));
+        let mut channels: Vec<Vec<f32>> = blocks
+            .iter()
+            .map(|block| {
+                let spectrum = processor.spectrum(block);
+                match &bands {
+                    Some(bands) => octave_band_levels(processor, &spectrum, bands),
+                    None => {
+                        let mags: Vec<f32> = spectrum.iter().map(|&m| processor.scale(m)).collect();
+                        processor.columns(&mags)
+                    }
+                }
+            })
+            .collect();
+
+        self.smoothers.resize_with(channels.len(), Smoother::default);
+        for (values, smoother) in channels.iter_mut().zip(self.smoothers.iter_mut()) {
+            smoother.apply(values, dt_secs, config.attack_ms, config.release_ms);
+        }
+
+        let peaks = (config.mode == SpectrumMode::PeakHold).then(|| {
+            self.peaks.resize_with(channels.len(), PeakHold::default);
+            channels
+                .iter()
+                .zip(self.peaks.iter_mut())
+                .map(|(values, hold)| hold.update(values, dt_secs, config.peak_hold_ms, config.peak_decay_db_per_sec, scale).to_vec())
+                .collect()
+        });
+
+        if config.mode == SpectrumMode::Spectrogram {
+            if let Some(row) = channels.first() {
+                self.history.push_back(row.clone());
+            }
+            while self.history.len() > config.history_len {
+                self.history.pop_front();
+            }
+        }
+
+        self.seq += 1;
+        SpectrumFrame {
+            mode: config.mode,
+            scale,
+            channels,
+            peaks,
+            band_centres: bands.map(|b| b.iter().map(|(c, _, _)| *c).collect()),
+            seq: self.seq,
+        }
+    }
+
+    /// 时频图历史（从旧到新），供新打开的窗口一次性补齐
+    pub fn history(&self) -> Vec<Vec<f32>> {
+        self.history.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spectrum_dsp::ProcessorConfig;
+    use std::f32::consts::PI;
+
+    fn close(a: f32, b: f32, tol: f32) -> bool {
+        (a - b).abs() <= tol
+    }
+
+    fn db_processor(sample_rate: u32) -> SpectrumProcessor {
+        SpectrumProcessor::new(ProcessorConfig { scale: MagnitudeScale::Db, ..Default::default() }, sample_rate, 1).unwrap()
+    }
+
+    #[test]
+    fn smoother_uses_attack_when_rising_and_release_when_falling() {
+        let mut s = Smoother::default();
+        // 第一帧只记录状态
+        let mut v = vec![0.0, 1.0];
+        s.apply(&mut v, 0.01, 10.0, 100.0);
+        assert_eq!(v, vec![0.0, 1.0]);
+
+        // dt = attack：上升走完 1 - e^-1；dt = release / 10：下降只走 1 - e^-0.1
+        let mut v = vec![1.0, 0.0];
+        s.apply(&mut v, 0.01, 10.0, 100.0);
+        assert!(close(v[0], 1.0 - (-1f32).exp(), 1e-5), "{:?}", v);
+        assert!(close(v[1], (-0.1f32).exp(), 1e-5), "{:?}", v);
+
+        // 时间常数为 0 时直接跟随
+        let mut v = vec![0.25, 0.75];
+        s.apply(&mut v, 0.01, 0.0, 0.0);
+        assert_eq!(v, vec![0.25, 0.75]);
+
+        // 长度变化（例如切换分声道）时重新开始
+        let mut v = vec![5.0; 3];
+        s.apply(&mut v, 0.01, 10.0, 100.0);
+        assert_eq!(v, vec![5.0; 3]);
+    }
+
+    #[test]
+    fn peak_hold_holds_then_decays() {
+        // 保持 125 ms、每步 62.5 ms，16 dB/s 即每步 1 dB（取 2 的幂避免浮点误差）
+        let (dt, hold_ms, decay) = (0.0625, 125.0, 16.0);
+        let mut hold = PeakHold::default();
+        assert_eq!(hold.update(&[-10.0], dt, hold_ms, decay, MagnitudeScale::Db), &[-10.0]);
+        assert_eq!(hold.update(&[-60.0], dt, hold_ms, decay, MagnitudeScale::Db), &[-10.0]);
+        assert_eq!(hold.update(&[-60.0], dt, hold_ms, decay, MagnitudeScale::Db), &[-10.0]);
+        assert_eq!(hold.update(&[-60.0], dt, hold_ms, decay, MagnitudeScale::Db), &[-11.0]);
+        assert_eq!(hold.update(&[-60.0], dt, hold_ms, decay, MagnitudeScale::Db), &[-12.0]);
+        // 不低于当前值
+        assert_eq!(hold.update(&[-12.5], dt, hold_ms, decay, MagnitudeScale::Db), &[-12.5]);
+        // 新的峰值重新开始保持
+        assert_eq!(hold.update(&[-3.0], dt, hold_ms, decay, MagnitudeScale::Db), &[-3.0]);
+        assert_eq!(hold.update(&[-60.0], dt, hold_ms, decay, MagnitudeScale::Db), &[-3.0]);
+
+        // 线性刻度按同样的 dB 速度衰减：每步乘 10^(-1/20)
+        let mut hold = PeakHold::default();
+        hold.update(&[1.0], dt, 0.0, decay, MagnitudeScale::Linear);
+        let mut peak = 1.0;
+        for _ in 0..20 {
+            peak = hold.update(&[0.0], dt, 0.0, decay, MagnitudeScale::Linear)[0];
+        }
+        assert!(close(peak, 0.1, 1e-4), "{}", peak);
+    }
+
+    #[test]
+    fn spectrogram_history_is_trimmed() {
+        let processor = db_processor(48_000);
+        let block = vec![vec![0.0; processor.config().fft_size]];
+        let mut view = SpectrumView::default();
+        let mut config = ViewConfig { mode: SpectrumMode::Spectrogram, history_len: 3, ..Default::default() };
+        for _ in 0..5 {
+            view.process(&processor, &block, &config, 0.02);
+        }
+        assert_eq!(view.history().len(), 3);
+
+        config.history_len = 2;
+        let frame = view.process(&processor, &block, &config, 0.02);
+        assert_eq!(frame.seq, 6);
+        assert_eq!(view.history().len(), 2);
+        assert_eq!(view.history()[1], frame.channels[0]);
+
+        // 切换模式后历史清空
+        config.mode = SpectrumMode::Columns;
+        view.process(&processor, &block, &config, 0.02);
+        assert!(view.history().is_empty());
+    }
+
+    #[test]
+    fn sine_lands_in_its_third_octave_band() {
+        let sample_rate = 48_000;
+        let mut processor = db_processor(sample_rate);
+        let n = processor.config().fft_size;
+        let samples: Vec<f32> = (0..n).map(|i| (2.0 * PI * 1000.0 * i as f32 / sample_rate as f32).sin()).collect();
+        processor.push(&samples);
+        let blocks = processor.next_blocks(false).unwrap();
+
+        let config = ViewConfig { mode: SpectrumMode::OctaveBands, ..Default::default() };
+        let frame = SpectrumView::default().process(&processor, &blocks, &config, 0.02);
+        let centres = frame.band_centres.unwrap();
+        let levels = &frame.channels[0];
+        assert_eq!(centres.len(), levels.len());
+        assert_eq!(centres.last(), Some(&20000.0));
+        // 20 kHz 频带的上边界约 22.6 kHz，44.1 kHz 采样时超过奈奎斯特频率
+        assert_eq!(third_octave_bands(44_100).last().map(|b| b.0), Some(16000.0));
+
+        let (loudest, level) = levels.iter().copied().enumerate().fold((0, f32::MIN), |b, (i, v)| if v > b.1 { (i, v) } else { b });
+        assert_eq!(centres[loudest], 1000.0);
+        // 满幅正弦的能量几乎都在本频带内
+        assert!(close(level, 0.0, 0.5), "{}", level);
+        assert!(levels[loudest - 1] < level - 10.0 && levels[loudest + 1] < level - 10.0, "{:?}", levels);
+    }
+}
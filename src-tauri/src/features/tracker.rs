@@ -1,10 +1,14 @@
-use crate::db::DbState;
-use chrono::Utc;
-use std::path::Path;
-use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
+use crate::categories::{self, Classification};
+use crate::db::{self, DbState};
+use crate::goals;
+use crate::idle_source;
+use crate::privacy;
+use crate::window_source::{self, ActiveWindowSource};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use rusqlite::Connection;
+use std::sync::{Arc, Mutex, atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering}};
 use std::time::Duration;
 use once_cell::sync::Lazy;
-use windows::{ Win32::Foundation::*, Win32::System::ProcessStatus::*, Win32::System::Threading::*, Win32::UI::WindowsAndMessaging::* };
 use tauri::Manager;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -15,52 +19,303 @@ pub struct ActiveWindowInfo { pub app_name: String, pub window_title: String }
 
 static LAST_KNOWN_ACTIVITY: Lazy<Mutex<Option<CurrentActivity>>> = Lazy::new(|| Mutex::new(None));
 
+// 供 get_active_window_info 命令使用的前台窗口来源（与追踪线程各自持有一份）
+static COMMAND_SOURCE: Lazy<Mutex<Box<dyn ActiveWindowSource + Send>>> = Lazy::new(|| Mutex::new(window_source::default_source()));
+
+/// 默认空闲阈值：5 分钟无输入即视为离开
+pub const DEFAULT_IDLE_THRESHOLD_SECS: u64 = 300;
+
+/// 空闲段在 activity_log 中使用的程序名
+pub const IDLE_APP_NAME: &str = "idle";
+
+/// 心跳间隔：进行中的分段每隔这么久把时长刷新到数据库
+pub const HEARTBEAT_INTERVAL_SECS: i64 = 10;
+
+/// 两次采样间隔超过该值视为系统休眠/挂起，当前分段在上次采样时结束
+const SUSPEND_GAP_SECS: i64 = 60;
+
+/// app_settings 中保存追踪器配置的键
+const TRACKER_SETTING_KEY: &str = "tracker";
+
+/// 追踪器运行时配置（可通过命令实时调整，修改后写入 app_settings，重启后沿用）
+pub struct TrackerConfig {
+    pub idle_threshold_secs: AtomicU64,
+    /// 暂停截止时间（Unix 秒）；0 表示未暂停，i64::MAX 表示直到手动恢复
+    pub paused_until: AtomicI64,
+}
+
+/// TrackerConfig 在 app_settings 中的持久化形式
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct TrackerSettings {
+    idle_threshold_secs: u64,
+    paused_until: i64,
+}
+
+impl Default for TrackerSettings {
+    fn default() -> Self {
+        Self { idle_threshold_secs: DEFAULT_IDLE_THRESHOLD_SECS, paused_until: 0 }
+    }
+}
+
+impl TrackerConfig {
+    /// 从 app_settings 读取上次保存的配置；读取失败时使用默认值
+    pub fn load(conn: &Connection) -> Self {
+        let settings = db::get_setting::<TrackerSettings>(conn, TRACKER_SETTING_KEY)
+            .unwrap_or_else(|e| {
+                eprintln!("[tracker] 读取追踪器配置失败，使用默认值: {}", e);
+                None
+            })
+            .unwrap_or_default();
+        Self {
+            idle_threshold_secs: AtomicU64::new(settings.idle_threshold_secs),
+            paused_until: AtomicI64::new(settings.paused_until),
+        }
+    }
+
+    fn save(&self, conn: &Connection) -> Result<(), String> {
+        let settings = TrackerSettings {
+            idle_threshold_secs: self.idle_threshold_secs.load(Ordering::Relaxed),
+            paused_until: self.paused_until.load(Ordering::Relaxed),
+        };
+        db::set_setting(conn, TRACKER_SETTING_KEY, &settings)
+    }
+}
+
+/// 把当前配置写入数据库
+fn save_config(app: &tauri::AppHandle) -> Result<(), String> {
+    let cfg: tauri::State<TrackerConfig> = app.state();
+    let db_state: tauri::State<DbState> = app.state();
+    let conn = db_state.db.lock().unwrap();
+    cfg.save(&conn)
+}
+
+#[derive(serde::Serialize, Clone)]
+pub struct PauseStatus {
+    pub paused: bool,
+    /// 自动恢复时间（RFC3339）；None 表示未暂停或需手动恢复
+    pub until: Option<String>,
+}
+
+/// 当前正在计时的分段
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Segment { Active(CurrentActivity), Idle }
+
+/// 活动分段记录器：去重连续相同的窗口，窗口切换或进入/离开空闲时切分分段
+///
+/// 分段一开始就以 is_open = 1 写入 activity_log，之后按心跳更新时长，结束时关闭；
+/// 这样崩溃或断电最多丢失一个心跳间隔的数据。
+pub struct ActivityRecorder {
+    current: Option<Segment>,
+    row_id: Option<i64>,
+    start_time: DateTime<Utc>,
+    last_flush: DateTime<Utc>,
+    last_seen: DateTime<Utc>,
+    idle_threshold: ChronoDuration,
+}
+
+impl ActivityRecorder {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            current: None,
+            row_id: None,
+            start_time: now,
+            last_flush: now,
+            last_seen: now,
+            idle_threshold: ChronoDuration::seconds(DEFAULT_IDLE_THRESHOLD_SECS as i64),
+        }
+    }
+
+    pub fn set_idle_threshold(&mut self, secs: u64) {
+        self.idle_threshold = ChronoDuration::seconds(secs as i64);
+    }
+
+    /// 处理一次采样；`idle_for` 为距最后一次输入的时长
+    pub fn observe(&mut self, conn: &Connection, current: Option<CurrentActivity>, idle_for: Duration, now: DateTime<Utc>) -> rusqlite::Result<()> {
+        // 采样出现大段空白（休眠/挂起），之前的分段在最后一次采样时结束
+        if now.signed_duration_since(self.last_seen).num_seconds() > SUSPEND_GAP_SECS {
+            let last_seen = self.last_seen;
+            self.finish(conn, last_seen)?;
+        }
+        self.last_seen = now;
+
+        // 写入前按隐私规则处理标题；分类也使用处理后的标题，与 reapply_rules 保持一致
+        let current = match current {
+            Some(a) => {
+                // 规则加载失败时宁可丢弃标题，也不写入未脱敏的原文
+                let window_title = privacy::redact(conn, &a.app_name, &a.window_title).unwrap_or_else(|e| {
+                    eprintln!("[privacy] 标题脱敏失败: {}", e);
+                    String::new()
+                });
+                Some(CurrentActivity { app_name: a.app_name, window_title })
+            }
+            None => None,
+        };
+
+        let idle_for = ChronoDuration::from_std(idle_for).unwrap_or(ChronoDuration::zero());
+        let (next, switch_time) = if self.idle_threshold > ChronoDuration::zero() && idle_for >= self.idle_threshold {
+            // 空闲从最后一次输入时开始算起，而不是从检测到阈值时开始
+            (Some(Segment::Idle), (now - idle_for).max(self.start_time))
+        } else {
+            (current.map(Segment::Active), now)
+        };
+        if self.current == next {
+            if self.row_id.is_some() && now.signed_duration_since(self.last_flush).num_seconds() >= HEARTBEAT_INTERVAL_SECS {
+                self.heartbeat(conn, now)?;
+            }
+            return Ok(());
+        }
+
+        self.close(conn, switch_time)?;
+        if let Some(seg) = &next {
+            let (app_name, window_title, is_idle) = match seg {
+                Segment::Active(a) => (a.app_name.as_str(), a.window_title.as_str(), false),
+                Segment::Idle => (IDLE_APP_NAME, "", true),
+            };
+            let class = if is_idle { Classification::default() } else { categories::classify(conn, app_name, window_title)? };
+            let duration = now.signed_duration_since(switch_time).num_seconds().max(0);
+            conn.execute(
+                "INSERT INTO activity_log (app_name, window_title, start_time, duration_seconds, is_idle, is_open, category_id, project) VALUES (?1, ?2, ?3, ?4, ?5, 1, ?6, ?7)",
+                (app_name, window_title, switch_time.to_rfc3339(), duration, is_idle, class.category_id, class.project),
+            )?;
+            self.row_id = Some(conn.last_insert_rowid());
+        }
+        self.current = next;
+        self.start_time = switch_time;
+        self.last_flush = now;
+        Ok(())
+    }
+
+    /// 正在计时的活动（标题已按隐私规则处理）；空闲或没有前台窗口时为 None
+    pub fn current_activity(&self) -> Option<&CurrentActivity> {
+        match &self.current {
+            Some(Segment::Active(a)) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// 结束当前分段（追踪停止时调用）
+    pub fn finish(&mut self, conn: &Connection, now: DateTime<Utc>) -> rusqlite::Result<()> {
+        self.close(conn, now)?;
+        self.current = None;
+        self.start_time = now;
+        Ok(())
+    }
+
+    fn heartbeat(&mut self, conn: &Connection, now: DateTime<Utc>) -> rusqlite::Result<()> {
+        if let Some(id) = self.row_id {
+            let duration = now.signed_duration_since(self.start_time).num_seconds();
+            conn.execute("UPDATE activity_log SET duration_seconds = ?1 WHERE id = ?2", (duration, id))?;
+        }
+        self.last_flush = now;
+        Ok(())
+    }
+
+    /// 关闭进行中的行；不超过 1 秒的分段直接删除
+    fn close(&mut self, conn: &Connection, end: DateTime<Utc>) -> rusqlite::Result<()> {
+        if let Some(id) = self.row_id.take() {
+            let duration = end.signed_duration_since(self.start_time).num_seconds();
+            if duration > 1 {
+                conn.execute("UPDATE activity_log SET duration_seconds = ?1, is_open = 0 WHERE id = ?2", (duration, id))?;
+            } else {
+                conn.execute("DELETE FROM activity_log WHERE id = ?1", [id])?;
+            }
+        }
+        Ok(())
+    }
+}
+
 pub fn run_tracker_loop(app_handle: tauri::AppHandle, stop: Arc<AtomicBool>) {
-    let mut last_activity: Option<CurrentActivity> = None;
-    let mut last_start_time = Utc::now();
+    let mut source = window_source::default_source();
+    let mut idle = idle_source::default_source();
+    let mut recorder = ActivityRecorder::new(Utc::now());
     loop {
         if stop.load(Ordering::Relaxed) { break; }
         std::thread::sleep(Duration::from_secs(2));
-        let current_activity = match get_active_window_info_internal() { Ok(info) => Some(info), Err(_) => None };
-        if last_activity != current_activity {
-            let now = Utc::now();
-            if let Some(prev) = last_activity {
-                let duration = now.signed_duration_since(last_start_time).num_seconds();
-                if duration > 1 {
-                    let db_state: tauri::State<DbState> = app_handle.state();
-                    let conn = db_state.db.lock().unwrap();
-                    let _ = conn.execute(
-                        "INSERT INTO activity_log (app_name, window_title, start_time, duration_seconds) VALUES (?1, ?2, ?3, ?4)",
-                        (&prev.app_name, &prev.window_title, last_start_time.to_rfc3339(), duration),
-                    );
-                }
+        let now = Utc::now();
+        let cfg: tauri::State<TrackerConfig> = app_handle.state();
+        if is_paused(&cfg, now) {
+            // 暂停期间不采样：结束进行中的分段即可
+            let db_state: tauri::State<DbState> = app_handle.state();
+            let conn = db_state.db.lock().unwrap();
+            if let Err(e) = recorder.finish(&conn, now) {
+                eprintln!("关闭进行中的分段失败: {}", e);
             }
-            last_activity = current_activity;
-            last_start_time = now;
+            goals::on_tracker_tick(&app_handle, &conn, None, now);
+            continue;
         }
+        let current_activity = source.current().ok();
+        let idle_for = idle.idle_duration().unwrap_or_default();
+        recorder.set_idle_threshold(cfg.idle_threshold_secs.load(Ordering::Relaxed));
+        let db_state: tauri::State<DbState> = app_handle.state();
+        let conn = db_state.db.lock().unwrap();
+        if let Err(e) = recorder.observe(&conn, current_activity, idle_for, now) {
+            eprintln!("写入 activity_log 失败: {}", e);
+        }
+        // 目标与番茄钟只拿到脱敏后的标题（分心提醒会把标题发给前端）
+        goals::on_tracker_tick(&app_handle, &conn, recorder.current_activity(), now);
+    }
+    // 正常退出：关闭进行中的分段
+    let db_state: tauri::State<DbState> = app_handle.state();
+    let conn = db_state.db.lock().unwrap();
+    if let Err(e) = recorder.finish(&conn, Utc::now()) {
+        eprintln!("关闭进行中的分段失败: {}", e);
     }
 }
 
-fn get_active_window_info_internal() -> Result<CurrentActivity, String> {
-    unsafe {
-        let hwnd = GetForegroundWindow();
-        if hwnd.is_invalid() { return Err("无法获取前台窗口".into()); }
-        let mut title_buffer = [0u16; 512];
-        let len = GetWindowTextW(hwnd, &mut title_buffer);
-        let window_title = String::from_utf16_lossy(&title_buffer[..len as usize]);
-        let mut process_id: u32 = 0;
-        GetWindowThreadProcessId(hwnd, Some(&mut process_id));
-        if process_id == 0 { return Err("无法获取进程ID".into()); }
-        let process_handle = OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, false, process_id)
-            .map_err(|e| format!("无法打开进程: {:?}", e))?;
-        let mut exe_path_buffer = [0u16; 1024];
-        let exe_len = K32GetModuleFileNameExW(Some(process_handle), None, &mut exe_path_buffer);
-        let _ = CloseHandle(process_handle);
-        if exe_len == 0 { return Err("无法获取模块文件名".into()); }
-        let exe_path_full = String::from_utf16_lossy(&exe_path_buffer[..exe_len as usize]);
-        let app_name = Path::new(&exe_path_full).file_name().and_then(|s| s.to_str()).unwrap_or("unknown").to_string();
-        Ok(CurrentActivity { app_name, window_title })
+pub fn get_idle_threshold(app: tauri::AppHandle) -> Result<u64, String> {
+    let cfg: tauri::State<TrackerConfig> = app.state();
+    Ok(cfg.idle_threshold_secs.load(Ordering::Relaxed))
+}
+
+/// 设置空闲阈值（秒）；0 表示关闭空闲检测
+pub fn set_idle_threshold(app: tauri::AppHandle, secs: u64) -> Result<(), String> {
+    if secs != 0 && secs < 30 { return Err("idle threshold must be 0 (disabled) or at least 30 seconds".into()); }
+    let cfg: tauri::State<TrackerConfig> = app.state();
+    cfg.idle_threshold_secs.store(secs, Ordering::Relaxed);
+    save_config(&app)
+}
+
+/// 暂停是否仍然有效；到期后自动清除
+fn is_paused(cfg: &TrackerConfig, now: DateTime<Utc>) -> bool {
+    let until = cfg.paused_until.load(Ordering::Relaxed);
+    if until == 0 { return false; }
+    if now.timestamp() < until { return true; }
+    cfg.paused_until.store(0, Ordering::Relaxed);
+    false
+}
+
+/// 暂停记录；`minutes` 为 None 时直到调用 resume_tracking 为止
+pub fn pause_tracking(app: tauri::AppHandle, minutes: Option<u64>) -> Result<PauseStatus, String> {
+    let until = match minutes {
+        Some(0) => return Err("minutes must be greater than 0".into()),
+        Some(m) => Utc::now().timestamp().saturating_add((m as i64).saturating_mul(60)),
+        None => i64::MAX,
+    };
+    let cfg: tauri::State<TrackerConfig> = app.state();
+    cfg.paused_until.store(until, Ordering::Relaxed);
+    save_config(&app)?;
+    get_pause_status(app)
+}
+
+pub fn resume_tracking(app: tauri::AppHandle) -> Result<(), String> {
+    let cfg: tauri::State<TrackerConfig> = app.state();
+    cfg.paused_until.store(0, Ordering::Relaxed);
+    save_config(&app)
+}
+
+pub fn get_pause_status(app: tauri::AppHandle) -> Result<PauseStatus, String> {
+    let cfg: tauri::State<TrackerConfig> = app.state();
+    if !is_paused(&cfg, Utc::now()) {
+        return Ok(PauseStatus { paused: false, until: None });
     }
+    let until = cfg.paused_until.load(Ordering::Relaxed);
+    let until = if until == i64::MAX { None } else { DateTime::<Utc>::from_timestamp(until, 0).map(|t| t.to_rfc3339()) };
+    Ok(PauseStatus { paused: true, until })
+}
+
+fn get_active_window_info_internal() -> Result<CurrentActivity, String> {
+    COMMAND_SOURCE.lock().map_err(|e| e.to_string())?.current()
 }
 
 pub fn get_active_window_info() -> Result<ActiveWindowInfo, String> {
@@ -88,3 +343,130 @@ pub fn get_active_window_info() -> Result<ActiveWindowInfo, String> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idle_source::{IdleTimeSource, ScriptedIdleSource};
+    use crate::window_source::ScriptedWindowSource;
+
+    const STEP_SECS: i64 = 2;
+
+    fn activity(app: &str) -> Option<CurrentActivity> {
+        Some(CurrentActivity { app_name: app.into(), window_title: format!("{} - 标题", app) })
+    }
+
+    /// 与 run_tracker_loop 相同的采样方式回放脚本，每次采样推进 STEP_SECS
+    fn replay(
+        conn: &Connection,
+        recorder: &mut ActivityRecorder,
+        windows: &mut ScriptedWindowSource,
+        idle: &mut ScriptedIdleSource,
+        now: &mut DateTime<Utc>,
+    ) {
+        while !windows.is_exhausted() {
+            let current = windows.current().ok();
+            let idle_for = idle.idle_duration().unwrap_or_default();
+            recorder.observe(conn, current, idle_for, *now).unwrap();
+            *now += ChronoDuration::seconds(STEP_SECS);
+        }
+    }
+
+    fn rows(conn: &Connection) -> Vec<(String, i64, bool, bool)> {
+        let mut stmt = conn
+            .prepare("SELECT app_name, duration_seconds, is_idle, is_open FROM activity_log ORDER BY id")
+            .unwrap();
+        stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect()
+    }
+
+    fn row(app: &str, secs: i64, idle: bool, open: bool) -> (String, i64, bool, bool) {
+        (app.to_string(), secs, idle, open)
+    }
+
+    #[test]
+    fn replay_switch_heartbeat_idle_and_suspend() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        let t0 = DateTime::parse_from_rfc3339("2024-05-01T09:00:00Z").unwrap().with_timezone(&Utc);
+        let mut now = t0;
+        let mut recorder = ActivityRecorder::new(t0);
+        recorder.set_idle_threshold(30);
+
+        // 同一窗口持续 60 秒：只有一行，进行中且时长由心跳刷新
+        let mut windows = ScriptedWindowSource::new((0..=30).map(|_| activity("code")));
+        let mut idle = ScriptedIdleSource::default();
+        replay(&conn, &mut recorder, &mut windows, &mut idle, &mut now);
+        assert_eq!(rows(&conn), vec![row("code", 60, false, true)]);
+
+        // 切到 chrome：t0+80 后无输入，t0+110 达到阈值，空闲段从最后一次输入算起；t0+122 恢复输入
+        let mut windows = ScriptedWindowSource::new((0..40).map(|_| activity("chrome")));
+        let idle_script = (0..10)
+            .map(|_| Duration::ZERO)
+            .chain((1..=20).map(|i| Duration::from_secs(i * STEP_SECS as u64)))
+            .chain((0..10).map(|_| Duration::ZERO));
+        let mut idle = ScriptedIdleSource::new(idle_script);
+        replay(&conn, &mut recorder, &mut windows, &mut idle, &mut now);
+        assert_eq!(
+            rows(&conn),
+            vec![
+                row("code", 62, false, false),
+                row("chrome", 18, false, false),
+                row(IDLE_APP_NAME, 42, true, false),
+                // 进行中的行只反映最近一次心跳（t0+132）
+                row("chrome", 10, false, true),
+            ]
+        );
+
+        // 挂起一小时：进行中的分段在最后一次采样（t0+140）时结束
+        now += ChronoDuration::seconds(3600);
+        let mut windows = ScriptedWindowSource::new([activity("code")]);
+        replay(&conn, &mut recorder, &mut windows, &mut idle, &mut now);
+        recorder.finish(&conn, now + ChronoDuration::seconds(8)).unwrap();
+        assert_eq!(
+            rows(&conn),
+            vec![
+                row("code", 62, false, false),
+                row("chrome", 18, false, false),
+                row(IDLE_APP_NAME, 42, true, false),
+                row("chrome", 18, false, false),
+                row("code", 10, false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_samples_close_the_segment() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        let t0 = DateTime::parse_from_rfc3339("2024-05-01T09:00:00Z").unwrap().with_timezone(&Utc);
+        let mut now = t0;
+        let mut recorder = ActivityRecorder::new(t0);
+
+        // 采样失败视为没有前台窗口：分段结束，恢复后另起一行
+        let script = (0..5).map(|_| activity("code")).chain([None]).chain((0..3).map(|_| activity("code")));
+        let mut windows = ScriptedWindowSource::new(script);
+        let mut idle = ScriptedIdleSource::default();
+        replay(&conn, &mut recorder, &mut windows, &mut idle, &mut now);
+        recorder.finish(&conn, now).unwrap();
+        assert_eq!(rows(&conn), vec![row("code", 10, false, false), row("code", 6, false, false)]);
+    }
+
+    #[test]
+    fn config_survives_restart() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        let cfg = TrackerConfig::load(&conn);
+        assert_eq!(cfg.idle_threshold_secs.load(Ordering::Relaxed), DEFAULT_IDLE_THRESHOLD_SECS);
+        assert_eq!(cfg.paused_until.load(Ordering::Relaxed), 0);
+
+        cfg.idle_threshold_secs.store(600, Ordering::Relaxed);
+        cfg.paused_until.store(i64::MAX, Ordering::Relaxed);
+        cfg.save(&conn).unwrap();
+        let restored = TrackerConfig::load(&conn);
+        assert_eq!(restored.idle_threshold_secs.load(Ordering::Relaxed), 600);
+        assert_eq!(restored.paused_until.load(Ordering::Relaxed), i64::MAX);
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::VecDeque;
+use std::path::Path;
+
+use crate::tracker::CurrentActivity;
+
+/// 前台窗口来源：追踪循环只依赖该 trait，不直接调用平台 API
+pub trait ActiveWindowSource {
+    /// 获取当前前台窗口对应的程序名与窗口标题
+    fn current(&mut self) -> Result<CurrentActivity, String>;
+}
+
+/// 按当前平台创建默认的前台窗口来源
+pub fn default_source() -> Box<dyn ActiveWindowSource + Send> {
+    #[cfg(target_os = "windows")]
+    {
+        Box::new(Win32WindowSource)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        match X11WindowSource::connect() {
+            Ok(source) => Box::new(source),
+            Err(e) => Box::new(UnsupportedWindowSource { reason: e }),
+        }
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        Box::new(UnsupportedWindowSource {
+            reason: "当前平台不支持前台窗口追踪".into(),
+        })
+    }
+}
+
+/// 从可执行文件路径中取出文件名作为程序名
+fn app_name_from_exe(exe_path: &str) -> String {
+    Path::new(exe_path)
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+// ==================== Windows ====================
+
+/// Win32 实现：GetForegroundWindow + K32GetModuleFileNameExW
+#[cfg(target_os = "windows")]
+pub struct Win32WindowSource;
+
+#[cfg(target_os = "windows")]
+impl ActiveWindowSource for Win32WindowSource {
+    fn current(&mut self) -> Result<CurrentActivity, String> {
+        use windows::{
+            Win32::Foundation::*, Win32::System::ProcessStatus::*, Win32::System::Threading::*,
+            Win32::UI::WindowsAndMessaging::*,
+        };
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.is_invalid() { return Err("无法获取前台窗口".into()); }
+            let mut title_buffer = [0u16; 512];
+            let len = GetWindowTextW(hwnd, &mut title_buffer);
+            let window_title = String::from_utf16_lossy(&title_buffer[..len as usize]);
+            let mut process_id: u32 = 0;
+            GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+            if process_id == 0 { return Err("无法获取进程ID".into()); }
+            let process_handle = OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, false, process_id)
+                .map_err(|e| format!("无法打开进程: {:?}", e))?;
+            let mut exe_path_buffer = [0u16; 1024];
+            let exe_len = K32GetModuleFileNameExW(Some(process_handle), None, &mut exe_path_buffer);
+            let _ = CloseHandle(process_handle);
+            if exe_len == 0 { return Err("无法获取模块文件名".into()); }
+            let exe_path_full = String::from_utf16_lossy(&exe_path_buffer[..exe_len as usize]);
+            Ok(CurrentActivity { app_name: app_name_from_exe(&exe_path_full), window_title })
+        }
+    }
+}
+
+// ==================== Linux (X11) ====================
+
+/// Linux 实现：读取根窗口的 _NET_ACTIVE_WINDOW，再通过 _NET_WM_PID 找到 /proc/<pid>/exe
+///
+/// Wayland 会话下只能看到经由 XWayland 运行的窗口。
+#[cfg(target_os = "linux")]
+pub struct X11WindowSource {
+    conn: x11rb::rust_connection::RustConnection,
+    root: u32,
+    net_active_window: u32,
+    net_wm_pid: u32,
+    net_wm_name: u32,
+    utf8_string: u32,
+}
+
+#[cfg(target_os = "linux")]
+impl X11WindowSource {
+    pub fn connect() -> Result<Self, String> {
+        use x11rb::connection::Connection as _;
+        use x11rb::protocol::xproto::ConnectionExt as _;
+
+        let (conn, screen_num) = x11rb::connect(None).map_err(|e| format!("无法连接 X11: {e}"))?;
+        let root = conn.setup().roots[screen_num].root;
+        let intern = |name: &[u8]| -> Result<u32, String> {
+            conn.intern_atom(false, name)
+                .map_err(|e| format!("intern_atom 失败: {e}"))?
+                .reply()
+                .map(|r| r.atom)
+                .map_err(|e| format!("intern_atom 失败: {e}"))
+        };
+        let net_active_window = intern(b"_NET_ACTIVE_WINDOW")?;
+        let net_wm_pid = intern(b"_NET_WM_PID")?;
+        let net_wm_name = intern(b"_NET_WM_NAME")?;
+        let utf8_string = intern(b"UTF8_STRING")?;
+        Ok(Self { conn, root, net_active_window, net_wm_pid, net_wm_name, utf8_string })
+    }
+
+    fn property(
+        &self,
+        window: u32,
+        property: u32,
+        type_: u32,
+        long_length: u32,
+    ) -> Result<x11rb::protocol::xproto::GetPropertyReply, String> {
+        use x11rb::protocol::xproto::ConnectionExt as _;
+        self.conn
+            .get_property(false, window, property, type_, 0, long_length)
+            .map_err(|e| format!("get_property 失败: {e}"))?
+            .reply()
+            .map_err(|e| format!("get_property 失败: {e}"))
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl ActiveWindowSource for X11WindowSource {
+    fn current(&mut self) -> Result<CurrentActivity, String> {
+        use x11rb::protocol::xproto::AtomEnum;
+
+        let active = self.property(self.root, self.net_active_window, AtomEnum::WINDOW.into(), 1)?;
+        let window = active
+            .value32()
+            .and_then(|mut v| v.next())
+            .filter(|w| *w != 0)
+            .ok_or("无法获取前台窗口")?;
+
+        // 优先使用 UTF-8 的 _NET_WM_NAME，缺失时回退到 WM_NAME
+        let mut title = self.property(window, self.net_wm_name, self.utf8_string, 1024)?.value;
+        if title.is_empty() {
+            title = self.property(window, AtomEnum::WM_NAME.into(), AtomEnum::STRING.into(), 1024)?.value;
+        }
+        let window_title = String::from_utf8_lossy(&title).into_owned();
+
+        let pid = self
+            .property(window, self.net_wm_pid, AtomEnum::CARDINAL.into(), 1)?
+            .value32()
+            .and_then(|mut v| v.next())
+            .filter(|p| *p != 0)
+            .ok_or("无法获取进程ID")?;
+        let exe_path = std::fs::read_link(format!("/proc/{}/exe", pid))
+            .map_err(|e| format!("无法读取 /proc/{}/exe: {}", pid, e))?;
+
+        Ok(CurrentActivity {
+            app_name: app_name_from_exe(&exe_path.to_string_lossy()),
+            window_title,
+        })
+    }
+}
+
+// ==================== 其他 ====================
+
+/// 不可用的来源：始终返回初始化时的错误原因
+pub struct UnsupportedWindowSource {
+    pub reason: String,
+}
+
+impl ActiveWindowSource for UnsupportedWindowSource {
+    fn current(&mut self) -> Result<CurrentActivity, String> {
+        Err(self.reason.clone())
+    }
+}
+
+/// 脚本化来源：按顺序回放预设的窗口序列，`None` 表示该次采样失败
+///
+/// 序列耗尽后持续返回错误，便于在不依赖桌面环境的情况下回放追踪循环。
+#[derive(Default)]
+pub struct ScriptedWindowSource {
+    script: VecDeque<Option<CurrentActivity>>,
+}
+
+impl ScriptedWindowSource {
+    pub fn new<I>(script: I) -> Self
+    where
+        I: IntoIterator<Item = Option<CurrentActivity>>,
+    {
+        Self { script: script.into_iter().collect() }
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.script.is_empty()
+    }
+}
+
+impl ActiveWindowSource for ScriptedWindowSource {
+    fn current(&mut self) -> Result<CurrentActivity, String> {
+        match self.script.pop_front() {
+            Some(Some(activity)) => Ok(activity),
+            Some(None) => Err("脚本: 无前台窗口".into()),
+            None => Err("脚本已结束".into()),
+        }
+    }
+}
@@ -0,0 +1,378 @@
+use encoding_rs::Encoding;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// 每隔多少个数据行记录一次字节偏移；定位任意行最多顺序解析 STRIDE - 1 行
+pub const CHECKPOINT_STRIDE: usize = 256;
+
+/// 用于探测编码与分隔符的文件开头长度
+const SAMPLE_BYTES: usize = 64 * 1024;
+const READ_BUFFER_BYTES: usize = 1 << 20;
+/// 建索引时每扫描这么多字节回调一次进度
+const PROGRESS_BYTES: u64 = 64 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QuoteState {
+    Unquoted,
+    Quoted,
+    /// 引号字段内读到一个 `"`：后面再跟 `"` 是转义，否则字段结束
+    QuoteInQuoted,
+}
+
+/// 按与 csv crate 相同的规则识别记录边界：
+/// 只有字段开头的 `"` 开启引号，引号内的换行不算行尾；`\r`、`\n`、`\r\n` 均为行终止符，空行被忽略
+struct RowScanner {
+    delimiter: u8,
+    state: QuoteState,
+    at_line_start: bool,
+    field_start: bool,
+}
+
+impl RowScanner {
+    fn new(delimiter: u8) -> Self {
+        RowScanner { delimiter, state: QuoteState::Unquoted, at_line_start: true, field_start: true }
+    }
+
+    /// 处理一个字节；该字节是一条新记录的第一个字节时返回 true
+    fn feed(&mut self, b: u8) -> bool {
+        if self.at_line_start {
+            if b == b'\r' || b == b'\n' { return false; }
+            self.at_line_start = false;
+            self.field_start = true;
+            self.state = QuoteState::Unquoted;
+            self.consume(b);
+            return true;
+        }
+        self.consume(b);
+        false
+    }
+
+    fn consume(&mut self, b: u8) {
+        match self.state {
+            QuoteState::Quoted => {
+                if b == b'"' { self.state = QuoteState::QuoteInQuoted; }
+            }
+            QuoteState::QuoteInQuoted => {
+                if b == b'"' {
+                    self.state = QuoteState::Quoted;
+                } else {
+                    self.state = QuoteState::Unquoted;
+                    self.unquoted(b);
+                }
+            }
+            QuoteState::Unquoted => self.unquoted(b),
+        }
+    }
+
+    fn unquoted(&mut self, b: u8) {
+        if b == b'"' && self.field_start {
+            self.state = QuoteState::Quoted;
+            self.field_start = false;
+            return;
+        }
+        self.field_start = b == self.delimiter;
+        if b == b'\n' || b == b'\r' { self.at_line_start = true; }
+    }
+}
+
+/// 根据 BOM 与样本内容判断编码。
+/// UTF-8 优先；否则依次尝试 GB18030（兼容 GBK，国内 Excel 导出的常见编码）与 Windows-1252。
+/// 以上编码都与 ASCII 兼容，分隔符、引号、换行可以直接按字节识别
+fn detect_encoding(sample: &[u8], truncated: bool) -> Result<(&'static Encoding, usize), String> {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(sample) {
+        if encoding == encoding_rs::UTF_8 {
+            return Ok((encoding, bom_len));
+        }
+        return Err(format!("{} 编码的 CSV 暂不支持，请先转换为 UTF-8", encoding.name()));
+    }
+    match std::str::from_utf8(sample) {
+        Ok(_) => return Ok((encoding_rs::UTF_8, 0)),
+        // 样本在多字节字符中间截断时，只要错误出现在末尾就仍视为 UTF-8
+        Err(e) if truncated && e.error_len().is_none() => return Ok((encoding_rs::UTF_8, 0)),
+        Err(_) => {}
+    }
+    let body = if truncated { &sample[..sample.len().saturating_sub(4)] } else { sample };
+    if encoding_rs::GB18030.decode_without_bom_handling_and_without_replacement(body).is_some() {
+        return Ok((encoding_rs::GB18030, 0));
+    }
+    Ok((encoding_rs::WINDOWS_1252, 0))
+}
+
+/// 按首个非空行中各候选分隔符的出现次数检测分隔符
+pub fn detect_delimiter(content: &str) -> char {
+    let first_line = content.lines().find(|l| !l.trim().is_empty()).unwrap_or(content);
+
+    let candidates = [(',', first_line.matches(',').count()),
+                      ('\t', first_line.matches('\t').count()),
+                      (';', first_line.matches(';').count()),
+                      ('|', first_line.matches('|').count())];
+
+    candidates.iter()
+        .max_by_key(|(_, count)| count)
+        .map(|(delim, _)| *delim)
+        .unwrap_or(',')
+}
+
+/// 基于文件的 CSV 读取器：打开时扫描一遍建立行偏移索引，之后按需从磁盘读取任意行区间
+#[derive(Debug)]
+pub struct CsvIndex {
+    path: PathBuf,
+    delimiter: u8,
+    encoding: &'static Encoding,
+    headers: Vec<String>,
+    /// 第 i * CHECKPOINT_STRIDE 个数据行（不含表头）的起始字节偏移
+    checkpoints: Vec<u64>,
+    total_rows: usize,
+    file_len: u64,
+}
+
+impl CsvIndex {
+    /// 扫描文件建立索引；delimiter 为 None 时自动检测。progress(已扫描字节, 文件总字节)
+    pub fn build(path: &Path, delimiter: Option<char>, progress: &dyn Fn(u64, u64)) -> Result<Self, String> {
+        let mut file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+        let file_len = file.metadata().map_err(|e| e.to_string())?.len();
+
+        let mut sample = Vec::with_capacity(SAMPLE_BYTES);
+        (&mut file).take(SAMPLE_BYTES as u64).read_to_end(&mut sample).map_err(|e| e.to_string())?;
+        let (encoding, bom_len) = detect_encoding(&sample, (sample.len() as u64) < file_len)?;
+        let delimiter = match delimiter {
+            Some(d) if d.is_ascii() => d as u8,
+            Some(d) => return Err(format!("分隔符必须是 ASCII 字符: '{}'", d)),
+            None => detect_delimiter(&encoding.decode_without_bom_handling(&sample[bom_len..]).0) as u8,
+        };
+
+        file.seek(SeekFrom::Start(bom_len as u64)).map_err(|e| e.to_string())?;
+        let mut reader = BufReader::with_capacity(READ_BUFFER_BYTES, file);
+        let mut scanner = RowScanner::new(delimiter);
+        let mut buf = vec![0u8; READ_BUFFER_BYTES];
+        let mut pos = bom_len as u64;
+        let mut header_offset: Option<u64> = None;
+        let mut checkpoints = Vec::new();
+        let mut rows = 0usize;
+        let mut next_progress = PROGRESS_BYTES;
+        progress(0, file_len);
+        loop {
+            let n = reader.read(&mut buf).map_err(|e| format!("Failed to read file: {}", e))?;
+            if n == 0 { break; }
+            for (i, &b) in buf[..n].iter().enumerate() {
+                if !scanner.feed(b) { continue; }
+                let offset = pos + i as u64;
+                if header_offset.is_none() {
+                    header_offset = Some(offset);
+                    continue;
+                }
+                if rows.is_multiple_of(CHECKPOINT_STRIDE) { checkpoints.push(offset); }
+                rows += 1;
+            }
+            pos += n as u64;
+            if pos >= next_progress {
+                progress(pos, file_len);
+                next_progress = pos + PROGRESS_BYTES;
+            }
+        }
+        progress(file_len, file_len);
+
+        let mut index = CsvIndex {
+            path: path.to_path_buf(),
+            delimiter,
+            encoding,
+            headers: Vec::new(),
+            checkpoints,
+            total_rows: rows,
+            file_len,
+        };
+        if let Some(offset) = header_offset {
+            let mut reader = index.reader_at(offset)?;
+            let mut record = csv::ByteRecord::new();
+            reader.read_byte_record(&mut record).map_err(|e| format!("Failed to read headers: {}", e))?;
+            index.headers = record.iter().map(|f| index.decode(f)).collect();
+        }
+        Ok(index)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn delimiter(&self) -> char {
+        self.delimiter as char
+    }
+
+    pub fn encoding_name(&self) -> &'static str {
+        self.encoding.name()
+    }
+
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    /// 数据行数（不含表头）
+    pub fn total_rows(&self) -> usize {
+        self.total_rows
+    }
+
+    pub fn file_len(&self) -> u64 {
+        self.file_len
+    }
+
+    /// 按文件编码解码一个字段；无法解码的字节以 U+FFFD 代替
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        self.encoding.decode_without_bom_handling(bytes).0.into_owned()
+    }
+
+    fn reader_at(&self, offset: u64) -> Result<csv::Reader<BufReader<File>>, String> {
+        let mut file = File::open(&self.path).map_err(|e| format!("Failed to open file: {}", e))?;
+        file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+        Ok(csv::ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(BufReader::with_capacity(READ_BUFFER_BYTES, file)))
+    }
+
+    /// 依次访问数据行 [start, end)；f 返回 false 时提前结束。
+    /// 字段数与表头不一致的行不交给 f，计入返回的跳过行数
+    pub fn scan(&self, start: usize, end: usize, mut f: impl FnMut(usize, &csv::ByteRecord) -> bool) -> Result<usize, String> {
+        let end = end.min(self.total_rows);
+        if start >= end { return Ok(0); }
+        let checkpoint = start / CHECKPOINT_STRIDE;
+        let mut reader = self.reader_at(self.checkpoints[checkpoint])?;
+        let mut record = csv::ByteRecord::new();
+        let mut row = checkpoint * CHECKPOINT_STRIDE;
+        let mut skipped = 0usize;
+        while row < end {
+            match reader.read_byte_record(&mut record) {
+                Ok(false) => break,
+                Ok(true) => {}
+                Err(e) => return Err(format!("Failed to read row {}: {}", row, e)),
+            }
+            if row >= start {
+                if record.len() != self.headers.len() {
+                    skipped += 1;
+                } else if !f(row, &record) {
+                    break;
+                }
+            }
+            row += 1;
+        }
+        Ok(skipped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_file(name: &str, bytes: &[u8]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("csv_index_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    fn build(name: &str, bytes: &[u8]) -> CsvIndex {
+        CsvIndex::build(&write_file(name, bytes), None, &|_, _| {}).unwrap()
+    }
+
+    /// [start, end) 内的行，字段按文件编码解码
+    fn rows(index: &CsvIndex, start: usize, end: usize) -> Vec<(usize, Vec<String>)> {
+        let mut out = Vec::new();
+        index
+            .scan(start, end, |i, record| {
+                out.push((i, record.iter().map(|f| index.decode(f)).collect()));
+                true
+            })
+            .unwrap();
+        out
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn quoted_fields_keep_newlines_and_escaped_quotes() {
+        let text = "name,note\r\n1,\"lf\nline\"\r\n2,\"crlf\r\nline\"\r\n3,\"say \"\"hi\"\"\"\n4,\"\"\"\"\"\nx\"\n5,plain\n";
+        let index = build("quoted.csv", text.as_bytes());
+        assert_eq!(index.headers(), strings(&["name", "note"]));
+        assert_eq!(index.total_rows(), 5);
+        let notes: Vec<String> = rows(&index, 0, 5).into_iter().map(|(_, r)| r[1].clone()).collect();
+        assert_eq!(notes, strings(&["lf\nline", "crlf\r\nline", "say \"hi\"", "\"\"\nx", "plain"]));
+    }
+
+    #[test]
+    fn blank_lines_and_bom_are_skipped() {
+        let text = "\u{FEFF}\r\na;b\n\n1;2\r\n\r\n\r\n3;4\r\n\n";
+        let index = build("bom.csv", text.as_bytes());
+        assert_eq!(index.encoding_name(), "UTF-8");
+        assert_eq!(index.delimiter(), ';');
+        assert_eq!(index.headers(), strings(&["a", "b"]));
+        assert_eq!(index.total_rows(), 2);
+        assert_eq!(rows(&index, 0, 10), vec![(0, strings(&["1", "2"])), (1, strings(&["3", "4"]))]);
+
+        let utf16 = write_file("utf16.csv", b"\xFF\xFEa\x00,\x00b\x00");
+        assert!(CsvIndex::build(&utf16, None, &|_, _| {}).is_err());
+    }
+
+    #[test]
+    fn detects_legacy_encodings() {
+        let (gbk, _, _) = encoding_rs::GB18030.encode("姓名,城市\n张三,北京\n李四,\"上海\n浦东\"\n");
+        let index = build("gbk.csv", &gbk);
+        assert_eq!(index.encoding_name(), "gb18030");
+        assert_eq!(index.headers(), strings(&["姓名", "城市"]));
+        assert_eq!(
+            rows(&index, 0, 2),
+            vec![(0, strings(&["张三", "北京"])), (1, strings(&["李四", "上海\n浦东"]))]
+        );
+
+        let (latin, _, _) = encoding_rs::WINDOWS_1252.encode("café,prix\nrésumé,5€\n");
+        let index = build("latin.csv", &latin);
+        assert_eq!(index.encoding_name(), "windows-1252");
+        assert_eq!(index.headers(), strings(&["café", "prix"]));
+        assert_eq!(rows(&index, 0, 1), vec![(0, strings(&["résumé", "5€"]))]);
+    }
+
+    #[test]
+    fn scan_starts_between_checkpoints() {
+        let total = CHECKPOINT_STRIDE * 3 + 100;
+        let mut text = String::from("id,value\n");
+        for i in 0..total {
+            if i == 600 {
+                // 字段数不一致的行
+                text.push_str("600\n");
+            } else if i % 7 == 0 {
+                // 引号内换行，使行号与物理行不一致
+                text.push_str(&format!("{},\"multi\nline {}\"\n", i, i));
+            } else {
+                text.push_str(&format!("{},v{}\n", i, i));
+            }
+        }
+        let index = build("large.csv", text.as_bytes());
+        assert_eq!(index.total_rows(), total);
+        assert_eq!(index.checkpoints.len(), 4);
+
+        // 每行的第一个字段就是行号
+        let ids = |start: usize, end: usize| -> Vec<usize> {
+            let rows = rows(&index, start, end);
+            assert!(rows.iter().all(|(i, r)| r[0] == i.to_string()));
+            rows.into_iter().map(|(i, _)| i).collect()
+        };
+        assert_eq!(ids(CHECKPOINT_STRIDE - 2, CHECKPOINT_STRIDE + 2), (CHECKPOINT_STRIDE - 2..CHECKPOINT_STRIDE + 2).collect::<Vec<_>>());
+        assert_eq!(ids(total - 3, total + 10), (total - 3..total).collect::<Vec<_>>());
+        assert!(ids(total, total + 1).is_empty());
+
+        let row_700 = &rows(&index, 700, 701)[0].1;
+        assert_eq!(row_700[1], "multi\nline 700");
+
+        // 字段数不一致的行计入跳过数；f 返回 false 时提前结束
+        let mut seen = Vec::new();
+        let skipped = index.scan(598, 610, |i, _| {
+            seen.push(i);
+            i < 602
+        });
+        assert_eq!(skipped, Ok(1));
+        assert_eq!(seen, vec![598, 599, 601, 602]);
+    }
+}
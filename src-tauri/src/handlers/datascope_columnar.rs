@@ -0,0 +1,269 @@
+// Datascope 列式分页格式：按列存放的定长数组，经 tauri::ipc::Response 以原始字节传给前端，
+// 避免逐行 HashMap<String, Value> 的 JSON 序列化（列名在每行重复、逐值装箱）。
+//
+// 二进制布局（小端）：
+//   b"DSCP" | u32 版本 | u32 头部长度 | 头部 JSON（补空格至 8 字节对齐）| 数据区
+// 头部 JSON 描述行数与每列的类型及缓冲区位置（相对数据区起点的字节偏移）。
+// 数据区中每段缓冲区都按 8 字节对齐，前端可直接在同一个 ArrayBuffer 上构造
+// Float64Array / Uint8Array / Uint32Array 视图而无需拷贝。
+
+use serde::Serialize;
+
+const MAGIC: &[u8; 4] = b"DSCP";
+const VERSION: u32 = 1;
+const ALIGN: usize = 8;
+
+/// 一列的数据
+pub enum ColumnValues {
+    /// 数值列统一为 f64；空值位置为 NaN，并由 validity 标记
+    F64(Vec<f64>),
+    /// 每行 1 字节，0/1
+    Bool(Vec<u8>),
+    /// UTF-8 字符串：第 i 行为 data[offsets[i]..offsets[i + 1]]
+    Utf8 { offsets: Vec<u32>, data: Vec<u8> },
+}
+
+pub struct ColumnarColumn {
+    pub name: String,
+    pub values: ColumnValues,
+    /// 每行 1 字节，1 表示有值；整列都有值时为 None
+    pub validity: Option<Vec<u8>>,
+}
+
+pub struct ColumnarPage {
+    pub row_count: usize,
+    pub skipped_rows: usize,
+    pub columns: Vec<ColumnarColumn>,
+}
+
+/// 逐行追加字符串的列构建器
+pub struct Utf8Builder {
+    offsets: Vec<u32>,
+    data: Vec<u8>,
+    validity: Vec<u8>,
+    has_null: bool,
+}
+
+impl Utf8Builder {
+    pub fn with_capacity(rows: usize) -> Self {
+        let mut offsets = Vec::with_capacity(rows + 1);
+        offsets.push(0);
+        Utf8Builder {
+            offsets,
+            data: Vec::new(),
+            validity: Vec::with_capacity(rows),
+            has_null: false,
+        }
+    }
+
+    pub fn push(&mut self, value: &str) {
+        self.data.extend_from_slice(value.as_bytes());
+        self.offsets.push(self.data.len() as u32);
+        self.validity.push(1);
+    }
+
+    pub fn push_null(&mut self) {
+        self.offsets.push(self.data.len() as u32);
+        self.validity.push(0);
+        self.has_null = true;
+    }
+
+    /// 字符串总长超过 u32 偏移范围时报错
+    pub fn finish(self, name: String) -> Result<ColumnarColumn, String> {
+        if self.data.len() > u32::MAX as usize {
+            return Err(format!("Column {} is too large for a columnar page", name));
+        }
+        Ok(ColumnarColumn {
+            name,
+            values: ColumnValues::Utf8 {
+                offsets: self.offsets,
+                data: self.data,
+            },
+            validity: self.has_null.then_some(self.validity),
+        })
+    }
+}
+
+/// 缓冲区在数据区中的位置
+#[derive(Serialize)]
+struct BufferRef {
+    offset: usize,
+    len: usize,
+}
+
+#[derive(Serialize)]
+struct ColumnHeader<'a> {
+    name: &'a str,
+    /// "f64" | "bool" | "utf8"
+    kind: &'static str,
+    values: BufferRef,
+    /// 仅 utf8：n + 1 个 u32
+    offsets: Option<BufferRef>,
+    validity: Option<BufferRef>,
+}
+
+#[derive(Serialize)]
+struct PageHeader<'a> {
+    row_count: usize,
+    skipped_rows: usize,
+    columns: Vec<ColumnHeader<'a>>,
+}
+
+/// 依次排布缓冲区，每段起点按 8 字节对齐
+struct BodyWriter {
+    body: Vec<u8>,
+}
+
+impl BodyWriter {
+    fn push_with(&mut self, write: impl FnOnce(&mut Vec<u8>)) -> BufferRef {
+        let offset = self.body.len();
+        write(&mut self.body);
+        let len = self.body.len() - offset;
+        self.body.resize(self.body.len().next_multiple_of(ALIGN), 0);
+        BufferRef { offset, len }
+    }
+
+    fn push(&mut self, bytes: &[u8]) -> BufferRef {
+        self.push_with(|body| body.extend_from_slice(bytes))
+    }
+}
+
+impl ColumnarPage {
+    /// 编码为二进制消息
+    pub fn encode(&self) -> Result<Vec<u8>, String> {
+        let body_len: usize = self
+            .columns
+            .iter()
+            .map(|c| {
+                let values = match &c.values {
+                    ColumnValues::F64(v) => v.len() * 8,
+                    ColumnValues::Bool(v) => v.len(),
+                    ColumnValues::Utf8 { offsets, data } => offsets.len() * 4 + data.len() + ALIGN,
+                };
+                values + c.validity.as_ref().map_or(0, Vec::len) + 2 * ALIGN
+            })
+            .sum();
+        let mut writer = BodyWriter {
+            body: Vec::with_capacity(body_len),
+        };
+
+        let mut columns = Vec::with_capacity(self.columns.len());
+        for column in &self.columns {
+            let (kind, values, offsets) = match &column.values {
+                ColumnValues::F64(v) => {
+                    let values = writer.push_with(|body| v.iter().for_each(|x| body.extend_from_slice(&x.to_le_bytes())));
+                    ("f64", values, None)
+                }
+                ColumnValues::Bool(v) => ("bool", writer.push(v), None),
+                ColumnValues::Utf8 { offsets, data } => {
+                    let offsets =
+                        writer.push_with(|body| offsets.iter().for_each(|x| body.extend_from_slice(&x.to_le_bytes())));
+                    ("utf8", writer.push(data), Some(offsets))
+                }
+            };
+            let validity = column.validity.as_ref().map(|v| writer.push(v));
+            columns.push(ColumnHeader {
+                name: &column.name,
+                kind,
+                values,
+                offsets,
+                validity,
+            });
+        }
+
+        let header = PageHeader {
+            row_count: self.row_count,
+            skipped_rows: self.skipped_rows,
+            columns,
+        };
+        let mut json = serde_json::to_vec(&header).map_err(|e| e.to_string())?;
+        // 头部补空格，使数据区起点对齐到 8 字节
+        json.resize((12 + json.len()).next_multiple_of(ALIGN) - 12, b' ');
+
+        let mut out = Vec::with_capacity(12 + json.len() + writer.body.len());
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        out.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        out.extend_from_slice(&json);
+        out.extend_from_slice(&writer.body);
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    /// 按头部描述取出一段缓冲区，并检查它在整个消息中 8 字节对齐
+    fn buffer<'a>(bytes: &'a [u8], base: usize, r: &Value) -> &'a [u8] {
+        let offset = r["offset"].as_u64().unwrap() as usize;
+        let len = r["len"].as_u64().unwrap() as usize;
+        assert_eq!((base + offset) % ALIGN, 0, "buffer at {} is not aligned", offset);
+        &bytes[base + offset..base + offset + len]
+    }
+
+    #[test]
+    fn encode_lays_out_aligned_buffers() {
+        let mut names = Utf8Builder::with_capacity(3);
+        names.push("a");
+        names.push_null();
+        names.push("héllo");
+        let mut all_present = Utf8Builder::with_capacity(1);
+        all_present.push("x");
+        let page = ColumnarPage {
+            row_count: 3,
+            skipped_rows: 2,
+            columns: vec![
+                ColumnarColumn { name: "v".into(), values: ColumnValues::F64(vec![1.5, f64::NAN, -2.0]), validity: Some(vec![1, 0, 1]) },
+                ColumnarColumn { name: "flag".into(), values: ColumnValues::Bool(vec![1, 0, 1]), validity: None },
+                names.finish("name".into()).unwrap(),
+            ],
+        };
+        assert!(all_present.finish("x".into()).unwrap().validity.is_none());
+
+        let bytes = page.encode().unwrap();
+        assert_eq!(&bytes[0..4], b"DSCP");
+        assert_eq!(u32_at(&bytes, 4), VERSION);
+        let header_len = u32_at(&bytes, 8) as usize;
+        let base = 12 + header_len;
+        assert_eq!(base % ALIGN, 0);
+        // 头部 JSON 之后只有补齐用的空格
+        let header_bytes = &bytes[12..base];
+        let json_end = header_bytes.iter().rposition(|&b| b != b' ').unwrap() + 1;
+        assert!(base - 12 - json_end < ALIGN);
+        let header: Value = serde_json::from_slice(&header_bytes[..json_end]).unwrap();
+        assert_eq!(header["row_count"], 3);
+        assert_eq!(header["skipped_rows"], 2);
+
+        let columns = header["columns"].as_array().unwrap();
+        let kinds: Vec<&str> = columns.iter().map(|c| c["kind"].as_str().unwrap()).collect();
+        assert_eq!(kinds, vec!["f64", "bool", "utf8"]);
+
+        let v = &columns[0];
+        let values: Vec<f64> =
+            buffer(&bytes, base, &v["values"]).chunks_exact(8).map(|b| f64::from_le_bytes(b.try_into().unwrap())).collect();
+        assert_eq!((values[0], values[2]), (1.5, -2.0));
+        assert!(values[1].is_nan());
+        assert_eq!(buffer(&bytes, base, &v["validity"]), &[1, 0, 1]);
+        assert!(v["offsets"].is_null());
+
+        let flag = &columns[1];
+        assert_eq!(buffer(&bytes, base, &flag["values"]), &[1, 0, 1]);
+        assert!(flag["validity"].is_null());
+
+        let name = &columns[2];
+        let offsets: Vec<u32> = buffer(&bytes, base, &name["offsets"]).chunks_exact(4).map(|b| u32_at(b, 0)).collect();
+        assert_eq!(offsets, vec![0, 1, 1, 7]);
+        let data = buffer(&bytes, base, &name["values"]);
+        assert_eq!(std::str::from_utf8(&data[offsets[2] as usize..offsets[3] as usize]).unwrap(), "héllo");
+        assert_eq!(buffer(&bytes, base, &name["validity"]), &[1, 0, 1]);
+
+        // 最后一段缓冲区之后只有补齐，消息总长为 8 的倍数
+        assert_eq!(bytes.len() % ALIGN, 0);
+    }
+}
@@ -0,0 +1,959 @@
+use crate::csv_handler::CsvDataset;
+use crate::datascope_columnar::ColumnarPage;
+use crate::datascope_query::{DatasetQuery, QueryPage, QueryRegistry};
+use crate::datascope_stats::{self, ColumnProfile, DatasetProfile, ProfileCache, ProfileOptions};
+use crate::parquet_handler::ParquetDataset;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::mem::size_of;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::ipc::Response;
+use tauri::{AppHandle, Emitter, State};
+
+pub const ROWS_PER_PAGE: usize = 200_000;
+const THUMBNAIL_SAMPLE_SIZE: usize = 1000;
+
+/// 一行数据：列名 -> 值。CSV 的值都是字符串，Parquet 保留 JSON 类型
+pub type DatasetRow = HashMap<String, Value>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatasetFormat {
+    Csv,
+    Parquet,
+}
+
+impl DatasetFormat {
+    /// 按扩展名判断格式；未知扩展名按 CSV 处理
+    pub fn from_path(path: &Path) -> Self {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .unwrap_or_default();
+        match ext.as_str() {
+            "parquet" | "pq" => DatasetFormat::Parquet,
+            _ => DatasetFormat::Csv,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetColumn {
+    pub name: String,
+    /// polars 的类型名；CSV 不做类型推断，统一为 "str"
+    pub dtype: String,
+}
+
+/// datascope_open 等命令的返回值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetInfo {
+    pub handle: u64,
+    pub path: String,
+    pub format: DatasetFormat,
+    pub total_rows: usize,
+    pub columns: Vec<DatasetColumn>,
+    /// 仅 CSV 有分隔符与编码
+    pub delimiter: Option<char>,
+    pub encoding: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenOptions {
+    /// CSV 分隔符；None 时自动检测
+    pub delimiter: Option<char>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageInfo {
+    pub page_index: usize,
+    pub start_row: usize,
+    pub end_row: usize,
+    pub row_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaginationState {
+    pub total_rows: usize,
+    pub total_pages: usize,
+    pub current_page: usize,
+    pub pages: Vec<PageInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetPage {
+    pub headers: Vec<String>,
+    pub rows: Vec<DatasetRow>,
+    pub skipped_rows: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThumbnailPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThumbnailData {
+    pub page_index: usize,
+    pub points: Vec<ThumbnailPoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DatascopeProgress {
+    /// 打开数据集期间尚无句柄，为 None
+    pub handle: Option<u64>,
+    pub current: u64,
+    pub total: u64,
+    pub message: String,
+}
+
+/// Datascope 的数据源。新增格式时实现该 trait，并在 open_dataset 中按扩展名分派
+pub trait Dataset: Send + Sync {
+    fn format(&self) -> DatasetFormat;
+
+    fn path(&self) -> &Path;
+
+    fn columns(&self) -> Vec<DatasetColumn>;
+
+    /// 数据行数（不含表头）
+    fn row_count(&self) -> usize;
+
+    /// 没有分隔符概念的格式返回 None，此时不能修改分隔符
+    fn delimiter(&self) -> Option<char> {
+        None
+    }
+
+    fn encoding(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// 读取 [start_row, end_row) 的数据行；columns 为空表示全部列。progress(已读行数, 总行数)
+    fn page(
+        &self,
+        start_row: usize,
+        end_row: usize,
+        columns: &[String],
+        progress: &dyn Fn(u64, u64),
+    ) -> Result<DatasetPage, String>;
+
+    /// 与 page 相同的行范围，按列读取；用于二进制传输的大页面
+    fn page_columnar(
+        &self,
+        start_row: usize,
+        end_row: usize,
+        columns: &[String],
+        progress: &dyn Fn(u64, u64),
+    ) -> Result<ColumnarPage, String>;
+
+    /// 缩略图：默认读取整页，在第一个数值列上分桶采样
+    fn thumbnail(&self, page_info: &PageInfo) -> Result<ThumbnailData, String> {
+        let page = self.page(page_info.start_row, page_info.end_row, &[], &|_, _| {})?;
+        Ok(thumbnail_from_page(&page, page_info))
+    }
+
+    /// 在整个数据集上过滤/搜索/排序并返回一页结果；cancel 置位后返回 CANCELLED
+    fn query(
+        &self,
+        query: &DatasetQuery,
+        cancel: &AtomicBool,
+        progress: &dyn Fn(u64, u64),
+    ) -> Result<QueryPage<DatasetRow>, String>;
+
+    /// 逐列统计（类型、空值、不同值、数值分布、高频值）。progress 的单位由实现决定
+    fn profile(&self, options: &ProfileOptions, progress: &dyn Fn(u64, u64)) -> Result<Vec<ColumnProfile>, String>;
+}
+
+/// 按扩展名打开数据集
+pub fn open_dataset(
+    path: &Path,
+    options: &OpenOptions,
+    progress: &dyn Fn(u64, u64),
+) -> Result<Box<dyn Dataset>, String> {
+    match DatasetFormat::from_path(path) {
+        DatasetFormat::Csv => Ok(Box::new(CsvDataset::open(path, options.delimiter, progress)?)),
+        DatasetFormat::Parquet => Ok(Box::new(ParquetDataset::open(path)?)),
+    }
+}
+
+fn describe(handle: u64, dataset: &dyn Dataset) -> DatasetInfo {
+    DatasetInfo {
+        handle,
+        path: dataset.path().to_string_lossy().to_string(),
+        format: dataset.format(),
+        total_rows: dataset.row_count(),
+        columns: dataset.columns(),
+        delimiter: dataset.delimiter(),
+        encoding: dataset.encoding().map(str::to_string),
+    }
+}
+
+pub fn is_missing_numeric_token(token: &str) -> bool {
+    let t = token.trim().to_ascii_lowercase();
+    matches!(
+        t.as_str(),
+        "nan"
+            | "na"
+            | "n/a"
+            | "null"
+            | "none"
+            | "undefined"
+            | "inf"
+            | "+inf"
+            | "-inf"
+            | "infinity"
+            | "+infinity"
+            | "-infinity"
+    )
+}
+
+/// 把单元格解释为有限数值；字符串按文本解析，缺失标记与非数值返回 None
+pub fn numeric_value(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64().filter(|f| f.is_finite()),
+        Value::String(s) => {
+            let t = s.trim();
+            if t.is_empty() || is_missing_numeric_token(t) {
+                return None;
+            }
+            t.parse::<f64>().ok().filter(|f| f.is_finite())
+        }
+        _ => None,
+    }
+}
+
+/// 找到第一个数值列：非缺失值中至少 70% 可解析为数值
+pub fn find_first_numeric_column(page: &DatasetPage) -> Option<String> {
+    let sample_size = page.rows.len().min(100);
+
+    for name in &page.headers {
+        let mut non_empty = 0;
+        let mut numeric = 0;
+
+        // 不要只取开头的 100 行；开头可能全是 NaN/缺失，后续有有效值。
+        // 这里在整页范围均匀抽样。
+        for sample_index in 0..sample_size {
+            let i = if sample_size <= 1 {
+                0
+            } else {
+                (sample_index * (page.rows.len() - 1)) / (sample_size - 1)
+            };
+            match page.rows[i].get(name) {
+                Some(Value::Null) | None => {}
+                Some(Value::String(s)) if s.trim().is_empty() || is_missing_numeric_token(s) => {}
+                Some(v) => {
+                    // arrays/objects/非数值字符串计入分母但不算数值
+                    non_empty += 1;
+                    if numeric_value(v).is_some() {
+                        numeric += 1;
+                    }
+                }
+            }
+        }
+
+        if non_empty > 0 && (numeric as f64 / non_empty as f64) >= 0.7 {
+            return Some(name.clone());
+        }
+    }
+
+    None
+}
+
+/// 在第一个数值列上生成缩略图采样点
+pub fn thumbnail_from_page(page: &DatasetPage, page_info: &PageInfo) -> ThumbnailData {
+    let Some(col_name) = find_first_numeric_column(page) else {
+        return ThumbnailData {
+            page_index: page_info.page_index,
+            points: vec![],
+        };
+    };
+
+    let step = (page.rows.len() / THUMBNAIL_SAMPLE_SIZE).max(1);
+    let mut points = Vec::new();
+
+    // 不要仅用固定步长采样：当存在开头/大段 NaN 时可能导致整页采样结果为空。
+    // 改为每个采样桶内寻找第一个有效值。
+    let mut bucket_start = 0usize;
+    while bucket_start < page.rows.len() {
+        let bucket_end = (bucket_start + step).min(page.rows.len());
+        let picked = (bucket_start..bucket_end)
+            .find_map(|i| page.rows[i].get(&col_name).and_then(numeric_value).map(|y| (i, y)));
+
+        if let Some((i, y)) = picked {
+            points.push(ThumbnailPoint {
+                x: (page_info.start_row + i) as f64,
+                y,
+            });
+        }
+
+        bucket_start += step;
+    }
+
+    ThumbnailData {
+        page_index: page_info.page_index,
+        points,
+    }
+}
+
+/// 计算分页信息
+fn calculate_pagination(total_rows: usize) -> PaginationState {
+    let total_pages = total_rows.div_ceil(ROWS_PER_PAGE);
+    let pages = (0..total_pages)
+        .map(|i| {
+            let start_row = i * ROWS_PER_PAGE;
+            let end_row = (start_row + ROWS_PER_PAGE).min(total_rows);
+            PageInfo {
+                page_index: i,
+                start_row,
+                end_row,
+                row_count: end_row - start_row,
+            }
+        })
+        .collect();
+
+    PaginationState {
+        total_rows,
+        total_pages,
+        current_page: 0,
+        pages,
+    }
+}
+
+fn page_cache_key(page_index: usize, page_info: &PageInfo, columns: &[String]) -> String {
+    // NOTE: columns 空表示“全列”，否则作为列裁剪的一部分。
+    // 为避免顺序影响，排序后拼接。
+    let mut cols = columns.to_vec();
+    cols.sort();
+    let cols_key = if cols.is_empty() {
+        "*".to_string()
+    } else {
+        cols.join("|")
+    };
+
+    format!(
+        "p={page_index};s={};n={};c={cols_key}",
+        page_info.start_row, page_info.row_count
+    )
+}
+
+/// 估算 JSON 值在堆上占用的字节数（不含值本身）
+fn value_heap_bytes(value: &Value) -> usize {
+    match value {
+        Value::String(s) => s.capacity(),
+        Value::Array(items) => {
+            items.capacity() * size_of::<Value>() + items.iter().map(value_heap_bytes).sum::<usize>()
+        }
+        Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| size_of::<String>() + size_of::<Value>() + k.capacity() + value_heap_bytes(v))
+            .sum(),
+        _ => 0,
+    }
+}
+
+/// 估算一页的内存占用：均匀抽样至多 256 行后按行数外推
+fn estimate_page_bytes(page: &DatasetPage) -> usize {
+    let n = page.rows.len();
+    if n == 0 {
+        return size_of::<DatasetPage>();
+    }
+    let samples = n.min(256);
+    let sampled: usize = (0..samples)
+        .map(|i| &page.rows[i * n / samples])
+        .map(|row| {
+            // 哈希表按 7/8 负载因子分配 2 的幂个槽位，每个槽位另有 1 字节控制位
+            let buckets = (row.capacity() * 8 / 7).next_power_of_two();
+            buckets * (size_of::<String>() + size_of::<Value>() + 1)
+                + row.iter().map(|(k, v)| k.capacity() + value_heap_bytes(v)).sum::<usize>()
+        })
+        .sum();
+    size_of::<DatasetPage>() + n * size_of::<DatasetRow>() + sampled * n / samples
+}
+
+fn estimate_thumbnail_bytes(thumbnail: &ThumbnailData) -> usize {
+    size_of::<ThumbnailData>() + thumbnail.points.capacity() * size_of::<ThumbnailPoint>()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum CacheKey {
+    Page(String),
+    /// 编码后的列式页面，键与 Page 相同
+    Columnar(String),
+    Thumbnail(usize),
+}
+
+enum CacheValue {
+    Page(DatasetPage),
+    Columnar(Vec<u8>),
+    Thumbnail(ThumbnailData),
+}
+
+struct CacheEntry {
+    value: CacheValue,
+    bytes: usize,
+    last_used: u64,
+}
+
+struct OpenDataset {
+    dataset: Arc<dyn Dataset>,
+    /// 每次 replace 加一；读取前记下，写缓存时不一致说明结果来自已被替换的数据集
+    generation: u64,
+    cache: HashMap<CacheKey, CacheEntry>,
+}
+
+#[derive(Default)]
+struct RegistryState {
+    datasets: HashMap<u64, OpenDataset>,
+    /// 所有数据集缓存的估算总字节数
+    used_bytes: usize,
+    /// LRU 时钟：每次命中或写入缓存加一
+    clock: u64,
+}
+
+impl RegistryState {
+    fn entry(&mut self, handle: u64) -> Result<&mut OpenDataset, String> {
+        self.datasets
+            .get_mut(&handle)
+            .ok_or_else(|| format!("Unknown dataset handle: {}", handle))
+    }
+
+    fn lookup(&mut self, handle: u64, key: &CacheKey) -> Option<&CacheValue> {
+        self.clock += 1;
+        let clock = self.clock;
+        let entry = self.datasets.get_mut(&handle)?.cache.get_mut(key)?;
+        entry.last_used = clock;
+        Some(&entry.value)
+    }
+
+    fn store(&mut self, handle: u64, generation: u64, key: CacheKey, value: CacheValue, bytes: usize, budget: usize) {
+        // 单项就超过预算时不缓存，避免把其它数据集的缓存全部挤掉
+        if bytes > budget {
+            return;
+        }
+        self.clock += 1;
+        let clock = self.clock;
+        let Some(open) = self.datasets.get_mut(&handle) else {
+            return;
+        };
+        if open.generation != generation {
+            return;
+        }
+        let previous = open.cache.insert(key, CacheEntry { value, bytes, last_used: clock });
+        self.used_bytes = self.used_bytes + bytes - previous.map_or(0, |p| p.bytes);
+        self.evict_to(budget);
+    }
+
+    /// 在所有数据集之间按最近最少使用淘汰缓存项，直到总占用不超过预算
+    fn evict_to(&mut self, budget: usize) {
+        while self.used_bytes > budget {
+            let victim = self
+                .datasets
+                .iter()
+                .flat_map(|(handle, open)| open.cache.iter().map(move |(key, e)| (e.last_used, *handle, key)))
+                .min_by_key(|(last_used, _, _)| *last_used)
+                .map(|(_, handle, key)| (handle, key.clone()));
+            let Some((handle, key)) = victim else {
+                break;
+            };
+            if let Some(removed) = self.datasets.get_mut(&handle).and_then(|open| open.cache.remove(&key)) {
+                self.used_bytes -= removed.bytes;
+            }
+        }
+    }
+
+    fn clear_cache(&mut self, handle: u64) -> Result<(), String> {
+        let freed: usize = self.entry(handle)?.cache.drain().map(|(_, e)| e.bytes).sum();
+        self.used_bytes -= freed;
+        Ok(())
+    }
+}
+
+/// 页面/缩略图缓存的默认总预算，由所有打开的数据集共享
+pub const DEFAULT_CACHE_BUDGET_BYTES: usize = 1 << 30;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub budget_bytes: usize,
+    /// 估算值：按抽样行外推
+    pub used_bytes: usize,
+    pub cached_pages: usize,
+    pub cached_thumbnails: usize,
+    pub open_datasets: usize,
+}
+
+// 已打开数据集的注册表：命令通过 datascope_open 返回的句柄访问数据集，可同时打开多个。
+// 各数据集的页面与缩略图缓存共享一个全局内存预算，超出时按 LRU 淘汰；
+// 数据集本身（CSV 行索引、Parquet 元数据）很小，不计入预算，需 datascope_close 释放
+pub struct DatasetRegistry {
+    next_handle: AtomicU64,
+    budget_bytes: AtomicUsize,
+    state: Mutex<RegistryState>,
+}
+
+impl Default for DatasetRegistry {
+    fn default() -> Self {
+        Self {
+            next_handle: AtomicU64::new(0),
+            budget_bytes: AtomicUsize::new(DEFAULT_CACHE_BUDGET_BYTES),
+            state: Mutex::new(RegistryState::default()),
+        }
+    }
+}
+
+impl DatasetRegistry {
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, RegistryState>, String> {
+        self.state.lock().map_err(|e| e.to_string())
+    }
+
+    fn budget(&self) -> usize {
+        self.budget_bytes.load(Ordering::Relaxed)
+    }
+
+    fn insert(&self, dataset: Box<dyn Dataset>) -> Result<u64, String> {
+        let handle = self.next_handle.fetch_add(1, Ordering::Relaxed) + 1;
+        self.lock()?.datasets.insert(
+            handle,
+            OpenDataset {
+                dataset: Arc::from(dataset),
+                generation: 0,
+                cache: HashMap::new(),
+            },
+        );
+        Ok(handle)
+    }
+
+    /// 在原句柄下替换数据集（如修改分隔符后重建索引），并清空其缓存；
+    /// 替换前开始的读取完成后不会再写入缓存
+    fn replace(&self, handle: u64, dataset: Box<dyn Dataset>) -> Result<(), String> {
+        let mut state = self.lock()?;
+        state.clear_cache(handle)?;
+        let open = state.entry(handle)?;
+        open.dataset = Arc::from(dataset);
+        open.generation += 1;
+        Ok(())
+    }
+
+    /// 关闭数据集并释放其缓存；返回该句柄是否存在
+    pub fn close(&self, handle: u64) -> Result<bool, String> {
+        let mut state = self.lock()?;
+        match state.datasets.remove(&handle) {
+            Some(open) => {
+                state.used_bytes -= open.cache.values().map(|e| e.bytes).sum::<usize>();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    pub fn get(&self, handle: u64) -> Result<Arc<dyn Dataset>, String> {
+        Ok(self.lock()?.entry(handle)?.dataset.clone())
+    }
+
+    /// 同 get，并返回当前代数，供读取完成后写缓存时校验
+    fn get_versioned(&self, handle: u64) -> Result<(Arc<dyn Dataset>, u64), String> {
+        let mut state = self.lock()?;
+        let open = state.entry(handle)?;
+        Ok((open.dataset.clone(), open.generation))
+    }
+
+    /// 按句柄顺序列出所有打开的数据集
+    pub fn list(&self) -> Result<Vec<DatasetInfo>, String> {
+        let state = self.lock()?;
+        let mut open: Vec<DatasetInfo> = state
+            .datasets
+            .iter()
+            .map(|(handle, o)| describe(*handle, o.dataset.as_ref()))
+            .collect();
+        open.sort_by_key(|info| info.handle);
+        Ok(open)
+    }
+
+    fn cached_page(&self, handle: u64, key: &str) -> Option<DatasetPage> {
+        match self.lock().ok()?.lookup(handle, &CacheKey::Page(key.to_string()))? {
+            CacheValue::Page(page) => Some(page.clone()),
+            _ => None,
+        }
+    }
+
+    fn cache_page(&self, handle: u64, generation: u64, key: String, page: DatasetPage) {
+        let bytes = estimate_page_bytes(&page);
+        let budget = self.budget();
+        if let Ok(mut state) = self.lock() {
+            state.store(handle, generation, CacheKey::Page(key), CacheValue::Page(page), bytes, budget);
+        }
+    }
+
+    fn cached_columnar(&self, handle: u64, key: &str) -> Option<Vec<u8>> {
+        match self.lock().ok()?.lookup(handle, &CacheKey::Columnar(key.to_string()))? {
+            CacheValue::Columnar(bytes) => Some(bytes.clone()),
+            _ => None,
+        }
+    }
+
+    fn cache_columnar(&self, handle: u64, generation: u64, key: String, bytes: Vec<u8>) {
+        let size = size_of::<Vec<u8>>() + bytes.capacity();
+        let budget = self.budget();
+        if let Ok(mut state) = self.lock() {
+            state.store(handle, generation, CacheKey::Columnar(key), CacheValue::Columnar(bytes), size, budget);
+        }
+    }
+
+    fn cached_thumbnail(&self, handle: u64, page_index: usize) -> Option<ThumbnailData> {
+        match self.lock().ok()?.lookup(handle, &CacheKey::Thumbnail(page_index))? {
+            CacheValue::Thumbnail(thumbnail) => Some(thumbnail.clone()),
+            _ => None,
+        }
+    }
+
+    fn cache_thumbnail(&self, handle: u64, generation: u64, page_index: usize, thumbnail: ThumbnailData) {
+        let bytes = estimate_thumbnail_bytes(&thumbnail);
+        let budget = self.budget();
+        if let Ok(mut state) = self.lock() {
+            state.store(handle, generation, CacheKey::Thumbnail(page_index), CacheValue::Thumbnail(thumbnail), bytes, budget);
+        }
+    }
+
+    pub fn clear_cache(&self, handle: u64) -> Result<(), String> {
+        self.lock()?.clear_cache(handle)
+    }
+
+    /// 修改缓存预算并立即按 LRU 淘汰到新预算以内
+    pub fn set_budget(&self, budget_bytes: usize) -> Result<(), String> {
+        self.budget_bytes.store(budget_bytes, Ordering::Relaxed);
+        self.lock()?.evict_to(budget_bytes);
+        Ok(())
+    }
+
+    pub fn stats(&self) -> Result<CacheStats, String> {
+        let state = self.lock()?;
+        let keys = || state.datasets.values().flat_map(|o| o.cache.keys());
+        Ok(CacheStats {
+            budget_bytes: self.budget(),
+            used_bytes: state.used_bytes,
+            cached_pages: keys().filter(|k| matches!(k, CacheKey::Page(_) | CacheKey::Columnar(_))).count(),
+            cached_thumbnails: keys().filter(|k| matches!(k, CacheKey::Thumbnail(_))).count(),
+            open_datasets: state.datasets.len(),
+        })
+    }
+}
+
+/// 在 "datascope:progress" 上报告进度的回调；handle 供多个标签页区分各自的进度
+fn progress_reporter(app_handle: AppHandle, handle: Option<u64>, message: String) -> impl Fn(u64, u64) {
+    move |current, total| {
+        let _ = app_handle.emit(
+            "datascope:progress",
+            DatascopeProgress {
+                handle,
+                current,
+                total,
+                message: message.clone(),
+            },
+        );
+    }
+}
+
+// Tauri 命令
+
+/// 打开 CSV/Parquet 等数据集，返回后续命令使用的句柄。
+/// 同一文件可以打开多次，各句柄互不影响；不再使用时调用 datascope_close
+#[tauri::command]
+pub async fn datascope_open(
+    path: String,
+    options: Option<OpenOptions>,
+    app_handle: AppHandle,
+    registry: State<'_, DatasetRegistry>,
+) -> Result<DatasetInfo, String> {
+    println!("🚀 [Backend] datascope_open 开始, 文件: {}", path);
+    let options = options.unwrap_or_default();
+    let progress = progress_reporter(app_handle, None, "打开数据集...".to_string());
+    let dataset = tokio::task::spawn_blocking(move || open_dataset(&PathBuf::from(&path), &options, &progress))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+        .inspect_err(|e| println!("❌ [Backend] 打开失败: {}", e))?;
+
+    let handle = registry.insert(dataset)?;
+    let info = describe(handle, registry.get(handle)?.as_ref());
+    println!(
+        "✅ [Backend] datascope_open 完成: 句柄 {}, {:?}, 总行数 {}, {} 列",
+        handle,
+        info.format,
+        info.total_rows,
+        info.columns.len()
+    );
+    Ok(info)
+}
+
+#[tauri::command]
+pub async fn datascope_get_pagination(
+    handle: u64,
+    registry: State<'_, DatasetRegistry>,
+) -> Result<PaginationState, String> {
+    Ok(calculate_pagination(registry.get(handle)?.row_count()))
+}
+
+#[tauri::command]
+pub async fn datascope_load_page(
+    handle: u64,
+    page_index: usize,
+    page_info: PageInfo,
+    columns: Option<Vec<String>>,
+    app_handle: AppHandle,
+    registry: State<'_, DatasetRegistry>,
+) -> Result<DatasetPage, String> {
+    println!("📄 [Backend] datascope_load_page 开始, 句柄: {}, 页码: {}, 行范围: {}-{}",
+             handle, page_index, page_info.start_row, page_info.end_row);
+
+    let columns = columns.unwrap_or_default();
+    let key = page_cache_key(page_index, &page_info, &columns);
+    if let Some(cached) = registry.cached_page(handle, &key) {
+        println!("✅ [Backend] 使用缓存的页面数据");
+        let total = cached.rows.len() as u64;
+        progress_reporter(app_handle, Some(handle), "已从缓存加载".to_string())(total, total);
+        return Ok(cached);
+    }
+
+    let (dataset, generation) = registry.get_versioned(handle)?;
+    let progress = progress_reporter(app_handle, Some(handle), format!("读取第 {} 页...", page_index + 1));
+    let parsed = tokio::task::spawn_blocking(move || {
+        let parsed = dataset.page(page_info.start_row, page_info.end_row, &columns, &progress)?;
+        let total = parsed.rows.len() as u64;
+        progress(total, total);
+        Ok::<_, String>(parsed)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+    .inspect_err(|e| println!("❌ [Backend] 读取失败: {}", e))?;
+
+    println!("✅ [Backend] datascope_load_page 完成: {} 列, {} 行", parsed.headers.len(), parsed.rows.len());
+    registry.cache_page(handle, generation, key, parsed.clone());
+    Ok(parsed)
+}
+
+/// 与 datascope_load_page 相同，但以列式二进制格式返回（见 datascope_columnar），
+/// 避免大页面逐行 JSON 序列化的开销
+#[tauri::command]
+pub async fn datascope_load_page_columnar(
+    handle: u64,
+    page_index: usize,
+    page_info: PageInfo,
+    columns: Option<Vec<String>>,
+    app_handle: AppHandle,
+    registry: State<'_, DatasetRegistry>,
+) -> Result<Response, String> {
+    println!("📄 [Backend] datascope_load_page_columnar 开始, 句柄: {}, 页码: {}, 行范围: {}-{}",
+             handle, page_index, page_info.start_row, page_info.end_row);
+
+    let columns = columns.unwrap_or_default();
+    let key = page_cache_key(page_index, &page_info, &columns);
+    if let Some(cached) = registry.cached_columnar(handle, &key) {
+        println!("✅ [Backend] 使用缓存的页面数据");
+        let total = page_info.row_count as u64;
+        progress_reporter(app_handle, Some(handle), "已从缓存加载".to_string())(total, total);
+        return Ok(Response::new(cached));
+    }
+
+    let (dataset, generation) = registry.get_versioned(handle)?;
+    let progress = progress_reporter(app_handle, Some(handle), format!("读取第 {} 页...", page_index + 1));
+    let encoded = tokio::task::spawn_blocking(move || {
+        let page = dataset.page_columnar(page_info.start_row, page_info.end_row, &columns, &progress)?;
+        let total = page.row_count as u64;
+        progress(total, total);
+        page.encode()
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+    .inspect_err(|e| println!("❌ [Backend] 读取失败: {}", e))?;
+
+    println!("✅ [Backend] datascope_load_page_columnar 完成: {} bytes", encoded.len());
+    registry.cache_columnar(handle, generation, key, encoded.clone());
+    Ok(Response::new(encoded))
+}
+
+#[tauri::command]
+pub async fn datascope_generate_thumbnail(
+    handle: u64,
+    page_index: usize,
+    page_info: PageInfo,
+    registry: State<'_, DatasetRegistry>,
+) -> Result<ThumbnailData, String> {
+    if let Some(cached) = registry.cached_thumbnail(handle, page_index) {
+        return Ok(cached);
+    }
+
+    let (dataset, generation) = registry.get_versioned(handle)?;
+    let thumbnail = tokio::task::spawn_blocking(move || dataset.thumbnail(&page_info))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??;
+
+    registry.cache_thumbnail(handle, generation, page_index, thumbnail.clone());
+    Ok(thumbnail)
+}
+
+/// 用新分隔符重新打开 CSV；句柄不变，缓存清空
+#[tauri::command]
+pub async fn datascope_set_delimiter(
+    handle: u64,
+    delimiter: char,
+    app_handle: AppHandle,
+    registry: State<'_, DatasetRegistry>,
+) -> Result<DatasetInfo, String> {
+    let dataset = registry.get(handle)?;
+    if dataset.delimiter().is_none() {
+        return Err(format!("{:?} 文件不支持修改分隔符", dataset.format()));
+    }
+
+    // 引号规则依赖分隔符，行边界需要按新分隔符重新扫描
+    let path = dataset.path().to_path_buf();
+    let options = OpenOptions { delimiter: Some(delimiter) };
+    let progress = progress_reporter(app_handle, Some(handle), "建立行索引...".to_string());
+    let reopened = tokio::task::spawn_blocking(move || open_dataset(&path, &options, &progress))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??;
+
+    registry.replace(handle, reopened)?;
+    Ok(describe(handle, registry.get(handle)?.as_ref()))
+}
+
+/// 在整个数据集上执行过滤/搜索/排序并返回一页结果，进度走 "datascope:progress"
+#[tauri::command]
+pub async fn datascope_run_query(
+    handle: u64,
+    query: DatasetQuery,
+    app_handle: AppHandle,
+    registry: State<'_, DatasetRegistry>,
+    queries: State<'_, QueryRegistry>,
+) -> Result<QueryPage<DatasetRow>, String> {
+    let dataset = registry.get(handle)?;
+    let query_id = query.query_id.clone();
+    let cancel = queries.begin(query_id.as_deref());
+
+    let progress = progress_reporter(app_handle, Some(handle), "查询中...".to_string());
+    let result = tokio::task::spawn_blocking(move || dataset.query(&query, &cancel, &progress))
+        .await
+        .map_err(|e| format!("Task join error: {}", e));
+    queries.finish(query_id.as_deref());
+    result?
+}
+
+/// 逐列统计数据集；结果按文件缓存，文件修改时间或大小变化后重新计算
+#[tauri::command]
+pub async fn datascope_profile(
+    handle: u64,
+    options: Option<ProfileOptions>,
+    app_handle: AppHandle,
+    registry: State<'_, DatasetRegistry>,
+    profiles: State<'_, ProfileCache>,
+) -> Result<DatasetProfile, String> {
+    let options = options.unwrap_or_default();
+    options.validate()?;
+    let dataset = registry.get(handle)?;
+    let path = dataset.path().to_path_buf();
+    // 先取文件版本再统计：统计期间文件被修改时，下次调用会重新计算
+    let version = datascope_stats::file_version(&path)?;
+    let delimiter = dataset.delimiter();
+    if let Some(cached) = profiles.get(&path, version, delimiter, &options) {
+        println!("✅ [Backend] 使用缓存的统计结果: {}", path.display());
+        return Ok(cached);
+    }
+
+    println!("📊 [Backend] datascope_profile 开始, 句柄: {}, 文件: {}", handle, path.display());
+    let progress = progress_reporter(app_handle, Some(handle), "统计列信息...".to_string());
+    let profile = tokio::task::spawn_blocking(move || {
+        let columns = dataset.profile(&options, &progress)?;
+        Ok::<_, String>(DatasetProfile {
+            path: dataset.path().to_string_lossy().to_string(),
+            format: dataset.format(),
+            total_rows: dataset.row_count(),
+            columns,
+            options,
+        })
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+    .inspect_err(|e| println!("❌ [Backend] 统计失败: {}", e))?;
+
+    println!("✅ [Backend] datascope_profile 完成: {} 列", profile.columns.len());
+    profiles.insert(path, version, delimiter, profile.clone());
+    Ok(profile)
+}
+
+#[tauri::command]
+pub async fn datascope_clear_cache(
+    handle: u64,
+    registry: State<'_, DatasetRegistry>,
+) -> Result<(), String> {
+    registry.clear_cache(handle)
+}
+
+/// 关闭数据集并释放其缓存；返回该句柄之前是否处于打开状态
+#[tauri::command]
+pub async fn datascope_close(
+    handle: u64,
+    registry: State<'_, DatasetRegistry>,
+) -> Result<bool, String> {
+    registry.close(handle)
+}
+
+/// 列出所有打开的数据集
+#[tauri::command]
+pub async fn datascope_list_open(
+    registry: State<'_, DatasetRegistry>,
+) -> Result<Vec<DatasetInfo>, String> {
+    registry.list()
+}
+
+#[tauri::command]
+pub async fn datascope_cache_stats(
+    registry: State<'_, DatasetRegistry>,
+) -> Result<CacheStats, String> {
+    registry.stats()
+}
+
+/// 修改所有数据集共享的缓存预算（字节）
+#[tauri::command]
+pub async fn datascope_set_cache_budget(
+    budget_bytes: usize,
+    registry: State<'_, DatasetRegistry>,
+) -> Result<CacheStats, String> {
+    registry.set_budget(budget_bytes)?;
+    registry.stats()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_read_before_replace_are_not_cached() {
+        let dir = std::env::temp_dir().join(format!("datascope_dataset_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.csv");
+        std::fs::write(&path, "a;b,c\n1;2,3\n4;5,6\n").unwrap();
+        let open = |delimiter| open_dataset(&path, &OpenOptions { delimiter: Some(delimiter) }, &|_, _| {}).unwrap();
+
+        let registry = DatasetRegistry::default();
+        let handle = registry.insert(open(',')).unwrap();
+        let (dataset, generation) = registry.get_versioned(handle).unwrap();
+        let stale = dataset.page(0, 2, &[], &|_, _| {}).unwrap();
+        assert_eq!(stale.headers, vec!["a;b", "c"]);
+
+        // 读取期间分隔符被修改：旧结果不能进入新数据集的缓存
+        registry.replace(handle, open(';')).unwrap();
+        registry.cache_page(handle, generation, "page".into(), stale.clone());
+        registry.cache_columnar(handle, generation, "page".into(), vec![0; 16]);
+        assert!(registry.cached_page(handle, "page").is_none());
+        assert!(registry.cached_columnar(handle, "page").is_none());
+        assert_eq!(registry.stats().unwrap().used_bytes, 0);
+
+        let (dataset, generation) = registry.get_versioned(handle).unwrap();
+        let fresh = dataset.page(0, 2, &[], &|_, _| {}).unwrap();
+        assert_eq!(fresh.headers, vec!["a", "b,c"]);
+        registry.cache_page(handle, generation, "page".into(), fresh);
+        assert_eq!(registry.cached_page(handle, "page").unwrap().headers, vec!["a", "b,c"]);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,367 @@
+use crate::csv_index::{CsvIndex, CHECKPOINT_STRIDE};
+use crate::datascope_dataset::DatasetRow;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// 每扫描这么多行检查一次取消标志并回调进度
+const CHECK_EVERY_ROWS: usize = 16_384;
+/// 单次查询最多返回的行数
+pub const MAX_QUERY_LIMIT: usize = 200_000;
+
+pub const CANCELLED: &str = "查询已取消";
+
+/// 单列过滤条件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum FilterOp {
+    /// 去掉首尾空白后完全相等
+    Equals { value: String },
+    /// 子串匹配，默认不区分大小写
+    Contains {
+        value: String,
+        #[serde(default)]
+        case_sensitive: bool,
+    },
+    Regex { pattern: String },
+    /// 数值闭区间；无法解析为数字的值不匹配
+    Range { min: Option<f64>, max: Option<f64> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnFilter {
+    pub column: String,
+    #[serde(flatten)]
+    pub op: FilterOp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SortSpec {
+    pub column: String,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// 过滤（AND）+ 全文搜索 + 多列排序 + 分页
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatasetQuery {
+    pub filters: Vec<ColumnFilter>,
+    pub sort: Vec<SortSpec>,
+    /// 任意列包含该词（不区分大小写）
+    pub search: Option<String>,
+    pub offset: usize,
+    pub limit: usize,
+    /// 前端生成的查询 ID，用于 datascope_cancel_query
+    pub query_id: Option<String>,
+}
+
+impl DatasetQuery {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.limit == 0 || self.limit > MAX_QUERY_LIMIT {
+            return Err(format!("limit must be within 1..{}", MAX_QUERY_LIMIT));
+        }
+        for f in &self.filters {
+            if let FilterOp::Range { min: Some(lo), max: Some(hi) } = f.op {
+                if lo > hi {
+                    return Err(format!("invalid range on column {}: min > max", f.column));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn search_term(&self) -> Option<String> {
+        self.search.as_deref().map(str::trim).filter(|s| !s.is_empty()).map(str::to_lowercase)
+    }
+}
+
+/// 查询结果的一页；R 为各后端的行类型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryPage<R> {
+    pub headers: Vec<String>,
+    pub rows: Vec<R>,
+    /// 每一行在原文件中的行号（从 0 开始，不含表头）
+    pub row_numbers: Vec<usize>,
+    /// 满足条件的总行数
+    pub total_matches: usize,
+    pub offset: usize,
+    /// 格式错误而跳过的行数
+    pub skipped_rows: usize,
+}
+
+/// 正在运行的查询的取消标志
+#[derive(Default)]
+pub struct QueryRegistry {
+    running: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl QueryRegistry {
+    /// 登记查询；没有 ID 的查询得到一个不可取消的标志
+    pub fn begin(&self, query_id: Option<&str>) -> Arc<AtomicBool> {
+        let flag = Arc::new(AtomicBool::new(false));
+        if let Some(id) = query_id {
+            if let Ok(mut running) = self.running.lock() {
+                running.insert(id.to_string(), flag.clone());
+            }
+        }
+        flag
+    }
+
+    pub fn finish(&self, query_id: Option<&str>) {
+        if let (Some(id), Ok(mut running)) = (query_id, self.running.lock()) {
+            running.remove(id);
+        }
+    }
+
+    /// 返回该查询是否仍在运行
+    pub fn cancel(&self, query_id: &str) -> bool {
+        match self.running.lock().ok().and_then(|r| r.get(query_id).cloned()) {
+            Some(flag) => {
+                flag.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// 非空且不是 NaN/NULL 等缺失标记时解析为有限的 f64
+pub fn parse_numeric(value: &str) -> Option<f64> {
+    let t = value.trim();
+    if t.is_empty() { return None; }
+    t.parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+/// 编译后的 CSV 过滤条件（列名已解析为下标）
+enum CsvPredicate {
+    Equals(usize, String),
+    Contains { column: usize, needle: String, case_sensitive: bool },
+    Regex(usize, Regex),
+    Range(usize, Option<f64>, Option<f64>),
+}
+
+impl CsvPredicate {
+    fn matches(&self, field: impl Fn(usize) -> String) -> bool {
+        match self {
+            CsvPredicate::Equals(c, v) => field(*c).trim() == v.as_str(),
+            CsvPredicate::Contains { column, needle, case_sensitive: true } => field(*column).contains(needle.as_str()),
+            CsvPredicate::Contains { column, needle, .. } => field(*column).to_lowercase().contains(needle.as_str()),
+            CsvPredicate::Regex(c, re) => re.is_match(&field(*c)),
+            CsvPredicate::Range(c, lo, hi) => match parse_numeric(&field(*c)) {
+                Some(v) => lo.is_none_or(|lo| v >= lo) && hi.is_none_or(|hi| v <= hi),
+                None => false,
+            },
+        }
+    }
+}
+
+fn column_index(headers: &[String], name: &str) -> Result<usize, String> {
+    headers.iter().position(|h| h == name).ok_or_else(|| format!("Unknown column: {}", name))
+}
+
+fn compile_csv_filters(headers: &[String], filters: &[ColumnFilter]) -> Result<Vec<CsvPredicate>, String> {
+    filters
+        .iter()
+        .map(|f| {
+            let c = column_index(headers, &f.column)?;
+            Ok(match &f.op {
+                FilterOp::Equals { value } => CsvPredicate::Equals(c, value.trim().to_string()),
+                FilterOp::Contains { value, case_sensitive } => CsvPredicate::Contains {
+                    column: c,
+                    needle: if *case_sensitive { value.clone() } else { value.to_lowercase() },
+                    case_sensitive: *case_sensitive,
+                },
+                FilterOp::Regex { pattern } => {
+                    CsvPredicate::Regex(c, Regex::new(pattern).map_err(|e| format!("Invalid regex: {}", e))?)
+                }
+                FilterOp::Range { min, max } => CsvPredicate::Range(c, *min, *max),
+            })
+        })
+        .collect()
+}
+
+/// 排序键：数字在前并按数值比较，其余按字符串比较，缺失值总在最后
+#[derive(Debug, Clone, PartialEq)]
+enum SortKey {
+    Number(f64),
+    Text(String),
+    Missing,
+}
+
+impl SortKey {
+    fn from_field(value: &str) -> Self {
+        let t = value.trim();
+        if t.is_empty() { return SortKey::Missing; }
+        match parse_numeric(t) {
+            Some(v) => SortKey::Number(v),
+            None => SortKey::Text(t.to_string()),
+        }
+    }
+
+    fn cmp(&self, other: &Self, descending: bool) -> CmpOrdering {
+        let ord = match (self, other) {
+            (SortKey::Missing, SortKey::Missing) => return CmpOrdering::Equal,
+            (SortKey::Missing, _) => return CmpOrdering::Greater,
+            (_, SortKey::Missing) => return CmpOrdering::Less,
+            (SortKey::Number(a), SortKey::Number(b)) => a.total_cmp(b),
+            (SortKey::Number(_), SortKey::Text(_)) => CmpOrdering::Less,
+            (SortKey::Text(_), SortKey::Number(_)) => CmpOrdering::Greater,
+            (SortKey::Text(a), SortKey::Text(b)) => a.cmp(b),
+        };
+        if descending { ord.reverse() } else { ord }
+    }
+}
+
+/// 在 CSV 行索引上流式执行查询，不转换、不整体载入文件。
+/// 有排序时只保留匹配行的排序键与行号，排序后再按行号回读这一页。
+/// progress(已扫描行数, 总行数)；cancel 置位后返回 CANCELLED
+pub fn query_csv(
+    index: &CsvIndex,
+    query: &DatasetQuery,
+    cancel: &AtomicBool,
+    progress: &dyn Fn(u64, u64),
+) -> Result<QueryPage<DatasetRow>, String> {
+    query.validate()?;
+    let headers = index.headers().to_vec();
+    let predicates = compile_csv_filters(&headers, &query.filters)?;
+    let sort_columns = query
+        .sort
+        .iter()
+        .map(|s| Ok((column_index(&headers, &s.column)?, s.descending)))
+        .collect::<Result<Vec<_>, String>>()?;
+    let search = query.search_term();
+    let total = index.total_rows() as u64;
+    let page_end = query.offset.saturating_add(query.limit);
+
+    let mut total_matches = 0usize;
+    let mut matched: Vec<(usize, DatasetRow)> = Vec::new();
+    let mut keyed: Vec<(Vec<SortKey>, usize)> = Vec::new();
+    let mut cancelled = false;
+
+    progress(0, total);
+    let skipped_rows = index.scan(0, index.total_rows(), |row, record| {
+        if row % CHECK_EVERY_ROWS == 0 && row > 0 {
+            if cancel.load(Ordering::Relaxed) {
+                cancelled = true;
+                return false;
+            }
+            progress(row as u64, total);
+        }
+        let field = |c: usize| index.decode(record.get(c).unwrap_or_default());
+        if !predicates.iter().all(|p| p.matches(field)) { return true; }
+        if let Some(term) = &search {
+            if !record.iter().any(|f| index.decode(f).to_lowercase().contains(term.as_str())) { return true; }
+        }
+        if sort_columns.is_empty() {
+            if total_matches >= query.offset && total_matches < page_end {
+                let fields = headers.iter().zip(record.iter()).map(|(h, f)| (h.clone(), Value::String(index.decode(f)))).collect();
+                matched.push((row, fields));
+            }
+        } else {
+            let key = sort_columns.iter().map(|(c, _)| SortKey::from_field(&field(*c))).collect();
+            keyed.push((key, row));
+        }
+        total_matches += 1;
+        true
+    })?;
+    if cancelled { return Err(CANCELLED.to_string()); }
+    progress(total, total);
+
+    if !sort_columns.is_empty() {
+        // 稳定排序：键相同的行保持文件顺序
+        keyed.sort_by(|(a, ra), (b, rb)| {
+            a.iter()
+                .zip(b)
+                .zip(&sort_columns)
+                .map(|((x, y), (_, desc))| x.cmp(y, *desc))
+                .find(|o| *o != CmpOrdering::Equal)
+                .unwrap_or_else(|| ra.cmp(rb))
+        });
+        // 按行号顺序回读本页：相距不足一个检查点的行在同一次扫描中读过，更远时才重新定位；之后恢复排序顺序
+        let page: Vec<usize> = keyed.iter().skip(query.offset).take(query.limit).map(|(_, row)| *row).collect();
+        let mut ordered = page.clone();
+        ordered.sort_unstable();
+        let mut fetched: HashMap<usize, DatasetRow> = HashMap::with_capacity(ordered.len());
+        let mut start = 0;
+        while start < ordered.len() {
+            if cancel.load(Ordering::Relaxed) { return Err(CANCELLED.to_string()); }
+            let mut end = start + 1;
+            while end < ordered.len() && ordered[end] - ordered[end - 1] < CHECKPOINT_STRIDE {
+                end += 1;
+            }
+            let run = &ordered[start..end];
+            let mut next = 0;
+            index.scan(run[0], run[run.len() - 1] + 1, |row, record| {
+                if row == run[next] {
+                    let fields = headers.iter().zip(record.iter()).map(|(h, f)| (h.clone(), Value::String(index.decode(f)))).collect();
+                    fetched.insert(row, fields);
+                    next += 1;
+                }
+                next < run.len()
+            })?;
+            start = end;
+        }
+        matched.extend(page.into_iter().filter_map(|row| fetched.remove(&row).map(|fields| (row, fields))));
+    }
+
+    let (row_numbers, rows) = matched.into_iter().unzip();
+    Ok(QueryPage { headers, rows, row_numbers, total_matches, offset: query.offset, skipped_rows })
+}
+
+/// 取消正在运行的查询；返回该查询是否仍在运行
+#[tauri::command]
+pub async fn datascope_cancel_query(
+    query_id: String,
+    registry: tauri::State<'_, QueryRegistry>,
+) -> Result<bool, String> {
+    Ok(registry.cancel(&query_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorted_csv_page_matches_reference_order() {
+        // 跨多个检查点的文件，第 700 行格式错误
+        let dir = std::env::temp_dir().join(format!("datascope_query_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sorted.csv");
+        let mut content = String::from("id,value\n");
+        for i in 0..3000usize {
+            if i == 700 {
+                content.push_str("broken\n");
+            } else {
+                content.push_str(&format!("{},{}\n", i, (i * 7919) % 1009));
+            }
+        }
+        std::fs::write(&path, content).unwrap();
+        let index = CsvIndex::build(&path, None, &|_, _| {}).unwrap();
+
+        // 参考结果：按 value 降序，相同值保持文件顺序
+        let mut expected: Vec<(usize, usize)> = (0..3000).filter(|i| *i != 700).map(|i| ((i * 7919) % 1009, i)).collect();
+        expected.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        for (offset, limit) in [(0, 50), (37, 200), (2900, 500)] {
+            let query: DatasetQuery = serde_json::from_value(serde_json::json!({
+                "sort": [{ "column": "value", "descending": true }],
+                "offset": offset,
+                "limit": limit,
+            }))
+            .unwrap();
+            let page = query_csv(&index, &query, &AtomicBool::new(false), &|_, _| {}).unwrap();
+            let want: Vec<usize> = expected.iter().skip(offset).take(limit).map(|(_, row)| *row).collect();
+            assert_eq!(page.total_matches, 2999);
+            assert_eq!(page.skipped_rows, 1);
+            assert_eq!(page.row_numbers, want);
+            let ids: Vec<String> = page.rows.iter().map(|r| r["id"].as_str().unwrap().to_string()).collect();
+            assert_eq!(ids, want.iter().map(|r| r.to_string()).collect::<Vec<_>>());
+        }
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,668 @@
+use crate::csv_index::CsvIndex;
+use crate::datascope_dataset::{is_missing_numeric_token, DatasetFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// 每扫描这么多行回调一次进度
+const PROGRESS_EVERY_ROWS: usize = 16_384;
+/// 分位数与直方图使用的水塘抽样容量；非空值不超过该数时结果精确
+const SAMPLE_CAPACITY: usize = 100_000;
+/// 精确统计频次的不同值上限；超出后频次与不同值个数改为估算
+const EXACT_DISTINCT_LIMIT: usize = 50_000;
+/// HyperLogLog 寄存器位数：2^12 个寄存器，标准误差约 1.6%
+const HLL_BITS: u32 = 12;
+
+fn default_top_k() -> usize {
+    10
+}
+
+fn default_histogram_bins() -> usize {
+    20
+}
+
+fn default_quantiles() -> Vec<f64> {
+    vec![0.05, 0.25, 0.5, 0.75, 0.95]
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileOptions {
+    #[serde(default = "default_top_k")]
+    pub top_k: usize,
+    #[serde(default = "default_histogram_bins")]
+    pub histogram_bins: usize,
+    /// 0..=1 之间的分位点
+    #[serde(default = "default_quantiles")]
+    pub quantiles: Vec<f64>,
+}
+
+impl Default for ProfileOptions {
+    fn default() -> Self {
+        ProfileOptions {
+            top_k: default_top_k(),
+            histogram_bins: default_histogram_bins(),
+            quantiles: default_quantiles(),
+        }
+    }
+}
+
+impl ProfileOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.top_k > 1000 {
+            return Err("top_k must be at most 1000".to_string());
+        }
+        if self.histogram_bins == 0 || self.histogram_bins > 1000 {
+            return Err("histogram_bins must be within 1..1000".to_string());
+        }
+        if self.quantiles.iter().any(|q| !(0.0..=1.0).contains(q)) {
+            return Err("quantiles must be within 0..1".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValueCount {
+    pub value: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantileValue {
+    pub q: f64,
+    pub value: f64,
+}
+
+/// 等宽直方图：edges 比 counts 多一个
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Histogram {
+    pub edges: Vec<f64>,
+    pub counts: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnProfile {
+    pub name: String,
+    /// polars 类型名，与 DatasetColumn 相同；CSV 按内容推断为 i64 / f64 / bool / str
+    pub dtype: String,
+    /// 非空值个数
+    pub count: u64,
+    pub null_count: u64,
+    pub distinct_count: u64,
+    /// 以下仅数值列有值
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    /// 样本标准差
+    pub std: Option<f64>,
+    pub quantiles: Vec<QuantileValue>,
+    pub histogram: Option<Histogram>,
+    /// 出现次数最多的值，按次数降序
+    pub top_values: Vec<ValueCount>,
+    /// 为 true 时不同值个数、分位数、直方图与频次为估算值
+    pub approximate: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetProfile {
+    pub path: String,
+    pub format: DatasetFormat,
+    pub total_rows: usize,
+    pub columns: Vec<ColumnProfile>,
+    pub options: ProfileOptions,
+}
+
+/// 按分位点在已排序的数据上线性插值
+fn quantiles_of_sorted(sorted: &[f64], quantiles: &[f64]) -> Vec<QuantileValue> {
+    if sorted.is_empty() {
+        return Vec::new();
+    }
+    quantiles
+        .iter()
+        .map(|&q| {
+            let pos = q * (sorted.len() - 1) as f64;
+            let lo = pos.floor() as usize;
+            let hi = pos.ceil() as usize;
+            let value = sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f64);
+            QuantileValue { q, value }
+        })
+        .collect()
+}
+
+/// 等宽分箱的边界；min == max 时只有一个箱
+pub fn histogram_edges(min: f64, max: f64, bins: usize) -> Vec<f64> {
+    if min >= max {
+        return vec![min, max];
+    }
+    let width = (max - min) / bins as f64;
+    (0..=bins)
+        .map(|i| if i == bins { max } else { min + width * i as f64 })
+        .collect()
+}
+
+/// 值所在的箱；最大值落入最后一个箱
+fn histogram_bin(value: f64, min: f64, max: f64, bins: usize) -> usize {
+    if min >= max {
+        return 0;
+    }
+    (((value - min) / (max - min) * bins as f64) as usize).min(bins - 1)
+}
+
+/// 近似不同值个数
+struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    fn new() -> Self {
+        HyperLogLog {
+            registers: vec![0; 1 << HLL_BITS],
+        }
+    }
+
+    fn insert(&mut self, value: &str) {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+        let index = (hash >> (64 - HLL_BITS)) as usize;
+        let rank = ((hash << HLL_BITS) | (1 << (HLL_BITS - 1))).leading_zeros() as u8 + 1;
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+        }
+    }
+
+    fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let raw = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        // 基数较小时改用线性计数
+        if raw <= 2.5 * m && zeros > 0 {
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            raw.round() as u64
+        }
+    }
+}
+
+/// 高频值：不同值较少时精确计数，超出上限后按 Misra-Gries 只保留高频值（次数偏低）
+struct FrequentValues {
+    counts: HashMap<String, u64>,
+    overflowed: bool,
+}
+
+impl FrequentValues {
+    fn new() -> Self {
+        FrequentValues {
+            counts: HashMap::new(),
+            overflowed: false,
+        }
+    }
+
+    fn insert(&mut self, value: &str) {
+        if let Some(count) = self.counts.get_mut(value) {
+            *count += 1;
+        } else if self.counts.len() < EXACT_DISTINCT_LIMIT {
+            self.counts.insert(value.to_string(), 1);
+        } else {
+            self.overflowed = true;
+            self.counts.retain(|_, count| {
+                *count -= 1;
+                *count > 0
+            });
+        }
+    }
+
+    fn top(&self, k: usize) -> Vec<ValueCount> {
+        let mut top: Vec<ValueCount> = self
+            .counts
+            .iter()
+            .map(|(value, &count)| ValueCount {
+                value: value.clone(),
+                count,
+            })
+            .collect();
+        top.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        top.truncate(k);
+        top
+    }
+}
+
+/// 固定种子的水塘抽样，同一文件多次统计结果一致
+struct Reservoir {
+    values: Vec<f64>,
+    seen: u64,
+    state: u64,
+}
+
+impl Reservoir {
+    fn new() -> Self {
+        Reservoir {
+            values: Vec::new(),
+            seen: 0,
+            state: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    fn next_random(&mut self) -> u64 {
+        // xorshift64
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    fn insert(&mut self, value: f64) {
+        self.seen += 1;
+        if self.values.len() < SAMPLE_CAPACITY {
+            self.values.push(value);
+        } else {
+            let j = self.next_random() % self.seen;
+            if (j as usize) < SAMPLE_CAPACITY {
+                self.values[j as usize] = value;
+            }
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.seen as usize <= SAMPLE_CAPACITY
+    }
+}
+
+/// CSV 单列的流式统计
+struct CsvColumnStats {
+    count: u64,
+    nulls: u64,
+    numeric: u64,
+    integral: u64,
+    boolean: u64,
+    // Welford 在线均值/方差
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+    sample: Reservoir,
+    frequent: FrequentValues,
+    distinct: HyperLogLog,
+}
+
+impl CsvColumnStats {
+    fn new() -> Self {
+        CsvColumnStats {
+            count: 0,
+            nulls: 0,
+            numeric: 0,
+            integral: 0,
+            boolean: 0,
+            mean: 0.0,
+            m2: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            sample: Reservoir::new(),
+            frequent: FrequentValues::new(),
+            distinct: HyperLogLog::new(),
+        }
+    }
+
+    fn insert(&mut self, raw: &str) {
+        let value = raw.trim();
+        if value.is_empty() || is_missing_numeric_token(value) {
+            self.nulls += 1;
+            return;
+        }
+        self.count += 1;
+        self.frequent.insert(value);
+        self.distinct.insert(value);
+
+        if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+            self.boolean += 1;
+        }
+        let Some(x) = value.parse::<f64>().ok().filter(|x| x.is_finite()) else {
+            return;
+        };
+        self.numeric += 1;
+        if value.parse::<i64>().is_ok() {
+            self.integral += 1;
+        }
+        let delta = x - self.mean;
+        self.mean += delta / self.numeric as f64;
+        self.m2 += delta * (x - self.mean);
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+        self.sample.insert(x);
+    }
+
+    fn finish(mut self, name: String, options: &ProfileOptions) -> ColumnProfile {
+        let is_numeric = self.count > 0 && self.numeric == self.count;
+        let dtype = if is_numeric && self.integral == self.count {
+            "i64"
+        } else if is_numeric {
+            "f64"
+        } else if self.count > 0 && self.boolean == self.count {
+            "bool"
+        } else {
+            "str"
+        };
+        let approximate = self.frequent.overflowed || (is_numeric && !self.sample.is_complete());
+        let distinct_count = if self.frequent.overflowed {
+            self.distinct.estimate()
+        } else {
+            self.frequent.counts.len() as u64
+        };
+
+        let mut profile = ColumnProfile {
+            name,
+            dtype: dtype.to_string(),
+            count: self.count,
+            null_count: self.nulls,
+            distinct_count,
+            min: None,
+            max: None,
+            mean: None,
+            std: None,
+            quantiles: Vec::new(),
+            histogram: None,
+            top_values: self.frequent.top(options.top_k),
+            approximate,
+        };
+        if !is_numeric {
+            return profile;
+        }
+
+        profile.min = Some(self.min);
+        profile.max = Some(self.max);
+        profile.mean = Some(self.mean);
+        profile.std = (self.numeric > 1).then(|| (self.m2 / (self.numeric - 1) as f64).sqrt());
+
+        let complete = self.sample.is_complete();
+        let sample = &mut self.sample.values;
+        sample.sort_by(f64::total_cmp);
+        profile.quantiles = quantiles_of_sorted(sample, &options.quantiles);
+
+        let bins = if self.min < self.max { options.histogram_bins } else { 1 };
+        let mut counts = vec![0u64; bins];
+        for &x in sample.iter() {
+            counts[histogram_bin(x, self.min, self.max, bins)] += 1;
+        }
+        // 抽样不完整时按总数放大
+        if !complete {
+            let scale = self.numeric as f64 / sample.len() as f64;
+            counts.iter_mut().for_each(|c| *c = (*c as f64 * scale).round() as u64);
+        }
+        profile.histogram = Some(Histogram {
+            edges: histogram_edges(self.min, self.max, bins),
+            counts,
+        });
+        profile
+    }
+}
+
+/// 单次顺序扫描 CSV 统计每一列，内存占用与行数无关。progress(已扫描行数, 总行数)
+pub fn profile_csv(
+    index: &CsvIndex,
+    options: &ProfileOptions,
+    progress: &dyn Fn(u64, u64),
+) -> Result<Vec<ColumnProfile>, String> {
+    let headers = index.headers();
+    let total = index.total_rows() as u64;
+    let mut stats: Vec<CsvColumnStats> = headers.iter().map(|_| CsvColumnStats::new()).collect();
+
+    progress(0, total);
+    index.scan(0, index.total_rows(), |row, record| {
+        if row % PROGRESS_EVERY_ROWS == 0 && row > 0 {
+            progress(row as u64, total);
+        }
+        for (i, column) in stats.iter_mut().enumerate() {
+            column.insert(&index.decode(record.get(i).unwrap_or_default()));
+        }
+        true
+    })?;
+    progress(total, total);
+
+    Ok(headers
+        .iter()
+        .zip(stats)
+        .map(|(name, column)| column.finish(name.clone(), options))
+        .collect())
+}
+
+struct CachedProfile {
+    modified: SystemTime,
+    len: u64,
+    /// CSV 分隔符不同则结果不同
+    delimiter: Option<char>,
+    profile: DatasetProfile,
+}
+
+/// 统计结果按文件缓存；文件修改时间或大小变化后失效
+#[derive(Default)]
+pub struct ProfileCache {
+    entries: Mutex<HashMap<PathBuf, CachedProfile>>,
+}
+
+/// 文件的修改时间与大小，用于判断缓存是否失效
+pub fn file_version(path: &Path) -> Result<(SystemTime, u64), String> {
+    let metadata = std::fs::metadata(path).map_err(|e| format!("Failed to read file metadata: {}", e))?;
+    let modified = metadata.modified().map_err(|e| e.to_string())?;
+    Ok((modified, metadata.len()))
+}
+
+impl ProfileCache {
+    pub fn get(
+        &self,
+        path: &Path,
+        version: (SystemTime, u64),
+        delimiter: Option<char>,
+        options: &ProfileOptions,
+    ) -> Option<DatasetProfile> {
+        let entries = self.entries.lock().ok()?;
+        let cached = entries.get(path)?;
+        let fresh = (cached.modified, cached.len) == version
+            && cached.delimiter == delimiter
+            && cached.profile.options == *options;
+        fresh.then(|| cached.profile.clone())
+    }
+
+    pub fn insert(&self, path: PathBuf, version: (SystemTime, u64), delimiter: Option<char>, profile: DatasetProfile) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(
+                path,
+                CachedProfile {
+                    modified: version.0,
+                    len: version.1,
+                    delimiter,
+                    profile,
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn column(values: &[&str]) -> ColumnProfile {
+        let mut stats = CsvColumnStats::new();
+        for v in values {
+            stats.insert(v);
+        }
+        stats.finish("c".to_string(), &ProfileOptions::default())
+    }
+
+    #[test]
+    fn csv_dtype_follows_non_null_values() {
+        assert_eq!(column(&["1", " 2 ", "-3", "NA", ""]).dtype, "i64");
+        assert_eq!(column(&["1", "2.5", "1e3"]).dtype, "f64");
+        assert_eq!(column(&["true", "FALSE", "null"]).dtype, "bool");
+        assert_eq!(column(&["1", "abc"]).dtype, "str");
+        // inf 算缺失值；溢出成无穷大的数字不是有限数，整列按文本处理
+        assert_eq!(column(&["1", "inf"]).dtype, "i64");
+        assert_eq!(column(&["1", "1e999"]).dtype, "str");
+
+        let empty = column(&["", "NaN", "n/a"]);
+        assert_eq!(empty.dtype, "str");
+        assert_eq!((empty.count, empty.null_count, empty.distinct_count), (0, 3, 0));
+
+        let ints = column(&["1", " 2 ", "-3", "NA", "", "2"]);
+        assert_eq!((ints.count, ints.null_count, ints.distinct_count), (4, 2, 3));
+        assert_eq!(ints.top_values[0].value, "2");
+        assert_eq!(ints.top_values[0].count, 2);
+
+        let text = column(&["1", "abc"]);
+        assert!(text.mean.is_none() && text.histogram.is_none() && text.quantiles.is_empty());
+    }
+
+    #[test]
+    fn welford_mean_and_sample_std() {
+        let p = column(&["2", "4", "4", "4", "5", "5", "7", "9"]);
+        assert!((p.mean.unwrap() - 5.0).abs() < 1e-12);
+        assert!((p.std.unwrap() - (32.0f64 / 7.0).sqrt()).abs() < 1e-12);
+        assert_eq!((p.min, p.max), (Some(2.0), Some(9.0)));
+        assert!(!p.approximate);
+
+        // 大偏移量下仍然稳定
+        let shifted: Vec<String> = [4.0, 7.0, 13.0, 16.0].iter().map(|x| (1e9 + x).to_string()).collect();
+        let refs: Vec<&str> = shifted.iter().map(String::as_str).collect();
+        let p = column(&refs);
+        assert!((p.mean.unwrap() - (1e9 + 10.0)).abs() < 1e-6);
+        assert!((p.std.unwrap() - 30f64.sqrt()).abs() < 1e-6);
+
+        // 只有一个值时没有样本标准差
+        assert!(column(&["3"]).std.is_none());
+    }
+
+    #[test]
+    fn quantiles_interpolate_linearly() {
+        let sorted = [1.0, 2.0, 3.0, 4.0];
+        let got: Vec<f64> = quantiles_of_sorted(&sorted, &[0.0, 0.25, 0.5, 1.0]).iter().map(|q| q.value).collect();
+        assert_eq!(got, vec![1.0, 1.75, 2.5, 4.0]);
+        assert_eq!(quantiles_of_sorted(&[7.0], &[0.0, 0.5, 1.0]).iter().map(|q| q.value).collect::<Vec<_>>(), vec![7.0; 3]);
+        assert!(quantiles_of_sorted(&[], &[0.5]).is_empty());
+
+        // 列统计会先排序
+        let p = column(&["4", "1", "3", "2"]);
+        let median = p.quantiles.iter().find(|q| q.q == 0.5).unwrap();
+        assert_eq!(median.value, 2.5);
+    }
+
+    #[test]
+    fn histogram_edges_and_bins() {
+        assert_eq!(histogram_edges(0.0, 10.0, 4), vec![0.0, 2.5, 5.0, 7.5, 10.0]);
+        // 最后一条边界正好是最大值，不受浮点累积误差影响
+        assert_eq!(*histogram_edges(0.1, 0.7, 3).last().unwrap(), 0.7);
+        assert_eq!(histogram_bin(0.0, 0.0, 10.0, 4), 0);
+        assert_eq!(histogram_bin(2.5, 0.0, 10.0, 4), 1);
+        assert_eq!(histogram_bin(9.99, 0.0, 10.0, 4), 3);
+        assert_eq!(histogram_bin(10.0, 0.0, 10.0, 4), 3);
+
+        assert_eq!(histogram_edges(3.0, 3.0, 20), vec![3.0, 3.0]);
+        assert_eq!(histogram_bin(3.0, 3.0, 3.0, 1), 0);
+
+        let p = column(&["0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "10"]);
+        let h = p.histogram.unwrap();
+        assert_eq!(h.edges.len(), h.counts.len() + 1);
+        assert_eq!(h.counts.len(), 20);
+        assert_eq!(h.counts.iter().sum::<u64>(), 11);
+        // 最大值落入最后一个箱，不会越界
+        assert_eq!((h.counts[18], h.counts[19]), (1, 1));
+
+        // 所有值相同：一个箱装下全部
+        let h = column(&["3", "3", "3"]).histogram.unwrap();
+        assert_eq!(h.edges, vec![3.0, 3.0]);
+        assert_eq!(h.counts, vec![3]);
+    }
+
+    #[test]
+    fn hyperloglog_stays_within_error_bound() {
+        // 标准误差约 1.6%，按 3 倍取 5%
+        for n in [1_000u64, 20_000, 200_000] {
+            let mut hll = HyperLogLog::new();
+            for i in 0..n {
+                hll.insert(&format!("value-{}", i));
+                // 重复值不影响估算
+                hll.insert(&format!("value-{}", i / 2));
+            }
+            let estimate = hll.estimate() as f64;
+            let error = (estimate - n as f64).abs() / n as f64;
+            assert!(error < 0.05, "n={} estimate={} error={}", n, estimate, error);
+        }
+        assert_eq!(HyperLogLog::new().estimate(), 0);
+    }
+
+    #[test]
+    fn frequent_values_switch_to_estimates_after_overflow() {
+        let mut stats = CsvColumnStats::new();
+        for i in 0..EXACT_DISTINCT_LIMIT + 1000 {
+            stats.insert(&format!("k{}", i));
+        }
+        for _ in 0..10 {
+            stats.insert("hot");
+        }
+        let p = stats.finish("c".to_string(), &ProfileOptions::default());
+        assert!(p.approximate);
+        let n = (EXACT_DISTINCT_LIMIT + 1001) as f64;
+        assert!((p.distinct_count as f64 - n).abs() / n < 0.05);
+        assert_eq!(p.top_values[0].value, "hot");
+    }
+
+    fn profile(options: ProfileOptions) -> DatasetProfile {
+        DatasetProfile {
+            path: "p.csv".to_string(),
+            format: DatasetFormat::Csv,
+            total_rows: 1,
+            columns: Vec::new(),
+            options,
+        }
+    }
+
+    #[test]
+    fn profile_cache_invalidates_on_file_change() {
+        let dir = std::env::temp_dir().join(format!("datascope_stats_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.csv");
+        std::fs::write(&path, "a\n1\n").unwrap();
+
+        let cache = ProfileCache::default();
+        let options = ProfileOptions::default();
+        let v1 = file_version(&path).unwrap();
+        assert!(cache.get(&path, v1, None, &options).is_none());
+        cache.insert(path.clone(), v1, None, profile(options.clone()));
+        assert!(cache.get(&path, v1, None, &options).is_some());
+
+        // 分隔符或选项不同不命中
+        assert!(cache.get(&path, v1, Some(';'), &options).is_none());
+        let other = ProfileOptions { top_k: 3, ..options.clone() };
+        assert!(cache.get(&path, v1, None, &other).is_none());
+
+        // 大小不变、修改时间变化
+        std::fs::write(&path, "a\n2\n").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(v1.0 + Duration::from_secs(10)).unwrap();
+        drop(file);
+        let v2 = file_version(&path).unwrap();
+        assert_eq!(v2.1, v1.1);
+        assert!(cache.get(&path, v2, None, &options).is_none());
+
+        // 修改时间不变、大小变化
+        std::fs::write(&path, "a\n22\n").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(v1.0).unwrap();
+        drop(file);
+        let v3 = file_version(&path).unwrap();
+        assert_eq!(v3.0, v1.0);
+        assert!(cache.get(&path, v3, None, &options).is_none());
+
+        cache.insert(path.clone(), v3, None, profile(options.clone()));
+        assert!(cache.get(&path, v3, None, &options).is_some());
+        assert!(cache.get(&path, v1, None, &options).is_none());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
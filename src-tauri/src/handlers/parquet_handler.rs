@@ -1,146 +1,25 @@
-use crate::csv_handler::{PageInfo, ThumbnailData, ThumbnailPoint};
+use crate::datascope_columnar::{ColumnValues, ColumnarColumn, ColumnarPage, Utf8Builder};
+use crate::datascope_dataset::{Dataset, DatasetColumn, DatasetFormat, DatasetPage, DatasetRow};
+use crate::datascope_query::{DatasetQuery, FilterOp, QueryPage, CANCELLED};
+use crate::datascope_stats::{histogram_edges, ColumnProfile, Histogram, ProfileOptions, QuantileValue, ValueCount};
 use base64::Engine as _;
 use parquet2::read::read_metadata;
 use polars::lazy::dsl::col;
 use polars::prelude::{
-    LazyCsvReader, LazyFileListReader, LazyFrame, PolarsError, ScanArgsParquet, SchemaNamesAndDtypes,
+    len, lit, when, DataFrame, DataType, Expr, IdxSize, LazyCsvReader, LazyFileListReader, LazyFrame, PolarsError,
+    QuantileMethod, ScanArgsParquet, Schema, SchemaNamesAndDtypes, SortMultipleOptions,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fs::File;
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Emitter, State};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
 
-const THUMBNAIL_SAMPLE_SIZE: usize = 1000;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ParquetColumn {
-    pub name: String,
-    pub dtype: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ParquetOpenResult {
-    pub path: String,
-    pub total_rows: u64,
-    pub columns: Vec<ParquetColumn>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ParsedPage {
-    pub headers: Vec<String>,
-    pub rows: Vec<HashMap<String, Value>>,
-    pub skipped_rows: usize,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct DatascopeProgress {
-    pub current: u64,
-    pub total: u64,
-    pub message: String,
-}
-
-// Parquet 缓存管理器：只缓存元数据、页/缩略图结果，不缓存整文件内容
-pub struct ParquetCacheManager {
-    current_file: Arc<Mutex<Option<PathBuf>>>,
-    total_rows: Arc<Mutex<Option<u64>>>,
-    columns: Arc<Mutex<Vec<ParquetColumn>>>,
-    page_cache: Arc<Mutex<HashMap<String, ParsedPage>>>,
-    thumbnail_cache: Arc<Mutex<HashMap<usize, ThumbnailData>>>,
-}
-
-impl Default for ParquetCacheManager {
-    fn default() -> Self {
-        Self {
-            current_file: Arc::new(Mutex::new(None)),
-            total_rows: Arc::new(Mutex::new(None)),
-            columns: Arc::new(Mutex::new(Vec::new())),
-            page_cache: Arc::new(Mutex::new(HashMap::new())),
-            thumbnail_cache: Arc::new(Mutex::new(HashMap::new())),
-        }
-    }
-}
-
-impl ParquetCacheManager {
-    pub fn clear(&self) {
-        if let Ok(mut f) = self.current_file.lock() {
-            *f = None;
-        }
-        if let Ok(mut t) = self.total_rows.lock() {
-            *t = None;
-        }
-        if let Ok(mut c) = self.columns.lock() {
-            c.clear();
-        }
-        if let Ok(mut pc) = self.page_cache.lock() {
-            pc.clear();
-        }
-        if let Ok(mut tc) = self.thumbnail_cache.lock() {
-            tc.clear();
-        }
-    }
-
-    fn set_dataset(&self, path: PathBuf, total_rows: u64, columns: Vec<ParquetColumn>) {
-        if let Ok(mut f) = self.current_file.lock() {
-            *f = Some(path);
-        }
-        if let Ok(mut t) = self.total_rows.lock() {
-            *t = Some(total_rows);
-        }
-        if let Ok(mut c) = self.columns.lock() {
-            *c = columns;
-        }
-        if let Ok(mut pc) = self.page_cache.lock() {
-            pc.clear();
-        }
-        if let Ok(mut tc) = self.thumbnail_cache.lock() {
-            tc.clear();
-        }
-    }
-
-    fn get_path(&self) -> Option<PathBuf> {
-        self.current_file.lock().ok()?.clone()
-    }
-
-    fn cache_page(&self, key: String, page: ParsedPage) {
-        if let Ok(mut pc) = self.page_cache.lock() {
-            pc.insert(key, page);
-        }
-    }
-
-    fn get_cached_page(&self, key: &str) -> Option<ParsedPage> {
-        self.page_cache.lock().ok()?.get(key).cloned()
-    }
-
-    fn cache_thumbnail(&self, page_index: usize, thumb: ThumbnailData) {
-        if let Ok(mut tc) = self.thumbnail_cache.lock() {
-            tc.insert(page_index, thumb);
-        }
-    }
-
-    fn get_cached_thumbnail(&self, page_index: usize) -> Option<ThumbnailData> {
-        self.thumbnail_cache.lock().ok()?.get(&page_index).cloned()
-    }
-}
-
-fn page_cache_key(page_index: usize, page_info: &PageInfo, columns: &[String]) -> String {
-    // NOTE: columns 空表示“全列”，否则作为列裁剪的一部分。
-    // 为避免顺序影响，排序后拼接。
-    let mut cols = columns.to_vec();
-    cols.sort();
-    let cols_key = if cols.is_empty() {
-        "*".to_string()
-    } else {
-        cols.join("|")
-    };
-
-    format!(
-        "p={page_index};s={};n={};c={cols_key}",
-        page_info.start_row, page_info.row_count
-    )
-}
+/// 查询时每次读取的行数；块之间检查取消并报告进度
+const QUERY_CHUNK_ROWS: usize = 500_000;
+/// 查询时附加的行号列
+const ROW_NUMBER_COLUMN: &str = "__datascope_row";
 
 fn map_polars_err(err: PolarsError) -> String {
     format!("{err}")
@@ -157,13 +36,13 @@ fn parquet_total_rows(path: &str) -> Result<u64, String> {
     Ok(total)
 }
 
-fn parquet_schema_columns(path: &str) -> Result<Vec<ParquetColumn>, String> {
+fn parquet_schema_columns(path: &str) -> Result<Vec<DatasetColumn>, String> {
     // collect_schema 只读 footer 元数据，不会把整文件加载进内存
     let mut lf = LazyFrame::scan_parquet(path, ScanArgsParquet::default()).map_err(map_polars_err)?;
     let schema = lf.collect_schema().map_err(map_polars_err)?;
     let cols = schema
         .iter_names_and_dtypes()
-        .map(|(name, dtype)| ParquetColumn {
+        .map(|(name, dtype)| DatasetColumn {
             name: name.to_string(),
             dtype: dtype.to_string(),
         })
@@ -289,7 +168,8 @@ fn any_to_json(v: &polars::prelude::AnyValue) -> Value {
     }
 }
 
-fn df_to_rows(df: &polars::prelude::DataFrame) -> Vec<HashMap<String, Value>> {
+/// 逐行转换为 JSON；progress(已转换行数, 总行数)
+fn df_to_rows(df: &polars::prelude::DataFrame, progress: &dyn Fn(u64, u64)) -> Vec<DatasetRow> {
     let height = df.height();
     let cols = df.get_columns();
 
@@ -302,252 +182,156 @@ fn df_to_rows(df: &polars::prelude::DataFrame) -> Vec<HashMap<String, Value>> {
             row.insert(name.to_string(), any_to_json(&av));
         }
         out.push(row);
+
+        if row_idx % 2000 == 0 {
+            progress(((row_idx as u64) + 1).min(height as u64), height as u64);
+        }
     }
     out
 }
 
-#[tauri::command]
-pub async fn parquet_open_file(
-    path: String,
-    cache: State<'_, ParquetCacheManager>,
-) -> Result<ParquetOpenResult, String> {
-    let path_clone = path.clone();
-
-    let total_rows = tokio::task::spawn_blocking(move || parquet_total_rows(&path_clone))
-        .await
-        .map_err(|e| format!("Task join error: {e}"))??;
-
-    let path_clone = path.clone();
-    let columns = tokio::task::spawn_blocking(move || parquet_schema_columns(&path_clone))
-        .await
-        .map_err(|e| format!("Task join error: {e}"))??;
-
-    cache.set_dataset(PathBuf::from(&path), total_rows, columns.clone());
-
-    Ok(ParquetOpenResult {
-        path,
-        total_rows,
-        columns,
-    })
-}
+/// 按列转换为列式页面：能用 f64 精确表示的数值列与布尔列保留类型，其余列与 JSON 页面一样转为字符串。
+/// progress(已转换行数, 总行数)，按列推进
+fn df_to_columns(df: &DataFrame, progress: &dyn Fn(u64, u64)) -> Result<ColumnarPage, String> {
+    const MAX_SAFE_INT: f64 = 9_007_199_254_740_991.0; // 2^53 - 1
 
-#[tauri::command]
-pub async fn parquet_load_page(
-    page_index: usize,
-    page_info: PageInfo,
-    columns: Option<Vec<String>>,
-    app_handle: AppHandle,
-    cache: State<'_, ParquetCacheManager>,
-) -> Result<ParsedPage, String> {
-    let cols = columns.unwrap_or_default();
-    let key = page_cache_key(page_index, &page_info, &cols);
-    if let Some(cached) = cache.get_cached_page(&key) {
-        let _ = app_handle.emit(
-            "datascope:progress",
-            DatascopeProgress {
-                current: cached.rows.len() as u64,
-                total: cached.rows.len() as u64,
-                message: "已从缓存加载".to_string(),
-            },
-        );
-        return Ok(cached);
+    let height = df.height();
+    let cols = df.get_columns();
+    let mut columns = Vec::with_capacity(cols.len());
+    for (i, c) in cols.iter().enumerate() {
+        let s = c.as_materialized_series();
+        let name = s.name().to_string();
+        let dtype = s.dtype();
+
+        let numeric = if dtype.is_primitive_numeric() && !matches!(dtype, DataType::Int128) {
+            let values: Vec<Option<f64>> = s
+                .cast(&DataType::Float64)
+                .map_err(map_polars_err)?
+                .f64()
+                .map_err(map_polars_err)?
+                .into_iter()
+                .collect();
+            // 64 位整数超出 2^53 时 f64 会丢精度，与 JSON 页面一样退回字符串
+            let exact = !matches!(dtype, DataType::Int64 | DataType::UInt64)
+                || values.iter().flatten().all(|v| v.abs() <= MAX_SAFE_INT);
+            exact.then_some(values)
+        } else {
+            None
+        };
+
+        let column = if let Some(values) = numeric {
+            let has_null = values.iter().any(Option::is_none);
+            ColumnarColumn {
+                name,
+                validity: has_null.then(|| values.iter().map(|v| v.is_some() as u8).collect()),
+                values: ColumnValues::F64(values.into_iter().map(|v| v.unwrap_or(f64::NAN)).collect()),
+            }
+        } else if matches!(dtype, DataType::Boolean) {
+            let values: Vec<Option<bool>> = s.bool().map_err(map_polars_err)?.into_iter().collect();
+            let has_null = values.iter().any(Option::is_none);
+            ColumnarColumn {
+                name,
+                validity: has_null.then(|| values.iter().map(|v| v.is_some() as u8).collect()),
+                values: ColumnValues::Bool(values.into_iter().map(|v| v.unwrap_or(false) as u8).collect()),
+            }
+        } else if matches!(dtype, DataType::String) {
+            let mut builder = Utf8Builder::with_capacity(height);
+            for v in s.str().map_err(map_polars_err)? {
+                match v {
+                    Some(v) => builder.push(v),
+                    None => builder.push_null(),
+                }
+            }
+            builder.finish(name)?
+        } else {
+            let mut builder = Utf8Builder::with_capacity(height);
+            for av in s.iter() {
+                match any_to_json(&av) {
+                    Value::Null => builder.push_null(),
+                    Value::String(v) => builder.push(&v),
+                    // 列表/结构体等以 JSON 文本传输
+                    other => builder.push(&other.to_string()),
+                }
+            }
+            builder.finish(name)?
+        };
+        columns.push(column);
+        progress((height * (i + 1) / cols.len()) as u64, height as u64);
     }
 
-    let path = cache
-        .get_path()
-        .ok_or_else(|| "No parquet file loaded".to_string())?;
+    Ok(ColumnarPage {
+        row_count: height,
+        skipped_rows: 0,
+        columns,
+    })
+}
 
-    let path_str = path.to_string_lossy().to_string();
+/// Parquet 数据集：只缓存 footer 中的行数与 schema，页数据按需读取
+pub struct ParquetDataset {
+    path: PathBuf,
+    total_rows: usize,
+    columns: Vec<DatasetColumn>,
+}
 
-    let _ = app_handle.emit(
-        "datascope:progress",
-        DatascopeProgress {
-            current: 0,
-            total: page_info.row_count as u64,
-            message: format!("读取 Parquet 第 {} 页...", page_index + 1),
-        },
-    );
+impl ParquetDataset {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let path_str = path.to_string_lossy().to_string();
+        Ok(ParquetDataset {
+            path: path.to_path_buf(),
+            total_rows: parquet_total_rows(&path_str)? as usize,
+            columns: parquet_schema_columns(&path_str)?,
+        })
+    }
 
-    let app_handle2 = app_handle.clone();
+    fn path_str(&self) -> String {
+        self.path.to_string_lossy().to_string()
+    }
 
-    let parsed = tokio::task::spawn_blocking(move || -> Result<ParsedPage, String> {
-        let mut lf = LazyFrame::scan_parquet(&path_str, ScanArgsParquet::default())
+    /// 读取 [start_row, end_row) 的所选列；columns 为空表示全部列
+    fn read_slice(&self, start_row: usize, end_row: usize, columns: &[String]) -> Result<DataFrame, String> {
+        let mut lf = LazyFrame::scan_parquet(self.path_str(), ScanArgsParquet::default())
             .map_err(map_polars_err)?;
 
-        if !cols.is_empty() {
-            let exprs = cols.iter().map(|c| col(c)).collect::<Vec<_>>();
+        if !columns.is_empty() {
+            let exprs = columns.iter().map(|c| col(c.as_str())).collect::<Vec<_>>();
             lf = lf.select(exprs);
         }
 
         // Parquet: 通过 row group + predicate pushdown/column pruning 只读需要的块
-        let len: polars::prelude::IdxSize = page_info
-            .row_count
+        let len: IdxSize = end_row
+            .saturating_sub(start_row)
             .try_into()
             .map_err(|_| "Page row_count is too large".to_string())?;
 
-        let df = lf
-            .slice(page_info.start_row as i64, len)
-            .collect()
-            .map_err(map_polars_err)?;
-
-        let total = df.height() as u64;
-        let _ = app_handle2.emit(
-            "datascope:progress",
-            DatascopeProgress {
-                current: 0,
-                total,
-                message: "序列化数据...".to_string(),
-            },
-        );
-
-        let headers = df
-            .get_columns()
-            .iter()
-            .map(|s| s.name().to_string())
-            .collect::<Vec<_>>();
-
-        let height = df.height();
-        let cols_ref = df.get_columns();
-        let mut out = Vec::with_capacity(height);
-        for row_idx in 0..height {
-            let mut row = HashMap::with_capacity(cols_ref.len());
-            for s in cols_ref {
-                let name = s.name();
-                let av = s.get(row_idx).unwrap_or(polars::prelude::AnyValue::Null);
-                row.insert(name.to_string(), any_to_json(&av));
-            }
-            out.push(row);
-
-            if row_idx % 2000 == 0 {
-                let _ = app_handle2.emit(
-                    "datascope:progress",
-                    DatascopeProgress {
-                        current: ((row_idx as u64) + 1).min(total),
-                        total,
-                        message: "序列化数据...".to_string(),
-                    },
-                );
-            }
-        }
-
-        let _ = app_handle2.emit(
-            "datascope:progress",
-            DatascopeProgress {
-                current: total,
-                total,
-                message: "加载完成".to_string(),
-            },
-        );
-
-        Ok(ParsedPage {
-            headers,
-            rows: out,
-            skipped_rows: 0,
-        })
-    })
-    .await
-    .map_err(|e| format!("Task join error: {e}"))??;
-
-    cache.cache_page(key, parsed.clone());
-    Ok(parsed)
-}
-
-fn find_first_numeric_column(page: &ParsedPage) -> Option<String> {
-    let sample_size = page.rows.len().min(100);
-    for name in &page.headers {
-        let mut non_empty = 0;
-        let mut numeric = 0;
-        // 不要只看开头：开头可能全是 NaN/缺失，后续才有有效值。
-        for sample_index in 0..sample_size {
-            let i = if sample_size <= 1 {
-                0
-            } else {
-                (sample_index * (page.rows.len() - 1)) / (sample_size - 1)
-            };
-            let v = page.rows[i].get(name);
-            match v {
-                Some(Value::Number(n)) => {
-                    if let Some(f) = n.as_f64() {
-                        if f.is_finite() {
-                            non_empty += 1;
-                            numeric += 1;
-                        }
-                    }
-                }
-                Some(Value::String(s)) => {
-                    let t = s.trim();
-                    if t.is_empty() || is_missing_numeric_token(t) {
-                        continue;
-                    }
-                    if let Ok(v) = t.parse::<f64>() {
-                        if v.is_finite() {
-                            non_empty += 1;
-                            numeric += 1;
-                        }
-                    }
-                }
-                Some(Value::Null) | None => {}
-                _ => {
-                    // arrays/objects 不视为数值列
-                    non_empty += 1;
-                }
-            }
-        }
-        if non_empty > 0 && (numeric as f64 / non_empty as f64) >= 0.7 {
-            return Some(name.clone());
-        }
+        lf.slice(start_row as i64, len).collect().map_err(map_polars_err)
     }
-    None
-}
-
-fn is_missing_numeric_token(token: &str) -> bool {
-    let t = token.trim().to_ascii_lowercase();
-    matches!(
-        t.as_str(),
-        "nan"
-            | "na"
-            | "n/a"
-            | "null"
-            | "none"
-            | "undefined"
-            | "inf"
-            | "+inf"
-            | "-inf"
-            | "infinity"
-            | "+infinity"
-            | "-infinity"
-    )
 }
 
-#[tauri::command]
-pub async fn parquet_generate_thumbnail(
-    page_index: usize,
-    page_info: PageInfo,
-    cache: State<'_, ParquetCacheManager>,
-) -> Result<ThumbnailData, String> {
-    if let Some(cached) = cache.get_cached_thumbnail(page_index) {
-        return Ok(cached);
+impl Dataset for ParquetDataset {
+    fn format(&self) -> DatasetFormat {
+        DatasetFormat::Parquet
     }
 
-    let path = cache
-        .get_path()
-        .ok_or_else(|| "No parquet file loaded".to_string())?;
-    let path_str = path.to_string_lossy().to_string();
+    fn path(&self) -> &Path {
+        &self.path
+    }
 
-    // 只取这一页的少量行用于检测数值列并采样
-    let page = tokio::task::spawn_blocking(move || -> Result<ParsedPage, String> {
-        let lf = LazyFrame::scan_parquet(&path_str, ScanArgsParquet::default())
-            .map_err(map_polars_err)?;
+    fn columns(&self) -> Vec<DatasetColumn> {
+        self.columns.clone()
+    }
 
-        let len: polars::prelude::IdxSize = page_info
-            .row_count
-            .try_into()
-            .map_err(|_| "Page row_count is too large".to_string())?;
+    fn row_count(&self) -> usize {
+        self.total_rows
+    }
 
-        let df = lf
-            .slice(page_info.start_row as i64, len)
-            .collect()
-            .map_err(map_polars_err)?;
+    fn page(
+        &self,
+        start_row: usize,
+        end_row: usize,
+        columns: &[String],
+        progress: &dyn Fn(u64, u64),
+    ) -> Result<DatasetPage, String> {
+        let df = self.read_slice(start_row, end_row, columns)?;
 
         let headers = df
             .get_columns()
@@ -555,76 +339,36 @@ pub async fn parquet_generate_thumbnail(
             .map(|s| s.name().to_string())
             .collect::<Vec<_>>();
 
-        Ok(ParsedPage {
+        Ok(DatasetPage {
             headers,
-            rows: df_to_rows(&df),
+            rows: df_to_rows(&df, progress),
             skipped_rows: 0,
         })
-    })
-    .await
-    .map_err(|e| format!("Task join error: {e}"))??;
-
-    let numeric_col = find_first_numeric_column(&page);
-    if numeric_col.is_none() {
-        let empty = ThumbnailData {
-            page_index,
-            points: vec![],
-        };
-        cache.cache_thumbnail(page_index, empty.clone());
-        return Ok(empty);
     }
-    let col_name = numeric_col.unwrap();
-
-    let step = (page.rows.len() / THUMBNAIL_SAMPLE_SIZE).max(1);
-    let mut points = Vec::new();
-
-    // 固定步长可能全踩在 NaN 上；这里用采样桶内找有效值。
-    let mut bucket_start = 0usize;
-    while bucket_start < page.rows.len() {
-        let bucket_end = (bucket_start + step).min(page.rows.len());
-        let mut picked: Option<(usize, f64)> = None;
-
-        for i in bucket_start..bucket_end {
-            if let Some(v) = page.rows[i].get(&col_name) {
-                let y = match v {
-                    Value::Number(n) => n.as_f64().filter(|f| f.is_finite()),
-                    Value::String(s) => {
-                        let t = s.trim();
-                        if t.is_empty() || is_missing_numeric_token(t) {
-                            None
-                        } else {
-                            t.parse::<f64>().ok().filter(|f| f.is_finite())
-                        }
-                    }
-                    _ => None,
-                };
-
-                if let Some(y) = y {
-                    picked = Some((i, y));
-                    break;
-                }
-            }
-        }
-
-        if let Some((i, y)) = picked {
-            points.push(ThumbnailPoint {
-                x: (page_info.start_row + i) as f64,
-                y,
-            });
-        }
 
-        bucket_start += step;
+    fn page_columnar(
+        &self,
+        start_row: usize,
+        end_row: usize,
+        columns: &[String],
+        progress: &dyn Fn(u64, u64),
+    ) -> Result<ColumnarPage, String> {
+        let df = self.read_slice(start_row, end_row, columns)?;
+        df_to_columns(&df, progress)
     }
 
-    let thumb = ThumbnailData { page_index, points };
-    cache.cache_thumbnail(page_index, thumb.clone());
-    Ok(thumb)
-}
+    fn query(
+        &self,
+        query: &DatasetQuery,
+        cancel: &AtomicBool,
+        progress: &dyn Fn(u64, u64),
+    ) -> Result<QueryPage<DatasetRow>, String> {
+        query_parquet(&self.path_str(), query, cancel, progress)
+    }
 
-#[tauri::command]
-pub async fn parquet_clear_cache(cache: State<'_, ParquetCacheManager>) -> Result<(), String> {
-    cache.clear();
-    Ok(())
+    fn profile(&self, options: &ProfileOptions, progress: &dyn Fn(u64, u64)) -> Result<Vec<ColumnProfile>, String> {
+        profile_parquet(&self.path_str(), options, progress)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -693,3 +437,349 @@ pub async fn convert_csv_to_parquet(
 
     Ok(())
 }
+
+/// 把过滤条件与搜索词翻译成 polars 谓词；都为空时返回 None
+fn parquet_predicate(schema: &Schema, query: &DatasetQuery) -> Result<Option<Expr>, String> {
+    let mut predicates = Vec::new();
+    for f in &query.filters {
+        let dtype = schema
+            .get(f.column.as_str())
+            .ok_or_else(|| format!("Unknown column: {}", f.column))?;
+        let as_text = col(f.column.as_str()).cast(DataType::String);
+        let expr = match &f.op {
+            FilterOp::Equals { value } => {
+                let value = value.trim();
+                match value.parse::<f64>() {
+                    // 数值列按数值比较，避免 "1" 与 "1.0" 不相等
+                    Ok(v) if dtype.is_primitive_numeric() => col(f.column.as_str()).cast(DataType::Float64).eq(lit(v)),
+                    _ => as_text.eq(lit(value.to_string())),
+                }
+            }
+            FilterOp::Contains { value, case_sensitive: true } => as_text.str().contains_literal(lit(value.clone())),
+            FilterOp::Contains { value, .. } => as_text.str().to_lowercase().str().contains_literal(lit(value.to_lowercase())),
+            FilterOp::Regex { pattern } => {
+                regex::Regex::new(pattern).map_err(|e| format!("Invalid regex: {}", e))?;
+                as_text.str().contains(lit(pattern.clone()), true)
+            }
+            FilterOp::Range { min, max } => {
+                let v = col(f.column.as_str()).cast(DataType::Float64);
+                let mut e = v.clone().is_not_null();
+                if let Some(lo) = min { e = e.and(v.clone().gt_eq(lit(*lo))); }
+                if let Some(hi) = max { e = e.and(v.lt_eq(lit(*hi))); }
+                e
+            }
+        };
+        predicates.push(expr.fill_null(lit(false)));
+    }
+    if let Some(term) = query.search_term() {
+        // 嵌套与二进制列不参与全文搜索
+        let any = schema
+            .iter_names_and_dtypes()
+            .filter(|(_, dtype)| !dtype.is_nested() && !matches!(dtype, DataType::Binary))
+            .map(|(name, _)| {
+                col(name.as_str())
+                    .cast(DataType::String)
+                    .str()
+                    .to_lowercase()
+                    .str()
+                    .contains_literal(lit(term.clone()))
+                    .fill_null(lit(false))
+            })
+            .reduce(|a, b| a.or(b));
+        predicates.push(any.unwrap_or(lit(false)));
+    }
+    Ok(predicates.into_iter().reduce(|a, b| a.and(b)))
+}
+
+/// 分块扫描 Parquet 执行查询：谓词交给 polars（可下推到 row group），块之间检查取消。
+/// 有排序时每块只保留前 offset + limit 行参与归并，内存与文件大小无关
+fn query_parquet(
+    path: &str,
+    query: &DatasetQuery,
+    cancel: &AtomicBool,
+    progress: &dyn Fn(u64, u64),
+) -> Result<QueryPage<DatasetRow>, String> {
+    use std::sync::atomic::Ordering;
+
+    query.validate()?;
+    let total = parquet_total_rows(path)?;
+    let mut base = LazyFrame::scan_parquet(path, ScanArgsParquet::default()).map_err(map_polars_err)?;
+    let schema = base.collect_schema().map_err(map_polars_err)?;
+    let predicate = parquet_predicate(&schema, query)?;
+    for s in &query.sort {
+        if schema.get(s.column.as_str()).is_none() {
+            return Err(format!("Unknown column: {}", s.column));
+        }
+    }
+    let base = base.with_row_index(ROW_NUMBER_COLUMN, None);
+
+    // 排序列之后以行号兜底，保证结果与分块方式无关
+    let mut sort_by: Vec<&str> = query.sort.iter().map(|s| s.column.as_str()).collect();
+    sort_by.push(ROW_NUMBER_COLUMN);
+    let mut descending: Vec<bool> = query.sort.iter().map(|s| s.descending).collect();
+    descending.push(false);
+    let sort_options = SortMultipleOptions::default()
+        .with_order_descending_multi(descending)
+        .with_nulls_last(true)
+        .with_maintain_order(true);
+    let keep = query.offset.saturating_add(query.limit);
+
+    let mut total_matches = 0usize;
+    let mut kept: Option<DataFrame> = None;
+    let mut start = 0u64;
+    while start < total {
+        if cancel.load(Ordering::Relaxed) { return Err(CANCELLED.to_string()); }
+        progress(start, total);
+        let mut chunk = base.clone().slice(start as i64, QUERY_CHUNK_ROWS as IdxSize);
+        if let Some(p) = &predicate { chunk = chunk.filter(p.clone()); }
+        let df = chunk.collect().map_err(map_polars_err)?;
+        let before = total_matches;
+        total_matches += df.height();
+        start += QUERY_CHUNK_ROWS as u64;
+
+        let part = if query.sort.is_empty() {
+            // 只保留与 [offset, offset + limit) 重叠的部分
+            if before >= keep || total_matches <= query.offset { continue; }
+            let s = query.offset.saturating_sub(before);
+            let e = (keep - before).min(df.height());
+            df.slice(s as i64, e - s)
+        } else {
+            df.sort(sort_by.clone(), sort_options.clone()).map_err(map_polars_err)?.slice(0, keep)
+        };
+        kept = Some(match kept {
+            None => part,
+            Some(prev) if query.sort.is_empty() => prev.vstack(&part).map_err(map_polars_err)?,
+            Some(prev) => prev
+                .vstack(&part)
+                .and_then(|all| all.sort(sort_by.clone(), sort_options.clone()))
+                .map_err(map_polars_err)?
+                .slice(0, keep),
+        });
+    }
+    progress(total, total);
+
+    let mut page = match kept {
+        Some(df) if !query.sort.is_empty() => df.slice(query.offset as i64, query.limit),
+        Some(df) => df,
+        None => DataFrame::empty(),
+    };
+    let row_numbers = match page.column(ROW_NUMBER_COLUMN) {
+        Ok(c) => c.idx().map_err(map_polars_err)?.into_iter().map(|v| v.unwrap_or_default() as usize).collect(),
+        Err(_) => Vec::new(),
+    };
+    if page.width() > 0 {
+        page = page.drop(ROW_NUMBER_COLUMN).map_err(map_polars_err)?;
+    }
+    let headers = schema.iter_names().map(|n| n.to_string()).collect();
+    Ok(QueryPage {
+        headers,
+        rows: df_to_rows(&page, &|_, _| {}),
+        row_numbers,
+        total_matches,
+        offset: query.offset,
+        skipped_rows: 0,
+    })
+}
+
+/// footer 统计信息中各顶层列的空值数之和；有 row group 缺少统计信息的列不返回
+fn parquet_footer_null_counts(path: &str) -> Result<HashMap<String, u64>, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to open parquet: {e}"))?;
+    let metadata = read_metadata(&mut file).map_err(|e| format!("Failed to read parquet metadata: {e}"))?;
+    let mut counts: HashMap<String, Option<u64>> = HashMap::new();
+    for rg in &metadata.row_groups {
+        for chunk in rg.columns() {
+            // 嵌套列的叶子与顶层列不一一对应
+            let [name] = chunk.descriptor().path_in_schema.as_slice() else {
+                continue;
+            };
+            let nulls = chunk
+                .statistics()
+                .and_then(|stats| stats.ok())
+                .and_then(|stats| stats.null_count())
+                .map(|n| n as u64);
+            let total = counts.entry(name.clone()).or_insert(Some(0));
+            *total = total.zip(nulls).map(|(a, b)| a + b);
+        }
+    }
+    Ok(counts.into_iter().filter_map(|(name, n)| Some((name, n?))).collect())
+}
+
+/// 单行聚合结果中的数值
+fn scalar_f64(df: &DataFrame, name: &str) -> Result<Option<f64>, String> {
+    let s = df
+        .column(name)
+        .map_err(map_polars_err)?
+        .as_materialized_series()
+        .cast(&DataType::Float64)
+        .map_err(map_polars_err)?;
+    Ok(s.f64().map_err(map_polars_err)?.get(0).filter(|v| v.is_finite()))
+}
+
+/// 统计时附加的计数列
+const PROFILE_COUNT_COLUMN: &str = "__datascope_count";
+
+/// Parquet 列统计：空值数优先取 footer 统计信息，其余由 polars lazy 聚合得到（精确值）。
+/// 每列单独查询，列裁剪保证只读取该列。progress(已完成列数, 总列数)
+fn profile_parquet(
+    path: &str,
+    options: &ProfileOptions,
+    progress: &dyn Fn(u64, u64),
+) -> Result<Vec<ColumnProfile>, String> {
+    let mut base = LazyFrame::scan_parquet(path, ScanArgsParquet::default()).map_err(map_polars_err)?;
+    let schema = base.collect_schema().map_err(map_polars_err)?;
+    let footer_nulls = parquet_footer_null_counts(path).unwrap_or_default();
+    let total = schema.len() as u64;
+
+    progress(0, total);
+    let mut profiles = Vec::with_capacity(schema.len());
+    for (i, (name, dtype)) in schema.iter().enumerate() {
+        let name = name.as_str();
+        profiles.push(profile_parquet_column(&base, name, dtype, footer_nulls.get(name).copied(), options)?);
+        progress(i as u64 + 1, total);
+    }
+    Ok(profiles)
+}
+
+fn profile_parquet_column(
+    base: &LazyFrame,
+    name: &str,
+    dtype: &DataType,
+    footer_nulls: Option<u64>,
+    options: &ProfileOptions,
+) -> Result<ColumnProfile, String> {
+    // 列表/结构体/二进制等无法哈希或转为字符串，只统计空值
+    let hashable = !matches!(dtype, DataType::List(_) | DataType::Array(_, _) | DataType::Struct(_) | DataType::Binary);
+    let numeric = dtype.is_primitive_numeric();
+
+    let mut aggs = vec![len().alias("rows")];
+    if footer_nulls.is_none() {
+        aggs.push(col(name).null_count().alias("nulls"));
+    }
+    if hashable {
+        aggs.push(col(name).drop_nulls().n_unique().alias("distinct"));
+    }
+    if numeric {
+        let x = col(name).cast(DataType::Float64);
+        let x = x.clone().filter(x.is_not_nan());
+        aggs.push(x.clone().min().alias("min"));
+        aggs.push(x.clone().max().alias("max"));
+        aggs.push(x.clone().mean().alias("mean"));
+        aggs.push(x.clone().std(1).alias("std"));
+        for (i, q) in options.quantiles.iter().enumerate() {
+            aggs.push(x.clone().quantile(lit(*q), QuantileMethod::Linear).alias(format!("q{i}")));
+        }
+    }
+    let stats = base.clone().select(aggs).collect().map_err(map_polars_err)?;
+
+    let rows = scalar_f64(&stats, "rows")?.unwrap_or(0.0) as u64;
+    let null_count = match footer_nulls {
+        Some(n) => n,
+        None => scalar_f64(&stats, "nulls")?.unwrap_or(0.0) as u64,
+    };
+    let mut profile = ColumnProfile {
+        name: name.to_string(),
+        dtype: dtype.to_string(),
+        count: rows.saturating_sub(null_count),
+        null_count,
+        distinct_count: if hashable { scalar_f64(&stats, "distinct")?.unwrap_or(0.0) as u64 } else { 0 },
+        min: None,
+        max: None,
+        mean: None,
+        std: None,
+        quantiles: Vec::new(),
+        histogram: None,
+        top_values: Vec::new(),
+        approximate: false,
+    };
+
+    if hashable && options.top_k > 0 {
+        let sort_options = SortMultipleOptions::default()
+            .with_order_descending_multi([true, false])
+            .with_maintain_order(true);
+        let top = base
+            .clone()
+            .select([col(name).cast(DataType::String)])
+            .filter(col(name).is_not_null())
+            .group_by([col(name)])
+            .agg([len().alias(PROFILE_COUNT_COLUMN)])
+            .sort_by_exprs([col(PROFILE_COUNT_COLUMN), col(name)], sort_options)
+            .limit(options.top_k as IdxSize)
+            .collect()
+            .map_err(map_polars_err)?;
+        let values = top.column(name).map_err(map_polars_err)?.as_materialized_series().clone();
+        let counts = top
+            .column(PROFILE_COUNT_COLUMN)
+            .map_err(map_polars_err)?
+            .as_materialized_series()
+            .cast(&DataType::UInt64)
+            .map_err(map_polars_err)?;
+        profile.top_values = values
+            .str()
+            .map_err(map_polars_err)?
+            .into_iter()
+            .zip(counts.u64().map_err(map_polars_err)?)
+            .filter_map(|(value, count)| {
+                Some(ValueCount {
+                    value: value?.to_string(),
+                    count: count?,
+                })
+            })
+            .collect();
+    }
+
+    if !numeric {
+        return Ok(profile);
+    }
+    let (Some(min), Some(max)) = (scalar_f64(&stats, "min")?, scalar_f64(&stats, "max")?) else {
+        return Ok(profile);
+    };
+    profile.min = Some(min);
+    profile.max = Some(max);
+    profile.mean = scalar_f64(&stats, "mean")?;
+    profile.std = scalar_f64(&stats, "std")?;
+    profile.quantiles = options
+        .quantiles
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &q)| match scalar_f64(&stats, &format!("q{i}")) {
+            Ok(Some(value)) => Some(Ok(QuantileValue { q, value })),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        })
+        .collect::<Result<_, String>>()?;
+
+    // 等宽分箱：箱号 = (x - min) / (max - min) * bins，最大值并入最后一箱
+    let bins = if min < max { options.histogram_bins } else { 1 };
+    let x = col(name).cast(DataType::Float64);
+    let bin = ((x.clone() - lit(min)) / lit((max - min).max(f64::MIN_POSITIVE)) * lit(bins as f64)).cast(DataType::Int64);
+    let bin = when(bin.clone().gt_eq(lit(bins as i64 - 1)))
+        .then(lit(bins as i64 - 1))
+        .otherwise(bin)
+        .alias("bin");
+    let hist = base
+        .clone()
+        .filter(x.clone().is_not_null().and(x.is_not_nan()))
+        .select([bin])
+        .group_by([col("bin")])
+        .agg([len().alias(PROFILE_COUNT_COLUMN)])
+        .collect()
+        .map_err(map_polars_err)?;
+    let bin_ids = hist.column("bin").map_err(map_polars_err)?.as_materialized_series().clone();
+    let bin_counts = hist
+        .column(PROFILE_COUNT_COLUMN)
+        .map_err(map_polars_err)?
+        .as_materialized_series()
+        .cast(&DataType::UInt64)
+        .map_err(map_polars_err)?;
+    let mut counts = vec![0u64; bins];
+    for (b, n) in bin_ids.i64().map_err(map_polars_err)?.into_iter().zip(bin_counts.u64().map_err(map_polars_err)?) {
+        if let (Some(b), Some(n)) = (b, n) {
+            counts[(b.max(0) as usize).min(bins - 1)] += n;
+        }
+    }
+    profile.histogram = Some(Histogram {
+        edges: histogram_edges(min, max, bins),
+        counts,
+    });
+    Ok(profile)
+}
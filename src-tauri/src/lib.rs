@@ -15,28 +15,40 @@ mod ssh;
 use ssh::{SshService, SshResult, SshTestResult, WorkerDeployResult};
 
 // 引入 CSV 处理模块
+#[path = "handlers/csv_index.rs"]
+mod csv_index;
 #[path = "handlers/csv_handler.rs"]
 mod csv_handler;
-use csv_handler::{
-    CsvCacheManager,
-    csv_load_file,
-    csv_get_pagination,
-    csv_load_page,
-    csv_generate_thumbnail,
-    csv_change_delimiter,
-    csv_clear_cache,
-};
+#[path = "handlers/datascope_query.rs"]
+mod datascope_query;
 
 // 引入 Parquet 处理模块
 #[path = "handlers/parquet_handler.rs"]
 mod parquet_handler;
-use parquet_handler::{
-    ParquetCacheManager,
-    parquet_open_file,
-    parquet_load_page,
-    parquet_generate_thumbnail,
-    parquet_clear_cache,
-    convert_csv_to_parquet,
+use parquet_handler::convert_csv_to_parquet;
+
+// 引入 Datascope 数据集模块（CSV / Parquet 统一接口）
+#[path = "handlers/datascope_dataset.rs"]
+mod datascope_dataset;
+#[path = "handlers/datascope_columnar.rs"]
+mod datascope_columnar;
+#[path = "handlers/datascope_stats.rs"]
+mod datascope_stats;
+use datascope_dataset::{
+    DatasetRegistry,
+    datascope_open,
+    datascope_get_pagination,
+    datascope_load_page,
+    datascope_load_page_columnar,
+    datascope_generate_thumbnail,
+    datascope_set_delimiter,
+    datascope_run_query,
+    datascope_clear_cache,
+    datascope_close,
+    datascope_list_open,
+    datascope_cache_stats,
+    datascope_set_cache_budget,
+    datascope_profile,
 };
 
 use once_cell::sync::OnceCell;
@@ -196,8 +208,9 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
-        .manage(CsvCacheManager::default())
-        .manage(ParquetCacheManager::default())
+        .manage(DatasetRegistry::default())
+        .manage(datascope_query::QueryRegistry::default())
+        .manage(datascope_stats::ProfileCache::default())
         .invoke_handler(tauri::generate_handler![
             greet,
             execute_python_script,
@@ -211,17 +224,21 @@ pub fn run() {
             ssh_upload_file,
             ssh_deploy_worker,
             ssh_stop_worker,
-            csv_load_file,
-            csv_get_pagination,
-            csv_load_page,
-            csv_generate_thumbnail,
-            csv_change_delimiter,
-            csv_clear_cache,
-            parquet_open_file,
-            parquet_load_page,
-            parquet_generate_thumbnail,
-            parquet_clear_cache,
+            datascope_open,
+            datascope_get_pagination,
+            datascope_load_page,
+            datascope_load_page_columnar,
+            datascope_generate_thumbnail,
+            datascope_set_delimiter,
+            datascope_run_query,
+            datascope_clear_cache,
+            datascope_close,
+            datascope_list_open,
+            datascope_cache_stats,
+            datascope_set_cache_budget,
+            datascope_profile,
             convert_csv_to_parquet,
+            datascope_query::datascope_cancel_query,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -7,7 +7,7 @@
 // chrono 仅在模块内部使用，这里无需导入
 use rusqlite::Connection;
 use std::sync::{
-    atomic::{AtomicBool, AtomicUsize, Ordering},
+    atomic::{AtomicBool, Ordering},
     Arc, Mutex,
 };
 use std::thread;
@@ -15,7 +15,7 @@ use tauri::Manager;
 
 use once_cell::sync::OnceCell;
 use tauri_plugin_autostart::Builder as AutostartBuilder;
-#[cfg(target_os = "windows")]
+// #[cfg(target_os = "windows")]
 // use window_vibrancy::apply_mica;
 
 // Python 服务
@@ -28,15 +28,61 @@ pub use python::{PythonInfo, PythonResult, ScriptInfo};
 mod app_paths;
 #[path = "core/db.rs"]
 mod db;
+#[path = "core/migrations.rs"]
+mod migrations;
 mod pdf_library;
 #[path = "services/python.rs"]
 mod python;
 #[path = "features/spectrum.rs"]
 mod spectrum;
+#[path = "features/audio_source.rs"]
+mod audio_source;
+#[path = "features/spectrum_dsp.rs"]
+mod spectrum_dsp;
+#[path = "features/spectrum_view.rs"]
+mod spectrum_view;
+#[path = "features/sample_format.rs"]
+mod sample_format;
+#[path = "features/loudness.rs"]
+mod loudness;
+#[path = "features/recorder.rs"]
+mod recorder;
+#[path = "features/pitch.rs"]
+mod pitch;
 #[path = "features/tracker.rs"]
 mod tracker;
+#[path = "features/window_source.rs"]
+mod window_source;
+#[path = "features/idle_source.rs"]
+mod idle_source;
+#[path = "features/categories.rs"]
+mod categories;
+#[path = "features/reports.rs"]
+mod reports;
+#[path = "features/activity_io.rs"]
+mod activity_io;
+#[path = "features/retention.rs"]
+mod retention;
+#[path = "features/privacy.rs"]
+mod privacy;
+#[path = "features/analytics.rs"]
+mod analytics;
+#[path = "features/goals.rs"]
+mod goals;
+#[path = "features/calendar.rs"]
+mod calendar;
+#[path = "handlers/csv_index.rs"]
+mod csv_index;
 #[path = "handlers/csv_handler.rs"]
 mod csv_handler;
+#[path = "handlers/datascope_query.rs"]
+mod datascope_query;
+#[path = "handlers/datascope_dataset.rs"]
+mod datascope_dataset;
+#[path = "handlers/datascope_columnar.rs"]
+mod datascope_columnar;
+#[path = "handlers/datascope_stats.rs"]
+mod datascope_stats;
 #[path = "handlers/parquet_handler.rs"]
 mod parquet_handler;
 
@@ -56,21 +102,29 @@ pub struct TrackerStop {
 }
 
 // 全局频谱采集停止标志
-pub use spectrum::{SpectrumConfig, SpectrumRuntime, SpectrumStop};
+pub use spectrum::{SpectrumConfigState, SpectrumRuntime, SpectrumSource, SpectrumStop};
 
 use db::{ActivityLog, TimelineActivity};
-use csv_handler::{
-    CsvCacheManager,
-    csv_load_file,
-    csv_get_pagination,
-    csv_load_page,
-    csv_generate_thumbnail,
-    csv_change_delimiter,
-    csv_clear_cache,
+use datascope_dataset::{
+    DatasetRegistry,
+    datascope_open,
+    datascope_get_pagination,
+    datascope_load_page,
+    datascope_load_page_columnar,
+    datascope_generate_thumbnail,
+    datascope_set_delimiter,
+    datascope_run_query,
+    datascope_clear_cache,
+    datascope_close,
+    datascope_list_open,
+    datascope_cache_stats,
+    datascope_set_cache_budget,
+    datascope_profile,
 };
 
 // 这个结构体用于前端请求时返回当前活动窗口信息
 pub use tracker::ActiveWindowInfo;
+use tracker::TrackerConfig;
 
 fn main() {
     tauri::Builder::default()
@@ -92,7 +146,17 @@ fn main() {
                 .unwrap_or_else(|_| panic!("failed to open database at {:?}", db_path));
 
             // 初始化 Schema
-            db::init_db(&conn).expect("failed to init db");
+            db::init_db(&conn).unwrap_or_else(|e| panic!("failed to init db: {}", e));
+
+            // 关闭上次异常退出（崩溃/断电）时遗留的进行中分段
+            match db::recover_open_segments(&conn) {
+                Ok(0) => {}
+                Ok(n) => println!("[tracker] 已关闭 {} 条未正常结束的活动记录", n),
+                Err(e) => eprintln!("[tracker] 恢复未结束的活动记录失败: {}", e),
+            }
+
+            // 追踪器配置（空闲阈值、暂停状态）沿用上次保存的值，默认 5 分钟无输入视为空闲
+            let tracker_config = TrackerConfig::load(&conn);
 
             // 将数据库连接句柄放入 Tauri 的托管状态中
             app.manage(DbState {
@@ -109,26 +173,35 @@ fn main() {
             app.manage(TrackerStop {
                 stop: tracker_stop.clone(),
             });
+            app.manage(tracker_config);
+            // 时间目标与番茄钟（由追踪线程驱动）
+            app.manage(goals::GoalsRuntime::default());
             // 管理频谱采集停止标志（按需启动）
             let spectrum_stop = Arc::new(AtomicBool::new(false));
             app.manage(SpectrumStop {
                 stop: spectrum_stop.clone(),
             });
-            // 频谱配置（默认 2048 点 FFT、96 列）
-            app.manage(SpectrumConfig {
-                fft_size: AtomicUsize::new(2048),
-                columns: AtomicUsize::new(96),
-            });
+            // 频谱配置（默认 2048 点 FFT、96 列、柱状模式）
+            app.manage(SpectrumConfigState::default());
             // 运行时状态
             app.manage(SpectrumRuntime {
                 running: Arc::new(AtomicBool::new(false)),
+                view: Mutex::new(Default::default()),
+                loudness_reset: AtomicBool::new(false),
+                format: Mutex::new(None),
+                recorder: Mutex::new(None),
             });
+            // 音频来源（默认平台环回；打开失败时报错，只有通过 set_spectrum_source 开启后才回退模拟数据）
+            app.manage(SpectrumSource::default());
+
+            // Datascope 已打开的数据集（CSV / Parquet）及其页面、缩略图缓存
+            app.manage(DatasetRegistry::default());
 
-            // 管理 CSV 缓存状态（供 CSV Viewer 后端使用）
-            app.manage(CsvCacheManager::default());
+            // Datascope 查询的取消标志
+            app.manage(datascope_query::QueryRegistry::default());
 
-            // 管理 Parquet 缓存状态（供 Datascope Parquet 后端使用）
-            app.manage(parquet_handler::ParquetCacheManager::default());
+            // Datascope 列统计结果（按文件缓存）
+            app.manage(datascope_stats::ProfileCache::default());
 
             // 在一个新的线程中启动我们的后台追踪器
             let app_handle = app.handle().clone();
@@ -136,6 +209,12 @@ fn main() {
                 tracker::run_tracker_loop(app_handle, tracker_stop);
             });
 
+            // 活动记录保留策略（压缩旧记录、按年归档）
+            let retention_handle = app.handle().clone();
+            thread::spawn(move || {
+                retention::run_retention_loop(retention_handle);
+            });
+
             // 频谱线程不再在启动时开启，改为按需 start/stop
 
             // 监听主窗口关闭/销毁，通知后台线程退出
@@ -165,6 +244,53 @@ fn main() {
             get_latest_activities,
             get_activities_for_day,
             get_database_size,
+            get_idle_threshold,
+            set_idle_threshold,
+            pause_tracking,
+            resume_tracking,
+            get_pause_status,
+            // 活动分类规则
+            categories::activity_list_categories,
+            categories::activity_create_category,
+            categories::activity_update_category,
+            categories::activity_delete_category,
+            categories::activity_list_rules,
+            categories::activity_create_rule,
+            categories::activity_update_rule,
+            categories::activity_delete_rule,
+            categories::activity_reapply_rules,
+            categories::activity_category_totals,
+            reports::activity_report,
+            reports::activity_period_report,
+            activity_io::export_activities,
+            activity_io::import_activities,
+            retention::activity_get_retention_policy,
+            retention::activity_set_retention_policy,
+            retention::activity_run_retention,
+            privacy::privacy_list_rules,
+            privacy::privacy_create_rule,
+            privacy::privacy_update_rule,
+            privacy::privacy_delete_rule,
+            privacy::privacy_list_denylist,
+            privacy::privacy_add_denylist,
+            privacy::privacy_set_denylist_enabled,
+            privacy::privacy_delete_denylist,
+            privacy::privacy_reredact_history,
+            analytics::activity_focus_analytics,
+            goals::goals_list,
+            goals::goals_create,
+            goals::goals_update,
+            goals::goals_delete,
+            goals::goals_progress,
+            goals::pomodoro_start,
+            goals::pomodoro_stop,
+            goals::pomodoro_skip,
+            goals::pomodoro_status,
+            calendar::calendar_export_ics,
+            calendar::calendar_import_ics,
+            calendar::calendar_list_planned,
+            calendar::calendar_delete_planned,
+            calendar::calendar_plan_vs_actual,
             open_spectrum_window,
             open_spectrum_floating_window,
             open_test_window,
@@ -172,6 +298,14 @@ fn main() {
             stop_spectrum,
             set_spectrum_fft_size,
             set_spectrum_columns,
+            set_spectrum_source,
+            get_spectrum_source,
+            get_spectrum_config,
+            set_spectrum_config,
+            get_spectrogram_history,
+            reset_spectrum_loudness,
+            start_spectrum_recording,
+            stop_spectrum_recording,
             // Python 命令
             execute_python_script,
             list_python_scripts,
@@ -179,18 +313,21 @@ fn main() {
             read_python_script,
             delete_python_script,
             get_python_info,
-            // CSV Viewer 后端命令
-            csv_load_file,
-            csv_get_pagination,
-            csv_load_page,
-            csv_generate_thumbnail,
-            csv_change_delimiter,
-            csv_clear_cache,
-            // Parquet Viewer 后端命令
-            parquet_handler::parquet_open_file,
-            parquet_handler::parquet_load_page,
-            parquet_handler::parquet_generate_thumbnail,
-            parquet_handler::parquet_clear_cache,
+            // Datascope 数据集命令（按句柄访问，CSV / Parquet 共用）
+            datascope_open,
+            datascope_get_pagination,
+            datascope_load_page,
+            datascope_load_page_columnar,
+            datascope_generate_thumbnail,
+            datascope_set_delimiter,
+            datascope_run_query,
+            datascope_clear_cache,
+            datascope_close,
+            datascope_list_open,
+            datascope_cache_stats,
+            datascope_set_cache_budget,
+            datascope_profile,
+            datascope_query::datascope_cancel_query,
             parquet_handler::convert_csv_to_parquet,
             // PDF Library 命令
             pdf_library::commands::pdflibrary_init_db,
@@ -259,8 +396,31 @@ fn get_latest_activities(state: tauri::State<DbState>) -> Result<Vec<ActivityLog
 fn get_activities_for_day(
     state: tauri::State<DbState>,
     date: String,
+    include_idle: Option<bool>,
 ) -> Result<Vec<TimelineActivity>, String> {
-    db::get_activities_for_day(state, date)
+    db::get_activities_for_day(state, date, include_idle.unwrap_or(false))
+}
+
+// 空闲检测阈值（秒）
+#[tauri::command]
+fn get_idle_threshold(app: tauri::AppHandle) -> Result<u64, String> {
+    tracker::get_idle_threshold(app)
+}
+#[tauri::command]
+fn set_idle_threshold(app: tauri::AppHandle, secs: u64) -> Result<(), String> {
+    tracker::set_idle_threshold(app, secs)
+}
+#[tauri::command]
+fn pause_tracking(app: tauri::AppHandle, minutes: Option<u64>) -> Result<tracker::PauseStatus, String> {
+    tracker::pause_tracking(app, minutes)
+}
+#[tauri::command]
+fn resume_tracking(app: tauri::AppHandle) -> Result<(), String> {
+    tracker::resume_tracking(app)
+}
+#[tauri::command]
+fn get_pause_status(app: tauri::AppHandle) -> Result<tracker::PauseStatus, String> {
+    tracker::get_pause_status(app)
 }
 
 // 原 get_latest_activities/get_activities_for_day 的实现已移至 db 模块
@@ -397,6 +557,42 @@ fn set_spectrum_columns(app: tauri::AppHandle, cols: usize) -> Result<(), String
     spectrum::set_spectrum_columns(app, cols)
 }
 #[tauri::command]
+fn set_spectrum_source(
+    app: tauri::AppHandle,
+    source: audio_source::AudioSourceSpec,
+    fallback_to_simulation: Option<bool>,
+) -> Result<(), String> {
+    spectrum::set_spectrum_source(app, source, fallback_to_simulation)
+}
+#[tauri::command]
+fn get_spectrum_source(app: tauri::AppHandle) -> Result<spectrum::SpectrumSourceInfo, String> {
+    spectrum::get_spectrum_source(app)
+}
+#[tauri::command]
+fn get_spectrum_config(app: tauri::AppHandle) -> Result<spectrum::SpectrumConfig, String> {
+    spectrum::get_spectrum_config(app)
+}
+#[tauri::command]
+fn set_spectrum_config(app: tauri::AppHandle, config: spectrum::SpectrumConfig) -> Result<(), String> {
+    spectrum::set_spectrum_config(app, config)
+}
+#[tauri::command]
+fn get_spectrogram_history(app: tauri::AppHandle) -> Result<Vec<Vec<f32>>, String> {
+    spectrum::get_spectrogram_history(app)
+}
+#[tauri::command]
+fn reset_spectrum_loudness(app: tauri::AppHandle) -> Result<(), String> {
+    spectrum::reset_spectrum_loudness(app)
+}
+#[tauri::command]
+fn start_spectrum_recording(app: tauri::AppHandle, options: recorder::RecordingOptions) -> Result<(), String> {
+    spectrum::start_spectrum_recording(app, options)
+}
+#[tauri::command]
+fn stop_spectrum_recording(app: tauri::AppHandle) -> Result<recorder::RecordingSummary, String> {
+    spectrum::stop_spectrum_recording(app)
+}
+#[tauri::command]
 fn start_spectrum(app: tauri::AppHandle) -> Result<(), String> {
     spectrum::start_spectrum(app)
 }
@@ -26,7 +26,7 @@ impl PdfLibraryState {
     }
     
     fn get_connection(&self) -> Result<rusqlite::Connection, String> {
-        database::init_db(&self.db_path).map_err(|e| e.to_string())
+        database::init_db(&self.db_path)
     }
 }
 
@@ -5,38 +5,44 @@ use std::path::Path;
 use chrono::Utc;
 
 use super::{Book, Tag, Directory, Category};
+use crate::migrations::{add_column_if_missing, run_migrations, Migration};
 
 /// 初始化数据库并返回连接
-pub fn init_db(db_path: &Path) -> Result<Connection> {
-    let conn = Connection::open(db_path)?;
-    
+pub fn init_db(db_path: &Path) -> Result<Connection, String> {
+    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+    init_connection(&conn)?;
+    Ok(conn)
+}
+
+/// 设置连接参数并迁移到最新 schema
+fn init_connection(conn: &Connection) -> Result<(), String> {
     // 开启 WAL 模式（使用 execute_batch 避免返回值问题）
     conn.execute_batch(
         "PRAGMA journal_mode=WAL;
          PRAGMA synchronous=NORMAL;
          PRAGMA foreign_keys=ON;"
-    )?;
+    ).map_err(|e| e.to_string())?;
     
-    // 创建表
-    create_tables(&conn)?;
+    // 迁移到最新 schema
+    run_migrations(conn, "pdf_library.db", MIGRATIONS)?;
     
-    Ok(conn)
+    Ok(())
 }
 
 /// 仅初始化数据库结构（不返回连接）
-pub fn ensure_schema(db_path: &Path) -> Result<()> {
-    let conn = Connection::open(db_path)?;
-    conn.execute_batch(
-        "PRAGMA journal_mode=WAL;
-         PRAGMA synchronous=NORMAL;
-         PRAGMA foreign_keys=ON;"
-    )?;
-    create_tables(&conn)?;
-    Ok(())
+pub fn ensure_schema(db_path: &Path) -> Result<(), String> {
+    init_db(db_path).map(|_| ())
 }
 
-/// 创建所有表
-fn create_tables(conn: &Connection) -> Result<()> {
+/// PDF 库的全部迁移，按版本顺序排列；已发布的迁移不能再修改，只能追加
+pub const MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, description: "directories, books, tags", up: migrate_v1_base },
+    Migration { version: 2, description: "books.is_missing", up: migrate_v2_books_is_missing },
+    Migration { version: 3, description: "tags.aliases", up: migrate_v3_tag_aliases },
+    Migration { version: 4, description: "categories", up: migrate_v4_categories },
+];
+
+fn migrate_v1_base(conn: &Connection) -> Result<()> {
     // 目录表
     conn.execute(
         "CREATE TABLE IF NOT EXISTS directories (
@@ -70,18 +76,10 @@ fn create_tables(conn: &Connection) -> Result<()> {
             import_date TEXT NOT NULL,
             modified_date TEXT NOT NULL,
 
-            is_missing INTEGER NOT NULL DEFAULT 0,
-
             FOREIGN KEY(directory_id) REFERENCES directories(id) ON DELETE CASCADE
         )",
         [],
     )?;
-
-    // 迁移：为旧表补充 is_missing 列（如果不存在）。忽略已存在时报错。
-    let _ = conn.execute(
-        "ALTER TABLE books ADD COLUMN is_missing INTEGER NOT NULL DEFAULT 0",
-        [],
-    );
     
     // 索引
     conn.execute(
@@ -101,18 +99,11 @@ fn create_tables(conn: &Connection) -> Result<()> {
             name TEXT NOT NULL UNIQUE,
             color TEXT,
             parent_id INTEGER,
-            aliases TEXT,
             FOREIGN KEY(parent_id) REFERENCES tags(id) ON DELETE CASCADE
         )",
         [],
     )?;
     
-    // 添加 aliases 列（如果不存在）
-    conn.execute(
-        "ALTER TABLE tags ADD COLUMN aliases TEXT",
-        [],
-    ).ok(); // 忽略错误（列已存在）
-    
     // 书籍-标签关联表
     conn.execute(
         "CREATE TABLE IF NOT EXISTS book_tags (
@@ -125,6 +116,18 @@ fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
     
+    Ok(())
+}
+
+fn migrate_v2_books_is_missing(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "books", "is_missing", "INTEGER NOT NULL DEFAULT 0")
+}
+
+fn migrate_v3_tag_aliases(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "tags", "aliases", "TEXT")
+}
+
+fn migrate_v4_categories(conn: &Connection) -> Result<()> {
     // 分类表
     conn.execute(
         "CREATE TABLE IF NOT EXISTS categories (
@@ -137,21 +140,22 @@ fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
     
-    // 为 books 表添加 category_id 列（如果不存在）
-    let _ = conn.execute(
-        "ALTER TABLE books ADD COLUMN category_id INTEGER REFERENCES categories(id) ON DELETE SET NULL",
-        [],
-    );
+    // 为 books 表添加 category_id 列
+    add_column_if_missing(
+        conn,
+        "books",
+        "category_id",
+        "INTEGER REFERENCES categories(id) ON DELETE SET NULL",
+    )?;
     
-    // 检查是否需要初始化默认分类
+    // 只在首次建立分类表时写入默认分类（之后用户删除也不会再补回）
     let category_count: i32 = conn.query_row(
         "SELECT COUNT(*) FROM categories",
         [],
         |row| row.get(0)
-    ).unwrap_or(0);
+    )?;
     
     if category_count == 0 {
-        // 添加默认分类
         conn.execute(
             "INSERT INTO categories (name, icon, color, display_order) VALUES 
             ('书籍', '📚', '#2196F3', 1),
@@ -661,3 +665,64 @@ pub fn update_directory_path(conn: &Connection, id: i32, new_path: &str) -> Resu
     )?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::schema_version;
+
+    fn columns(conn: &Connection, table: &str) -> Vec<String> {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table)).unwrap();
+        stmt.query_map([], |row| row.get(1)).unwrap().map(|c| c.unwrap()).collect()
+    }
+
+    fn category_names(conn: &Connection) -> Vec<String> {
+        let mut stmt = conn.prepare("SELECT name FROM categories ORDER BY id").unwrap();
+        stmt.query_map([], |row| row.get(0)).unwrap().map(|c| c.unwrap()).collect()
+    }
+
+    #[test]
+    fn migrates_from_every_version() {
+        for version in 0..=MIGRATIONS.len() {
+            let conn = Connection::open_in_memory().unwrap();
+            run_migrations(&conn, "fixture", &MIGRATIONS[..version]).unwrap();
+            assert_eq!(schema_version(&conn).unwrap() as usize, version);
+
+            init_connection(&conn).unwrap();
+            assert_eq!(schema_version(&conn).unwrap() as usize, MIGRATIONS.len());
+            let books = columns(&conn, "books");
+            assert!(books.iter().any(|c| c == "is_missing"), "v{}: {:?}", version, books);
+            assert_eq!(books.last().map(String::as_str), Some("category_id"), "v{}", version);
+            assert!(columns(&conn, "tags").iter().any(|c| c == "aliases"));
+            assert_eq!(category_names(&conn), ["书籍", "论文", "乐谱"]);
+        }
+    }
+
+    #[test]
+    fn keeps_user_categories_of_unversioned_database() {
+        // 引入版本号之前的库：分类表已存在且用户改过默认分类，不应再补回
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE categories (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL UNIQUE, icon TEXT,
+                color TEXT, display_order INTEGER NOT NULL DEFAULT 0
+             );
+             INSERT INTO categories (name) VALUES ('Mine');",
+        )
+        .unwrap();
+        init_connection(&conn).unwrap();
+        assert_eq!(schema_version(&conn).unwrap() as usize, MIGRATIONS.len());
+        assert_eq!(category_names(&conn), ["Mine"]);
+        assert!(columns(&conn, "books").iter().any(|c| c == "category_id"));
+    }
+
+    #[test]
+    fn refuses_newer_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_connection(&conn).unwrap();
+        let newer = MIGRATIONS.len() as u32 + 1;
+        conn.pragma_update(None, "user_version", newer).unwrap();
+        let err = init_connection(&conn).unwrap_err();
+        assert!(err.contains(&newer.to_string()), "{}", err);
+    }
+}
@@ -119,6 +119,23 @@ pub fn show_folder(path: &Path) -> Result<(), String> {
     Ok(())
 }
 
+#[cfg(not(target_os = "windows"))]
+pub fn show_folder(path: &Path) -> Result<(), String> {
+    use std::process::Command;
+
+    #[cfg(target_os = "macos")]
+    let opener = "open";
+    #[cfg(not(target_os = "macos"))]
+    let opener = "xdg-open";
+
+    Command::new(opener)
+        .arg(path)
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
 
 /// 在文件管理器中显示文件
 #[cfg(target_os = "windows")]
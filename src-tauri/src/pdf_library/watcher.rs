@@ -144,7 +144,7 @@ fn handle_new_file(app_handle: &AppHandle, file_path: &Path, workspace_path: &Pa
     // 获取数据库连接
     let state = app_handle.state::<std::sync::Mutex<PdfLibraryState>>();
     let state_guard = state.lock().unwrap();
-    let conn = database::init_db(&state_guard.db_path).map_err(|e| e.to_string())?;
+    let conn = database::init_db(&state_guard.db_path)?;
     
     // 获取 Workspace 目录 ID
     let dirs = database::get_all_directories(&conn).map_err(|e| e.to_string())?;
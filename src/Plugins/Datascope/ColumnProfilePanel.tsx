@@ -0,0 +1,115 @@
+import { Component, For, Show } from "solid-js";
+import styles from "./ColumnProfilePanel.module.css";
+import type { ColumnProfile, DatasetProfile } from "./datasetBackend";
+
+interface ColumnProfilePanelProps {
+  profile: DatasetProfile;
+  onClose: () => void;
+}
+
+const formatNumber = (value: number | null | undefined) => {
+  if (value === null || value === undefined || !Number.isFinite(value)) return "—";
+  if (Number.isInteger(value)) return value.toLocaleString();
+  return value.toLocaleString(undefined, { maximumSignificantDigits: 6 });
+};
+
+const median = (column: ColumnProfile) =>
+  column.quantiles.find((q) => q.q === 0.5)?.value ?? null;
+
+const HistogramBars: Component<{ column: ColumnProfile }> = (props) => {
+  const peak = () => Math.max(1, ...(props.column.histogram?.counts ?? []));
+  return (
+    <Show when={props.column.histogram} fallback={<span>—</span>}>
+      {(histogram) => (
+        <div class={styles.histogram}>
+          <For each={histogram().counts}>
+            {(count, i) => (
+              <div
+                class={styles.bar}
+                style={{ height: `${(count / peak()) * 100}%` }}
+                title={`[${formatNumber(histogram().edges[i()])}, ${formatNumber(
+                  histogram().edges[i() + 1]
+                )}] ${count.toLocaleString()}`}
+              />
+            )}
+          </For>
+        </div>
+      )}
+    </Show>
+  );
+};
+
+const ColumnProfilePanel: Component<ColumnProfilePanelProps> = (props) => {
+  const hasApproximate = () => props.profile.columns.some((c) => c.approximate);
+
+  return (
+    <div class={styles.container}>
+      <div class={styles.header}>
+        <div class={styles.title}>
+          列统计 ({props.profile.columns.length} 列 · {props.profile.total_rows.toLocaleString()} 行)
+        </div>
+        <button class={styles.closeButton} onClick={props.onClose} title="关闭列统计">
+          ✕
+        </button>
+      </div>
+      <div class={styles.tableWrapper}>
+        <table class={styles.table}>
+          <thead>
+            <tr>
+              <th class={styles.left}>列</th>
+              <th class={styles.left}>类型</th>
+              <th>非空</th>
+              <th>空值</th>
+              <th>不同值</th>
+              <th>最小</th>
+              <th>最大</th>
+              <th>均值</th>
+              <th>标准差</th>
+              <th>中位数</th>
+              <th class={styles.left}>高频值</th>
+              <th class={styles.left}>分布</th>
+            </tr>
+          </thead>
+          <tbody>
+            <For each={props.profile.columns}>
+              {(column) => (
+                <tr>
+                  <td class={styles.left}>{column.name}</td>
+                  <td class={`${styles.left} ${styles.dtype}`}>{column.dtype}</td>
+                  <td>{formatNumber(column.count)}</td>
+                  <td>{formatNumber(column.null_count)}</td>
+                  <td>
+                    {column.approximate ? "≈" : ""}
+                    {formatNumber(column.distinct_count)}
+                  </td>
+                  <td>{formatNumber(column.min)}</td>
+                  <td>{formatNumber(column.max)}</td>
+                  <td>{formatNumber(column.mean)}</td>
+                  <td>{formatNumber(column.std)}</td>
+                  <td>{formatNumber(median(column))}</td>
+                  <td
+                    class={`${styles.left} ${styles.topValues}`}
+                    title={column.top_values.map((v) => `${v.value}: ${v.count}`).join("\n")}
+                  >
+                    {column.top_values
+                      .slice(0, 3)
+                      .map((v) => `${v.value} (${v.count.toLocaleString()})`)
+                      .join(", ") || "—"}
+                  </td>
+                  <td class={styles.left}>
+                    <HistogramBars column={column} />
+                  </td>
+                </tr>
+              )}
+            </For>
+          </tbody>
+        </table>
+      </div>
+      <Show when={hasApproximate()}>
+        <div class={styles.note}>≈ 表示该列数据量较大，不同值、分位数、分布与高频值为估算值</div>
+      </Show>
+    </div>
+  );
+};
+
+export default ColumnProfilePanel;
@@ -23,12 +23,16 @@ import {
   axisTypeLabel,
 } from "./csvUtils";
 import {
-  CsvBackendService,
+  DatasetBackendService,
+  type DatasetColumn,
+  type DatasetInfo,
+  type DatasetProfile,
   type PaginationState as BackendPaginationState,
-} from "./csvBackend";
-import { ParquetBackendService } from "./parquetBackend";
+} from "./datasetBackend";
+import { columnTable, EMPTY_TABLE, type ColumnTable } from "./columnarPage";
 import ThumbnailGrid, { type ThumbnailData } from "./ThumbnailGrid";
-import type { CSVRecord, AxisType } from "./types";
+import ColumnProfilePanel from "./ColumnProfilePanel";
+import type { AxisType } from "./types";
 import { showProgressNotification, type ProgressNotificationHandle } from "../../services/NotificationService";
 
 export interface ChartComputationResult {
@@ -56,11 +60,20 @@ type RecentFileEntry = {
 const RECENT_FILES_KEY = "datascope.recentFiles.v1";
 const MAX_RECENT_FILES = 10;
 
-type DataFormat = "csv" | "parquet";
+// schema 中的数值类型（polars dtype 名）
+const isNumericDtype = (dtype: string) =>
+  /^(Int|UInt|Float|Decimal)/i.test(dtype) || /\b(Int|UInt|Float|Decimal)\b/i.test(dtype);
 
-const Datascope: Component = () => {
+export interface DatascopeProps {
+  /** 是否为当前可见的页签，默认 true */
+  active?: boolean;
+  /** 当前文件名变化时回调，用于页签标题 */
+  onTitleChange?: (title: string) => void;
+}
+
+const Datascope: Component<DatascopeProps> = (props) => {
   const [headers, setHeaders] = createSignal<string[]>([]);
-  const [rows, setRows] = createSignal<CSVRecord[]>([]);
+  const [table, setTable] = createSignal<ColumnTable>(EMPTY_TABLE);
   const [xColumn, setXColumn] = createSignal<string>("");
   const [valueColumns, setValueColumns] = createSignal<string[]>([]);
   const [fileName, setFileName] = createSignal<string>("");
@@ -70,7 +83,7 @@ const Datascope: Component = () => {
   const [isLoading, setIsLoading] = createSignal<boolean>(false);
   const [delimiter, setDelimiter] = createSignal<string>(",");
   const [skippedRows, setSkippedRows] = createSignal<number>(0);
-  const csvExists = createMemo(() => rows().length > 0);
+  const csvExists = createMemo(() => table().rowCount > 0);
   const [isSmooth] = createSignal<boolean>(false);
 
   const [isSettingsOpen, setIsSettingsOpen] = createSignal<boolean>(true);
@@ -82,18 +95,29 @@ const Datascope: Component = () => {
   let pageLoadNotification: ProgressNotificationHandle | null = null;
   const [currentFilePath, setCurrentFilePath] = createSignal<string>("");
   const [dragOver, setDragOver] = createSignal<boolean>(false);
-  const [dataFormat, setDataFormat] = createSignal<DataFormat>("csv");
-  const isParquet = createMemo(() => dataFormat() === "parquet");
-  const [parquetSchema, setParquetSchema] = createSignal<
-    Array<{ name: string; dtype: string }>
-  >([]);
-  const [parquetInferredNumericColumns, setParquetInferredNumericColumns] =
-    createSignal<string[]>([]);
+  const [dataset, setDataset] = createSignal<DatasetInfo | null>(null);
+  const [schema, setSchema] = createSignal<DatasetColumn[]>([]);
+  const [profile, setProfile] = createSignal<DatasetProfile | null>(null);
+  const [isProfiling, setIsProfiling] = createSignal(false);
+  // 有类型信息的 schema（如 Parquet）直接据此判断数值列并做列裁剪；
+  // 全为字符串的 schema（如 CSV）加载全部列，从数据中推断数值列
+  const hasTypedSchema = createMemo(() => schema().some((c) => c.dtype !== "str"));
+  const supportsDelimiter = createMemo(() => dataset()?.delimiter != null);
+  const isActive = () => props.active ?? true;
+
+  createEffect(() => props.onTitleChange?.(fileName()));
+
+  const currentHandle = () => {
+    const info = dataset();
+    if (!info) throw new Error("没有加载的文件");
+    return info.handle;
+  };
 
   type PageInfoLike = BackendPaginationState["pages"][number];
   const [lastLoadedPageInfo, setLastLoadedPageInfo] = createSignal<PageInfoLike | null>(null);
 
-  const selectedParquetColumns = () => {
+  const selectedColumns = () => {
+    if (!hasTypedSchema()) return undefined;
     const cols = new Set<string>();
     const x = xColumn();
     if (x && x !== ROW_INDEX_KEY) cols.add(x);
@@ -101,6 +125,12 @@ const Datascope: Component = () => {
     return cols.size ? Array.from(cols) : undefined;
   };
 
+  // 页面以列式二进制传输，避免后端逐行 JSON 序列化；前端按列读取，不展开为逐行对象
+  const fetchPage = async (handle: number, pageIndex: number, pageInfo: PageInfoLike) =>
+    columnTable(
+      await DatasetBackendService.loadPageColumnar(handle, pageIndex, pageInfo, selectedColumns())
+    );
+
   let dragCounter = 0;
   let unlistenFileDrop: (() => void) | undefined;
   let unlistenDatascopeProgress: (() => void) | undefined;
@@ -163,12 +193,13 @@ const Datascope: Component = () => {
   };
 
   const closeCurrentFile = () => {
+    const info = dataset();
     setIsLoading(false);
     setIsPageLoading(false);
     pageLoadNotification?.close();
 
     batch(() => {
-      setRows([]);
+      setTable(EMPTY_TABLE);
       setHeaders([]);
       setFileName("");
       setCurrentFilePath("");
@@ -182,15 +213,14 @@ const Datascope: Component = () => {
       setXColumn("");
       setSkippedRows(0);
       setDelimiter(",");
-      setDataFormat("csv");
-      setParquetSchema([]);
-      setParquetInferredNumericColumns([]);
+      setDataset(null);
+      setSchema([]);
+      setProfile(null);
     });
 
-    void Promise.allSettled([
-      CsvBackendService.clearCache(),
-      ParquetBackendService.clearCache(),
-    ]);
+    if (info) {
+      void DatasetBackendService.close(info.handle).catch(console.warn);
+    }
   };
 
   // 监听 Tauri v2 的拖拽事件 tauri://drag-drop，直接获取本地路径
@@ -198,6 +228,8 @@ const Datascope: Component = () => {
     setRecentFiles(loadRecentFiles());
     try {
       unlistenFileDrop = await listen<any>("tauri://drag-drop", async (event) => {
+        // 多页签时只由当前可见的页签处理拖入的文件
+        if (!isActive()) return;
         console.log("[tauri://drag-drop] raw payload:", event.payload);
         const payload: any = event.payload;
 
@@ -230,6 +262,8 @@ const Datascope: Component = () => {
       unlistenDatascopeProgress = await listen<any>("datascope:progress", (event) => {
         const payload: any = event.payload;
         if (!payload) return;
+        // 其他页签的数据集的进度忽略；打开期间 handle 为 null
+        if (payload.handle != null && payload.handle !== dataset()?.handle) return;
         const current = Number(payload.current);
         const total = Number(payload.total);
         const message = typeof payload.message === "string" ? payload.message : "";
@@ -252,6 +286,11 @@ const Datascope: Component = () => {
       unlistenDatascopeProgress();
       unlistenDatascopeProgress = undefined;
     }
+    // 页签关闭或工具卸载时释放后端数据集
+    const info = dataset();
+    if (info) {
+      void DatasetBackendService.close(info.handle).catch(console.warn);
+    }
   });
 
   // 拖拽事件处理
@@ -322,24 +361,13 @@ const Datascope: Component = () => {
     setErrorMessage("拖拽内容不是有效的文件");
   };
 
-  const columnMeta = createMemo(() => buildColumnMeta(rows(), headers()));
-
-  const parquetNumericColumns = createMemo(() => {
-    const cols = parquetSchema();
-    return cols
-      .filter((c) =>
-        /^(Int|UInt|Float|Decimal)/i.test(c.dtype) ||
-        /\b(Int|UInt|Float|Decimal)\b/i.test(c.dtype)
-      )
-      .map((c) => c.name);
-  });
+  const columnMeta = createMemo(() => buildColumnMeta(table(), headers()));
 
   const numericColumns = createMemo(() => {
-    if (isParquet()) {
-      const schemaNumeric = parquetNumericColumns();
-      if (schemaNumeric.length) return schemaNumeric;
-      // fallback: schema 无法判断时，使用一次性采样推断结果（稳定，不随换页丢失）
-      return parquetInferredNumericColumns();
+    if (hasTypedSchema()) {
+      return schema()
+        .filter((c) => isNumericDtype(c.dtype))
+        .map((c) => c.name);
     }
     return columnMeta()
       .filter((meta) => meta.isNumeric)
@@ -349,9 +377,9 @@ const Datascope: Component = () => {
   const axisType = createMemo<AxisType>(() => {
     if (xColumn() === ROW_INDEX_KEY) return "value";
 
-    if (isParquet()) {
+    if (hasTypedSchema()) {
       const colName = xColumn();
-      const dtype = parquetSchema().find((c) => c.name === colName)?.dtype ?? "";
+      const dtype = schema().find((c) => c.name === colName)?.dtype ?? "";
       if (/Datetime|Date|Time/i.test(dtype)) return "time";
       if (/Int|UInt|Float|Decimal/i.test(dtype)) return "value";
       return "category";
@@ -368,10 +396,10 @@ const Datascope: Component = () => {
     }
   });
 
-  // Parquet 使用列裁剪：当用户切换 X / 数值列时，需要重新加载当前页把新列取回来。
+  // 有类型 schema 时使用列裁剪：当用户切换 X / 数值列时，需要重新加载当前页把新列取回来。
   const [lastSelectionKey, setLastSelectionKey] = createSignal<string>("");
   createEffect(() => {
-    if (!isParquet()) return;
+    if (!hasTypedSchema()) return;
     if (!currentFilePath()) return;
     if (isLoading() || isPageLoading()) return;
 
@@ -391,7 +419,7 @@ const Datascope: Component = () => {
     if (!info) return;
 
     // 小文件模式：用最近一次加载的页信息刷新当前页
-    const total = info.row_count ?? rows().length;
+    const total = info.row_count ?? table().rowCount;
     setIsPageLoading(true);
     pageLoadNotification?.close();
     pageLoadNotification = showProgressNotification({
@@ -400,16 +428,16 @@ const Datascope: Component = () => {
       current: 0,
       total,
     });
-    void ParquetBackendService.loadPage(0, info as any, selectedParquetColumns())
+    void fetchPage(currentHandle(), 0, info)
       .then((parsed) => {
         batch(() => {
-          setRows(parsed.rows);
-          setSkippedRows(parsed.skipped_rows);
+          setTable(parsed);
+          setSkippedRows(parsed.skippedRows);
           pageLoadNotification?.updateProgress(total, total, "完成");
         });
       })
       .catch((err) => {
-        console.warn("Parquet 重新加载列失败:", err);
+        console.warn("重新加载列失败:", err);
         pageLoadNotification?.fail("更新列失败", (err as Error).message);
       })
       .finally(() => {
@@ -436,10 +464,10 @@ const Datascope: Component = () => {
   });
 
   const chartData = createMemo<ChartComputationResult | null>(() => {
-    const dataRows = rows();
+    const dataRows = table();
     const xCol = xColumn();
     const selected = valueColumns();
-    if (!dataRows.length || !xCol || selected.length === 0) {
+    if (!dataRows.rowCount || !xCol || selected.length === 0) {
       return null;
     }
 
@@ -472,6 +500,51 @@ const Datascope: Component = () => {
     }
   };
 
+  // 计算各列统计；后端按文件缓存，文件未变化时直接返回
+  const loadProfile = async () => {
+    const info = dataset();
+    if (!info || isProfiling()) return;
+    setIsProfiling(true);
+    pageLoadNotification?.close();
+    pageLoadNotification = showProgressNotification({
+      title: "正在统计列信息",
+      message: "处理中...",
+      current: 0,
+      total: info.total_rows,
+    });
+    try {
+      const result = await DatasetBackendService.profile(info.handle);
+      // 统计期间切换了文件则丢弃结果
+      if (dataset()?.handle === info.handle) {
+        setProfile(result);
+      }
+      pageLoadNotification?.done("统计完成");
+    } catch (err) {
+      console.warn("列统计失败:", err);
+      pageLoadNotification?.fail("列统计失败", (err as Error).message);
+    } finally {
+      pageLoadNotification = null;
+      setIsProfiling(false);
+    }
+  };
+
+  // 打开文件或修改分隔符后重置 schema 与列选择
+  const applyDatasetInfo = (info: DatasetInfo) => {
+    batch(() => {
+      setDataset(info);
+      setSchema(info.columns);
+      // 分隔符变化后原有统计不再适用
+      setProfile(null);
+      setHeaders(info.columns.map((c) => c.name));
+      setDelimiter(info.delimiter ?? ",");
+      setSkippedRows(0);
+      // 避免沿用上一个文件的列选择导致列裁剪选择不存在的列而加载失败
+      setXColumn(ROW_INDEX_KEY);
+      const schemaNumeric = info.columns.filter((c) => isNumericDtype(c.dtype));
+      setValueColumns(schemaNumeric.length ? [schemaNumeric[0].name] : []);
+    });
+  };
+
   const handleFileSelection = async (filePath: string) => {
     console.log("🚀 开始加载文件:", filePath);
     setIsLoading(true);
@@ -480,80 +553,22 @@ const Datascope: Component = () => {
     setCurrentFilePath(filePath);
     setTotalRowCount(0);
 
-    // 简单按扩展名判定格式
-    const ext = (filePath.split(".").pop() || "").toLowerCase();
-    const nextFormat: DataFormat = ext === "parquet" || ext === "pq" ? "parquet" : "csv";
-    setDataFormat(nextFormat);
-
     try {
-      // 清理两边缓存，避免切换格式时残留
-      await Promise.allSettled([
-        CsvBackendService.clearCache(),
-        ParquetBackendService.clearCache(),
-      ]);
-
-      let totalRows = 0;
-      let displayPath = filePath;
-
-      if (nextFormat === "parquet") {
-        console.log("📡 调用后端 parquet_open_file...");
-        const opened = await ParquetBackendService.openFile(filePath);
-        totalRows = opened.total_rows;
-        displayPath = opened.path;
-        setParquetSchema(opened.columns);
-        setHeaders(opened.columns.map((c) => c.name));
-        // 避免沿用上一个文件的列选择导致 Parquet column pruning 选择不存在的列而加载失败
-        setXColumn(ROW_INDEX_KEY);
-        setValueColumns([]);
-
-        // 先用 schema 推断数值列；若 schema 无法判断（比如数值存成 Utf8/String），做一次小样本推断。
-        const schemaNumeric = opened.columns
-          .filter((c) =>
-            /^(Int|UInt|Float|Decimal)/i.test(c.dtype) ||
-            /\b(Int|UInt|Float|Decimal)\b/i.test(c.dtype)
-          )
-          .map((c) => c.name);
-
-        if (schemaNumeric.length) {
-          setParquetInferredNumericColumns(schemaNumeric);
-          setValueColumns([schemaNumeric[0]]);
-        } else {
-          setStatus("分析列类型... (采样)");
-          try {
-            const sampleCount = 200;
-            const samplePageInfo = {
-              page_index: 0,
-              start_row: 0,
-              end_row: sampleCount,
-              row_count: sampleCount,
-            };
-            const sample = await ParquetBackendService.loadPage(0, samplePageInfo, undefined);
-            const inferred = buildColumnMeta(sample.rows as any, sample.headers)
-              .filter((m) => m.isNumeric)
-              .map((m) => m.name);
-            setParquetInferredNumericColumns(inferred);
-            if (inferred.length) {
-              setValueColumns([inferred[0]]);
-            }
-          } catch (e) {
-            console.warn("Parquet 数值列采样推断失败:", e);
-            setParquetInferredNumericColumns([]);
-          }
-        }
-        // Parquet 没有分隔符概念，这里保留 UI 兼容
-        setDelimiter(",");
-        setSkippedRows(0);
-      } else {
-        console.log("📡 调用后端 csv_load_file...");
-        const [path, rowsCount, delim] = await CsvBackendService.loadFile(filePath);
-        totalRows = rowsCount;
-        displayPath = path;
-        setDelimiter(delim);
-        setParquetSchema([]);
-        setParquetInferredNumericColumns([]);
+      // 打开新文件前关闭本页签上一个数据集，释放其缓存
+      const previous = dataset();
+      if (previous) {
+        setDataset(null);
+        setProfile(null);
+        await DatasetBackendService.close(previous.handle).catch(console.warn);
       }
 
-      console.log("✅ 后端返回:", { path: displayPath, totalRows, format: nextFormat });
+      console.log("📡 调用后端 datascope_open...");
+      const opened = await DatasetBackendService.open(filePath);
+      applyDatasetInfo(opened);
+      const totalRows = opened.total_rows;
+      const displayPath = opened.path;
+
+      console.log("✅ 后端返回:", { path: displayPath, totalRows, format: opened.format });
 
       setCurrentFilePath(displayPath);
       setFileName(displayPath.split(/[/\\]/).pop() || displayPath);
@@ -578,13 +593,14 @@ const Datascope: Component = () => {
         stack: (err as Error).stack,
       });
       
-      setRows([]);
+      setTable(EMPTY_TABLE);
       setHeaders([]);
       setFileName("");
       setTotalRowCount(0);
       setPagination(null);
       setThumbnails([]);
-      setParquetInferredNumericColumns([]);
+      setDataset(null);
+      setSchema([]);
       setErrorMessage(`加载失败: ${(err as Error).message}`);
       setStatus("");
     } finally {
@@ -606,7 +622,7 @@ const Datascope: Component = () => {
 
     try {
       console.log("📡 获取分页信息...");
-      const paginationState = await CsvBackendService.getPagination(totalRows);
+      const paginationState = await DatasetBackendService.getPagination(currentHandle());
       console.log("✅ 分页信息:", paginationState);
       
       const pageInfo = paginationState.pages[0];
@@ -615,38 +631,30 @@ const Datascope: Component = () => {
 
       pageLoadNotification?.updateProgress(0, pageInfo.row_count, "加载中...");
 
-      const parsed = isParquet()
-        ? await ParquetBackendService.loadPage(
-            0,
-            pageInfo,
-            selectedParquetColumns()
-          )
-        : await CsvBackendService.loadPage(0, pageInfo);
+      const parsed = await fetchPage(currentHandle(), 0, pageInfo);
       console.log("✅ 数据解析完成:", {
         headers: parsed.headers.length,
-        rows: parsed.rows.length,
-        skipped: parsed.skipped_rows,
+        rows: parsed.rowCount,
+        skipped: parsed.skippedRows,
       });
 
-      const keepSchemaHeaders = isParquet() && parquetSchema().length > 0;
+      // 列裁剪时页面只含所选列，表头以 schema 为准
+      const keepSchemaHeaders = schema().length > 0;
 
       batch(() => {
         if (!keepSchemaHeaders) {
           setHeaders(parsed.headers);
         }
-        setRows(parsed.rows);
-        setSkippedRows(parsed.skipped_rows);
+        setTable(parsed);
+        setSkippedRows(parsed.skippedRows);
         pageLoadNotification?.updateProgress(pageInfo.row_count, pageInfo.row_count, "完成");
-        if (!isParquet()) {
-          setValueColumns([]);
-          setXColumn(ROW_INDEX_KEY);
-        } else if (!xColumn()) {
+        if (!xColumn()) {
           setXColumn(ROW_INDEX_KEY);
         }
         setPagination(null);
         setThumbnails([]);
         setStatus(
-          `已加载 ${parsed.rows.length.toLocaleString()} 行, ${
+          `已加载 ${parsed.rowCount.toLocaleString()} 行, ${
             parsed.headers.length
           } 列`
         );
@@ -670,7 +678,7 @@ const Datascope: Component = () => {
     console.log("📚 处理大文件, 总行数:", totalRows);
     
     console.log("📡 获取分页信息...");
-    const paginationState = await CsvBackendService.getPagination(totalRows);
+    const paginationState = await DatasetBackendService.getPagination(currentHandle());
     console.log("✅ 分页信息:", paginationState);
     setPagination(paginationState);
 
@@ -712,26 +720,18 @@ const Datascope: Component = () => {
     });
 
     try {
-      const parsed = isParquet()
-        ? await ParquetBackendService.loadPage(
-            pageIndex,
-            pageInfo,
-            selectedParquetColumns()
-          )
-        : await CsvBackendService.loadPage(pageIndex, pageInfo);
+      const handle = currentHandle();
+      const parsed = await fetchPage(handle, pageIndex, pageInfo);
 
-      const keepSchemaHeaders = isParquet() && parquetSchema().length > 0;
+      const keepSchemaHeaders = schema().length > 0;
 
       batch(() => {
         if (!keepSchemaHeaders) {
           setHeaders(parsed.headers);
         }
-        setRows(parsed.rows);
-        setSkippedRows(parsed.skipped_rows);
-        if (!isParquet()) {
-          setValueColumns([]);
-          setXColumn(ROW_INDEX_KEY);
-        } else if (!xColumn()) {
+        setTable(parsed);
+        setSkippedRows(parsed.skippedRows);
+        if (!xColumn()) {
           setXColumn(ROW_INDEX_KEY);
         }
         setPagination({ ...pg, current_page: pageIndex });
@@ -744,11 +744,7 @@ const Datascope: Component = () => {
       // 预加载下一页
       if (pageIndex + 1 < pg.total_pages) {
         const nextPageInfo = pg.pages[pageIndex + 1];
-        if (isParquet()) {
-          ParquetBackendService.loadPage(pageIndex + 1, nextPageInfo, selectedParquetColumns()).catch(console.warn);
-        } else {
-          CsvBackendService.loadPage(pageIndex + 1, nextPageInfo).catch(console.warn);
-        }
+        DatasetBackendService.loadPageColumnar(handle, pageIndex + 1, nextPageInfo, selectedColumns()).catch(console.warn);
       }
     } catch (err) {
       setErrorMessage(`加载第 ${pageIndex + 1} 页失败: ${(err as Error).message}`);
@@ -762,11 +758,13 @@ const Datascope: Component = () => {
 
   // 生成所有缩略图
   const generateAllThumbnails = async (pg: BackendPaginationState) => {
+    const handle = dataset()?.handle;
+    if (handle === undefined) return;
     for (let i = 0; i < pg.pages.length; i++) {
+      // 中途关闭或切换了文件
+      if (dataset()?.handle !== handle) return;
       try {
-        const thumbData = isParquet()
-          ? await ParquetBackendService.generateThumbnail(i, pg.pages[i])
-          : await CsvBackendService.generateThumbnail(i, pg.pages[i]);
+        const thumbData = await DatasetBackendService.generateThumbnail(handle, i, pg.pages[i]);
 
         setThumbnails((prev) =>
           prev.map((thumb) =>
@@ -796,9 +794,9 @@ const Datascope: Component = () => {
   };
 
   const handleDelimiterChange = async (event: Event) => {
-    if (isParquet()) {
-      // Parquet 不支持分隔符；保留 UI 但禁用行为
-      setErrorMessage("Parquet 文件不支持修改分隔符");
+    if (!supportsDelimiter()) {
+      // 没有分隔符概念的格式（如 Parquet）；保留 UI 但禁用行为
+      setErrorMessage("该文件格式不支持修改分隔符");
       return;
     }
     const next = (event.currentTarget as HTMLSelectElement).value;
@@ -814,8 +812,10 @@ const Datascope: Component = () => {
       setStatus("重新解析文件...");
       setDelimiter(next);
 
-      // 调用后端更改分隔符
-      const totalRows = await CsvBackendService.changeDelimiter(next);
+      // 调用后端更改分隔符（句柄不变，列可能随分隔符变化）
+      const info = await DatasetBackendService.setDelimiter(currentHandle(), next);
+      applyDatasetInfo(info);
+      const totalRows = info.total_rows;
 
       if (totalRows > ROWS_PER_PAGE) {
         await handleLargeFile(totalRows);
@@ -932,10 +932,10 @@ const Datascope: Component = () => {
             <Show when={fileName()}>
               <div class={styles.fileInfo}>
                 当前文件: <strong>{fileName()}</strong>
-                {rows().length > 0 &&
-                  ` · ${rows().length.toLocaleString()} 行 · ${
+                {table().rowCount > 0 &&
+                  ` · ${table().rowCount.toLocaleString()} 行 · ${
                     headers().length
-                  } 列${isParquet() ? "" : ` · 分隔符 \"${delimiter()}\"`}`}
+                  } 列${supportsDelimiter() ? ` · 分隔符 \"${delimiter()}\"` : ""}`}
                 {skippedRows() > 0 && ` · 忽略空行 ${skippedRows()}`}
               </div>
             </Show>
@@ -1029,7 +1029,7 @@ const Datascope: Component = () => {
                   </Show>
                   <div class={styles.fileMetaRow}>
                     <span>格式</span>
-                    <strong>{isParquet() ? "Parquet" : "CSV"}</strong>
+                    <strong>{dataset()?.format === "parquet" ? "Parquet" : "CSV"}</strong>
                   </div>
                   <Show when={totalRowCount() > 0}>
                     <div class={styles.fileMetaRow}>
@@ -1053,6 +1053,17 @@ const Datascope: Component = () => {
                         关闭
                       </button>
                     </div>
+                    <div class={styles.fileMetaRow}>
+                      <span>列统计</span>
+                      <button
+                        class={styles.fileMetaActionButton}
+                        onClick={() => void loadProfile()}
+                        disabled={isProfiling() || !dataset()}
+                        title="统计每列的空值、不同值、分布与高频值"
+                      >
+                        {isProfiling() ? "统计中..." : "统计"}
+                      </button>
+                    </div>
                   </Show>
                 </div>
 
@@ -1104,7 +1115,7 @@ const Datascope: Component = () => {
                   <select
                     value={delimiter()}
                     onChange={handleDelimiterChange}
-                    disabled={isParquet()}
+                    disabled={!supportsDelimiter()}
                   >
                     <option value=",">逗号 (,)</option>
                     <option value=";">分号 (;)</option>
@@ -1132,6 +1143,11 @@ const Datascope: Component = () => {
             />
           </Show>
 
+          {/* 列统计 */}
+          <Show when={profile()}>
+            {(result) => <ColumnProfilePanel profile={result()} onClose={() => setProfile(null)} />}
+          </Show>
+
           {/* 加载/进度使用 Notification 统一展示 */}
         </div>
       </Show>
@@ -0,0 +1,91 @@
+// Datascope 多页签：每个页签持有一个独立的后端数据集句柄，缓存共享全局预算
+import { Component, For, createSignal } from "solid-js";
+import Datascope from "./DatascopeBackend";
+import styles from "./DatascopeTabs.module.css";
+
+const DatascopeTabs: Component = () => {
+  let nextId = 1;
+  const newTab = () => nextId++;
+
+  // For 按值对应页签，标题单独存放，避免改标题时重新挂载页签内容
+  const [tabs, setTabs] = createSignal<number[]>([newTab()]);
+  const [titles, setTitles] = createSignal<Record<number, string>>({});
+  const [activeId, setActiveId] = createSignal<number>(tabs()[0]);
+
+  const addTab = () => {
+    const tab = newTab();
+    setTabs((prev) => [...prev, tab]);
+    setActiveId(tab);
+  };
+
+  // 关闭页签会卸载对应的 Datascope，由其 onCleanup 关闭后端数据集
+  const closeTab = (id: number) => {
+    const list = tabs();
+    const index = list.indexOf(id);
+    if (index < 0) return;
+    const rest = list.filter((t) => t !== id);
+    if (rest.length === 0) {
+      rest.push(newTab());
+    }
+    if (activeId() === id) {
+      setActiveId(rest[Math.min(index, rest.length - 1)]);
+    }
+    setTabs(rest);
+    setTitles((prev) => {
+      const next = { ...prev };
+      delete next[id];
+      return next;
+    });
+  };
+
+  const setTitle = (id: number, title: string) => {
+    setTitles((prev) => (prev[id] === title ? prev : { ...prev, [id]: title }));
+  };
+
+  return (
+    <div class={styles.root}>
+      <div class={styles.tabBar}>
+        <For each={tabs()}>
+          {(tab) => (
+            <div
+              class={`${styles.tab} ${activeId() === tab ? styles.active : ""}`}
+              title={titles()[tab] || "未打开文件"}
+              onClick={() => setActiveId(tab)}
+              onAuxClick={(e) => {
+                if (e.button === 1) closeTab(tab);
+              }}
+            >
+              <span class={styles.tabTitle}>{titles()[tab] || "新页签"}</span>
+              <button
+                class={styles.tabClose}
+                title="关闭页签"
+                onClick={(e) => {
+                  e.stopPropagation();
+                  closeTab(tab);
+                }}
+              >
+                ×
+              </button>
+            </div>
+          )}
+        </For>
+        <button class={styles.addButton} title="新建页签" onClick={addTab}>
+          +
+        </button>
+      </div>
+      {/* 非活动页签保持挂载，仅隐藏，切换时不丢失状态 */}
+      <For each={tabs()}>
+        {(tab) => (
+          <div class={`${styles.panel} ${activeId() === tab ? "" : styles.hidden}`}>
+            <Datascope
+              active={activeId() === tab}
+              onTitleChange={(title) => setTitle(tab, title)}
+            />
+          </div>
+        )}
+      </For>
+    </div>
+  );
+};
+
+export default DatascopeTabs;
@@ -0,0 +1,154 @@
+// 解码 datascope_load_page_columnar 返回的列式二进制页面（格式见后端 datascope_columnar.rs）
+
+const MAGIC = "DSCP";
+const VERSION = 1;
+
+interface BufferRef {
+  offset: number;
+  len: number;
+}
+
+interface ColumnHeader {
+  name: string;
+  kind: "f64" | "bool" | "utf8";
+  values: BufferRef;
+  offsets: BufferRef | null;
+  validity: BufferRef | null;
+}
+
+interface PageHeader {
+  row_count: number;
+  skipped_rows: number;
+  columns: ColumnHeader[];
+}
+
+export type ColumnarColumn =
+  | { name: string; kind: "f64"; values: Float64Array; validity: Uint8Array | null }
+  | { name: string; kind: "bool"; values: Uint8Array; validity: Uint8Array | null }
+  | {
+      name: string;
+      kind: "utf8";
+      /** 第 i 行为 data[offsets[i], offsets[i + 1]) */
+      offsets: Uint32Array;
+      data: Uint8Array;
+      validity: Uint8Array | null;
+    };
+
+export interface ColumnarPage {
+  rowCount: number;
+  skippedRows: number;
+  columns: ColumnarColumn[];
+}
+
+/**
+ * 解析二进制页面；各列直接是原 ArrayBuffer 上的视图，不拷贝数据
+ */
+export function decodeColumnarPage(buffer: ArrayBuffer): ColumnarPage {
+  const bytes = new Uint8Array(buffer);
+  const view = new DataView(buffer);
+  const magic = String.fromCharCode(...bytes.subarray(0, 4));
+  if (magic !== MAGIC) throw new Error("无效的列式页面数据");
+  const version = view.getUint32(4, true);
+  if (version !== VERSION) throw new Error(`不支持的列式页面版本: ${version}`);
+
+  const headerLen = view.getUint32(8, true);
+  const header = JSON.parse(new TextDecoder().decode(bytes.subarray(12, 12 + headerLen))) as PageHeader;
+  const base = 12 + headerLen;
+  const u8 = (ref: BufferRef) => new Uint8Array(buffer, base + ref.offset, ref.len);
+
+  const columns = header.columns.map((c): ColumnarColumn => {
+    const validity = c.validity ? u8(c.validity) : null;
+    switch (c.kind) {
+      case "f64":
+        return { name: c.name, kind: "f64", values: new Float64Array(buffer, base + c.values.offset, c.values.len / 8), validity };
+      case "bool":
+        return { name: c.name, kind: "bool", values: u8(c.values), validity };
+      case "utf8": {
+        const offsets = c.offsets!;
+        return {
+          name: c.name,
+          kind: "utf8",
+          offsets: new Uint32Array(buffer, base + offsets.offset, offsets.len / 4),
+          data: u8(c.values),
+          validity,
+        };
+      }
+      default:
+        throw new Error(`未知的列类型: ${(c as ColumnHeader).kind}`);
+    }
+  });
+
+  return { rowCount: header.row_count, skippedRows: header.skipped_rows, columns };
+}
+
+/**
+ * 按行读取一列的函数，直接访问列视图；空值（以及 NaN）为 null，与 JSON 页面一致
+ */
+export function cellReader(column: ColumnarColumn): (row: number) => unknown {
+  const validity = column.validity;
+  switch (column.kind) {
+    case "f64": {
+      const values = column.values;
+      return (i) => {
+        const v = values[i];
+        return (validity && !validity[i]) || Number.isNaN(v) ? null : v;
+      };
+    }
+    case "bool": {
+      const values = column.values;
+      return (i) => (validity && !validity[i] ? null : values[i] !== 0);
+    }
+    case "utf8": {
+      const decoder = new TextDecoder();
+      const { offsets, data } = column;
+      return (i) => (validity && !validity[i] ? null : decoder.decode(data.subarray(offsets[i], offsets[i + 1])));
+    }
+  }
+}
+
+/**
+ * 读取一列为 JS 值数组
+ */
+export function columnValues(column: ColumnarColumn, rowCount: number): unknown[] {
+  const read = cellReader(column);
+  const out = new Array<unknown>(rowCount);
+  for (let i = 0; i < rowCount; i++) out[i] = read(i);
+  return out;
+}
+
+/**
+ * 按列访问的页面：图表、列类型推断只读取用到的列和行，不展开为逐行对象
+ */
+export interface ColumnTable {
+  headers: string[];
+  rowCount: number;
+  skippedRows: number;
+  /** 某列的按行读取函数；列不存在时总是返回 undefined */
+  column(name: string): (row: number) => unknown;
+}
+
+export const EMPTY_TABLE: ColumnTable = {
+  headers: [],
+  rowCount: 0,
+  skippedRows: 0,
+  column: () => () => undefined,
+};
+
+export function columnTable(page: ColumnarPage): ColumnTable {
+  const byName = new Map(page.columns.map((c) => [c.name, c] as const));
+  const readers = new Map<string, (row: number) => unknown>();
+  return {
+    headers: page.columns.map((c) => c.name),
+    rowCount: page.rowCount,
+    skippedRows: page.skippedRows,
+    column(name) {
+      let read = readers.get(name);
+      if (!read) {
+        const column = byName.get(name);
+        read = column ? cellReader(column) : () => undefined;
+        readers.set(name, read);
+      }
+      return read;
+    },
+  };
+}
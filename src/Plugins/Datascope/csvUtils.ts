@@ -1,4 +1,5 @@
 import type { CSVRecord, AxisType } from "./types";
+import type { ColumnTable } from "./columnarPage";
 import { ROW_INDEX_KEY, ChartComputationResult, DEFAULT_MAX_POINTS,MAX_POINTS,MIN_POINTS } from "./Datascope";
 
 export interface ChartSeries {
@@ -18,6 +19,18 @@ interface ParsedCSV {
   skippedRows: number;
 }
 
+/** 图表与列推断的数据来源：前端解析出的逐行对象，或后端返回的列式页面 */
+export type RowSource = CSVRecord[] | ColumnTable;
+
+function rowCountOf(source: RowSource): number {
+  return Array.isArray(source) ? source.length : source.rowCount;
+}
+
+function columnOf(source: RowSource, name: string): (row: number) => unknown {
+  if (Array.isArray(source)) return (i) => source[i]?.[name];
+  return source.column(name);
+}
+
 interface AxisConversionResult {
   valid: boolean;
   value: number | string;
@@ -226,18 +239,19 @@ function dedupeHeaders(headers: string[]): string[] {
  * @returns 
  */
 export function buildColumnMeta(
-  dataRows: CSVRecord[],
+  dataRows: RowSource,
   headerList: string[]
 ): ColumnMeta[] {
-  const sampleSize = Math.min(500, dataRows.length);
+  const sampleSize = Math.min(500, rowCountOf(dataRows));
   return headerList.map((name) => {
     let numericCount = 0;
     let temporalCount = 0;
     let nonEmpty = 0;
     const temporalNameHint = hasTemporalNameHint(name);
+    const valueAt = columnOf(dataRows, name);
 
     for (let i = 0; i < sampleSize; i += 1) {
-      const value = dataRows[i]?.[name];
+      const value = valueAt(i);
       if (value == null) continue;
       const trimmed = toText(value).trim();
       if (!trimmed) continue;
@@ -428,7 +442,7 @@ export function largestTriangleThreeBucketsIndices(
  * @returns 
  */
 export function buildChartData(params: {
-  rows: CSVRecord[];
+  rows: RowSource;
   xColumn: string;
   yColumns: string[];
   axisType: AxisType;
@@ -441,31 +455,34 @@ export function buildChartData(params: {
   }> = [];
 
   let droppedRows = 0;
+  const rowCount = rowCountOf(rows);
+  const xAt = xColumn === ROW_INDEX_KEY ? null : columnOf(rows, xColumn);
+  const yAt = yColumns.map((col) => [col, columnOf(rows, col)] as const);
 
-  rows.forEach((row, index) => {
+  for (let index = 0; index < rowCount; index += 1) {
     let axis: AxisConversionResult;
-    if (xColumn === ROW_INDEX_KEY) {
+    if (!xAt) {
       const seqVal = index + 1;
       axis = { valid: true, value: seqVal, numeric: seqVal };
     } else {
-      axis = convertAxisValue(row[xColumn], axisType, index);
+      axis = convertAxisValue(xAt(index), axisType, index);
     }
 
     if (!axis.valid) {
       droppedRows += 1;
-      return;
+      continue;
     }
 
     const valueMap: Record<string, number | null> = {};
-    yColumns.forEach((col) => {
-      valueMap[col] = parseNumeric(row[col]);
+    yAt.forEach(([col, valueAt]) => {
+      valueMap[col] = parseNumeric(valueAt(index));
     });
 
     // 仅当所有 Y 列均为 null 时跳过，避免无意义数据点
     const hasAnyValue = yColumns.some((col) => valueMap[col] !== null);
     if (!hasAnyValue) {
       droppedRows += 1;
-      return;
+      continue;
     }
 
     processed.push({
@@ -473,7 +490,7 @@ export function buildChartData(params: {
       axisNumeric: axis.numeric,
       values: valueMap,
     });
-  });
+  }
 
   if (!processed.length) {
     return {
@@ -0,0 +1,273 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { DatasetQuery, QueryPage } from "./types";
+import { decodeColumnarPage, type ColumnarPage } from "./columnarPage";
+
+export type DatasetFormat = "csv" | "parquet";
+
+export interface DatasetColumn {
+  name: string;
+  /** polars 类型名；CSV 统一为 "str" */
+  dtype: string;
+}
+
+export interface DatasetInfo {
+  /** 后续命令使用的数据集句柄 */
+  handle: number;
+  path: string;
+  format: DatasetFormat;
+  total_rows: number;
+  columns: DatasetColumn[];
+  /** 仅 CSV 有分隔符与编码；为 null 时不能修改分隔符 */
+  delimiter: string | null;
+  encoding: string | null;
+}
+
+export interface OpenOptions {
+  delimiter?: string | null;
+}
+
+export interface PageInfo {
+  page_index: number;
+  start_row: number;
+  end_row: number;
+  row_count: number;
+}
+
+export interface PaginationState {
+  total_rows: number;
+  total_pages: number;
+  current_page: number;
+  pages: PageInfo[];
+}
+
+export interface DatasetPage {
+  headers: string[];
+  rows: Record<string, unknown>[];
+  skipped_rows: number;
+}
+
+export interface ThumbnailPoint {
+  x: number;
+  y: number;
+}
+
+export interface ThumbnailData {
+  page_index: number;
+  points: ThumbnailPoint[];
+}
+
+export interface CacheStats {
+  budget_bytes: number;
+  /** 估算值：按抽样行外推 */
+  used_bytes: number;
+  cached_pages: number;
+  cached_thumbnails: number;
+  open_datasets: number;
+}
+
+export interface ProfileOptions {
+  top_k?: number;
+  histogram_bins?: number;
+  /** 0..1 之间的分位点 */
+  quantiles?: number[];
+}
+
+export interface ValueCount {
+  value: string;
+  count: number;
+}
+
+export interface QuantileValue {
+  q: number;
+  value: number;
+}
+
+/** 等宽直方图：edges 比 counts 多一个 */
+export interface Histogram {
+  edges: number[];
+  counts: number[];
+}
+
+export interface ColumnProfile {
+  name: string;
+  /** polars 类型名；CSV 按内容推断为 i64 / f64 / bool / str */
+  dtype: string;
+  /** 非空值个数 */
+  count: number;
+  null_count: number;
+  distinct_count: number;
+  /** 以下仅数值列有值 */
+  min: number | null;
+  max: number | null;
+  mean: number | null;
+  std: number | null;
+  quantiles: QuantileValue[];
+  histogram: Histogram | null;
+  /** 出现次数最多的值，按次数降序 */
+  top_values: ValueCount[];
+  /** 为 true 时不同值个数、分位数、直方图与频次为估算值 */
+  approximate: boolean;
+}
+
+export interface DatasetProfile {
+  path: string;
+  format: DatasetFormat;
+  total_rows: number;
+  columns: ColumnProfile[];
+  options: Required<ProfileOptions>;
+}
+
+export interface CsvToParquetOptions {
+  delimiter?: string;
+  has_header?: boolean;
+  infer_schema_length?: number;
+  compression?: "zstd" | "snappy" | "uncompressed";
+}
+
+/** CSV / Parquet 共用的数据集接口，按句柄访问 */
+export class DatasetBackendService {
+  /**
+   * 打开数据集（按扩展名选择后端）
+   */
+  static async open(path: string, options?: OpenOptions): Promise<DatasetInfo> {
+    return await invoke<DatasetInfo>("datascope_open", { path, options });
+  }
+
+  /**
+   * 获取分页信息
+   */
+  static async getPagination(handle: number): Promise<PaginationState> {
+    return await invoke<PaginationState>("datascope_get_pagination", { handle });
+  }
+
+  /**
+   * 加载指定页数据；columns 为空时加载全部列
+   */
+  static async loadPage(
+    handle: number,
+    pageIndex: number,
+    pageInfo: PageInfo,
+    columns?: string[]
+  ): Promise<DatasetPage> {
+    return await invoke<DatasetPage>("datascope_load_page", {
+      handle,
+      pageIndex,
+      pageInfo,
+      columns,
+    });
+  }
+
+  /**
+   * 以列式二进制格式加载指定页，避免大页面逐行 JSON 的序列化开销
+   */
+  static async loadPageColumnar(
+    handle: number,
+    pageIndex: number,
+    pageInfo: PageInfo,
+    columns?: string[]
+  ): Promise<ColumnarPage> {
+    const buffer = await invoke<ArrayBuffer>("datascope_load_page_columnar", {
+      handle,
+      pageIndex,
+      pageInfo,
+      columns,
+    });
+    return decodeColumnarPage(buffer);
+  }
+
+  /**
+   * 生成缩略图
+   */
+  static async generateThumbnail(
+    handle: number,
+    pageIndex: number,
+    pageInfo: PageInfo
+  ): Promise<ThumbnailData> {
+    return await invoke<ThumbnailData>("datascope_generate_thumbnail", {
+      handle,
+      pageIndex,
+      pageInfo,
+    });
+  }
+
+  /**
+   * 更改分隔符（仅 CSV），句柄不变
+   * @returns 重新扫描后的数据集信息
+   */
+  static async setDelimiter(handle: number, delimiter: string): Promise<DatasetInfo> {
+    return await invoke<DatasetInfo>("datascope_set_delimiter", {
+      handle,
+      delimiter,
+    });
+  }
+
+  /**
+   * 在整个数据集上过滤/搜索/排序，返回一页结果（进度见 datascope:progress）
+   */
+  static async query(handle: number, query: DatasetQuery): Promise<QueryPage> {
+    return await invoke<QueryPage>("datascope_run_query", { handle, query });
+  }
+
+  /**
+   * 取消正在运行的查询
+   * @returns 查询是否仍在运行
+   */
+  static async cancelQuery(queryId: string): Promise<boolean> {
+    return await invoke<boolean>("datascope_cancel_query", { queryId });
+  }
+
+  /**
+   * 逐列统计（进度见 datascope:progress）；结果按文件缓存，文件修改后重新计算
+   */
+  static async profile(handle: number, options?: ProfileOptions): Promise<DatasetProfile> {
+    return await invoke<DatasetProfile>("datascope_profile", { handle, options });
+  }
+
+  /**
+   * 清空该数据集的页面与缩略图缓存
+   */
+  static async clearCache(handle: number): Promise<void> {
+    await invoke("datascope_clear_cache", { handle });
+  }
+
+  /**
+   * 关闭数据集并释放其缓存
+   * @returns 该句柄之前是否处于打开状态
+   */
+  static async close(handle: number): Promise<boolean> {
+    return await invoke<boolean>("datascope_close", { handle });
+  }
+
+  /**
+   * 列出所有打开的数据集
+   */
+  static async listOpen(): Promise<DatasetInfo[]> {
+    return await invoke<DatasetInfo[]>("datascope_list_open");
+  }
+
+  /**
+   * 获取共享缓存的占用情况
+   */
+  static async cacheStats(): Promise<CacheStats> {
+    return await invoke<CacheStats>("datascope_cache_stats");
+  }
+
+  /**
+   * 修改所有数据集共享的缓存预算（字节），超出部分立即按 LRU 淘汰
+   */
+  static async setCacheBudget(budgetBytes: number): Promise<CacheStats> {
+    return await invoke<CacheStats>("datascope_set_cache_budget", { budgetBytes });
+  }
+
+  static async convertCsvToParquet(
+    csvPath: string,
+    parquetPath: string,
+    options?: CsvToParquetOptions
+  ): Promise<void> {
+    await invoke("convert_csv_to_parquet", {
+      csvPath,
+      parquetPath,
+      options,
+    });
+  }
+}
@@ -9,6 +9,6 @@ export const datascopeToolConfig: ToolConfig = {
   icon: ShowChart,
   description: "面向大数据量的 CSV 可视化与下采样（后端加速）",
   category: ToolCategory.PRODUCTIVITY,
-  component: () => import("./DatascopeBackend"),
+  component: () => import("./DatascopeTabs"),
   saveState: false,
 };
@@ -1,2 +1,34 @@
 export type CSVRecord = Record<string, unknown>;
-export type AxisType = "value" | "time" | "category";
\ No newline at end of file
+export type AxisType = "value" | "time" | "category";
+/** 单列过滤条件（多个条件之间为 AND） */
+export type ColumnFilter =
+  | { column: string; op: "equals"; value: string }
+  | { column: string; op: "contains"; value: string; case_sensitive?: boolean }
+  | { column: string; op: "regex"; pattern: string }
+  | { column: string; op: "range"; min?: number | null; max?: number | null };
+
+export interface SortSpec {
+  column: string;
+  descending?: boolean;
+}
+
+export interface DatasetQuery {
+  filters?: ColumnFilter[];
+  sort?: SortSpec[];
+  /** 任意列包含该词（不区分大小写） */
+  search?: string | null;
+  offset: number;
+  limit: number;
+  /** 用于 datascope_cancel_query */
+  query_id?: string | null;
+}
+
+export interface QueryPage<R = CSVRecord> {
+  headers: string[];
+  rows: R[];
+  /** 每一行在原文件中的行号（从 0 开始，不含表头） */
+  row_numbers: number[];
+  total_matches: number;
+  offset: number;
+  skipped_rows: number;
+}
@@ -4,6 +4,7 @@ import { useAppFramework } from "../../core/AppFramework";
 import "./spectrum.css";
 import { debounce } from "../../components/Utils/debounce";
 import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
+import type { SpectrumStatus } from "../../core/Repository";
 
 // 使用随机字符展示频谱柱，字符数量随窗口大小自适应
 export default function Spectrum(props: { fullscreen?: boolean }) {
@@ -162,9 +163,10 @@ export default function Spectrum(props: { fullscreen?: boolean }) {
     if (unlisten) disposers.push(unlisten);
 
     // 监听状态信息（后端会在初始化/无数据超时等情况下推送）
-    const offStatus = await listen<string>("spectrum:status", (ev) => {
-      const msg = (ev.payload ?? "").toString();
-      if (msg) setStatus(msg);
+    const offStatus = await listen<SpectrumStatus>("spectrum:status", (ev) => {
+      const st = ev.payload;
+      if (!st?.message) return;
+      setStatus(st.state === "simulation" ? `模拟数据：${st.message}` : st.message);
     });
     if (offStatus) disposers.push(offStatus);
 
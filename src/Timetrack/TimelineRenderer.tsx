@@ -7,6 +7,7 @@ export interface TimelineActivity {
   app_name: string;
   start_time: string;
   duration_seconds: number;
+  is_idle?: boolean;
 }
 
 // 每小时分桶片段
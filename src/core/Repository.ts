@@ -19,6 +19,297 @@ export interface ActivityLog {
   window_title: string;
   start_time: string;
   duration_seconds: number;
+  is_idle: boolean;
+}
+
+export interface AppTotal {
+  app_name: string;
+  total_seconds: number;
+}
+
+export interface TimeReport {
+  start_date: string;
+  end_date: string;
+  granularity: 'day' | 'week' | 'month';
+  total_seconds: number;
+  by_app: AppTotal[];
+  by_category: { category_id: number | null; name: string | null; total_seconds: number }[];
+  /** 本地时间 0-23 点，每小时各程序的耗时（已按整点切分） */
+  by_hour: { hour: number; total_seconds: number; apps: AppTotal[] }[];
+  by_period: { period: string; start_date: string; total_seconds: number }[];
+}
+
+export interface ImportSummary {
+  inserted: number;
+  /** 本地已存在、但导入的时长更长而更新的记录数 */
+  updated: number;
+  /** 本地已存在而跳过的记录数 */
+  skipped: number;
+}
+
+export interface RetentionPolicy {
+  /** 原始记录保留天数，更早的合并为每小时汇总；0 表示不合并 */
+  raw_retention_days: number;
+  /** 保留今年及之前 N 个完整年份，更早的移入归档库；0 表示不归档 */
+  archive_after_years: number;
+}
+
+export interface RetentionReport {
+  compacted_rows: number;
+  summary_rows: number;
+  archived: { year: number; path: string; raw_rows: number; summary_rows: number }[];
+  vacuumed: boolean;
+  size_before: number;
+  size_after: number;
+}
+
+export interface PauseStatus {
+  paused: boolean;
+  /** 自动恢复时间；null 表示未暂停或需手动恢复 */
+  until: string | null;
+}
+
+export interface FocusOptions {
+  deep_work_min_minutes?: number;
+  interruption_tolerance_secs?: number;
+  focus_category_ids?: number[];
+}
+
+export interface FocusStreak {
+  key: string;
+  category_id: number | null;
+  start_time: string;
+  end_time: string;
+  seconds: number;
+}
+
+export interface FocusAnalytics {
+  start_date: string;
+  end_date: string;
+  active_seconds: number;
+  /** 已压缩为每小时汇总、无法参与分析的时长 */
+  compacted_seconds: number;
+  context_switches: number;
+  switches_by_hour: { hour_start: string; hour: number; switches: number }[];
+  app_streaks: FocusStreak[];
+  category_streaks: FocusStreak[];
+  deep_work: {
+    start_time: string;
+    end_time: string;
+    focused_seconds: number;
+    interruptions: number;
+    main_category_id: number | null;
+    main_category: string | null;
+  }[];
+  deep_work_seconds: number;
+}
+
+export interface ActivityGoal {
+  id: number;
+  name: string;
+  period: 'day' | 'week';
+  direction: 'at_least' | 'at_most';
+  target_seconds: number;
+  category_id: number | null;
+  app_pattern: string | null;
+  enabled: boolean;
+}
+
+export type GoalLevel = 'below' | 'approaching' | 'reached' | 'exceeded';
+
+/** goals_progress 的返回值，也是 "goals:status" 事件的内容 */
+export interface GoalProgress {
+  goal_id: number;
+  name: string;
+  period: 'day' | 'week';
+  direction: 'at_least' | 'at_most';
+  level: GoalLevel;
+  progress_seconds: number;
+  target_seconds: number;
+}
+
+export interface PomodoroConfig {
+  work_minutes: number;
+  short_break_minutes: number;
+  long_break_minutes: number;
+  long_break_every: number;
+  focus_category_ids?: number[];
+}
+
+export type PomodoroPhase = 'stopped' | 'work' | 'short_break' | 'long_break';
+
+/** "pomodoro:tick" 事件的内容 */
+export interface PomodoroStatus {
+  phase: PomodoroPhase;
+  phase_end: string | null;
+  remaining_seconds: number;
+  completed_work: number;
+  on_task_seconds: number;
+  off_task_seconds: number;
+  off_task: boolean;
+}
+
+/** "pomodoro:event" 事件的内容 */
+export type PomodoroEvent =
+  | { kind: 'phase_finished'; phase: PomodoroPhase; on_task_seconds: number; off_task_seconds: number; completed_work: number }
+  | { kind: 'phase_started'; phase: PomodoroPhase; phase_end: string }
+  | { kind: 'distraction'; app_name: string; window_title: string }
+  | { kind: 'back_on_task' };
+
+export interface IcsExportOptions {
+  min_session_minutes?: number;
+  merge_gap_secs?: number;
+  group_by?: 'app' | 'category';
+}
+
+export interface IcsExportSummary {
+  path: string;
+  events: number;
+}
+
+export interface IcsImportSummary {
+  inserted: number;
+  updated: number;
+  skipped: number;
+}
+
+export interface PlannedBlock {
+  id: number;
+  uid: string;
+  title: string;
+  start_time: string;
+  end_time: string;
+  category_id: number | null;
+  source: string | null;
+}
+
+export interface PlanComparison {
+  block: PlannedBlock;
+  planned_seconds: number;
+  tracked_seconds: number;
+  matching_seconds: number | null;
+  top_apps: { app_name: string; total_seconds: number }[];
+}
+
+export interface PlanVsActual {
+  date: string;
+  blocks: PlanComparison[];
+  planned_seconds: number;
+  tracked_seconds: number;
+  unplanned_seconds: number;
+}
+
+export type AudioSourceSpec =
+  | { kind: 'default' }
+  | { kind: 'wasapi_loopback' }
+  | { kind: 'pulse_monitor'; device?: string }
+  | { kind: 'file'; path: string; looping?: boolean }
+  | { kind: 'simulation' };
+
+export interface SpectrumSourceInfo {
+  source: AudioSourceSpec;
+  fallback_to_simulation: boolean;
+}
+
+/** "spectrum:status" 事件的内容 */
+export interface SpectrumStatus {
+  state: 'capturing' | 'waiting' | 'simulation' | 'ended' | 'stopped' | 'error' | 'info';
+  source: string | null;
+  /** 真实来源不可用、回退到模拟数据时为 true */
+  fallback: boolean;
+  message: string;
+}
+
+export type SpectrumMode = 'columns' | 'spectrogram' | 'peak_hold' | 'octave_bands';
+export type WindowFunction = 'hann' | 'hamming' | 'blackman_harris' | 'flat_top';
+export type MagnitudeScale = 'linear' | 'db';
+
+export interface SpectrumConfig {
+  fft_size: number;
+  columns: number;
+  window: WindowFunction;
+  scale: MagnitudeScale;
+  db_floor: number;
+  a_weighting: boolean;
+  min_freq: number;
+  mode: SpectrumMode;
+  per_channel: boolean;
+  attack_ms: number;
+  release_ms: number;
+  peak_hold_ms: number;
+  peak_decay_db_per_sec: number;
+  history_len: number;
+  tuner: TunerConfig;
+}
+
+export interface TunerConfig {
+  enabled: boolean;
+  /** 标准音 A4，默认 440 */
+  a4_hz: number;
+  min_freq: number;
+  max_freq: number;
+  /** YIN 阈值，越小越严格 */
+  threshold: number;
+}
+
+/** "spectrum:pitch" 事件的内容；未检测到音高时事件内容为 null */
+export interface PitchEstimate {
+  frequency: number;
+  note: string;
+  octave: number;
+  /** 相对最近音的偏差，-50..50 音分 */
+  cents: number;
+  confidence: number;
+  midi: number;
+}
+
+/** "spectrum:frame" 事件的内容 */
+export interface SpectrumFrame {
+  mode: SpectrumMode;
+  scale: MagnitudeScale;
+  /** per_channel 时为 [左, 右]，否则只有混合后的一组 */
+  channels: number[][];
+  peaks: number[][] | null;
+  band_centres: number[] | null;
+  seq: number;
+}
+
+export interface ChannelLevel {
+  rms_db: number;
+  peak_db: number;
+}
+
+/** "spectrum:levels" 事件的内容（约每 100 ms 一次） */
+export interface SpectrumLevels {
+  channels: ChannelLevel[];
+  momentary_lufs: number | null;
+  short_term_lufs: number | null;
+  integrated_lufs: number | null;
+  integrated_seconds: number;
+}
+
+export interface RecordingOptions {
+  /** 32 位浮点 WAV */
+  wav_path: string;
+  frames_path?: string | null;
+  frames_format?: 'binary' | 'parquet';
+}
+
+export interface RecordingSummary {
+  wav_path: string;
+  frames_path: string | null;
+  audio_frames: number;
+  spectrum_frames: number;
+  /** 写入跟不上而丢弃的消息数 */
+  dropped: number;
+  duration_secs: number;
+}
+
+/** "spectrum:recording" 事件的内容 */
+export interface RecordingStatus {
+  recording: boolean;
+  summary: RecordingSummary | null;
+  error: string | null;
 }
 
 export interface DatabaseStats {
@@ -73,6 +364,27 @@ export class Repository {
     );
   }
 
+  async getTimeReport(startDate: string, endDate: string, granularity: 'day' | 'week' | 'month' = 'day'): Promise<TimeReport> {
+    return withErrorHandling(
+      () => invoke<TimeReport>("activity_report", { startDate, endDate, granularity }),
+      "getTimeReport"
+    );
+  }
+
+  async getPeriodReport(date: string, period: 'day' | 'week' | 'month'): Promise<TimeReport> {
+    return withErrorHandling(
+      () => invoke<TimeReport>("activity_period_report", { date, period }),
+      "getPeriodReport"
+    );
+  }
+
+  async getFocusAnalytics(date: string, period: 'day' | 'week' = 'day', options?: FocusOptions): Promise<FocusAnalytics> {
+    return withErrorHandling(
+      () => invoke<FocusAnalytics>("activity_focus_analytics", { date, period, options }),
+      "getFocusAnalytics"
+    );
+  }
+
   async getActivitiesForDateRange(startDate: string, endDate: string): Promise<TimelineActivity[]> {
     return withErrorHandling(async () => {
       // 如果后端还没有这个命令，可以先调用单日的多次
@@ -165,6 +477,62 @@ export class Repository {
     );
   }
 
+  async setSpectrumSource(source: AudioSourceSpec, fallbackToSimulation?: boolean): Promise<void> {
+    return withErrorHandling(
+      () => invoke("set_spectrum_source", { source, fallbackToSimulation }),
+      "setSpectrumSource"
+    );
+  }
+
+  async getSpectrumConfig(): Promise<SpectrumConfig> {
+    return withErrorHandling(
+      () => invoke<SpectrumConfig>("get_spectrum_config"),
+      "getSpectrumConfig"
+    );
+  }
+
+  async setSpectrumConfig(config: SpectrumConfig): Promise<void> {
+    return withErrorHandling(
+      () => invoke("set_spectrum_config", { config }),
+      "setSpectrumConfig"
+    );
+  }
+
+  async getSpectrogramHistory(): Promise<number[][]> {
+    return withErrorHandling(
+      () => invoke<number[][]>("get_spectrogram_history"),
+      "getSpectrogramHistory"
+    );
+  }
+
+  async resetSpectrumLoudness(): Promise<void> {
+    return withErrorHandling(
+      () => invoke("reset_spectrum_loudness"),
+      "resetSpectrumLoudness"
+    );
+  }
+
+  async startSpectrumRecording(options: RecordingOptions): Promise<void> {
+    return withErrorHandling(
+      () => invoke("start_spectrum_recording", { options }),
+      "startSpectrumRecording"
+    );
+  }
+
+  async stopSpectrumRecording(): Promise<RecordingSummary> {
+    return withErrorHandling(
+      () => invoke<RecordingSummary>("stop_spectrum_recording"),
+      "stopSpectrumRecording"
+    );
+  }
+
+  async getSpectrumSource(): Promise<SpectrumSourceInfo> {
+    return withErrorHandling(
+      () => invoke<SpectrumSourceInfo>("get_spectrum_source"),
+      "getSpectrumSource"
+    );
+  }
+
   // ========== 系统控制相关 ==========
   
   async startTracking(): Promise<void> {
@@ -188,6 +556,36 @@ export class Repository {
     );
   }
 
+  /** 暂停记录；不传 minutes 则直到 resumeTracking */
+  async pauseTracking(minutes?: number): Promise<PauseStatus> {
+    return withErrorHandling(
+      () => invoke<PauseStatus>("pause_tracking", { minutes }),
+      "pauseTracking"
+    );
+  }
+
+  async resumeTracking(): Promise<void> {
+    return withErrorHandling(
+      () => invoke<void>("resume_tracking"),
+      "resumeTracking"
+    );
+  }
+
+  async getPauseStatus(): Promise<PauseStatus> {
+    return withErrorHandling(
+      () => invoke<PauseStatus>("get_pause_status"),
+      "getPauseStatus"
+    );
+  }
+
+  /** 用当前隐私规则重新脱敏历史记录，返回改动的行数 */
+  async reredactHistory(startDate?: string, endDate?: string): Promise<number> {
+    return withErrorHandling(
+      () => invoke<number>("privacy_reredact_history", { startDate, endDate }),
+      "reredactHistory"
+    );
+  }
+
   async setLaunchOnStartup(enabled: boolean): Promise<void> {
     return withErrorHandling(async () => {
       if (enabled) {
@@ -204,20 +602,115 @@ export class Repository {
 
   // ========== 数据导出相关 ==========
   
-  async exportActivities(startDate: string, endDate: string, format: 'json' | 'csv' = 'json'): Promise<string> {
+  /** 不传 filePath 时返回导出的文本；传入时写入文件并返回路径（parquet 必须传 filePath） */
+  async exportActivities(
+    startDate: string,
+    endDate: string,
+    format: 'json' | 'csv' | 'parquet' = 'json',
+    filePath?: string
+  ): Promise<string> {
     return withErrorHandling(
-      () => invoke<string>("export_activities", { startDate, endDate, format }),
+      () => invoke<string>("export_activities", { startDate, endDate, format, filePath }),
       "exportActivities"
     );
   }
 
-  async importActivities(filePath: string): Promise<number> {
+  async importActivities(filePath: string): Promise<ImportSummary> {
     return withErrorHandling(
-      () => invoke<number>("import_activities", { filePath }),
+      () => invoke<ImportSummary>("import_activities", { filePath }),
       "importActivities"
     );
   }
 
+  // ========== 目标与番茄钟 ==========
+
+  async listGoals(): Promise<ActivityGoal[]> {
+    return withErrorHandling(() => invoke<ActivityGoal[]>("goals_list"), "listGoals");
+  }
+
+  async createGoal(goal: Partial<Omit<ActivityGoal, 'id'>>): Promise<ActivityGoal> {
+    return withErrorHandling(() => invoke<ActivityGoal>("goals_create", { goal }), "createGoal");
+  }
+
+  async updateGoal(id: number, goal: Partial<Omit<ActivityGoal, 'id'>>): Promise<ActivityGoal> {
+    return withErrorHandling(() => invoke<ActivityGoal>("goals_update", { id, goal }), "updateGoal");
+  }
+
+  async deleteGoal(id: number): Promise<void> {
+    return withErrorHandling(() => invoke<void>("goals_delete", { id }), "deleteGoal");
+  }
+
+  async getGoalProgress(): Promise<GoalProgress[]> {
+    return withErrorHandling(() => invoke<GoalProgress[]>("goals_progress"), "getGoalProgress");
+  }
+
+  async startPomodoro(config?: PomodoroConfig): Promise<PomodoroStatus> {
+    return withErrorHandling(() => invoke<PomodoroStatus>("pomodoro_start", { config }), "startPomodoro");
+  }
+
+  async stopPomodoro(): Promise<PomodoroStatus> {
+    return withErrorHandling(() => invoke<PomodoroStatus>("pomodoro_stop"), "stopPomodoro");
+  }
+
+  async skipPomodoroPhase(): Promise<PomodoroStatus> {
+    return withErrorHandling(() => invoke<PomodoroStatus>("pomodoro_skip"), "skipPomodoroPhase");
+  }
+
+  async getPomodoroStatus(): Promise<PomodoroStatus> {
+    return withErrorHandling(() => invoke<PomodoroStatus>("pomodoro_status"), "getPomodoroStatus");
+  }
+
+  // ========== 日历相关 ==========
+
+  async exportCalendar(startDate: string, endDate: string, filePath: string, options?: IcsExportOptions): Promise<IcsExportSummary> {
+    return withErrorHandling(
+      () => invoke<IcsExportSummary>("calendar_export_ics", { startDate, endDate, filePath, options }),
+      "exportCalendar"
+    );
+  }
+
+  async importCalendar(filePath: string): Promise<IcsImportSummary> {
+    return withErrorHandling(() => invoke<IcsImportSummary>("calendar_import_ics", { filePath }), "importCalendar");
+  }
+
+  async getPlannedBlocks(startDate: string, endDate?: string): Promise<PlannedBlock[]> {
+    return withErrorHandling(
+      () => invoke<PlannedBlock[]>("calendar_list_planned", { startDate, endDate }),
+      "getPlannedBlocks"
+    );
+  }
+
+  async deletePlannedBlock(id: number): Promise<void> {
+    return withErrorHandling(() => invoke<void>("calendar_delete_planned", { id }), "deletePlannedBlock");
+  }
+
+  async getPlanVsActual(date: string): Promise<PlanVsActual> {
+    return withErrorHandling(() => invoke<PlanVsActual>("calendar_plan_vs_actual", { date }), "getPlanVsActual");
+  }
+
+  // ========== 数据保留相关 ==========
+
+  async getRetentionPolicy(): Promise<RetentionPolicy> {
+    return withErrorHandling(
+      () => invoke<RetentionPolicy>("activity_get_retention_policy"),
+      "getRetentionPolicy"
+    );
+  }
+
+  async setRetentionPolicy(policy: RetentionPolicy): Promise<void> {
+    return withErrorHandling(
+      () => invoke<void>("activity_set_retention_policy", { policy }),
+      "setRetentionPolicy"
+    );
+  }
+
+  async runRetention(): Promise<RetentionReport> {
+    return withErrorHandling(
+      () => invoke<RetentionReport>("activity_run_retention"),
+      "runRetention"
+    );
+  }
+
   // ========== 配置相关 ==========
   
   async saveUserConfig(config: any): Promise<void> {